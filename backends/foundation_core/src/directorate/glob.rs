@@ -0,0 +1,177 @@
+// Provides glob matching over the `/` separated paths of a directorate.
+
+/// `Glob` matches `/` separated paths against a glob pattern:
+///
+/// - `?` matches a single character other than `/`.
+/// - `*` matches any run of characters other than `/`.
+/// - `**` as a whole segment matches any number of directories, including none.
+/// - `{sql,md}` matches any of the comma separated alternatives, sets can nest.
+///
+/// e.g `schema/**/*.{sql,md}` matches `schema/schema.sql` and
+/// `schema/partials/partial_1.sql`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    alternatives: Vec<Vec<String>>,
+}
+
+// -- Constructors
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let alternatives = expand_braces(pattern.trim_start_matches('/'))
+            .into_iter()
+            .map(|alternative| {
+                alternative
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            pattern: pattern.to_string(),
+            alternatives,
+        }
+    }
+}
+
+// -- Methods
+
+impl Glob {
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        let segments: Vec<&str> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        self.alternatives
+            .iter()
+            .any(|pattern| match_segments(pattern, &segments))
+    }
+}
+
+/// `expand_braces` turns each brace set into one pattern per alternative,
+/// unbalanced braces are kept as literal characters.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else {
+        return vec![pattern.to_string()];
+    };
+
+    let mut depth = 0;
+    let mut close = None;
+    let mut splits = Vec::new();
+    for (index, character) in pattern[open..].char_indices() {
+        match character {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + index);
+                    break;
+                }
+            }
+            ',' if depth == 1 => splits.push(open + index),
+            _ => {}
+        }
+    }
+
+    let Some(close) = close else {
+        return vec![pattern.to_string()];
+    };
+
+    let (prefix, suffix) = (&pattern[..open], &pattern[close + 1..]);
+    let mut bounds = vec![open];
+    bounds.extend(splits);
+    bounds.push(close);
+
+    bounds
+        .windows(2)
+        .flat_map(|window| {
+            let option = &pattern[window[0] + 1..window[1]];
+            expand_braces(&format!("{prefix}{option}{suffix}"))
+        })
+        .collect()
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((segment, rest)) if segment == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            Some((head, tail)) => match_segment(segment, head) && match_segments(rest, tail),
+            None => false,
+        },
+    }
+}
+
+/// `match_segment` matches a single path segment, backtracking to the
+/// last `*` on a mismatch.
+fn match_segment(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                last_star = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(character) if *character == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match last_star {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    last_star = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|character| *character == '*')
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards_and_brace_sets() {
+        let glob = Glob::new("schema/**/*.sql");
+        assert!(glob.is_match("schema/schema.sql"));
+        assert!(glob.is_match("schema/partials/partial_1.sql"));
+        assert!(!glob.is_match("docs/schema.sql"));
+        assert!(!glob.is_match("schema/schema.sqlite"));
+
+        let single = Glob::new("*.{js,md}");
+        assert!(single.is_match("elem.js"));
+        assert!(single.is_match("README.md"));
+        assert!(!single.is_match("docs/README.md"));
+
+        let nested = Glob::new("{docs/*.sh,schema/{partials/,}*_?.sql}");
+        assert!(nested.is_match("docs/runner.sh"));
+        assert!(nested.is_match("schema/partials/partial_1.sql"));
+        assert!(!nested.is_match("schema/schema.sql"));
+
+        assert!(Glob::new("**").is_match("a/b/c"));
+        assert!(Glob::new("a*b*c").is_match("aXbYbZc"));
+        assert!(Glob::new("{unbalanced").is_match("{unbalanced"));
+    }
+}
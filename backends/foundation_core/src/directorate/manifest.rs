@@ -0,0 +1,250 @@
+// Provides a content-addressed manifest of the files of a directorate.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::tree::hash_hex;
+use super::{EmbeddedFileResponse, PackageDirectorate};
+use crate::wire::simple_http::{SimpleHeader, SimpleHeaders, SimpleOutgoingResponse};
+
+/// `IMMUTABLE_CACHE_CONTROL` is sent for fingerprinted files, their content
+/// can never change under the same name.
+pub const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// `FINGERPRINT_LENGTH` is how many hex characters of the hash go into a
+/// fingerprinted filename.
+const FINGERPRINT_LENGTH: usize = 8;
+
+/// `ManifestEntry` describes one file of an [`AssetManifest`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// `path` is the path of the file within the directorate.
+    pub path: String,
+
+    /// `hash` is the sha256 hash of the file as lowercase hex.
+    pub hash: String,
+
+    /// `fingerprinted` is the path with a prefix of the hash inserted
+    /// before the extension, e.g `js/app.3fa9c2d1.js`.
+    pub fingerprinted: String,
+}
+
+/// `AssetManifest` maps the files of a directorate to their hashes and
+/// fingerprinted names, so templates can emit cache-busted URLs and the
+/// static layer can serve fingerprinted files as immutable.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetManifest {
+    entries: BTreeMap<String, ManifestEntry>,
+
+    #[serde(skip)]
+    by_fingerprint: BTreeMap<String, String>,
+}
+
+// -- Constructors
+
+impl AssetManifest {
+    pub fn new(entries: impl IntoIterator<Item = ManifestEntry>) -> Self {
+        let mut manifest = Self::default();
+        for entry in entries {
+            manifest.insert(entry);
+        }
+        manifest
+    }
+
+    /// `from_json` loads a manifest written by [`Self::to_json`].
+    pub fn from_json(content: &str) -> serde_json::Result<Self> {
+        let manifest: Self = serde_json::from_str(content)?;
+        Ok(Self::new(manifest.entries.into_values()))
+    }
+}
+
+// -- Methods
+
+impl AssetManifest {
+    pub fn insert(&mut self, entry: ManifestEntry) {
+        self.by_fingerprint
+            .insert(entry.fingerprinted.clone(), entry.path.clone());
+        self.entries.insert(entry.path.clone(), entry);
+    }
+
+    pub fn get(&self, path: &str) -> Option<&ManifestEntry> {
+        self.entries.get(path.trim_start_matches('/'))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries.values()
+    }
+
+    /// `url_for` returns the fingerprinted name of `path` for use in URLs.
+    pub fn url_for(&self, path: &str) -> Option<&str> {
+        self.get(path).map(|entry| entry.fingerprinted.as_str())
+    }
+
+    /// `resolve` finds the entry for a requested path, which can be either
+    /// a plain or a fingerprinted name, the flag tells which it was.
+    pub fn resolve(&self, request_path: &str) -> Option<(&ManifestEntry, bool)> {
+        let request_path = request_path.trim_start_matches('/');
+        if let Some(path) = self.by_fingerprint.get(request_path) {
+            return self.entries.get(path).map(|entry| (entry, true));
+        }
+        self.entries.get(request_path).map(|entry| (entry, false))
+    }
+
+    /// `file_response` serves `request_path` from `directorate`, fingerprinted
+    /// names are answered with [`IMMUTABLE_CACHE_CONTROL`] as long as the
+    /// file still has the hash recorded in the manifest. A file changed
+    /// since, e.g in the overlay of an [`super::OverlayDirectorate`], is
+    /// served with its usual caching instead.
+    pub fn file_response(
+        &self,
+        directorate: &dyn PackageDirectorate,
+        request_path: &str,
+        request_headers: &SimpleHeaders,
+    ) -> Option<SimpleOutgoingResponse> {
+        let (entry, fingerprinted) = self.resolve(request_path)?;
+        let file = directorate.get_file(&entry.path)?;
+        let unchanged = hash_hex(&file.metadata.sha256_hash()) == entry.hash;
+        if fingerprinted && !unchanged {
+            tracing::debug!(
+                "{} changed since the manifest was built, not serving it as immutable",
+                entry.path
+            );
+        }
+
+        let mut response = file.to_response(request_headers);
+        if fingerprinted && unchanged {
+            response
+                .headers
+                .insert(SimpleHeader::CACHE_CONTROL, IMMUTABLE_CACHE_CONTROL.into());
+        }
+        Some(response)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// `build_manifest` hashes every file listed in `files`, skipping the ones
+/// `lookup` cannot find.
+pub(crate) fn build_manifest(
+    files: &[String],
+    lookup: impl Fn(&str) -> Option<rust_embed::EmbeddedFile>,
+) -> AssetManifest {
+    AssetManifest::new(files.iter().filter_map(|path| {
+        let hash = hash_hex(&lookup(path)?.metadata.sha256_hash());
+        Some(ManifestEntry {
+            fingerprinted: fingerprint(path, &hash[..FINGERPRINT_LENGTH]),
+            path: path.clone(),
+            hash,
+        })
+    }))
+}
+
+/// `fingerprint` inserts `digest` before the extension of the filename in
+/// `path`, or appends it when there is none.
+fn fingerprint(path: &str, digest: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let dot = name_start + dot;
+            format!("{}.{digest}{}", &path[..dot], &path[dot..])
+        }
+        _ => format!("{path}.{digest}"),
+    }
+}
+
+#[cfg(test)]
+mod manifest_tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::directorate::Directorate;
+    use crate::wire::simple_http::Status;
+
+    #[derive(rust_embed::Embed, Default)]
+    #[folder = "test_directory/"]
+    struct Directory;
+
+    #[test]
+    fn fingerprints_before_the_extension() {
+        assert_eq!(fingerprint("js/app.js", "3fa9c2d1"), "js/app.3fa9c2d1.js");
+        assert_eq!(
+            fingerprint("app.min.css", "3fa9c2d1"),
+            "app.min.3fa9c2d1.css"
+        );
+        assert_eq!(
+            fingerprint("docs.d/LICENSE", "3fa9c2d1"),
+            "docs.d/LICENSE.3fa9c2d1"
+        );
+        assert_eq!(fingerprint(".env", "3fa9c2d1"), ".env.3fa9c2d1");
+    }
+
+    #[test]
+    fn resolves_and_serves_fingerprinted_files() {
+        let directorate: Box<dyn PackageDirectorate> = Directorate::<Directory>::default().into();
+        let manifest = directorate.manifest();
+        assert_eq!(manifest.entries().count(), 5);
+
+        let entry = manifest.get("/elem.js").expect("should have entry");
+        let url = manifest.url_for("elem.js").expect("should have url");
+        assert_eq!(url, format!("elem.{}.js", &entry.hash[..8]));
+        assert_eq!(manifest.resolve(url).map(|(_, hit)| hit), Some(true));
+        assert_eq!(manifest.resolve("elem.js").map(|(_, hit)| hit), Some(false));
+
+        let immutable = manifest
+            .file_response(directorate.as_ref(), url, &BTreeMap::new())
+            .expect("should serve file");
+        assert!(matches!(immutable.status, Status::OK));
+        assert_eq!(
+            immutable
+                .headers
+                .get(&SimpleHeader::CACHE_CONTROL)
+                .map(String::as_str),
+            Some(IMMUTABLE_CACHE_CONTROL)
+        );
+
+        let plain = manifest
+            .file_response(directorate.as_ref(), "elem.js", &BTreeMap::new())
+            .expect("should serve file");
+        assert_eq!(
+            plain
+                .headers
+                .get(&SimpleHeader::CACHE_CONTROL)
+                .map(String::as_str),
+            Some("no-cache")
+        );
+
+        let loaded = AssetManifest::from_json(&manifest.to_json().expect("should encode"))
+            .expect("should decode");
+        assert_eq!(loaded, manifest);
+    }
+
+    #[test]
+    fn changed_files_are_not_served_as_immutable() {
+        let embedded: Box<dyn PackageDirectorate> = Directorate::<Directory>::default().into();
+        let manifest = embedded.manifest();
+        let url = manifest.url_for("elem.js").expect("should have url");
+
+        let overlay = std::env::temp_dir().join(format!("ewe-manifest-{}", std::process::id()));
+        std::fs::create_dir_all(&overlay).expect("should create overlay");
+        std::fs::write(overlay.join("elem.js"), "edited").expect("should write");
+        let directorate: Box<dyn PackageDirectorate> = Directorate::<Directory>::default()
+            .with_overlay(&overlay)
+            .into();
+
+        let response = manifest
+            .file_response(directorate.as_ref(), url, &BTreeMap::new())
+            .expect("should serve file");
+        assert_eq!(
+            response
+                .headers
+                .get(&SimpleHeader::CACHE_CONTROL)
+                .map(String::as_str),
+            Some("no-cache")
+        );
+
+        std::fs::remove_dir_all(&overlay).expect("should clean up");
+    }
+}
@@ -3,6 +3,20 @@
 use rust_embed;
 use std::marker::PhantomData;
 
+use crate::wire::simple_http::{SimpleHeaders, SimpleOutgoingResponse};
+
+mod glob;
+mod manifest;
+mod overlay;
+mod responses;
+mod tree;
+
+pub use glob::*;
+pub use manifest::*;
+pub use overlay::*;
+pub use responses::*;
+pub use tree::*;
+
 pub type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 pub struct Directorate<T: rust_embed::RustEmbed> {
@@ -36,6 +50,42 @@ pub trait PackageDirectorate {
 
     /// Returns all filenames for giving root directory.
     fn files_for(&self, directory: &str) -> Option<Vec<String>>;
+
+    /// tree returns all files arranged by directory, with their sizes and hashes.
+    fn tree(&self) -> DirectoryEntry {
+        tree::build_tree(&self.as_vec(), |file| self.get_file(file))
+    }
+
+    /// `directories_under` returns all directories at any depth below `path`,
+    /// an empty `path` lists every directory in the package.
+    fn directories_under(&self, path: &str) -> Vec<String> {
+        tree::directories_in(&self.as_vec(), path)
+    }
+
+    /// `manifest` returns the hashes and fingerprinted names of all files.
+    fn manifest(&self) -> AssetManifest {
+        manifest::build_manifest(&self.as_vec(), |file| self.get_file(file))
+    }
+
+    /// `files_matching` returns all files matching the glob `pattern`, see [`Glob`].
+    fn files_matching(&self, pattern: &str) -> Vec<String> {
+        let glob = Glob::new(pattern);
+        self.as_vec()
+            .into_iter()
+            .filter(|file| glob.is_match(file))
+            .collect()
+    }
+
+    /// `file_response` serves `target_file` to a request with the given headers,
+    /// see [`EmbeddedFileResponse`], returning `None` when the file does not exist.
+    fn file_response(
+        &self,
+        target_file: &str,
+        request_headers: &SimpleHeaders,
+    ) -> Option<SimpleOutgoingResponse> {
+        self.get_file(target_file.trim_start_matches('/'))
+            .map(|file| file.to_response(request_headers))
+    }
 }
 
 impl<T: rust_embed::Embed + 'static> Into<Box<dyn PackageDirectorate>> for Directorate<T> {
@@ -127,6 +177,56 @@ mod directorate_tests {
         );
     }
 
+    #[test]
+    fn validate_can_read_directory_tree() {
+        let generator = Directorate::<Directory>::default();
+        assert_eq!(
+            generator.directories_under(""),
+            vec! {"docs", "schema", "schema/partials"}
+        );
+        assert_eq!(generator.directories_under("schema/"), vec! {"schema/partials"});
+
+        let tree = generator.tree();
+        let names: Vec<&str> = match &tree {
+            DirectoryEntry::Directory { children, .. } => {
+                children.iter().map(DirectoryEntry::name).collect()
+            }
+            DirectoryEntry::File { .. } => panic!("root should be a directory"),
+        };
+        assert_eq!(names, vec! {"docs", "schema", "README.md", "elem.js"});
+
+        let partial = tree
+            .find("schema/partials/partial_1.sql")
+            .expect("should find partial");
+        let embedded = generator
+            .get_file("schema/partials/partial_1.sql")
+            .expect("should find file");
+        assert_eq!(partial.path(), "schema/partials/partial_1.sql");
+        assert_eq!(partial.size(), embedded.data.len());
+        assert_eq!(partial.hash_hex().map(|hash| hash.len()), Some(64));
+
+        let schema = tree.find("schema").expect("should find schema");
+        assert!(schema.is_dir());
+        assert_eq!(
+            schema.size(),
+            partial.size() + generator.get_file("schema/schema.sql").unwrap().data.len()
+        );
+    }
+
+    #[test]
+    fn validate_can_select_files_by_glob() {
+        let generator = Directorate::<Directory>::default();
+        assert_eq!(
+            generator.files_matching("schema/**/*.sql"),
+            vec! {"schema/partials/partial_1.sql", "schema/schema.sql"}
+        );
+        assert_eq!(
+            generator.files_matching("{*.md,**/*.sh}"),
+            vec! {"README.md", "docs/runner.sh"}
+        );
+        assert_eq!(generator.files_matching("**/*.rs"), Vec::<String>::new());
+    }
+
     #[test]
     fn validate_can_read_all_directories() {
         let generator = Directorate::<Directory>::default();
@@ -0,0 +1,202 @@
+// Provides a Directorate that prefers files from a real directory over the embedded ones.
+
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+
+use rust_embed;
+
+use super::{Directorate, PackageDirectorate};
+
+/// `OverlayDirectorate` serves files from an overlay directory on disk
+/// first and falls back to the embedded assets of `T`.
+///
+/// Edits made in the overlay are picked up on the next lookup, which is
+/// what development servers want, while release builds can keep using a
+/// plain [`Directorate`]:
+///
+/// ```ignore
+/// let assets: Box<dyn PackageDirectorate> = if cfg!(debug_assertions) {
+///     Box::new(Directorate::<Assets>::default().with_overlay("./assets"))
+/// } else {
+///     Box::new(Directorate::<Assets>::default())
+/// };
+/// ```
+pub struct OverlayDirectorate<T: rust_embed::RustEmbed> {
+    overlay: PathBuf,
+    _data: PhantomData<T>,
+}
+
+// -- Constructors
+
+impl<T: rust_embed::Embed> OverlayDirectorate<T> {
+    pub fn new(overlay: impl Into<PathBuf>) -> Self {
+        Self {
+            overlay: overlay.into(),
+            _data: PhantomData,
+        }
+    }
+}
+
+impl<T: rust_embed::Embed> Directorate<T> {
+    /// `with_overlay` turns the directorate into an [`OverlayDirectorate`]
+    /// checking `path` before the embedded assets.
+    pub fn with_overlay(self, path: impl Into<PathBuf>) -> OverlayDirectorate<T> {
+        OverlayDirectorate::new(path)
+    }
+}
+
+// -- Methods
+
+impl<T: rust_embed::Embed> OverlayDirectorate<T> {
+    pub fn overlay(&self) -> &Path {
+        &self.overlay
+    }
+
+    /// `overlay_path` maps `target_file` into the overlay directory,
+    /// refusing paths that would escape it.
+    fn overlay_path(&self, target_file: &str) -> Option<PathBuf> {
+        let relative = Path::new(target_file);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return None;
+        }
+        Some(self.overlay.join(relative))
+    }
+
+    /// `overlay_files` lists the files under the overlay directory as
+    /// `/` separated paths relative to it.
+    fn overlay_files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.overlay.clone()];
+        while let Some(directory) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&directory) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.overlay) {
+                    let parts: Vec<String> = relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                        .collect();
+                    files.push(parts.join("/"));
+                }
+            }
+        }
+        files
+    }
+}
+
+impl<T: rust_embed::Embed + 'static> From<OverlayDirectorate<T>> for Box<dyn PackageDirectorate> {
+    fn from(directorate: OverlayDirectorate<T>) -> Self {
+        Box::new(directorate)
+    }
+}
+
+impl<T: rust_embed::Embed> PackageDirectorate for OverlayDirectorate<T> {
+    fn get_file(&self, target_file: &str) -> Option<rust_embed::EmbeddedFile> {
+        if let Some(path) = self.overlay_path(target_file) {
+            if path.is_file() {
+                match rust_embed::utils::read_file_from_fs(&path) {
+                    Ok(file) => return Some(file),
+                    Err(err) => {
+                        tracing::warn!("Failed to read overlay file {:?}: {:?}", path, err);
+                    }
+                }
+            }
+        }
+        T::get(target_file)
+    }
+
+    /// files only lists the embedded files, as `rust_embed::Filenames`
+    /// cannot hold the overlay's, use `as_vec` for the merged listing.
+    fn files(&self) -> rust_embed::Filenames {
+        T::iter()
+    }
+
+    fn top_directories(&self) -> Vec<String> {
+        let mut dirs: Vec<String> = self
+            .as_vec()
+            .iter()
+            .filter_map(|t| {
+                t.split_once('/')
+                    .map(|(directory, _)| directory.to_string())
+            })
+            .collect();
+
+        dirs.dedup();
+        dirs
+    }
+
+    fn as_vec(&self) -> Vec<String> {
+        let mut files: Vec<String> = T::iter().map(String::from).collect();
+        files.extend(self.overlay_files());
+
+        // sort and de-dup
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    fn files_for(&self, directory: &str) -> Option<Vec<String>> {
+        let target_dir = if directory.ends_with('/') {
+            directory.to_string()
+        } else {
+            format!("{directory}/")
+        };
+
+        let files: Vec<String> = self
+            .as_vec()
+            .into_iter()
+            .filter(|t| t.starts_with(&target_dir))
+            .collect();
+
+        if files.is_empty() {
+            return None;
+        }
+
+        Some(files)
+    }
+}
+
+#[cfg(test)]
+mod overlay_directorate_tests {
+    use super::*;
+
+    #[derive(rust_embed::Embed, Default)]
+    #[folder = "test_directory/"]
+    struct Directory;
+
+    #[test]
+    fn overlay_files_shadow_embedded_ones() {
+        let overlay = std::env::temp_dir().join(format!("ewe-overlay-{}", std::process::id()));
+        std::fs::create_dir_all(overlay.join("styles")).expect("should create overlay");
+        std::fs::write(overlay.join("README.md"), "edited").expect("should write");
+        std::fs::write(overlay.join("styles/site.css"), "body {}").expect("should write");
+
+        let directorate = Directorate::<Directory>::default().with_overlay(&overlay);
+
+        let readme = directorate
+            .get_file("README.md")
+            .expect("should find readme");
+        assert_eq!(readme.data.as_ref(), b"edited");
+        assert!(directorate.get_file("schema/schema.sql").is_some());
+        assert!(directorate.get_file("../README.md").is_none());
+
+        assert_eq!(
+            directorate.top_directories(),
+            vec!["docs", "schema", "styles"]
+        );
+        assert_eq!(
+            directorate.files_for("styles"),
+            Some(vec!["styles/site.css".to_string()])
+        );
+        assert_eq!(directorate.as_vec().len(), 6);
+
+        std::fs::remove_dir_all(&overlay).expect("should clean up");
+    }
+}
@@ -0,0 +1,218 @@
+// Provides the bridge from embedded files to simple_http responses.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rust_embed;
+
+use super::tree::hash_hex;
+use crate::wire::simple_http::{
+    parse_cookie_date, SimpleHeader, SimpleHeaders, SimpleOutgoingResponse, Status,
+};
+
+/// `EmbeddedFileResponse` turns an embedded file into a ready response
+/// carrying its `Content-Type`, a strong `ETag` from the embedded sha256
+/// hash and its `Last-Modified` date, answering conditional requests with
+/// `304 Not Modified`:
+///
+/// ```ignore
+/// let response = directorate
+///     .file_response("index.html", &request.headers)
+///     .unwrap_or_else(not_found);
+/// ```
+pub trait EmbeddedFileResponse {
+    /// `etag` is the strong entity tag of the file, quoted.
+    fn etag(&self) -> String;
+
+    /// `last_modified_date` is the modification time of the file as an
+    /// HTTP date, when `rust_embed` recorded one.
+    fn last_modified_date(&self) -> Option<String>;
+
+    /// `is_not_modified` returns true when the conditional headers of a
+    /// request show the client already holds this version of the file.
+    fn is_not_modified(&self, request_headers: &SimpleHeaders) -> bool;
+
+    /// `to_response` returns the file as a `200 OK` response or an empty
+    /// `304 Not Modified` one following [`Self::is_not_modified`].
+    fn to_response(&self, request_headers: &SimpleHeaders) -> SimpleOutgoingResponse;
+}
+
+impl EmbeddedFileResponse for rust_embed::EmbeddedFile {
+    fn etag(&self) -> String {
+        let hex = hash_hex(&self.metadata.sha256_hash());
+        format!("\"{hex}\"")
+    }
+
+    fn last_modified_date(&self) -> Option<String> {
+        self.metadata
+            .last_modified()
+            .map(|seconds| format_http_date(UNIX_EPOCH + Duration::from_secs(seconds)))
+    }
+
+    fn is_not_modified(&self, request_headers: &SimpleHeaders) -> bool {
+        // If-None-Match takes precedence over If-Modified-Since, RFC 9110 section 13.2.2.
+        if let Some(candidates) = request_headers.get(&SimpleHeader::IF_NONE_MATCH) {
+            let etag = self.etag();
+            return candidates
+                .split(',')
+                .map(str::trim)
+                .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag);
+        }
+
+        let Some(since) = request_headers
+            .get(&SimpleHeader::IF_MODIFIED_SINCE)
+            .and_then(|value| parse_cookie_date(value))
+        else {
+            return false;
+        };
+
+        self.metadata
+            .last_modified()
+            .is_some_and(|seconds| UNIX_EPOCH + Duration::from_secs(seconds) <= since)
+    }
+
+    fn to_response(&self, request_headers: &SimpleHeaders) -> SimpleOutgoingResponse {
+        let mut builder = SimpleOutgoingResponse::builder()
+            .add_header(SimpleHeader::ETAG, self.etag())
+            .add_header(SimpleHeader::CACHE_CONTROL, "no-cache");
+
+        if let Some(last_modified) = self.last_modified_date() {
+            builder = builder.add_header(SimpleHeader::LAST_MODIFIED, last_modified);
+        }
+
+        builder = if self.is_not_modified(request_headers) {
+            builder.with_status(Status::NotModified)
+        } else {
+            builder
+                .with_status(Status::OK)
+                .add_header(SimpleHeader::CONTENT_TYPE, self.metadata.mimetype())
+                .with_body_bytes(self.data.as_ref())
+        };
+
+        builder
+            .build()
+            .expect("should build embedded file response")
+    }
+}
+
+/// `format_http_date` formats `time` as an IMF-fixdate,
+/// e.g `Sun, 06 Nov 1994 08:49:37 GMT`.
+#[must_use]
+pub fn format_http_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // civil date from days since 1970-01-01, see days_since_epoch in cookies.
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = (month_index + 2) % 12;
+    let year = year_of_era + era * 400 + u64::from(month < 2);
+
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} GMT",
+        DAYS[(days % 7) as usize],
+        MONTHS[month as usize],
+        seconds_of_day / 3_600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+    )
+}
+
+#[cfg(test)]
+mod embedded_response_tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::directorate::{Directorate, PackageDirectorate};
+    use crate::wire::simple_http::SimpleBody;
+
+    #[derive(rust_embed::Embed, Default)]
+    #[folder = "test_directory/"]
+    struct Directory;
+
+    #[test]
+    fn formats_http_dates() {
+        assert_eq!(
+            format_http_date(UNIX_EPOCH + Duration::from_secs(784_111_777)),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            format_http_date(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    fn serves_embedded_files_with_caching_headers() {
+        let directorate: Box<dyn PackageDirectorate> = Directorate::<Directory>::default().into();
+
+        let response = directorate
+            .file_response("/elem.js", &BTreeMap::new())
+            .expect("should find file");
+        let etag = response
+            .headers
+            .get(&SimpleHeader::ETAG)
+            .cloned()
+            .expect("should have etag");
+        assert!(matches!(response.status, Status::OK));
+        assert_eq!(etag.len(), 66);
+        assert_eq!(
+            response
+                .headers
+                .get(&SimpleHeader::CONTENT_TYPE)
+                .map(String::as_str),
+            Some("text/javascript")
+        );
+        assert!(matches!(response.body, Some(SimpleBody::Bytes(ref body)) if !body.is_empty()));
+
+        let mut conditional = BTreeMap::new();
+        conditional.insert(SimpleHeader::IF_NONE_MATCH, format!("\"other\", W/{etag}"));
+        let cached = directorate
+            .file_response("elem.js", &conditional)
+            .expect("should find file");
+        assert!(matches!(cached.status, Status::NotModified));
+        assert!(matches!(cached.body, Some(SimpleBody::None)));
+
+        conditional.insert(SimpleHeader::IF_NONE_MATCH, "\"other\"".into());
+        let changed = directorate
+            .file_response("elem.js", &conditional)
+            .expect("should find file");
+        assert!(matches!(changed.status, Status::OK));
+
+        assert!(directorate
+            .file_response("missing.js", &BTreeMap::new())
+            .is_none());
+    }
+
+    #[test]
+    fn honours_if_modified_since() {
+        let file = Directorate::<Directory>::default()
+            .get_file("README.md")
+            .expect("should find file");
+        let Some(last_modified) = file.last_modified_date() else {
+            return;
+        };
+
+        let mut headers = BTreeMap::new();
+        headers.insert(SimpleHeader::IF_MODIFIED_SINCE, last_modified);
+        assert!(file.is_not_modified(&headers));
+
+        headers.insert(
+            SimpleHeader::IF_MODIFIED_SINCE,
+            "Thu, 01 Jan 1970 00:00:00 GMT".into(),
+        );
+        assert!(!file.is_not_modified(&headers));
+    }
+}
@@ -0,0 +1,181 @@
+// Provides a hierarchical view over the flat file listing of a directorate.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use rust_embed;
+
+/// `DirectoryEntry` is a node of the tree returned by
+/// [`super::PackageDirectorate::tree`], paths are `/` separated and
+/// relative to the directorate root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DirectoryEntry {
+    Directory {
+        name: String,
+        path: String,
+        children: Vec<DirectoryEntry>,
+    },
+    File {
+        name: String,
+        path: String,
+        size: usize,
+        hash: [u8; 32],
+    },
+}
+
+impl DirectoryEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Directory { name, .. } | Self::File { name, .. } => name,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Directory { path, .. } | Self::File { path, .. } => path,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Self::Directory { .. })
+    }
+
+    /// `size` is the size of a file or the total size of all files under
+    /// a directory.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::File { size, .. } => *size,
+            Self::Directory { children, .. } => children.iter().map(Self::size).sum(),
+        }
+    }
+
+    /// `hash_hex` is the sha256 hash of a file as lowercase hex, directories
+    /// have none.
+    pub fn hash_hex(&self) -> Option<String> {
+        match self {
+            Self::File { hash, .. } => Some(hash_hex(hash)),
+            Self::Directory { .. } => None,
+        }
+    }
+
+    /// `find` returns the entry at `path` below this one.
+    pub fn find(&self, path: &str) -> Option<&DirectoryEntry> {
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        let (head, rest) = path.split_once('/').unwrap_or((path, ""));
+        match self {
+            Self::Directory { children, .. } => children
+                .iter()
+                .find(|child| child.name() == head)
+                .and_then(|child| child.find(rest)),
+            Self::File { .. } => None,
+        }
+    }
+}
+
+/// `hash_hex` formats a sha256 hash as lowercase hex.
+pub(crate) fn hash_hex(hash: &[u8; 32]) -> String {
+    hash.iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// `build_tree` arranges `files` into a tree, directories first and then
+/// files, both by name. `lookup` provides the size and hash of files.
+pub(crate) fn build_tree(
+    files: &[String],
+    lookup: impl Fn(&str) -> Option<rust_embed::EmbeddedFile>,
+) -> DirectoryEntry {
+    let mut root = DirectoryEntry::Directory {
+        name: String::new(),
+        path: String::new(),
+        children: Vec::new(),
+    };
+
+    for file in files {
+        let (size, hash) = lookup(file).map_or((0, [0; 32]), |embedded| {
+            (embedded.data.len(), embedded.metadata.sha256_hash())
+        });
+        insert_file(&mut root, file, size, hash);
+    }
+
+    sort_children(&mut root);
+    root
+}
+
+fn insert_file(root: &mut DirectoryEntry, file: &str, size: usize, hash: [u8; 32]) {
+    let parts: Vec<&str> = file.split('/').filter(|part| !part.is_empty()).collect();
+    let Some((name, directories)) = parts.split_last() else {
+        return;
+    };
+
+    let mut current = root;
+    for (depth, directory) in directories.iter().enumerate() {
+        let DirectoryEntry::Directory { children, .. } = current else {
+            return;
+        };
+
+        let position = if let Some(position) = children
+            .iter()
+            .position(|child| child.is_dir() && child.name() == *directory)
+        {
+            position
+        } else {
+            children.push(DirectoryEntry::Directory {
+                name: (*directory).to_string(),
+                path: directories[..=depth].join("/"),
+                children: Vec::new(),
+            });
+            children.len() - 1
+        };
+        current = &mut children[position];
+    }
+
+    if let DirectoryEntry::Directory { children, .. } = current {
+        children.push(DirectoryEntry::File {
+            name: (*name).to_string(),
+            path: parts.join("/"),
+            size,
+            hash,
+        });
+    }
+}
+
+fn sort_children(entry: &mut DirectoryEntry) {
+    if let DirectoryEntry::Directory { children, .. } = entry {
+        children.sort_by(|left, right| {
+            right
+                .is_dir()
+                .cmp(&left.is_dir())
+                .then_with(|| left.name().cmp(right.name()))
+        });
+        children.iter_mut().for_each(sort_children);
+    }
+}
+
+/// `directories_in` returns every directory at any depth below `path`
+/// found in `files`, sorted. An empty `path` is the root.
+pub(crate) fn directories_in(files: &[String], path: &str) -> Vec<String> {
+    let prefix = path.trim_matches('/');
+    let prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{prefix}/")
+    };
+
+    let mut directories = BTreeSet::new();
+    for file in files.iter().filter(|file| file.starts_with(&prefix)) {
+        let mut end = prefix.len();
+        while let Some(offset) = file[end..].find('/') {
+            end += offset;
+            directories.insert(file[..end].to_string());
+            end += 1;
+        }
+    }
+    directories.into_iter().collect()
+}
@@ -0,0 +1,538 @@
+use std::any::{Any, TypeId};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use tokio::sync::Notify;
+
+use super::{Topic, TopicPattern};
+
+/// `DEFAULT_QUEUE_CAPACITY` is the queue size of [`EventBus::subscribe`].
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// `OverflowPolicy` decides what happens to an event published to a
+/// subscriber whose queue is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// the oldest queued event is dropped to make room.
+    DropOldest,
+
+    /// the new event is dropped.
+    DropNewest,
+
+    /// [`EventBus::publish`] waits until the subscriber makes room.
+    Block,
+}
+
+/// `Event` is what subscribers receive, the topic tells which one matched
+/// their pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event<T> {
+    pub topic: Arc<str>,
+    pub payload: T,
+}
+
+/// `Delivery` reports what happened to a published event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Delivery {
+    /// subscribers the event was queued for.
+    pub delivered: usize,
+
+    /// subscribers which missed the event, or dropped an older one for it,
+    /// because their queue was full.
+    pub dropped: usize,
+}
+
+/// `EventBus` is a topic based publish/subscribe bus, each subscriber gets
+/// its own bounded queue so a slow subscriber never holds events back from
+/// the others, unless it asked publishers to wait with
+/// [`OverflowPolicy::Block`].
+///
+/// An `EventBus` is cheap to clone, all clones share the same subscribers.
+/// Subscriptions end when dropped, and receive `None` once every clone of
+/// the bus is gone.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    inner: Arc<BusInner>,
+}
+
+#[derive(Default)]
+struct BusInner {
+    next_id: AtomicU64,
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+struct Subscriber {
+    id: u64,
+    pattern: TopicPattern,
+    type_id: TypeId,
+    queue: Arc<dyn AnyQueue>,
+}
+
+impl std::fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.subscriber_count())
+            .finish()
+    }
+}
+
+// -- Constructors
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// -- Methods
+
+impl EventBus {
+    /// `subscribe` receives the events of type `T` published to topics
+    /// matching `pattern`, queueing up to [`DEFAULT_QUEUE_CAPACITY`] of them
+    /// and dropping the oldest beyond that.
+    pub fn subscribe<T: Send + 'static>(&self, pattern: &str) -> Subscription<T> {
+        self.subscribe_with(pattern, DEFAULT_QUEUE_CAPACITY, OverflowPolicy::DropOldest)
+    }
+
+    /// `subscribe_with` is [`Self::subscribe`] with the giving queue
+    /// `capacity` and overflow `policy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn subscribe_with<T: Send + 'static>(
+        &self,
+        pattern: &str,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Subscription<T> {
+        assert!(capacity > 0, "capacity should be greater than zero");
+
+        let queue = Arc::new(Queue::<T>::new(capacity, policy));
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .subscribers
+            .lock()
+            .expect("should acquire subscribers lock")
+            .push(Subscriber {
+                id,
+                pattern: TopicPattern::new(pattern),
+                type_id: TypeId::of::<T>(),
+                queue: queue.clone(),
+            });
+
+        Subscription {
+            id,
+            queue,
+            bus: Arc::downgrade(&self.inner),
+        }
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.inner
+            .subscribers
+            .lock()
+            .expect("should acquire subscribers lock")
+            .len()
+    }
+
+    /// `publish` queues `payload` for every matching subscriber, waiting
+    /// for room in the queues of subscribers using [`OverflowPolicy::Block`].
+    pub async fn publish<T: Clone + Send + 'static>(
+        &self,
+        topic: &Topic<T>,
+        payload: T,
+    ) -> Delivery {
+        let (name, queues) = self.matching(topic);
+        let mut delivery = Delivery::default();
+        let payloads = fan_out(payload, queues.len());
+        for (queue, payload) in queues.into_iter().zip(payloads) {
+            let event = Event {
+                topic: name.clone(),
+                payload,
+            };
+            delivery.record(queue.push_waiting(event).await);
+        }
+        delivery
+    }
+
+    /// `try_publish` is [`Self::publish`] without waiting, subscribers
+    /// using [`OverflowPolicy::Block`] whose queue is full miss the event.
+    pub fn try_publish<T: Clone + Send + 'static>(&self, topic: &Topic<T>, payload: T) -> Delivery {
+        let (name, queues) = self.matching(topic);
+        let mut delivery = Delivery::default();
+        let payloads = fan_out(payload, queues.len());
+        for (queue, payload) in queues.into_iter().zip(payloads) {
+            let event = Event {
+                topic: name.clone(),
+                payload,
+            };
+            delivery.record(queue.push(event).unwrap_or(Pushed::Dropped));
+        }
+        delivery
+    }
+
+    fn matching<T: Send + 'static>(&self, topic: &Topic<T>) -> (Arc<str>, Vec<Arc<Queue<T>>>) {
+        let subscribers = self
+            .inner
+            .subscribers
+            .lock()
+            .expect("should acquire subscribers lock");
+
+        let queues = subscribers
+            .iter()
+            .filter(|subscriber| {
+                subscriber.type_id == TypeId::of::<T>() && subscriber.pattern.is_match(topic.name())
+            })
+            .filter_map(|subscriber| {
+                subscriber
+                    .queue
+                    .clone()
+                    .as_any()
+                    .downcast::<Queue<T>>()
+                    .ok()
+            })
+            .collect();
+        (Arc::from(topic.name()), queues)
+    }
+}
+
+impl Drop for BusInner {
+    fn drop(&mut self) {
+        let subscribers = self
+            .subscribers
+            .get_mut()
+            .expect("should acquire subscribers lock");
+        for subscriber in subscribers.drain(..) {
+            subscriber.queue.close();
+        }
+    }
+}
+
+/// `fan_out` yields `count` copies of `payload`, moving it into the last one.
+fn fan_out<T: Clone>(payload: T, count: usize) -> impl Iterator<Item = T> {
+    let mut payload = Some(payload);
+    (1..=count).filter_map(move |index| {
+        if index == count {
+            payload.take()
+        } else {
+            payload.clone()
+        }
+    })
+}
+
+impl Delivery {
+    fn record(&mut self, pushed: Pushed) {
+        match pushed {
+            Pushed::Queued => self.delivered += 1,
+            Pushed::ReplacedOldest => {
+                self.delivered += 1;
+                self.dropped += 1;
+            }
+            Pushed::Dropped => self.dropped += 1,
+        }
+    }
+}
+
+/// `Subscription` receives the events matching its pattern, unsubscribing
+/// when dropped.
+pub struct Subscription<T> {
+    id: u64,
+    queue: Arc<Queue<T>>,
+    bus: Weak<BusInner>,
+}
+
+impl<T> std::fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// `recv` waits for the next event, returning `None` once the bus is
+    /// gone and the queue was drained.
+    pub async fn recv(&self) -> Option<Event<T>> {
+        loop {
+            let notified = self.queue.readable.notified();
+            {
+                let mut state = self.queue.state.lock().expect("should acquire queue lock");
+                if let Some(event) = state.events.pop_front() {
+                    drop(state);
+                    self.queue.writable.notify_one();
+                    return Some(event);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// `try_recv` returns the next queued event without waiting.
+    pub fn try_recv(&self) -> Option<Event<T>> {
+        let event = self
+            .queue
+            .state
+            .lock()
+            .expect("should acquire queue lock")
+            .events
+            .pop_front();
+        if event.is_some() {
+            self.queue.writable.notify_one();
+        }
+        event
+    }
+
+    /// `dropped` returns how many events this subscriber lost to overflow.
+    pub fn dropped(&self) -> u64 {
+        self.queue
+            .state
+            .lock()
+            .expect("should acquire queue lock")
+            .dropped
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        // publishers waiting for room in our queue give up on it.
+        self.queue.close();
+        if let Some(bus) = self.bus.upgrade() {
+            bus.subscribers
+                .lock()
+                .expect("should acquire subscribers lock")
+                .retain(|subscriber| subscriber.id != self.id);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Pushed {
+    Queued,
+    ReplacedOldest,
+    Dropped,
+}
+
+trait AnyQueue: Send + Sync {
+    fn close(&self);
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+struct Queue<T> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    state: Mutex<QueueState<T>>,
+    readable: Notify,
+    writable: Notify,
+}
+
+struct QueueState<T> {
+    events: VecDeque<Event<T>>,
+    dropped: u64,
+    closed: bool,
+}
+
+impl<T: Send + 'static> AnyQueue for Queue<T> {
+    fn close(&self) {
+        Queue::close(self);
+    }
+
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+impl<T> Queue<T> {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            capacity,
+            policy,
+            state: Mutex::new(QueueState {
+                events: VecDeque::with_capacity(capacity),
+                dropped: 0,
+                closed: false,
+            }),
+            readable: Notify::new(),
+            writable: Notify::new(),
+        }
+    }
+
+    /// `close` marks the queue closed once the bus or the subscription is
+    /// gone, waking both receivers and blocked publishers.
+    fn close(&self) {
+        self.state.lock().expect("should acquire queue lock").closed = true;
+        self.readable.notify_waiters();
+        self.writable.notify_waiters();
+    }
+
+    /// `push` queues the event following the overflow policy, handing it
+    /// back when the policy is to block and the queue is full. Events for a
+    /// closed queue are dropped.
+    fn push(&self, event: Event<T>) -> Result<Pushed, Event<T>> {
+        let mut state = self.state.lock().expect("should acquire queue lock");
+        let pushed = if state.closed {
+            Pushed::Dropped
+        } else if state.events.len() < self.capacity {
+            state.events.push_back(event);
+            Pushed::Queued
+        } else {
+            match self.policy {
+                OverflowPolicy::Block => return Err(event),
+                OverflowPolicy::DropNewest => {
+                    state.dropped += 1;
+                    Pushed::Dropped
+                }
+                OverflowPolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.events.push_back(event);
+                    state.dropped += 1;
+                    Pushed::ReplacedOldest
+                }
+            }
+        };
+        drop(state);
+
+        if !matches!(pushed, Pushed::Dropped) {
+            self.readable.notify_one();
+        }
+        Ok(pushed)
+    }
+
+    async fn push_waiting(&self, mut event: Event<T>) -> Pushed {
+        loop {
+            let writable = self.writable.notified();
+            match self.push(event) {
+                Ok(pushed) => return pushed,
+                Err(returned) => event = returned,
+            }
+            writable.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod event_bus_tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    const BUILD_STARTED: Topic<u32> = Topic::new("build.started");
+    const BUILD_FINISHED: Topic<u32> = Topic::new("build.finished");
+    const RELOAD: Topic<String> = Topic::new("reload.assets");
+
+    #[test]
+    fn delivers_by_topic_pattern_and_type() {
+        let bus = EventBus::new();
+        let builds = bus.subscribe::<u32>("build.*");
+        let finished = bus.subscribe::<u32>("build.finished");
+        let reloads = bus.subscribe::<String>("**");
+
+        assert_eq!(bus.try_publish(&BUILD_STARTED, 1).delivered, 1);
+        assert_eq!(bus.try_publish(&BUILD_FINISHED, 2).delivered, 2);
+        assert_eq!(
+            bus.try_publish(&RELOAD, String::from("app.css")).delivered,
+            1
+        );
+
+        let received: Vec<(String, u32)> = std::iter::from_fn(|| builds.try_recv())
+            .map(|event| (event.topic.to_string(), event.payload))
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                (String::from("build.started"), 1),
+                (String::from("build.finished"), 2)
+            ]
+        );
+        assert_eq!(finished.try_recv().map(|event| event.payload), Some(2));
+        assert_eq!(
+            reloads.try_recv().map(|event| event.payload),
+            Some(String::from("app.css"))
+        );
+
+        drop(finished);
+        assert_eq!(bus.subscriber_count(), 2);
+    }
+
+    #[test]
+    fn applies_overflow_policies() {
+        let bus = EventBus::new();
+        let oldest = bus.subscribe_with::<u32>("build.*", 2, OverflowPolicy::DropOldest);
+        let newest = bus.subscribe_with::<u32>("build.*", 2, OverflowPolicy::DropNewest);
+
+        for id in 1..=3 {
+            bus.try_publish(&BUILD_STARTED, id);
+        }
+
+        let payloads = |subscription: &Subscription<u32>| -> Vec<u32> {
+            std::iter::from_fn(|| subscription.try_recv())
+                .map(|event| event.payload)
+                .collect()
+        };
+        assert_eq!(payloads(&oldest), vec![2, 3]);
+        assert_eq!(payloads(&newest), vec![1, 2]);
+        assert_eq!((oldest.dropped(), newest.dropped()), (1, 1));
+    }
+
+    #[test]
+    fn blocking_subscribers_hold_publishers_back() {
+        let bus = EventBus::new();
+        let slow = bus.subscribe_with::<u32>("build.*", 1, OverflowPolicy::Block);
+
+        assert_eq!(bus.try_publish(&BUILD_STARTED, 1).delivered, 1);
+        assert_eq!(bus.try_publish(&BUILD_STARTED, 2).dropped, 1);
+
+        block_on(async {
+            let publisher = bus.clone();
+            let publishing =
+                tokio::spawn(async move { publisher.publish(&BUILD_FINISHED, 3).await });
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            assert!(!publishing.is_finished());
+            assert_eq!(slow.recv().await.map(|event| event.payload), Some(1));
+
+            let delivery = publishing.await.expect("should publish");
+            assert_eq!(delivery.delivered, 1);
+            assert_eq!(slow.recv().await.map(|event| event.payload), Some(3));
+
+            drop(bus);
+            assert_eq!(slow.recv().await, None);
+        });
+    }
+
+    #[test]
+    fn dropping_a_full_subscription_releases_blocked_publishers() {
+        let bus = EventBus::new();
+        let slow = bus.subscribe_with::<u32>("build.*", 1, OverflowPolicy::Block);
+        assert_eq!(bus.try_publish(&BUILD_STARTED, 1).delivered, 1);
+
+        block_on(async {
+            let publisher = bus.clone();
+            let publishing =
+                tokio::spawn(async move { publisher.publish(&BUILD_FINISHED, 2).await });
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            assert!(!publishing.is_finished());
+            drop(slow);
+
+            let delivery = tokio::time::timeout(Duration::from_secs(1), publishing)
+                .await
+                .expect("should not wait for the dropped subscriber")
+                .expect("should publish");
+            assert_eq!(
+                delivery,
+                Delivery {
+                    delivered: 0,
+                    dropped: 1
+                }
+            );
+        });
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+}
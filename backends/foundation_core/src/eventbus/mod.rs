@@ -0,0 +1,5 @@
+mod bus;
+mod topic;
+
+pub use bus::*;
+pub use topic::*;
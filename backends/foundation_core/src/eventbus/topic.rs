@@ -0,0 +1,116 @@
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// `Topic` names a stream of events of type `T` on an
+/// [`EventBus`](super::EventBus), names are `.` separated segments like
+/// `build.finished`, so they can be declared as constants:
+///
+/// ```ignore
+/// const BUILD_FINISHED: Topic<BuildEvent> = Topic::new("build.finished");
+/// ```
+pub struct Topic<T> {
+    name: Cow<'static, str>,
+    _type: PhantomData<fn() -> T>,
+}
+
+// -- Constructors
+
+impl<T> Topic<T> {
+    #[must_use]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            _type: PhantomData,
+        }
+    }
+
+    /// `named` creates a topic from a name built at runtime.
+    #[must_use]
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: Cow::Owned(name.into()),
+            _type: PhantomData,
+        }
+    }
+}
+
+// -- Methods
+
+impl<T> Topic<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Topic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Topic").field(&self.name).finish()
+    }
+}
+
+/// `TopicPattern` selects topics by name, `*` matches exactly one segment
+/// and `**` any number of them, e.g `build.*` or `reload.**`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopicPattern {
+    segments: Vec<String>,
+}
+
+impl TopicPattern {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            segments: pattern.split('.').map(String::from).collect(),
+        }
+    }
+
+    pub fn is_match(&self, topic: &str) -> bool {
+        let topic: Vec<&str> = topic.split('.').collect();
+        matches_segments(&self.segments, &topic)
+    }
+}
+
+fn matches_segments(pattern: &[String], topic: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => topic.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=topic.len()).any(|skipped| matches_segments(rest, &topic[skipped..]))
+        }
+        Some((first, rest)) => match topic.split_first() {
+            Some((segment, remaining)) if first == "*" || first == segment => {
+                matches_segments(rest, remaining)
+            }
+            _ => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod topic_tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards_by_segment() {
+        let exact = TopicPattern::new("build.finished");
+        assert!(exact.is_match("build.finished"));
+        assert!(!exact.is_match("build.finished.ok"));
+
+        let single = TopicPattern::new("build.*");
+        assert!(single.is_match("build.started"));
+        assert!(!single.is_match("build"));
+        assert!(!single.is_match("build.step.done"));
+
+        let any = TopicPattern::new("reload.**");
+        assert!(any.is_match("reload"));
+        assert!(any.is_match("reload.assets.css"));
+        assert!(TopicPattern::new("**.failed").is_match("build.step.failed"));
+        assert!(!any.is_match("build.reload"));
+    }
+}
@@ -0,0 +1,165 @@
+/// `RUST_KEYWORDS` are the words `sanitize_for_ident` will not return as is.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// `StringCaseExt` converts between the casing conventions used for file
+/// names, template names and Rust identifiers.
+///
+/// Words are split on any non alphanumeric character and on case changes,
+/// keeping acronyms together, so `HTTPServer`, `http_server` and
+/// `http-server` all give the same words.
+pub trait StringCaseExt {
+    fn to_snake_case(&self) -> String;
+
+    fn to_kebab_case(&self) -> String;
+
+    fn to_pascal_case(&self) -> String;
+
+    /// `sanitize_for_path` makes the string safe to use as a single file
+    /// name, replacing separators and characters reserved on common
+    /// filesystems with `_` and never returning `.` or `..`.
+    fn sanitize_for_path(&self) -> String;
+
+    /// `sanitize_for_ident` makes the string a valid Rust identifier,
+    /// replacing invalid characters with `_` and suffixing keywords with
+    /// one.
+    fn sanitize_for_ident(&self) -> String;
+}
+
+impl StringCaseExt for str {
+    fn to_snake_case(&self) -> String {
+        split_words(self)
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("_")
+    }
+
+    fn to_kebab_case(&self) -> String {
+        split_words(self)
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<String>>()
+            .join("-")
+    }
+
+    fn to_pascal_case(&self) -> String {
+        split_words(self)
+            .iter()
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect()
+                })
+            })
+            .collect()
+    }
+
+    fn sanitize_for_path(&self) -> String {
+        let sanitized: String = self
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+
+        let sanitized = sanitized.trim_matches(|c: char| c == '.' || c.is_whitespace());
+        if sanitized.is_empty() {
+            return String::from("_");
+        }
+        String::from(sanitized)
+    }
+
+    fn sanitize_for_ident(&self) -> String {
+        let mut ident: String = self
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+            ident.insert(0, '_');
+        }
+        if ident == "_" || RUST_KEYWORDS.contains(&ident.as_str()) {
+            ident.push('_');
+        }
+        ident
+    }
+}
+
+/// `split_words` splits `value` into its words, see [`StringCaseExt`].
+fn split_words(value: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    let chars: Vec<(usize, char)> = value.char_indices().collect();
+    for (position, &(index, current)) in chars.iter().enumerate() {
+        if !current.is_alphanumeric() {
+            if let Some(word_start) = start.take() {
+                words.push(&value[word_start..index]);
+            }
+            continue;
+        }
+
+        let Some(word_start) = start else {
+            start = Some(index);
+            continue;
+        };
+
+        let previous = chars[position - 1].1;
+        let next = chars.get(position + 1).map(|&(_, next)| next);
+        let boundary = current.is_uppercase()
+            && (previous.is_lowercase()
+                || previous.is_numeric()
+                || (previous.is_uppercase() && next.is_some_and(char::is_lowercase)));
+
+        if boundary {
+            words.push(&value[word_start..index]);
+            start = Some(index);
+        }
+    }
+
+    if let Some(word_start) = start {
+        words.push(&value[word_start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod case_tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_cases() {
+        assert_eq!("HTTPServer".to_snake_case(), "http_server");
+        assert_eq!(
+            "blog post-template.html".to_snake_case(),
+            "blog_post_template_html"
+        );
+        assert_eq!("userID2Name".to_kebab_case(), "user-id2-name");
+        assert_eq!("retro_project".to_pascal_case(), "RetroProject");
+        assert_eq!("  --index--  ".to_pascal_case(), "Index");
+        assert_eq!("".to_snake_case(), "");
+    }
+
+    #[test]
+    fn sanitizes_for_paths_and_idents() {
+        assert_eq!("../etc/passwd".sanitize_for_path(), "_etc_passwd");
+        assert_eq!("report: v1?.txt".sanitize_for_path(), "report_ v1_.txt");
+        assert_eq!("..".sanitize_for_path(), "_");
+
+        assert_eq!("404.html".sanitize_for_ident(), "_404_html");
+        assert_eq!("blog-post".sanitize_for_ident(), "blog_post");
+        assert_eq!("match".sanitize_for_ident(), "match_");
+        assert_eq!("".sanitize_for_ident(), "__");
+    }
+}
@@ -1,5 +1,11 @@
+mod case;
+
+pub use case::*;
+
 use core::str;
 use std::borrow;
+use std::ffi;
+use std::path;
 
 pub type IntoStringResult = core::result::Result<String, TryIntoStringError>;
 
@@ -118,6 +124,109 @@ impl TryIntoString<'_> for std::path::PathBuf {
     }
 }
 
+impl TryIntoString<'_> for i128 {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(self.to_string())
+    }
+}
+
+impl TryIntoString<'_> for u128 {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(self.to_string())
+    }
+}
+
+impl TryIntoString<'_> for isize {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(self.to_string())
+    }
+}
+
+impl TryIntoString<'_> for f32 {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(self.to_string())
+    }
+}
+
+impl TryIntoString<'_> for f64 {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(self.to_string())
+    }
+}
+
+impl<'a> TryIntoString<'a> for &'a [u8] {
+    fn try_into_string(&self) -> IntoStringResult {
+        Ok(String::from(
+            str::from_utf8(self).map_err(|_| TryIntoStringError::InvalidUTF8)?,
+        ))
+    }
+}
+
+impl<'a> TryIntoString<'a> for &'a path::Path {
+    fn try_into_string(&self) -> IntoStringResult {
+        self.as_os_str().try_into_string()
+    }
+}
+
+impl<'a> TryIntoString<'a> for &'a ffi::OsStr {
+    fn try_into_string(&self) -> IntoStringResult {
+        match self.to_str() {
+            None => Err(TryIntoStringError::InvalidUTF8),
+            Some(c) => Ok(String::from(c)),
+        }
+    }
+}
+
+impl TryIntoString<'_> for ffi::OsString {
+    fn try_into_string(&self) -> IntoStringResult {
+        self.as_os_str().try_into_string()
+    }
+}
+
+/// `IntoStringLossy` is the lossy companion of [`IntoString`] for paths and
+/// bytes which may not be valid UTF-8, invalid sequences are replaced with
+/// `U+FFFD` instead of failing.
+pub trait IntoStringLossy {
+    #[allow(clippy::wrong_self_convention)]
+    fn into_string_lossy(&self) -> String;
+}
+
+impl IntoStringLossy for [u8] {
+    fn into_string_lossy(&self) -> String {
+        String::from_utf8_lossy(self).into_owned()
+    }
+}
+
+impl IntoStringLossy for Vec<u8> {
+    fn into_string_lossy(&self) -> String {
+        self.as_slice().into_string_lossy()
+    }
+}
+
+impl IntoStringLossy for ffi::OsStr {
+    fn into_string_lossy(&self) -> String {
+        self.to_string_lossy().into_owned()
+    }
+}
+
+impl IntoStringLossy for ffi::OsString {
+    fn into_string_lossy(&self) -> String {
+        self.as_os_str().into_string_lossy()
+    }
+}
+
+impl IntoStringLossy for path::Path {
+    fn into_string_lossy(&self) -> String {
+        self.as_os_str().into_string_lossy()
+    }
+}
+
+impl IntoStringLossy for path::PathBuf {
+    fn into_string_lossy(&self) -> String {
+        self.as_os_str().into_string_lossy()
+    }
+}
+
 pub type TryIntoStrResult<'a> = core::result::Result<borrow::Cow<'a, str>, TryIntoStrError>;
 
 #[derive(Debug, derive_more::From)]
@@ -182,3 +291,34 @@ where
         Ok(borrow::Cow::Owned(to_string))
     }
 }
+
+#[cfg(test)]
+mod strings_ext_tests {
+    use super::*;
+
+    #[test]
+    fn converts_paths_bytes_and_numbers() {
+        let path = path::Path::new("assets/index.html");
+        assert_eq!(path.try_into_string().unwrap(), "assets/index.html");
+        assert_eq!(
+            IntoString::into_string(&path.as_os_str().to_os_string()),
+            "assets/index.html"
+        );
+        assert_eq!(b"hello".as_slice().into_string(), "hello");
+        assert!(matches!(
+            [0xff, 0xfe].as_slice().try_into_string(),
+            Err(TryIntoStringError::InvalidUTF8)
+        ));
+        assert_eq!(1.5f64.into_string(), "1.5");
+        assert_eq!((-3isize).into_string(), "-3");
+    }
+
+    #[test]
+    fn lossy_conversion_replaces_invalid_utf8() {
+        assert_eq!(vec![b'o', b'k', 0xff].into_string_lossy(), "ok\u{fffd}");
+        assert_eq!(
+            path::PathBuf::from("src/lib.rs").into_string_lossy(),
+            "src/lib.rs"
+        );
+    }
+}
@@ -23,3 +23,14 @@ impl<T: Send> SenderExt<T> for broadcast::Sender<T> {
         self.send(message)
     }
 }
+
+/// `block_on` drives `future` to completion on a new current thread
+/// runtime with timers enabled, shared by the tests of the async types.
+#[cfg(test)]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .expect("should build runtime")
+        .block_on(future)
+}
@@ -1,11 +1,141 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
 /// VecExt implements convenient methods to extract a that can be applied to
 /// Vec<T> objects for special methods.
-pub trait VecExt {
-    fn to_vec_string(self) -> Vec<String>;
+pub trait VecExt<T> {
+    fn to_vec_string(self) -> Vec<String>
+    where
+        T: ToString;
+
+    /// `group_by_key` groups the items by the key `key` returns for them,
+    /// keeping their order within each group.
+    fn group_by_key<K, F>(self, key: F) -> HashMap<K, Vec<T>>
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K;
+
+    /// `dedup_by_key_stable` removes every item whose key was already seen,
+    /// unlike [`Vec::dedup_by_key`] duplicates need not be next to each
+    /// other. The first occurrence is kept and the order preserved.
+    fn dedup_by_key_stable<K, F>(&mut self, key: F)
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K;
+
+    /// `partition_map` maps every item and splits the results into the
+    /// `Ok` and `Err` values.
+    fn partition_map<L, R, F>(self, map: F) -> (Vec<L>, Vec<R>)
+    where
+        F: FnMut(T) -> Result<L, R>;
+
+    /// `join_display` joins the displayed items with `separator`.
+    fn join_display(&self, separator: &str) -> String
+    where
+        T: Display;
+}
+
+impl<T> VecExt<T> for Vec<T> {
+    fn to_vec_string(self) -> Vec<String>
+    where
+        T: ToString,
+    {
+        self.iter().map(ToString::to_string).collect()
+    }
+
+    fn group_by_key<K, F>(self, mut key: F) -> HashMap<K, Vec<T>>
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K,
+    {
+        let mut groups: HashMap<K, Vec<T>> = HashMap::new();
+        for item in self {
+            groups.entry(key(&item)).or_default().push(item);
+        }
+        groups
+    }
+
+    fn dedup_by_key_stable<K, F>(&mut self, mut key: F)
+    where
+        K: Eq + Hash,
+        F: FnMut(&T) -> K,
+    {
+        let mut seen = HashSet::new();
+        self.retain(|item| seen.insert(key(item)));
+    }
+
+    fn partition_map<L, R, F>(self, map: F) -> (Vec<L>, Vec<R>)
+    where
+        F: FnMut(T) -> Result<L, R>,
+    {
+        let mut lefts = Vec::new();
+        let mut rights = Vec::new();
+        for result in self.into_iter().map(map) {
+            match result {
+                Ok(left) => lefts.push(left),
+                Err(right) => rights.push(right),
+            }
+        }
+        (lefts, rights)
+    }
+
+    fn join_display(&self, separator: &str) -> String
+    where
+        T: Display,
+    {
+        let mut joined = String::new();
+        for (index, item) in self.iter().enumerate() {
+            if index > 0 {
+                joined.push_str(separator);
+            }
+            write!(joined, "{item}").expect("should write into string");
+        }
+        joined
+    }
 }
 
-impl VecExt for Vec<&str> {
-    fn to_vec_string(self) -> Vec<String> {
-        self.iter().map(|item| String::from(*item)).collect()
+#[cfg(test)]
+mod vec_ext_tests {
+    use super::*;
+
+    #[test]
+    fn groups_and_dedups_by_key() {
+        let files = vec![
+            "index.html",
+            "app.js",
+            "about.html",
+            "vendor.js",
+            "logo.png",
+        ];
+
+        let groups = files
+            .clone()
+            .group_by_key(|file| file.rsplit('.').next().unwrap_or(""));
+        assert_eq!(groups["html"], vec!["index.html", "about.html"]);
+        assert_eq!(groups["js"], vec!["app.js", "vendor.js"]);
+        assert_eq!(groups.len(), 3);
+
+        let mut events = vec![
+            ("a.rs", 1),
+            ("b.rs", 2),
+            ("a.rs", 3),
+            ("c.rs", 4),
+            ("b.rs", 5),
+        ];
+        events.dedup_by_key_stable(|(path, _)| *path);
+        assert_eq!(events, vec![("a.rs", 1), ("b.rs", 2), ("c.rs", 4)]);
+    }
+
+    #[test]
+    fn partitions_and_joins() {
+        let (numbers, invalid) =
+            vec!["1", "two", "3"].partition_map(|value| value.parse::<u8>().map_err(|_| value));
+        assert_eq!(numbers, vec![1, 3]);
+        assert_eq!(invalid, vec!["two"]);
+
+        assert_eq!(numbers.join_display(", "), "1, 3");
+        assert_eq!(Vec::<u8>::new().join_display(", "), "");
+        assert_eq!(vec!["a", "b"].to_vec_string(), vec!["a", "b"]);
     }
 }
@@ -0,0 +1,33 @@
+extern crate test;
+
+use super::*;
+
+use self::test::{black_box, Bencher};
+
+const READ_SIZE: usize = 4 * 1024;
+
+#[bench]
+fn bench_fresh_vec_per_read(b: &mut Bencher) {
+    b.iter(|| {
+        black_box({
+            let mut buffer = vec![0u8; READ_SIZE];
+            buffer[0] = 1;
+            buffer
+        })
+    })
+}
+
+#[bench]
+fn bench_pooled_buffer_per_read(b: &mut Bencher) {
+    let pool = BufferPool::new(READ_SIZE, 8);
+
+    b.iter(|| {
+        black_box({
+            let mut buffer = pool.lease_zeroed(READ_SIZE);
+            buffer[0] = 1;
+            buffer.len()
+        })
+    });
+
+    assert!(pool.metrics().hit_rate() > 0.99);
+}
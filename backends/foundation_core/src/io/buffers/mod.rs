@@ -0,0 +1,6 @@
+mod pool;
+pub use pool::*;
+
+#[cfg(test)]
+#[cfg(feature = "nightly")]
+mod bench;
@@ -0,0 +1,258 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// `DEFAULT_SLAB_SIZE` is the capacity of buffers handed out by the
+/// shared pool, enough for a typical socket read.
+pub const DEFAULT_SLAB_SIZE: usize = 8 * 1024;
+
+/// `BufferPoolMetrics` is a snapshot of the activity of a `BufferPool`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BufferPoolMetrics {
+    /// hits is the number of leases served from an idle buffer.
+    pub hits: u64,
+
+    /// misses is the number of leases that had to allocate.
+    pub misses: u64,
+
+    /// returned is the number of buffers put back for reuse.
+    pub returned: u64,
+
+    /// discarded is the number of buffers dropped instead of returned, as
+    /// the pool was full or they had grown too large.
+    pub discarded: u64,
+
+    /// idle is the number of buffers currently waiting for a lease.
+    pub idle: usize,
+}
+
+impl BufferPoolMetrics {
+    /// `hit_rate` is the fraction (0 to 1) of leases served without
+    /// allocating.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / total as f64
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    slab_size: usize,
+    max_idle: usize,
+    idle: Mutex<Vec<Vec<u8>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    returned: AtomicU64,
+    discarded: AtomicU64,
+}
+
+/// `BufferPool` hands out byte buffers of a fixed slab size and takes
+/// them back once their lease is dropped, so hot IO paths stop
+/// allocating a fresh `Vec` for every read.
+///
+/// Cloning a pool shares it.
+#[derive(Clone, Debug)]
+pub struct BufferPool {
+    state: Arc<PoolState>,
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLAB_SIZE, 64)
+    }
+}
+
+// -- Constructors
+
+impl BufferPool {
+    /// `new` creates a pool of `slab_size` buffers keeping at most
+    /// `max_idle` of them around for reuse.
+    #[must_use]
+    pub fn new(slab_size: usize, max_idle: usize) -> Self {
+        Self {
+            state: Arc::new(PoolState {
+                slab_size,
+                max_idle,
+                idle: Mutex::new(Vec::with_capacity(max_idle)),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                returned: AtomicU64::new(0),
+                discarded: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// `shared` returns the process wide pool used by the wire module.
+    #[must_use]
+    pub fn shared() -> &'static BufferPool {
+        static SHARED: OnceLock<BufferPool> = OnceLock::new();
+        SHARED.get_or_init(BufferPool::default)
+    }
+}
+
+// -- Methods
+
+impl BufferPool {
+    #[must_use]
+    pub fn slab_size(&self) -> usize {
+        self.state.slab_size
+    }
+
+    /// `lease` returns an empty buffer with at least the slab size as
+    /// capacity, it goes back to the pool when dropped.
+    #[must_use]
+    pub fn lease(&self) -> PooledBuffer {
+        let reused = self
+            .state
+            .idle
+            .lock()
+            .expect("should acquire buffer pool lock")
+            .pop();
+
+        let buffer = if let Some(buffer) = reused {
+            self.state.hits.fetch_add(1, Ordering::Relaxed);
+            buffer
+        } else {
+            self.state.misses.fetch_add(1, Ordering::Relaxed);
+            Vec::with_capacity(self.state.slab_size)
+        };
+
+        PooledBuffer {
+            buffer,
+            pool: Some(self.state.clone()),
+        }
+    }
+
+    /// `lease_zeroed` returns a buffer filled with `len` zeroes, ready to
+    /// be read into.
+    #[must_use]
+    pub fn lease_zeroed(&self, len: usize) -> PooledBuffer {
+        let mut buffer = self.lease();
+        buffer.resize(len, 0);
+        buffer
+    }
+
+    #[must_use]
+    pub fn metrics(&self) -> BufferPoolMetrics {
+        BufferPoolMetrics {
+            hits: self.state.hits.load(Ordering::Relaxed),
+            misses: self.state.misses.load(Ordering::Relaxed),
+            returned: self.state.returned.load(Ordering::Relaxed),
+            discarded: self.state.discarded.load(Ordering::Relaxed),
+            idle: self
+                .state
+                .idle
+                .lock()
+                .expect("should acquire buffer pool lock")
+                .len(),
+        }
+    }
+}
+
+impl PoolState {
+    fn give_back(&self, mut buffer: Vec<u8>) {
+        // buffers that grew well past the slab size would pin that memory
+        // for as long as they sit idle.
+        if buffer.capacity() < self.slab_size || buffer.capacity() > self.slab_size * 4 {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let mut idle = self.idle.lock().expect("should acquire buffer pool lock");
+        if idle.len() >= self.max_idle {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        buffer.clear();
+        idle.push(buffer);
+        self.returned.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// `PooledBuffer` is a leased buffer, it derefs to a `Vec<u8>` and is
+/// returned to its pool when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: Vec<u8>,
+    pool: Option<Arc<PoolState>>,
+}
+
+impl PooledBuffer {
+    /// `unpooled` wraps a buffer that does not belong to any pool, for
+    /// code paths that may or may not be handed one.
+    #[must_use]
+    pub fn unpooled(buffer: Vec<u8>) -> Self {
+        Self { buffer, pool: None }
+    }
+
+    /// `detach` takes the buffer out of the pool for good.
+    #[must_use]
+    pub fn detach(mut self) -> Vec<u8> {
+        self.pool = None;
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            pool.give_back(std::mem::take(&mut self.buffer));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_buffer_pool {
+    use super::*;
+
+    #[test]
+    fn reuses_returned_buffers() {
+        let pool = BufferPool::new(16, 1);
+
+        let mut first = pool.lease();
+        first.extend_from_slice(b"data");
+        let second = pool.lease_zeroed(4);
+        assert_eq!(second.as_slice(), &[0; 4]);
+        drop(first);
+        drop(second);
+
+        let reused = pool.lease();
+        assert!(reused.is_empty());
+        assert!(reused.capacity() >= 16);
+
+        let mut grown = pool.lease();
+        grown.reserve(1024);
+        drop(grown);
+        let detached = pool.lease().detach();
+        assert!(detached.capacity() >= 16);
+        drop(reused);
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 4);
+        assert_eq!(metrics.returned, 2);
+        assert_eq!(metrics.discarded, 2);
+        assert_eq!(metrics.idle, 1);
+        assert!((metrics.hit_rate() - 0.2).abs() < f64::EPSILON);
+    }
+}
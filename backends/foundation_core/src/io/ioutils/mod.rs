@@ -306,9 +306,11 @@ impl<T: Read> PeekableReadStream for BufferedReader<T> {
             last_len = current_len;
         }
 
+        // the stream may have ended before enough bytes arrived.
         let buffer = self.inner.buffer();
-        buf.copy_from_slice(&buffer[0..buf.len()]);
-        Ok(buf.len())
+        let count = buffer.len().min(buf.len());
+        buf[..count].copy_from_slice(&buffer[..count]);
+        Ok(count)
     }
 }
 
@@ -0,0 +1,269 @@
+use std::io;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AccumulatorErrors {
+    #[error("The accumulator exceeded its in-memory size without a spill target")]
+    MemoryLimitExceededError,
+
+    #[error("Failed to spill a chunk: {0}")]
+    SpillFailed(io::Error),
+}
+
+type AccumulatorResult<T> = std::result::Result<T, AccumulatorErrors>;
+
+/// `SpillFn` receives each chunk flushed out of an [`Accumulator`].
+pub type SpillFn = Box<dyn FnMut(&[u8]) -> io::Result<()>>;
+
+/// `SpilledSegment` records where a flushed chunk sits in the accumulated
+/// stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpilledSegment {
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// `AccumulatorSummary` is returned by [`Accumulator::finish`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AccumulatorSummary {
+    pub segments: Vec<SpilledSegment>,
+    pub total_bytes: usize,
+}
+
+/// `Accumulator` collects bytes in memory up to `max_in_memory`, handing
+/// full chunks of that size to its spill callback instead of growing, so
+/// building multi-megabyte batches does not exhaust wasm linear memory.
+///
+/// Without a spill callback it fails once the limit is reached.
+pub struct Accumulator {
+    buffer: Vec<u8>,
+    max_in_memory: usize,
+    spill: Option<SpillFn>,
+    segments: Vec<SpilledSegment>,
+    spilled: usize,
+}
+
+impl Accumulator {
+    pub fn new(max_in_memory: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            max_in_memory,
+            spill: None,
+            segments: Vec::new(),
+            spilled: 0,
+        }
+    }
+
+    pub fn with_spill(
+        max_in_memory: usize,
+        spill: impl FnMut(&[u8]) -> io::Result<()> + 'static,
+    ) -> Self {
+        Self {
+            spill: Some(Box::new(spill)),
+            ..Self::new(max_in_memory)
+        }
+    }
+
+    /// `spill_to_writer` spills chunks into `writer`.
+    pub fn spill_to_writer(max_in_memory: usize, mut writer: impl io::Write + 'static) -> Self {
+        Self::with_spill(max_in_memory, move |chunk| writer.write_all(chunk))
+    }
+
+    /// `in_memory` is the part not spilled yet.
+    #[inline]
+    pub fn in_memory(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    #[inline]
+    pub fn spilled_bytes(&self) -> usize {
+        self.spilled
+    }
+
+    /// `total_bytes` is everything pushed so far, spilled or not.
+    #[inline]
+    pub fn total_bytes(&self) -> usize {
+        self.spilled + self.buffer.len()
+    }
+
+    /// `push` appends `bytes`, spilling every chunk that fills up.
+    ///
+    /// If spilling fails partway, the bytes taken before the failure stay
+    /// accumulated, [`Accumulator::total_bytes`] tells how many, and the
+    /// chunk that failed is spilled again on the next push or flush.
+    pub fn push(&mut self, bytes: &[u8]) -> AccumulatorResult<()> {
+        self.push_some(bytes).1
+    }
+
+    /// `push_some` is [`Accumulator::push`] returning how many of `bytes`
+    /// were taken alongside the outcome.
+    fn push_some(&mut self, bytes: &[u8]) -> (usize, AccumulatorResult<()>) {
+        if self.spill.is_none() && self.buffer.len() + bytes.len() > self.max_in_memory {
+            return (0, Err(AccumulatorErrors::MemoryLimitExceededError));
+        }
+
+        let mut consumed = 0;
+        while consumed < bytes.len() {
+            // a chunk a previous push failed to spill goes out first.
+            if self.buffer.len() >= self.max_in_memory {
+                if let Err(err) = self.flush() {
+                    return (consumed, Err(err));
+                }
+            }
+
+            let room = self.max_in_memory.saturating_sub(self.buffer.len()).max(1);
+            let head = &bytes[consumed..(consumed + room).min(bytes.len())];
+            self.buffer.extend_from_slice(head);
+            consumed += head.len();
+
+            if self.buffer.len() >= self.max_in_memory {
+                if let Err(err) = self.flush() {
+                    return (consumed, Err(err));
+                }
+            }
+        }
+        (consumed, Ok(()))
+    }
+
+    /// `flush` spills whatever is held in memory, doing nothing without a
+    /// spill callback.
+    pub fn flush(&mut self) -> AccumulatorResult<()> {
+        let Some(spill) = self.spill.as_mut() else {
+            return Ok(());
+        };
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        spill(&self.buffer).map_err(AccumulatorErrors::SpillFailed)?;
+        self.segments.push(SpilledSegment {
+            offset: self.spilled,
+            len: self.buffer.len(),
+        });
+        self.spilled += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// `finish` flushes the remaining bytes and summarizes what was spilled.
+    pub fn finish(mut self) -> AccumulatorResult<AccumulatorSummary> {
+        self.flush()?;
+        Ok(AccumulatorSummary {
+            total_bytes: self.total_bytes(),
+            segments: std::mem::take(&mut self.segments),
+        })
+    }
+}
+
+impl From<AccumulatorErrors> for io::Error {
+    fn from(err: AccumulatorErrors) -> Self {
+        match err {
+            AccumulatorErrors::SpillFailed(err) => err,
+            AccumulatorErrors::MemoryLimitExceededError => {
+                io::Error::new(io::ErrorKind::OutOfMemory, err)
+            }
+        }
+    }
+}
+
+impl io::Write for Accumulator {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.push_some(buf) {
+            (_, Ok(())) => Ok(buf.len()),
+            (consumed, Err(_)) if consumed > 0 => Ok(consumed),
+            (_, Err(err)) => Err(err.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(Accumulator::flush(self)?)
+    }
+}
+
+#[cfg(test)]
+mod accumulator_tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn spills_full_chunks_and_summarizes_segments() {
+        let chunks: Rc<RefCell<Vec<Vec<u8>>>> = Rc::default();
+        let sink = Rc::clone(&chunks);
+        let mut accumulator = Accumulator::with_spill(4, move |chunk| {
+            sink.borrow_mut().push(chunk.to_vec());
+            Ok(())
+        });
+
+        accumulator.push(&[1, 2, 3]).unwrap();
+        assert!(chunks.borrow().is_empty());
+
+        accumulator.push(&[4, 5, 6, 7, 8, 9, 10]).unwrap();
+        assert_eq!(*chunks.borrow(), vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+        assert_eq!(accumulator.in_memory(), &[9, 10]);
+        assert_eq!(accumulator.total_bytes(), 10);
+
+        let summary = accumulator.finish().unwrap();
+        assert_eq!(chunks.borrow().last(), Some(&vec![9, 10]));
+        assert_eq!(summary.total_bytes, 10);
+        assert_eq!(
+            summary.segments,
+            vec![
+                SpilledSegment { offset: 0, len: 4 },
+                SpilledSegment { offset: 4, len: 4 },
+                SpilledSegment { offset: 8, len: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn fails_without_spill_target_or_when_spilling_fails() {
+        let mut bounded = Accumulator::new(2);
+        bounded.push(&[1, 2]).unwrap();
+        assert!(matches!(
+            bounded.push(&[3]),
+            Err(AccumulatorErrors::MemoryLimitExceededError)
+        ));
+
+        let mut failing =
+            Accumulator::with_spill(1, |_| Err(io::Error::other("host rejected chunk")));
+        assert!(matches!(
+            failing.push(&[1]),
+            Err(AccumulatorErrors::SpillFailed(_))
+        ));
+    }
+
+    #[test]
+    fn failed_spills_keep_the_bytes_taken_so_far() {
+        use std::io::Write;
+
+        let chunks: Rc<RefCell<Vec<Vec<u8>>>> = Rc::default();
+        let accepting = Rc::new(RefCell::new(1));
+        let (sink, budget) = (Rc::clone(&chunks), Rc::clone(&accepting));
+        let mut accumulator = Accumulator::with_spill(4, move |chunk| {
+            if *budget.borrow() == 0 {
+                return Err(io::Error::other("host rejected chunk"));
+            }
+            *budget.borrow_mut() -= 1;
+            sink.borrow_mut().push(chunk.to_vec());
+            Ok(())
+        });
+
+        // the second chunk fails to spill but is still held in memory.
+        assert_eq!(accumulator.write(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap(), 8);
+        assert_eq!(accumulator.in_memory(), &[5, 6, 7, 8]);
+        assert_eq!(accumulator.total_bytes(), 8);
+        assert!(accumulator.write(&[9]).is_err());
+        assert_eq!(accumulator.total_bytes(), 8);
+
+        *accepting.borrow_mut() = 2;
+        accumulator.push(&[9, 10]).unwrap();
+        let summary = accumulator.finish().unwrap();
+        assert_eq!(
+            *chunks.borrow(),
+            vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+        );
+        assert_eq!(summary.total_bytes, 10);
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use super::memory::{MemoryErrors, MemoryLimiter};
+
+type SharedMemoryLimiter = std::rc::Rc<std::cell::RefCell<MemoryLimiter>>;
+
+type MemoryResult<T> = std::result::Result<T, MemoryErrors>;
+
+/// `Handle` points to a value stored in a [`GenerationalArena`], a handle
+/// whose value was removed stays invalid even once its slot is reused
+/// because the slot's generation moved on.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(index: usize, generation: u32) -> Self {
+        Self {
+            index: u32::try_from(index).expect("should have less than u32::MAX slots"),
+            generation,
+            _type: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// `to_bits` packs the handle into a single u64, handy for passing
+    /// it across the wasm boundary.
+    #[inline]
+    pub fn to_bits(&self) -> u64 {
+        (u64::from(self.generation) << 32) | u64::from(self.index)
+    }
+
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+            _type: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Slot<T> {
+    Occupied { generation: u32, value: T },
+    Free { generation: u32, next: Option<u32> },
+}
+
+/// `GenerationalArena` stores values in reusable slots addressed by typed
+/// [`Handle`]s. Freed slots are kept and reused, so objects created and
+/// destroyed in frame-sized batches do not go back to the allocator, and
+/// [`GenerationalArena::clear`] frees a whole batch at once.
+///
+/// Only the growth of the slot storage is accounted against the limiter.
+///
+/// This is not thread-safe.
+#[derive(Debug)]
+pub struct GenerationalArena<T> {
+    limiter: SharedMemoryLimiter,
+    slots: Vec<Slot<T>>,
+    free_head: Option<u32>,
+    accounted: usize,
+    len: usize,
+}
+
+impl<T> GenerationalArena<T> {
+    pub fn new(limiter: SharedMemoryLimiter) -> Self {
+        Self {
+            limiter,
+            slots: Vec::new(),
+            free_head: None,
+            accounted: 0,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(limiter: SharedMemoryLimiter, capacity: usize) -> Self {
+        limiter
+            .borrow_mut()
+            .preallocate(size_of::<Slot<T>>() * capacity);
+
+        Self {
+            limiter,
+            slots: Vec::with_capacity(capacity),
+            free_head: None,
+            accounted: capacity,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// `capacity` is the number of slots, used or free, held by the arena.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// `alloc` stores `value` in a free slot, or a new one when there is
+    /// none, failing when a new slot goes over the memory limit.
+    pub fn alloc(&mut self, value: T) -> MemoryResult<Handle<T>> {
+        if let Some(index) = self.free_head {
+            let slot = &mut self.slots[index as usize];
+            let Slot::Free { generation, next } = *slot else {
+                unreachable!("free list should only point at free slots");
+            };
+
+            *slot = Slot::Occupied { generation, value };
+            self.free_head = next;
+            self.len += 1;
+            return Ok(Handle {
+                index,
+                generation,
+                _type: PhantomData,
+            });
+        }
+
+        if self.slots.len() == self.accounted {
+            let mut limiter = self.limiter.borrow_mut();
+            if let Err(err) = limiter.increase_usage(size_of::<Slot<T>>()) {
+                limiter.decrease_usage(size_of::<Slot<T>>());
+                return Err(err);
+            }
+            self.accounted += 1;
+        }
+
+        let handle = Handle::new(self.slots.len(), 0);
+        self.slots.push(Slot::Occupied {
+            generation: 0,
+            value,
+        });
+        self.len += 1;
+        Ok(handle)
+    }
+
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        match self.slots.get(handle.index as usize) {
+            Some(Slot::Occupied { generation, value }) if *generation == handle.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(Slot::Occupied { generation, value }) if *generation == handle.generation => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// `remove` takes the value out of the arena, invalidating `handle`
+    /// and every copy of it.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        match slot {
+            Slot::Occupied { generation, .. } if *generation == handle.generation => {}
+            _ => return None,
+        }
+
+        let freed = Slot::Free {
+            generation: handle.generation.wrapping_add(1),
+            next: self.free_head,
+        };
+        let Slot::Occupied { value, .. } = std::mem::replace(slot, freed) else {
+            unreachable!("slot was checked to be occupied");
+        };
+
+        self.free_head = Some(handle.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// `clear` removes every value at once, invalidating all handles while
+    /// keeping the slots for reuse.
+    pub fn clear(&mut self) {
+        let mut next = None;
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            let generation = match slot {
+                Slot::Occupied { generation, .. } => generation.wrapping_add(1),
+                Slot::Free { generation, .. } => *generation,
+            };
+            *slot = Slot::Free { generation, next };
+            next = Some(u32::try_from(index).expect("should have less than u32::MAX slots"));
+        }
+
+        self.free_head = next;
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied { generation, value } => {
+                    Some((Handle::new(index, *generation), value))
+                }
+                Slot::Free { .. } => None,
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle<T>, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied { generation, value } => {
+                    Some((Handle::new(index, *generation), value))
+                }
+                Slot::Free { .. } => None,
+            })
+    }
+}
+
+impl<T> Drop for GenerationalArena<T> {
+    fn drop(&mut self) {
+        self.limiter
+            .borrow_mut()
+            .decrease_usage(size_of::<Slot<T>>() * self.accounted);
+    }
+}
+
+#[cfg(test)]
+mod generational_arena_tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn stale_handles_do_not_see_reused_slots() {
+        let limiter = MemoryLimiter::create_shared(1024);
+        let mut arena: GenerationalArena<String> = GenerationalArena::new(Rc::clone(&limiter));
+
+        let first = arena.alloc(String::from("first")).unwrap();
+        let second = arena.alloc(String::from("second")).unwrap();
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.get(first).map(String::as_str), Some("first"));
+
+        assert_eq!(arena.remove(first).as_deref(), Some("first"));
+        assert_eq!(arena.remove(first), None);
+        assert!(!arena.contains(first));
+
+        let third = arena.alloc(String::from("third")).unwrap();
+        assert_eq!(third.index(), first.index());
+        assert_ne!(third, first);
+        assert_eq!(arena.get(first), None);
+        assert_eq!(arena.capacity(), 2);
+
+        arena.get_mut(second).unwrap().push('!');
+        let values: Vec<&str> = arena.iter().map(|(_, value)| value.as_str()).collect();
+        assert_eq!(values, vec!["third", "second!"]);
+        assert_eq!(Handle::from_bits(third.to_bits()), third);
+    }
+
+    #[test]
+    fn clear_invalidates_every_handle_and_reuses_slots() {
+        let limiter = MemoryLimiter::create_shared(1024);
+        {
+            let mut arena: GenerationalArena<u64> = GenerationalArena::new(Rc::clone(&limiter));
+
+            let handles: Vec<Handle<u64>> =
+                (0..4).map(|value| arena.alloc(value).unwrap()).collect();
+            let usage = limiter.borrow().current_usage();
+            assert!(usage > 0);
+
+            arena.clear();
+            assert!(arena.is_empty());
+            assert!(handles.iter().all(|handle| !arena.contains(*handle)));
+
+            for value in 0..4 {
+                arena.alloc(value).unwrap();
+            }
+            arena.iter_mut().for_each(|(_, value)| *value *= 10);
+            assert_eq!(arena.capacity(), 4);
+            assert_eq!(limiter.borrow().current_usage(), usage);
+            assert_eq!(arena.iter().map(|(_, value)| *value).sum::<u64>(), 60);
+        }
+
+        assert_eq!(limiter.borrow().current_usage(), 0);
+    }
+
+    #[test]
+    fn respects_memory_limits() {
+        let limiter = MemoryLimiter::create_shared(size_of::<Slot<u64>>() * 2);
+        let mut arena: GenerationalArena<u64> = GenerationalArena::new(Rc::clone(&limiter));
+
+        arena.alloc(1).unwrap();
+        arena.alloc(2).unwrap();
+        assert_eq!(
+            arena.alloc(3).unwrap_err(),
+            MemoryErrors::MemoryLimitExceededError
+        );
+        assert_eq!(limiter.borrow().current_usage(), size_of::<Slot<u64>>() * 2);
+    }
+}
@@ -0,0 +1,43 @@
+extern crate test;
+
+use super::generational::GenerationalArena;
+use super::memory::MemoryLimiter;
+
+use self::test::{black_box, Bencher};
+
+#[derive(Clone)]
+struct DummyNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+}
+
+#[bench]
+fn bench_dummy_node_generational_arena_frame_batch(b: &mut Bencher) {
+    let limiter = MemoryLimiter::create_shared(1024 * 1024 * 1024);
+    let mut arena: GenerationalArena<DummyNode> = GenerationalArena::new(limiter);
+
+    b.iter(|| {
+        black_box({
+            for id in 0..1024 {
+                arena
+                    .alloc(DummyNode { id, parent: None })
+                    .expect("received handle");
+            }
+            arena.clear();
+        })
+    })
+}
+
+#[bench]
+fn bench_dummy_node_box_per_object_frame_batch(b: &mut Bencher) {
+    let mut nodes: Vec<Box<DummyNode>> = Vec::with_capacity(1024);
+
+    b.iter(|| {
+        black_box({
+            for id in 0..1024 {
+                nodes.push(Box::new(DummyNode { id, parent: None }));
+            }
+            nodes.clear();
+        })
+    })
+}
@@ -18,27 +18,55 @@ type SharedMemoryLimiter = rc::Rc<cell::RefCell<MemoryLimiter>>;
 
 type MemoryResult<T> = result::Result<T, MemoryErrors>;
 
+/// `MemoryStats` counts what went through a [`MemoryLimiter`], increases
+/// are counted as allocations and decreases as frees.
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub allocs: usize,
+    pub frees: usize,
+    pub live_bytes: usize,
+    pub peak_bytes: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryLimiter {
     current_usage: usize,
     max: usize,
+    #[cfg(feature = "stats")]
+    stats: MemoryStats,
 }
 
 impl MemoryLimiter {
     pub fn create_shared(max: usize) -> SharedMemoryLimiter {
-        rc::Rc::new(cell::RefCell::new(MemoryLimiter {
-            current_usage: 0,
-            max,
-        }))
+        rc::Rc::new(cell::RefCell::new(MemoryLimiter::non_shared(max)))
     }
 
     pub fn non_shared(max: usize) -> MemoryLimiter {
         Self {
             current_usage: 0,
             max,
+            #[cfg(feature = "stats")]
+            stats: MemoryStats::default(),
         }
     }
 
+    #[cfg(feature = "stats")]
+    #[inline]
+    pub fn stats(&self) -> MemoryStats {
+        self.stats
+    }
+
+    /// `reset_stats` starts counting afresh, keeping the bytes currently in use as live.
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats = MemoryStats {
+            live_bytes: self.current_usage,
+            peak_bytes: self.current_usage,
+            ..MemoryStats::default()
+        };
+    }
+
     #[inline]
     pub fn set_capacity(&mut self, new_max: usize) {
         self.max = new_max
@@ -66,12 +94,25 @@ impl MemoryLimiter {
             return;
         }
         self.current_usage -= by_amount;
+
+        #[cfg(feature = "stats")]
+        {
+            self.stats.frees += 1;
+            self.stats.live_bytes = self.current_usage;
+        }
     }
 
     #[inline]
     pub fn increase_usage(&mut self, by_amount: usize) -> MemoryResult<()> {
         self.current_usage += by_amount;
 
+        #[cfg(feature = "stats")]
+        {
+            self.stats.allocs += 1;
+            self.stats.live_bytes = self.current_usage;
+            self.stats.peak_bytes = self.stats.peak_bytes.max(self.current_usage);
+        }
+
         if self.current_usage > self.max {
             return Err(MemoryErrors::MemoryLimitExceededError);
         }
@@ -98,6 +139,29 @@ mod memory_limiter_tests {
         assert_eq!(limiter.current_usage(), 15);
     }
 
+    #[test]
+    #[cfg(feature = "stats")]
+    fn counts_allocations_when_stats_are_enabled() {
+        let mut limiter = MemoryLimiter::non_shared(100);
+
+        limiter.increase_usage(40).unwrap();
+        limiter.increase_usage(20).unwrap();
+        limiter.decrease_usage(50);
+        assert_eq!(
+            limiter.stats(),
+            MemoryStats {
+                allocs: 2,
+                frees: 1,
+                live_bytes: 10,
+                peak_bytes: 60,
+            }
+        );
+
+        limiter.reset_stats();
+        assert_eq!(limiter.stats().peak_bytes, 10);
+        assert_eq!(limiter.stats().allocs, 0);
+    }
+
     #[test]
     fn can_get_current_usage() {
         let limiter_rc = MemoryLimiter::create_shared(10);
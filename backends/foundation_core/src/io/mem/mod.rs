@@ -1,10 +1,13 @@
-#![cfg_attr(feature = "nightly", feature(test))]
-
+pub mod accumulator;
 pub mod encoding;
+pub mod generational;
 pub mod memory;
 pub mod primitives;
 pub mod stringpointer;
 
+// bench.rs still benches the ArenaPool API that moved out of this module,
+// it is left out of the build until ported.
+
 #[cfg(test)]
 #[cfg(feature = "nightly")]
-mod bench;
+mod generational_bench;
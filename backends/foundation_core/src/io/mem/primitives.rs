@@ -144,3 +144,323 @@ impl Debug for Bytes<'_> {
         write!(f, "`{}`", self.to_utf8_string())
     }
 }
+
+/// `RingBuffer` is a fixed-capacity FIFO queue of `N` elements which never
+/// allocates after creation, it only relies on `core` so it can be shared
+/// with `no_std` code.
+///
+/// [`RingBuffer::push`] refuses new elements once full while
+/// [`RingBuffer::push_overwrite`] evicts the oldest one instead.
+#[derive(Clone)]
+pub struct RingBuffer<T, const N: usize> {
+    slots: [Option<T>; N],
+    head: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Default for RingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> RingBuffer<T, N> {
+    pub fn new() -> Self {
+        assert!(N > 0, "RingBuffer capacity must be above zero");
+        Self {
+            slots: core::array::from_fn(|_| None),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// `push` adds `value` at the back, handing it back when full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.slots[(self.head + self.len) % N] = Some(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// `push_overwrite` adds `value` at the back, evicting and returning
+    /// the oldest element when full.
+    pub fn push_overwrite(&mut self, value: T) -> Option<T> {
+        if !self.is_full() {
+            self.slots[(self.head + self.len) % N] = Some(value);
+            self.len += 1;
+            return None;
+        }
+
+        let evicted = self.slots[self.head].replace(value);
+        self.head = (self.head + 1) % N;
+        evicted
+    }
+
+    /// `pop` removes the oldest element.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let value = self.slots[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        value
+    }
+
+    /// `peek` returns the oldest element without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.slots[self.head].as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// `iter` walks the elements from the oldest to the newest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).filter_map(move |offset| self.slots[(self.head + offset) % N].as_ref())
+    }
+}
+
+impl<T: Debug, const N: usize> Debug for RingBuffer<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// `AtomicRingBuffer` is the thread-safe single-producer single-consumer
+/// variant of [`RingBuffer`], available where pointer sized atomics are.
+///
+/// [`AtomicRingBuffer::split`] hands out the only [`RingProducer`] and
+/// [`RingConsumer`], which can be moved to different threads. Evicting the
+/// oldest element would race the consumer so there is no overwrite mode.
+#[cfg(target_has_atomic = "ptr")]
+pub struct AtomicRingBuffer<T, const N: usize> {
+    slots: [core::cell::UnsafeCell<Option<T>>; N],
+
+    // head and tail run over 0..2N so a full buffer is told apart from an
+    // empty one without a separate counter.
+    head: core::sync::atomic::AtomicUsize,
+    tail: core::sync::atomic::AtomicUsize,
+}
+
+// SAFETY: a slot is only written by the producer while it is outside of
+// head..tail and only read by the consumer while inside, the Acquire and
+// Release on head and tail order those accesses.
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl<T: Send, const N: usize> Sync for AtomicRingBuffer<T, N> {}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> Default for AtomicRingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> AtomicRingBuffer<T, N> {
+    pub fn new() -> Self {
+        assert!(N > 0, "AtomicRingBuffer capacity must be above zero");
+        Self {
+            slots: core::array::from_fn(|_| core::cell::UnsafeCell::new(None)),
+            head: core::sync::atomic::AtomicUsize::new(0),
+            tail: core::sync::atomic::AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        let head = self.head.load(core::sync::atomic::Ordering::Acquire);
+        let tail = self.tail.load(core::sync::atomic::Ordering::Acquire);
+        (tail + 2 * N - head) % (2 * N)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `split` returns the producing and consuming ends of the buffer.
+    pub fn split(&mut self) -> (RingProducer<'_, T, N>, RingConsumer<'_, T, N>) {
+        (RingProducer { buffer: self }, RingConsumer { buffer: self })
+    }
+}
+
+/// `RingProducer` is the pushing end of an [`AtomicRingBuffer`].
+#[cfg(target_has_atomic = "ptr")]
+pub struct RingProducer<'a, T, const N: usize> {
+    buffer: &'a AtomicRingBuffer<T, N>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> RingProducer<'_, T, N> {
+    /// `push` adds `value` at the back, handing it back when full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        use core::sync::atomic::Ordering;
+
+        let tail = self.buffer.tail.load(Ordering::Relaxed);
+        let head = self.buffer.head.load(Ordering::Acquire);
+        if (tail + 2 * N - head) % (2 * N) == N {
+            return Err(value);
+        }
+
+        // SAFETY: the slot at tail is outside of head..tail so the consumer
+        // does not touch it until the Release store below.
+        unsafe {
+            *self.buffer.slots[tail % N].get() = Some(value);
+        }
+        self.buffer
+            .tail
+            .store((tail + 1) % (2 * N), Ordering::Release);
+        Ok(())
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.buffer.len() == N
+    }
+}
+
+/// `RingConsumer` is the popping end of an [`AtomicRingBuffer`].
+#[cfg(target_has_atomic = "ptr")]
+pub struct RingConsumer<'a, T, const N: usize> {
+    buffer: &'a AtomicRingBuffer<T, N>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> RingConsumer<'_, T, N> {
+    /// `pop` removes the oldest element.
+    pub fn pop(&mut self) -> Option<T> {
+        use core::sync::atomic::Ordering;
+
+        let head = self.buffer.head.load(Ordering::Relaxed);
+        let tail = self.buffer.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        // SAFETY: the slot at head is inside of head..tail so the producer
+        // does not touch it until the Release store below.
+        let value = unsafe { (*self.buffer.slots[head % N].get()).take() };
+        self.buffer
+            .head
+            .store((head + 1) % (2 * N), Ordering::Release);
+        value
+    }
+
+    /// `peek` returns the oldest element without removing it, it stays
+    /// valid until the next [`RingConsumer::pop`].
+    pub fn peek(&self) -> Option<&T> {
+        use core::sync::atomic::Ordering;
+
+        let head = self.buffer.head.load(Ordering::Relaxed);
+        let tail = self.buffer.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+
+        // SAFETY: see pop, the slot is only released by popping which needs
+        // a mutable borrow of the consumer.
+        unsafe { (*self.buffer.slots[head % N].get()).as_ref() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod ring_buffer_tests {
+    use super::*;
+
+    #[test]
+    fn pushes_and_pops_in_order() {
+        let mut ring: RingBuffer<u8, 3> = RingBuffer::new();
+        assert_eq!(ring.pop(), None);
+
+        ring.push(1).unwrap();
+        ring.push(2).unwrap();
+        ring.push(3).unwrap();
+        assert!(ring.is_full());
+        assert_eq!(ring.push(4), Err(4));
+        assert_eq!(ring.peek(), Some(&1));
+
+        assert_eq!(ring.pop(), Some(1));
+        ring.push(4).unwrap();
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        ring.clear();
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn overwrites_the_oldest_when_full() {
+        let mut ring: RingBuffer<String, 2> = RingBuffer::new();
+        assert_eq!(ring.push_overwrite("a".into()), None);
+        assert_eq!(ring.push_overwrite("b".into()), None);
+        assert_eq!(ring.push_overwrite("c".into()).as_deref(), Some("a"));
+        assert_eq!(format!("{ring:?}"), r#"["b", "c"]"#);
+        assert_eq!(ring.pop().as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn atomic_ring_buffer_keeps_order_across_threads() {
+        const TOTAL: usize = 10_000;
+
+        let mut ring: AtomicRingBuffer<usize, 8> = AtomicRingBuffer::new();
+        let (mut producer, mut consumer) = ring.split();
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for value in 0..TOTAL {
+                    let mut pending = value;
+                    while let Err(back) = producer.push(pending) {
+                        pending = back;
+                        std::thread::yield_now();
+                    }
+                }
+            });
+
+            let mut expected = 0;
+            while expected < TOTAL {
+                if let Some(peeked) = consumer.peek().copied() {
+                    assert_eq!(consumer.pop(), Some(peeked));
+                    assert_eq!(peeked, expected);
+                    expected += 1;
+                } else {
+                    std::thread::yield_now();
+                }
+            }
+            assert!(consumer.is_empty());
+        });
+
+        assert_eq!(ring.len(), 0);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod buffers;
 pub mod ioutils;
 pub mod mem;
 pub mod ubytes;
@@ -1,13 +1,21 @@
+#![cfg_attr(feature = "nightly", feature(test))]
+
 extern crate url;
 
 #[cfg(all(feature = "native-tls", not(target_arch = "wasm32")))]
 extern crate native_tls_crate as native_tls;
 
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+extern crate rustls_crate as rustls;
+
 pub mod directorate;
+pub mod eventbus;
 pub mod extensions;
 pub mod io;
 pub mod macros;
+pub mod ratelimit;
 pub mod retries;
+pub mod scheduler;
 pub mod synca;
 pub mod valtron;
 pub mod wire;
@@ -0,0 +1,199 @@
+use std::sync::Mutex;
+use std::time;
+
+use super::{RateLimited, RateLimiter};
+use crate::synca::{Clock, SharedClock, SystemClock};
+
+/// `TokenBucket` allows bursts of up to `capacity` permits, refilled at a
+/// steady `refill_per_second`.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    state: Mutex<BucketState>,
+    clock: SharedClock,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    refilled_at: time::Instant,
+}
+
+// -- Constructors
+
+impl TokenBucket {
+    /// `new` creates a full bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `refill_per_second` is not a positive number.
+    #[must_use]
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        assert!(
+            refill_per_second > 0.0,
+            "refill_per_second should be positive"
+        );
+        Self {
+            capacity: f64::from(capacity),
+            refill_per_second,
+            state: Mutex::new(BucketState {
+                tokens: f64::from(capacity),
+                refilled_at: time::Instant::now(),
+            }),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// `with_clock` makes the bucket refill by `clock`, the bucket starts
+    /// out full at its current time.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.state
+            .get_mut()
+            .expect("should acquire bucket lock")
+            .refilled_at = clock.now();
+        self.clock = std::sync::Arc::new(clock);
+        self
+    }
+
+    /// `per_duration` allows `permits` every `period`, all of which may be
+    /// used in a single burst.
+    #[must_use]
+    pub fn per_duration(permits: u32, period: time::Duration) -> Self {
+        Self::new(permits, f64::from(permits) / period.as_secs_f64())
+    }
+}
+
+// -- Methods
+
+impl TokenBucket {
+    /// `available` returns how many whole permits can be taken right now.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn available(&self) -> u32 {
+        let mut state = self.state.lock().expect("should acquire bucket lock");
+        self.refill(&mut state);
+        state.tokens.floor() as u32
+    }
+
+    fn refill(&self, state: &mut BucketState) {
+        let now = self.clock.now();
+        let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        state.refilled_at = now;
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn try_acquire_n(&self, permits: u32) -> Result<(), RateLimited> {
+        let permits = f64::from(permits);
+        if permits > self.capacity {
+            return Err(RateLimited {
+                retry_after: time::Duration::MAX,
+            });
+        }
+
+        let mut state = self.state.lock().expect("should acquire bucket lock");
+        self.refill(&mut state);
+        if state.tokens >= permits {
+            state.tokens -= permits;
+            return Ok(());
+        }
+
+        let missing = permits - state.tokens;
+        Err(RateLimited {
+            retry_after: time::Duration::try_from_secs_f64(missing / self.refill_per_second)
+                .unwrap_or(time::Duration::MAX),
+        })
+    }
+    fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+}
+
+#[cfg(test)]
+mod test_token_bucket {
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+    use crate::synca::TestClock;
+
+    #[test]
+    fn allows_bursts_then_refills() {
+        let bucket = TokenBucket::new(3, 100.0);
+
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire_n(2).is_ok());
+        assert!(!bucket.try_acquire());
+
+        let retry_after = bucket
+            .try_acquire_n(1)
+            .expect_err("should be limited")
+            .retry_after;
+        assert!(retry_after <= time::Duration::from_millis(10));
+
+        std::thread::sleep(time::Duration::from_millis(25));
+        assert!(bucket.available() >= 2);
+        assert!(bucket.try_acquire_n(2).is_ok());
+
+        assert_eq!(
+            bucket.try_acquire_n(4),
+            Err(RateLimited {
+                retry_after: time::Duration::MAX
+            })
+        );
+    }
+
+    #[test]
+    fn refills_by_the_giving_clock() {
+        let clock = TestClock::new();
+        let bucket = TokenBucket::new(2, 10.0).with_clock(clock.clone());
+
+        assert!(bucket.try_acquire_n(2).is_ok());
+        assert_eq!(
+            bucket.try_acquire_n(1),
+            Err(RateLimited {
+                retry_after: time::Duration::from_millis(100)
+            })
+        );
+
+        clock.advance(time::Duration::from_millis(100));
+        assert_eq!(bucket.available(), 1);
+        clock.advance(time::Duration::from_secs(60));
+        assert_eq!(bucket.available(), 2);
+    }
+
+    #[test]
+    fn acquire_waits_for_permits() {
+        let bucket = TokenBucket::per_duration(1, time::Duration::from_millis(20));
+        let started = time::Instant::now();
+        block_on(async {
+            bucket.acquire().await.expect("should acquire");
+            bucket.acquire().await.expect("should acquire");
+        });
+        assert!(started.elapsed() >= time::Duration::from_millis(15));
+    }
+
+    #[test]
+    fn acquire_waits_on_the_giving_clock() {
+        let clock = TestClock::new();
+        let bucket = TokenBucket::new(1, 10.0).with_clock(clock.clone());
+        block_on(async {
+            bucket.acquire().await.expect("should acquire");
+            bucket.acquire().await.expect("should acquire");
+        });
+        assert_eq!(clock.elapsed(), time::Duration::from_millis(100));
+    }
+
+    #[test]
+    fn acquire_above_capacity_fails_right_away() {
+        let bucket = TokenBucket::new(2, 1.0);
+        assert_eq!(
+            block_on(bucket.acquire_n(3)),
+            Err(RateLimited {
+                retry_after: time::Duration::MAX
+            })
+        );
+        assert_eq!(bucket.available(), 2);
+    }
+}
@@ -0,0 +1,80 @@
+use std::future::Future;
+use std::time;
+
+use crate::synca::{SharedClock, SystemClock};
+
+/// `RateLimited` is returned when permits are not available yet, it says
+/// how long to wait before they could be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after: time::Duration,
+}
+
+impl std::error::Error for RateLimited {}
+
+impl core::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `RateLimiter` hands out permits at a bounded rate, implementations are
+/// thread-safe and meant to be shared across threads or tasks.
+pub trait RateLimiter: Send + Sync {
+    /// `try_acquire_n` takes `permits` if they are available right now.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimited`] with the time until they could be available
+    /// otherwise, requests larger than the limiter's capacity can never
+    /// succeed and get a `retry_after` of [`time::Duration::MAX`].
+    fn try_acquire_n(&self, permits: u32) -> Result<(), RateLimited>;
+
+    /// `clock` is what the limiter reads time from, [`RateLimiter::acquire_n`]
+    /// waits on it too.
+    fn clock(&self) -> SharedClock {
+        SystemClock::shared()
+    }
+
+    /// `try_acquire` takes a single permit if one is available right now.
+    fn try_acquire(&self) -> bool {
+        self.try_acquire_n(1).is_ok()
+    }
+
+    /// `acquire_n` waits until `permits` are available and takes them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimited`] with a `retry_after` of
+    /// [`time::Duration::MAX`] right away when `permits` is more than the
+    /// limiter's capacity, as the wait would never end.
+    fn acquire_n(&self, permits: u32) -> impl Future<Output = Result<(), RateLimited>> + Send + '_
+    where
+        Self: Sized,
+    {
+        let clock = self.clock();
+        async move {
+            loop {
+                match self.try_acquire_n(permits) {
+                    Ok(()) => return Ok(()),
+                    Err(limited) if limited.retry_after == time::Duration::MAX => {
+                        return Err(limited);
+                    }
+                    Err(limited) => clock.sleep_async(limited.retry_after).await,
+                }
+            }
+        }
+    }
+
+    /// `acquire` waits until a single permit is available and takes it.
+    ///
+    /// # Errors
+    ///
+    /// See [`RateLimiter::acquire_n`].
+    fn acquire(&self) -> impl Future<Output = Result<(), RateLimited>> + Send + '_
+    where
+        Self: Sized,
+    {
+        self.acquire_n(1)
+    }
+}
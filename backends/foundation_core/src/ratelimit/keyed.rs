@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+use super::{RateLimited, RateLimiter};
+
+pub type LimiterFactory<L> = Box<dyn Fn() -> L + Send + Sync>;
+
+/// `KeyedRateLimiter` keeps a separate limiter per key, e.g per client IP
+/// or API key, created on first use by `factory`.
+///
+/// Limiters unused for `idle_timeout` are evicted, and once `max_keys`
+/// limiters exist the least recently used one makes room for a new key.
+pub struct KeyedRateLimiter<K, L> {
+    factory: LimiterFactory<L>,
+    idle_timeout: time::Duration,
+    max_keys: usize,
+    limiters: Mutex<HashMap<K, KeyedEntry<L>>>,
+}
+
+struct KeyedEntry<L> {
+    limiter: Arc<L>,
+    used_at: time::Instant,
+}
+
+impl<K, L> std::fmt::Debug for KeyedRateLimiter<K, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedRateLimiter")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("max_keys", &self.max_keys)
+            .finish_non_exhaustive()
+    }
+}
+
+// -- Constructors
+
+impl<K, L> KeyedRateLimiter<K, L>
+where
+    K: Eq + Hash + Clone,
+    L: RateLimiter,
+{
+    pub fn new(factory: impl Fn() -> L + Send + Sync + 'static) -> Self {
+        Self {
+            factory: Box::new(factory),
+            idle_timeout: time::Duration::from_secs(600),
+            max_keys: 10_000,
+            limiters: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<K, L> KeyedRateLimiter<K, L> {
+    #[must_use]
+    pub fn with_idle_timeout(mut self, idle_timeout: time::Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys.max(1);
+        self
+    }
+}
+
+// -- Methods
+
+impl<K, L> KeyedRateLimiter<K, L>
+where
+    K: Eq + Hash + Clone,
+    L: RateLimiter,
+{
+    /// `try_acquire_n` takes `permits` from the limiter of `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimited`] when that key is over its limit.
+    pub fn try_acquire_n(&self, key: &K, permits: u32) -> Result<(), RateLimited> {
+        self.limiter(key).try_acquire_n(permits)
+    }
+
+    /// `try_acquire` takes a single permit from the limiter of `key`.
+    pub fn try_acquire(&self, key: &K) -> bool {
+        self.try_acquire_n(key, 1).is_ok()
+    }
+
+    /// `acquire_n` waits until `permits` are available for `key`.
+    ///
+    /// # Errors
+    ///
+    /// See [`RateLimiter::acquire_n`].
+    pub async fn acquire_n(&self, key: &K, permits: u32) -> Result<(), RateLimited> {
+        let limiter = self.limiter(key);
+        limiter.acquire_n(permits).await
+    }
+
+    /// `acquire` waits until a single permit is available for `key`.
+    ///
+    /// # Errors
+    ///
+    /// See [`RateLimiter::acquire_n`].
+    pub async fn acquire(&self, key: &K) -> Result<(), RateLimited> {
+        self.acquire_n(key, 1).await
+    }
+
+    /// `len` returns how many keys currently have a limiter.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.limiters
+            .lock()
+            .expect("should acquire limiters lock")
+            .len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `evict_idle` drops the limiters unused for longer than the idle
+    /// timeout, returning how many were dropped.
+    pub fn evict_idle(&self) -> usize {
+        let mut limiters = self.limiters.lock().expect("should acquire limiters lock");
+        let before = limiters.len();
+        limiters.retain(|_, entry| entry.used_at.elapsed() < self.idle_timeout);
+        before - limiters.len()
+    }
+
+    fn limiter(&self, key: &K) -> Arc<L> {
+        let now = time::Instant::now();
+        let mut limiters = self.limiters.lock().expect("should acquire limiters lock");
+        if let Some(entry) = limiters.get_mut(key) {
+            entry.used_at = now;
+            return entry.limiter.clone();
+        }
+
+        if limiters.len() >= self.max_keys {
+            limiters.retain(|_, entry| now.duration_since(entry.used_at) < self.idle_timeout);
+        }
+        if limiters.len() >= self.max_keys {
+            let oldest = limiters
+                .iter()
+                .min_by_key(|(_, entry)| entry.used_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                limiters.remove(&oldest);
+            }
+        }
+
+        let limiter = Arc::new((self.factory)());
+        limiters.insert(
+            key.clone(),
+            KeyedEntry {
+                limiter: limiter.clone(),
+                used_at: now,
+            },
+        );
+        limiter
+    }
+}
+
+#[cfg(test)]
+mod test_keyed_rate_limiter {
+    use std::net::IpAddr;
+
+    use super::super::TokenBucket;
+    use super::*;
+
+    #[test]
+    fn limits_each_key_separately_and_evicts() {
+        let limiter = KeyedRateLimiter::new(|| TokenBucket::new(2, 1.0))
+            .with_idle_timeout(time::Duration::from_millis(20))
+            .with_max_keys(2);
+
+        let first: IpAddr = "10.0.0.1".parse().expect("should parse");
+        let second: IpAddr = "10.0.0.2".parse().expect("should parse");
+        let third: IpAddr = "10.0.0.3".parse().expect("should parse");
+
+        assert!(limiter.try_acquire_n(&first, 2).is_ok());
+        assert!(!limiter.try_acquire(&first));
+        assert!(limiter.try_acquire(&second));
+        assert_eq!(limiter.len(), 2);
+
+        // a third key pushes out the least recently used one.
+        assert!(limiter.try_acquire(&third));
+        assert_eq!(limiter.len(), 2);
+        assert!(limiter.try_acquire_n(&first, 2).is_ok());
+
+        std::thread::sleep(time::Duration::from_millis(30));
+        assert_eq!(limiter.evict_idle(), 2);
+        assert!(limiter.is_empty());
+    }
+}
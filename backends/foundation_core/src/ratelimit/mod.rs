@@ -0,0 +1,9 @@
+mod bucket;
+mod core;
+mod keyed;
+mod window;
+
+pub use bucket::*;
+pub use core::*;
+pub use keyed::*;
+pub use window::*;
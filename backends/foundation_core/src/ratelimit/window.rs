@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time;
+
+use super::{RateLimited, RateLimiter};
+use crate::synca::{Clock, SharedClock, SystemClock};
+
+/// `SlidingWindow` allows at most `limit` permits within any `window` long
+/// period, unlike a fixed window it never lets twice the limit through
+/// around a window boundary.
+///
+/// Each permit taken is remembered until it leaves the window, so memory
+/// grows with `limit`.
+#[derive(Debug)]
+pub struct SlidingWindow {
+    limit: u32,
+    window: time::Duration,
+    taken: Mutex<VecDeque<time::Instant>>,
+    clock: SharedClock,
+}
+
+// -- Constructors
+
+impl SlidingWindow {
+    #[must_use]
+    pub fn new(limit: u32, window: time::Duration) -> Self {
+        Self {
+            limit,
+            window,
+            taken: Mutex::new(VecDeque::new()),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// `with_clock` makes the window slide by `clock`.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = std::sync::Arc::new(clock);
+        self
+    }
+}
+
+// -- Methods
+
+impl SlidingWindow {
+    /// `available` returns how many permits can be taken right now.
+    #[must_use]
+    pub fn available(&self) -> u32 {
+        let mut taken = self.taken.lock().expect("should acquire window lock");
+        self.expire(&mut taken, self.clock.now());
+        self.limit - Self::count(&taken)
+    }
+
+    fn expire(&self, taken: &mut VecDeque<time::Instant>, now: time::Instant) {
+        while taken
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= self.window)
+        {
+            taken.pop_front();
+        }
+    }
+
+    fn count(taken: &VecDeque<time::Instant>) -> u32 {
+        u32::try_from(taken.len()).unwrap_or(u32::MAX)
+    }
+}
+
+impl RateLimiter for SlidingWindow {
+    fn try_acquire_n(&self, permits: u32) -> Result<(), RateLimited> {
+        if permits > self.limit {
+            return Err(RateLimited {
+                retry_after: time::Duration::MAX,
+            });
+        }
+
+        let now = self.clock.now();
+        let mut taken = self.taken.lock().expect("should acquire window lock");
+        self.expire(&mut taken, now);
+
+        let in_use = Self::count(&taken);
+        if in_use.saturating_add(permits) <= self.limit {
+            taken.extend(std::iter::repeat(now).take(permits as usize));
+            return Ok(());
+        }
+
+        // enough permits are free once the oldest ones leave the window.
+        let must_expire = (in_use.saturating_add(permits) - self.limit) as usize;
+        let frees_at = taken[must_expire - 1] + self.window;
+        Err(RateLimited {
+            retry_after: frees_at.saturating_duration_since(now),
+        })
+    }
+    fn clock(&self) -> SharedClock {
+        self.clock.clone()
+    }
+}
+
+#[cfg(test)]
+mod test_sliding_window {
+    use super::*;
+    use crate::synca::TestClock;
+
+    #[test]
+    fn slides_by_the_giving_clock() {
+        let clock = TestClock::new();
+        let window = SlidingWindow::new(2, time::Duration::from_secs(60)).with_clock(clock.clone());
+
+        assert!(window.try_acquire());
+        clock.advance(time::Duration::from_secs(30));
+        assert!(window.try_acquire());
+        assert_eq!(
+            window.try_acquire_n(1),
+            Err(RateLimited {
+                retry_after: time::Duration::from_secs(30)
+            })
+        );
+
+        clock.advance(time::Duration::from_secs(30));
+        assert_eq!(window.available(), 1);
+    }
+
+    #[test]
+    fn limits_permits_within_the_window() {
+        let window = SlidingWindow::new(3, time::Duration::from_millis(40));
+
+        assert!(window.try_acquire_n(2).is_ok());
+        assert!(window.try_acquire());
+        assert_eq!(window.available(), 0);
+
+        assert!(!window.try_acquire());
+        let retry_after = window
+            .try_acquire_n(2)
+            .expect_err("should be limited")
+            .retry_after;
+        assert!(retry_after > time::Duration::ZERO);
+        assert!(retry_after <= time::Duration::from_millis(40));
+
+        std::thread::sleep(time::Duration::from_millis(50));
+        assert_eq!(window.available(), 3);
+        assert!(window.try_acquire_n(3).is_ok());
+        assert!(window.try_acquire_n(4).is_err());
+    }
+}
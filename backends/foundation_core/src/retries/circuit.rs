@@ -0,0 +1,538 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time;
+
+use crate::synca::{Clock, SharedClock, SystemClock};
+
+/// `CircuitState` describes whether a `CircuitBreaker` lets calls through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// calls go through and their outcomes are recorded.
+    Closed,
+
+    /// calls are rejected without reaching the downstream.
+    Open,
+
+    /// a limited number of trial calls go through to probe recovery.
+    HalfOpen,
+}
+
+/// `CircuitEvent` is sent to subscribers whenever a breaker changes state.
+#[derive(Clone, Debug)]
+pub struct CircuitEvent {
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub at: time::Instant,
+}
+
+/// `CircuitBreakerConfig` defines when a `CircuitBreaker` trips and recovers.
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    /// fraction (0 to 1) of failed calls in the window that opens the circuit.
+    pub failure_rate_threshold: f64,
+
+    /// calls taking at least this long are counted as slow.
+    pub slow_call_duration: Option<time::Duration>,
+
+    /// fraction (0 to 1) of slow calls in the window that opens the circuit.
+    pub slow_call_rate_threshold: f64,
+
+    /// number of most recent calls the rates are computed over.
+    pub window_size: usize,
+
+    /// calls required in the window before the rates are considered.
+    pub minimum_calls: usize,
+
+    /// how long the circuit stays open before allowing trial calls.
+    pub open_duration: time::Duration,
+
+    /// number of successful trial calls needed to close the circuit again.
+    pub half_open_calls: usize,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate_threshold: 0.5,
+            slow_call_duration: None,
+            slow_call_rate_threshold: 1.0,
+            window_size: 20,
+            minimum_calls: 10,
+            open_duration: time::Duration::from_secs(30),
+            half_open_calls: 3,
+        }
+    }
+}
+
+// -- Builder methods
+
+impl CircuitBreakerConfig {
+    #[must_use]
+    pub fn with_failure_rate(mut self, threshold: f64) -> Self {
+        self.failure_rate_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    #[must_use]
+    pub fn with_slow_calls(mut self, duration: time::Duration, threshold: f64) -> Self {
+        self.slow_call_duration = Some(duration);
+        self.slow_call_rate_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    #[must_use]
+    pub fn with_window(mut self, window_size: usize, minimum_calls: usize) -> Self {
+        self.window_size = window_size.max(1);
+        self.minimum_calls = minimum_calls.clamp(1, self.window_size);
+        self
+    }
+
+    #[must_use]
+    pub fn with_open_duration(mut self, duration: time::Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+
+    #[must_use]
+    pub fn with_half_open_calls(mut self, calls: usize) -> Self {
+        self.half_open_calls = calls.max(1);
+        self
+    }
+}
+
+/// `CircuitError` is returned by calls made through a `CircuitBreaker`.
+#[derive(Debug)]
+pub enum CircuitError<E> {
+    /// the circuit is open and the call was not made.
+    Open,
+
+    /// the call was made and failed.
+    Failed(E),
+}
+
+impl<E> CircuitError<E> {
+    /// `is_open` returns true when the call was rejected by the breaker,
+    /// useful as a `RetryPolicy` predicate to avoid retrying into an open circuit.
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Open)
+    }
+
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            Self::Open => None,
+            Self::Failed(err) => Some(err),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> std::error::Error for CircuitError<E> {}
+
+impl<E: std::fmt::Debug> core::fmt::Display for CircuitError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Outcome {
+    failed: bool,
+    slow: bool,
+}
+
+struct BreakerState {
+    state: CircuitState,
+    opened_at: Option<time::Instant>,
+    window: VecDeque<Outcome>,
+    trial_calls: usize,
+    trial_successes: usize,
+    subscribers: Vec<flume::Sender<CircuitEvent>>,
+}
+
+impl BreakerState {
+    fn transition(&mut self, to: CircuitState, now: time::Instant) {
+        let from = self.state;
+        if from == to {
+            return;
+        }
+
+        self.state = to;
+        self.trial_calls = 0;
+        self.trial_successes = 0;
+        match to {
+            CircuitState::Open => self.opened_at = Some(now),
+            CircuitState::Closed => {
+                self.opened_at = None;
+                self.window.clear();
+            }
+            CircuitState::HalfOpen => {}
+        }
+
+        tracing::debug!("Circuit breaker moved from {:?} to {:?}", from, to);
+        let event = CircuitEvent { from, to, at: now };
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+/// `CircuitBreaker` stops calls to a failing downstream for a while once
+/// too many of the recent calls failed or were slow, letting the downstream
+/// recover instead of having outages cascade through its clients.
+///
+/// A `CircuitBreaker` is cheap to clone, all clones share the same circuit.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Arc<Mutex<BreakerState>>,
+    clock: SharedClock,
+}
+
+// -- Constructors
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            inner: Arc::new(Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                opened_at: None,
+                window: VecDeque::new(),
+                trial_calls: 0,
+                trial_successes: 0,
+                subscribers: Vec::new(),
+            })),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// `with_clock` times the open duration and calls by `clock`.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+// -- Methods
+
+impl CircuitBreaker {
+    /// `subscribe` returns a channel receiving every state change of the breaker.
+    pub fn subscribe(&self) -> flume::Receiver<CircuitEvent> {
+        let (sender, receiver) = flume::unbounded();
+        let mut inner = self.inner.lock().expect("should acquire breaker lock");
+        inner.subscribers.push(sender);
+        receiver
+    }
+
+    /// `state` returns the current state, moving an open circuit whose
+    /// open duration elapsed to half-open.
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().expect("should acquire breaker lock");
+        self.refresh(&mut inner);
+        inner.state
+    }
+
+    /// `try_acquire` returns true if a call may go through now, callers
+    /// getting true must report the call's outcome with `record`.
+    pub fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().expect("should acquire breaker lock");
+        self.refresh(&mut inner);
+
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if inner.trial_calls >= self.config.half_open_calls {
+                    return false;
+                }
+                inner.trial_calls += 1;
+                true
+            }
+        }
+    }
+
+    /// `record` reports the outcome of a call allowed by `try_acquire`.
+    pub fn record(&self, failed: bool, elapsed: time::Duration) {
+        let slow = self
+            .config
+            .slow_call_duration
+            .is_some_and(|threshold| elapsed >= threshold);
+
+        let mut inner = self.inner.lock().expect("should acquire breaker lock");
+        match inner.state {
+            CircuitState::HalfOpen => {
+                if failed || slow {
+                    inner.transition(CircuitState::Open, self.clock.now());
+                    return;
+                }
+                inner.trial_successes += 1;
+                if inner.trial_successes >= self.config.half_open_calls {
+                    inner.transition(CircuitState::Closed, self.clock.now());
+                }
+            }
+            CircuitState::Closed => {
+                inner.window.push_back(Outcome { failed, slow });
+                while inner.window.len() > self.config.window_size {
+                    inner.window.pop_front();
+                }
+                if self.should_trip(&inner.window) {
+                    inner.transition(CircuitState::Open, self.clock.now());
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    /// `call` runs the operation if the circuit allows it, recording its outcome.
+    pub fn call<T, E, F>(&self, operation: F) -> Result<T, CircuitError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let Some(call) = CallGuard::acquire(self) else {
+            return Err(CircuitError::Open);
+        };
+
+        let result = operation();
+        call.finish(result.is_err());
+        result.map_err(CircuitError::Failed)
+    }
+
+    /// `call_async` awaits the future if the circuit allows it, recording its outcome.
+    pub async fn call_async<T, E, Fut>(&self, operation: Fut) -> Result<T, CircuitError<E>>
+    where
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let Some(call) = CallGuard::acquire(self) else {
+            return Err(CircuitError::Open);
+        };
+
+        let result = operation.await;
+        call.finish(result.is_err());
+        result.map_err(CircuitError::Failed)
+    }
+
+    fn refresh(&self, inner: &mut BreakerState) {
+        if inner.state != CircuitState::Open {
+            return;
+        }
+        let now = self.clock.now();
+        let elapsed = inner
+            .opened_at
+            .is_some_and(|opened| now.duration_since(opened) >= self.config.open_duration);
+        if elapsed {
+            inner.transition(CircuitState::HalfOpen, now);
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn should_trip(&self, window: &VecDeque<Outcome>) -> bool {
+        if window.len() < self.config.minimum_calls {
+            return false;
+        }
+
+        let total = window.len() as f64;
+        let failures = window.iter().filter(|outcome| outcome.failed).count() as f64;
+        let slow = window.iter().filter(|outcome| outcome.slow).count() as f64;
+
+        failures / total >= self.config.failure_rate_threshold
+            || (self.config.slow_call_duration.is_some()
+                && slow / total >= self.config.slow_call_rate_threshold)
+    }
+}
+
+/// `CallGuard` is a call let through by the breaker, recording it as
+/// failed if it is abandoned before finishing, e.g a dropped future or a
+/// panicking operation, so a half-open trial is never lost.
+struct CallGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    started: time::Instant,
+    finished: bool,
+}
+
+impl<'a> CallGuard<'a> {
+    fn acquire(breaker: &'a CircuitBreaker) -> Option<Self> {
+        if !breaker.try_acquire() {
+            return None;
+        }
+        Some(Self {
+            breaker,
+            started: breaker.clock.now(),
+            finished: false,
+        })
+    }
+
+    fn finish(mut self, failed: bool) {
+        self.finished = true;
+        self.record(failed);
+    }
+
+    fn record(&self, failed: bool) {
+        let elapsed = self.breaker.clock.now().duration_since(self.started);
+        self.breaker.record(failed, elapsed);
+    }
+}
+
+impl Drop for CallGuard<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.record(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod circuit_breaker_test {
+    use super::*;
+    use crate::synca::TestClock;
+
+    fn breaker(clock: &TestClock) -> CircuitBreaker {
+        CircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .with_window(4, 4)
+                .with_failure_rate(0.5)
+                .with_open_duration(time::Duration::from_millis(20))
+                .with_half_open_calls(2),
+        )
+        .with_clock(clock.clone())
+    }
+
+    #[test]
+    fn trips_open_on_failure_rate_and_recovers() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        let events = breaker.subscribe();
+
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+        assert!(breaker.call(|| Err::<(), _>("down")).is_err());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        assert!(breaker.call(|| Err::<(), _>("down")).is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.call(|| Ok::<_, ()>(())),
+            Err(CircuitError::Open)
+        ));
+
+        clock.advance(time::Duration::from_millis(20));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let transitions: Vec<(CircuitState, CircuitState)> = events
+            .try_iter()
+            .map(|event| (event.from, event.to))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_trial_call_reopens_circuit() {
+        let clock = TestClock::new();
+        let breaker = breaker(&clock);
+        for _ in 0..4 {
+            let _ = breaker.call(|| Err::<(), _>("down"));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        clock.advance(time::Duration::from_millis(20));
+        assert!(breaker.call(|| Err::<(), _>("still down")).is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    #[test]
+    fn abandoned_trial_calls_reopen_circuit() {
+        struct NoopWaker;
+
+        impl std::task::Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let clock = TestClock::new();
+        let breaker = CircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .with_window(2, 2)
+                .with_open_duration(time::Duration::from_secs(30))
+                .with_half_open_calls(1),
+        )
+        .with_clock(clock.clone());
+        let reopen = |breaker: &CircuitBreaker| {
+            for _ in 0..2 {
+                let _ = breaker.call(|| Err::<(), _>("down"));
+            }
+            clock.advance(time::Duration::from_secs(30));
+            assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        };
+
+        // a trial future dropped while in flight
+        reopen(&breaker);
+        {
+            let waker = std::task::Waker::from(Arc::new(NoopWaker));
+            let mut context = std::task::Context::from_waker(&waker);
+            let mut call = Box::pin(breaker.call_async(std::future::pending::<Result<(), ()>>()));
+            assert!(call.as_mut().poll(&mut context).is_pending());
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // a trial operation which panicked
+        clock.advance(time::Duration::from_secs(30));
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            breaker.call(|| -> Result<(), ()> { panic!("operation panicked") })
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        clock.advance(time::Duration::from_secs(30));
+        assert!(breaker.call(|| Ok::<_, ()>(())).is_ok());
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn recovers_by_the_giving_clock() {
+        let clock = TestClock::new();
+        let breaker = CircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .with_window(2, 2)
+                .with_open_duration(time::Duration::from_secs(30))
+                .with_half_open_calls(1),
+        )
+        .with_clock(clock.clone());
+
+        for _ in 0..2 {
+            let _ = breaker.call(|| Err::<(), _>("down"));
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        clock.advance(time::Duration::from_secs(29));
+        assert_eq!(breaker.state(), CircuitState::Open);
+        clock.advance(time::Duration::from_secs(1));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn trips_on_slow_calls() {
+        let breaker = CircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .with_window(2, 2)
+                .with_slow_calls(time::Duration::from_millis(5), 1.0),
+        );
+
+        breaker.record(false, time::Duration::from_millis(10));
+        breaker.record(false, time::Duration::from_millis(10));
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+}
@@ -1,7 +1,11 @@
+mod circuit;
 mod core;
 mod exponential;
+mod policy;
 mod same;
 
+pub use circuit::*;
 pub use core::*;
 pub use exponential::*;
+pub use policy::*;
 pub use same::*;
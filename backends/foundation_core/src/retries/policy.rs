@@ -0,0 +1,532 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::{io, time};
+
+use crate::synca::{Clock, SharedClock, SystemClock};
+use crate::wire::simple_http::Status;
+
+use super::{RetryDecider, RetryState, DEFAULT_MIN_DURATION};
+
+/// `FullJitterBackoffDecider` implements exponential backoff with "full jitter",
+/// where each wait is a random duration between zero and the exponential
+/// delay for the attempt, spreading retries of many clients evenly over time.
+#[derive(Clone, Debug)]
+pub struct FullJitterBackoffDecider {
+    pub base: time::Duration,
+    pub max_duration: time::Duration,
+}
+
+impl Default for FullJitterBackoffDecider {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_DURATION, time::Duration::from_secs(30))
+    }
+}
+
+impl FullJitterBackoffDecider {
+    pub fn new(base: time::Duration, max_duration: time::Duration) -> Self {
+        Self { base, max_duration }
+    }
+
+    /// ceiling returns the largest wait for the giving attempt.
+    pub fn ceiling(&self, attempt: u32) -> time::Duration {
+        let exponent = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base.saturating_mul(exponent).min(self.max_duration)
+    }
+}
+
+impl RetryDecider for FullJitterBackoffDecider {
+    fn decide(&self, state: RetryState) -> Option<RetryState> {
+        if state.attempt >= state.total_allowed {
+            return None;
+        }
+
+        let next_attempt = state.attempt.saturating_add(1);
+        let ceiling = u64::try_from(self.ceiling(next_attempt).as_millis()).unwrap_or(u64::MAX);
+        let wait = time::Duration::from_millis(fastrand::u64(0..=ceiling));
+
+        Some(RetryState {
+            wait: Some(wait),
+            attempt: next_attempt,
+            total_allowed: state.total_allowed,
+        })
+    }
+}
+
+/// `RetryBudget` limits retries to a fraction of the overall calls made, so a
+/// struggling downstream isn't hit by a retry storm from all its clients.
+///
+/// Every first attempt deposits `ratio` tokens (up to `max_tokens`) and every
+/// retry withdraws one, a `RetryBudget` is cheap to clone and all clones share
+/// the same tokens, letting many policies draw from one budget.
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+    ratio: f64,
+    max_tokens: f64,
+    tokens: Arc<Mutex<f64>>,
+}
+
+impl RetryBudget {
+    /// new creates a budget allowing `ratio` retries per call (e.g 0.2 for
+    /// 20%) that starts out full with `max_tokens` retries available.
+    pub fn new(ratio: f64, max_tokens: u32) -> Self {
+        let max_tokens = f64::from(max_tokens);
+        Self {
+            ratio: ratio.max(0.0),
+            max_tokens,
+            tokens: Arc::new(Mutex::new(max_tokens)),
+        }
+    }
+
+    pub fn deposit(&self) {
+        let mut tokens = self.tokens.lock().expect("should acquire budget lock");
+        *tokens = (*tokens + self.ratio).min(self.max_tokens);
+    }
+
+    /// `try_withdraw` takes a token for a retry, returning false if the
+    /// budget is exhausted.
+    pub fn try_withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().expect("should acquire budget lock");
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+
+    pub fn available(&self) -> f64 {
+        *self.tokens.lock().expect("should acquire budget lock")
+    }
+}
+
+/// `RetryError` is returned when a `RetryPolicy` gives up on an operation.
+#[derive(Debug)]
+pub enum RetryError<E> {
+    /// all attempts failed, carrying the last error.
+    Exhausted { attempts: u32, last: E },
+
+    /// the error was not one the policy retries on.
+    NotRetryable { attempts: u32, error: E },
+
+    /// the shared retry budget had no tokens left, `last` is `None` when the
+    /// last attempt timed out.
+    BudgetExhausted { attempts: u32, last: Option<E> },
+
+    /// the last attempt did not complete within the per-attempt timeout.
+    TimedOut { attempts: u32 },
+}
+
+impl<E> RetryError<E> {
+    pub fn attempts(&self) -> u32 {
+        match self {
+            Self::Exhausted { attempts, .. }
+            | Self::NotRetryable { attempts, .. }
+            | Self::BudgetExhausted { attempts, .. }
+            | Self::TimedOut { attempts } => *attempts,
+        }
+    }
+
+    /// `into_inner` returns the last error of the operation, if any.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            Self::Exhausted { last, .. } => Some(last),
+            Self::BudgetExhausted { last, .. } => last,
+            Self::NotRetryable { error, .. } => Some(error),
+            Self::TimedOut { .. } => None,
+        }
+    }
+}
+
+impl<E: std::fmt::Debug> std::error::Error for RetryError<E> {}
+
+impl<E: std::fmt::Debug> core::fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+pub type RetryPredicate<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// `retry_on_io_kinds` returns a predicate that retries io errors of the giving kinds.
+pub fn retry_on_io_kinds(kinds: &'static [io::ErrorKind]) -> RetryPredicate<io::Error> {
+    Arc::new(move |err: &io::Error| kinds.contains(&err.kind()))
+}
+
+/// `is_retryable_status` returns true for HTTP statuses that signal a
+/// transient failure worth retrying.
+pub fn is_retryable_status(status: &Status) -> bool {
+    matches!(
+        status,
+        Status::RequestTimeout
+            | Status::TooManyRequests
+            | Status::InternalServerError
+            | Status::BadGateway
+            | Status::ServiceUnavailable
+            | Status::GatewayTimeout
+    )
+}
+
+/// `RetryPolicy` composes how a fallible operation is retried: how often,
+/// how long to wait in between, which errors are worth retrying, how
+/// long each attempt may take and how many retries the shared budget allows.
+///
+/// ```ignore
+/// let policy = RetryPolicy::new(5)
+///     .with_decider(FullJitterBackoffDecider::default())
+///     .retry_if(|err: &io::Error| err.kind() == io::ErrorKind::ConnectionRefused)
+///     .with_budget(RetryBudget::new(0.2, 10));
+///
+/// let stream = policy.run(|_attempt| TcpStream::connect("127.0.0.1:8080"))?;
+/// ```
+pub struct RetryPolicy<E> {
+    max_attempts: u32,
+    decider: Arc<dyn RetryDecider + Send + Sync>,
+    retry_on: Option<RetryPredicate<E>>,
+    attempt_timeout: Option<time::Duration>,
+    budget: Option<RetryBudget>,
+    clock: SharedClock,
+}
+
+impl<E> Clone for RetryPolicy<E> {
+    fn clone(&self) -> Self {
+        Self {
+            max_attempts: self.max_attempts,
+            decider: self.decider.clone(),
+            retry_on: self.retry_on.clone(),
+            attempt_timeout: self.attempt_timeout,
+            budget: self.budget.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+// -- Constructors
+
+impl<E> RetryPolicy<E> {
+    /// new creates a policy making at most `max_attempts` attempts (the
+    /// first call included) with full jitter backoff in between.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            decider: Arc::new(FullJitterBackoffDecider::default()),
+            retry_on: None,
+            attempt_timeout: None,
+            budget: None,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// `with_decider` sets the `RetryDecider` deciding the wait between attempts.
+    #[must_use]
+    pub fn with_decider<D: RetryDecider + Send + Sync + 'static>(mut self, decider: D) -> Self {
+        self.decider = Arc::new(decider);
+        self
+    }
+
+    /// `retry_if` limits retries to errors matching the predicate, all
+    /// errors are retried by default.
+    #[must_use]
+    pub fn retry_if<F: Fn(&E) -> bool + Send + Sync + 'static>(mut self, predicate: F) -> Self {
+        self.retry_on = Some(Arc::new(predicate));
+        self
+    }
+
+    #[must_use]
+    pub fn with_predicate(mut self, predicate: RetryPredicate<E>) -> Self {
+        self.retry_on = Some(predicate);
+        self
+    }
+
+    /// `with_attempt_timeout` bounds how long each attempt of `run_async` may
+    /// take, synchronous operations can't be interrupted so `run` ignores it.
+    #[must_use]
+    pub fn with_attempt_timeout(mut self, timeout: time::Duration) -> Self {
+        self.attempt_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn with_budget(mut self, budget: RetryBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// `with_clock` makes `run` and `run_async` wait between attempts on
+    /// `clock`, a [`TestClock`](crate::synca::TestClock) lets backoff be
+    /// tested without sleeping. Attempt timeouts still run on the tokio timer.
+    #[must_use]
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+}
+
+// -- Execution
+
+enum Next<E> {
+    Wait(RetryState),
+    GiveUp(RetryError<E>),
+}
+
+/// `Failure` is how an attempt failed, timed out attempts have no error
+/// for the retry predicate to look at and are always retried.
+enum Failure<E> {
+    Error(E),
+    TimedOut,
+}
+
+impl<E> Failure<E> {
+    fn into_error(self) -> Option<E> {
+        match self {
+            Self::Error(error) => Some(error),
+            Self::TimedOut => None,
+        }
+    }
+}
+
+impl<E> RetryPolicy<E> {
+    fn after_failure(&self, state: &RetryState, failure: Failure<E>) -> Next<E> {
+        let attempts = state.attempt + 1;
+
+        if let (Some(predicate), Failure::Error(error)) = (&self.retry_on, &failure) {
+            if !predicate(error) {
+                let error = failure.into_error().expect("should be an error");
+                return Next::GiveUp(RetryError::NotRetryable { attempts, error });
+            }
+        }
+
+        let Some(next) = self.decider.decide(state.clone()) else {
+            return Next::GiveUp(match failure {
+                Failure::Error(last) => RetryError::Exhausted { attempts, last },
+                Failure::TimedOut => RetryError::TimedOut { attempts },
+            });
+        };
+
+        if let Some(budget) = &self.budget {
+            if !budget.try_withdraw() {
+                tracing::warn!("Retry budget exhausted after {} attempts", attempts);
+                return Next::GiveUp(RetryError::BudgetExhausted {
+                    attempts,
+                    last: failure.into_error(),
+                });
+            }
+        }
+
+        tracing::debug!(
+            "Attempt {} of {} failed, retrying in {:?}",
+            attempts,
+            self.max_attempts,
+            next.wait
+        );
+        Next::Wait(next)
+    }
+
+    fn initial_state(&self) -> RetryState {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+        RetryState::new(0, self.max_attempts - 1, None)
+    }
+
+    /// run calls the operation until it succeeds or the policy gives up,
+    /// sleeping the current thread in between attempts. The operation
+    /// receives the zero based attempt number.
+    pub fn run<T, F>(&self, mut operation: F) -> Result<T, RetryError<E>>
+    where
+        F: FnMut(u32) -> Result<T, E>,
+    {
+        let mut state = self.initial_state();
+        loop {
+            match operation(state.attempt) {
+                Ok(value) => return Ok(value),
+                Err(error) => match self.after_failure(&state, Failure::Error(error)) {
+                    Next::GiveUp(err) => return Err(err),
+                    Next::Wait(next) => {
+                        if let Some(wait) = next.wait {
+                            self.clock.sleep(wait);
+                        }
+                        state = next;
+                    }
+                },
+            }
+        }
+    }
+
+    /// `run_async` calls the operation until it succeeds or the policy gives up,
+    /// applying the per-attempt timeout if one is set.
+    pub async fn run_async<T, F, Fut>(&self, mut operation: F) -> Result<T, RetryError<E>>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut state = self.initial_state();
+        loop {
+            let attempt = operation(state.attempt);
+            let result = match self.attempt_timeout {
+                Some(timeout) => {
+                    if let Ok(result) = tokio::time::timeout(timeout, attempt).await {
+                        result.map_err(Failure::Error)
+                    } else {
+                        tracing::warn!(
+                            "Attempt {} timed out after {:?}",
+                            state.attempt + 1,
+                            timeout
+                        );
+                        Err(Failure::TimedOut)
+                    }
+                }
+                None => attempt.await.map_err(Failure::Error),
+            };
+
+            match result {
+                Ok(value) => return Ok(value),
+                Err(failure) => match self.after_failure(&state, failure) {
+                    Next::GiveUp(err) => return Err(err),
+                    Next::Wait(next) => {
+                        if let Some(wait) = next.wait {
+                            self.clock.sleep_async(wait).await;
+                        }
+                        state = next;
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod retry_policy_test {
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+    use crate::retries::SameBackoffDecider;
+    use crate::synca::TestClock;
+
+    fn fast_policy<E>(max_attempts: u32) -> RetryPolicy<E> {
+        RetryPolicy::new(max_attempts)
+            .with_decider(SameBackoffDecider::new(time::Duration::from_millis(1)))
+    }
+
+    #[test]
+    fn full_jitter_waits_stay_under_ceiling() {
+        let decider = FullJitterBackoffDecider::new(
+            time::Duration::from_millis(100),
+            time::Duration::from_millis(350),
+        );
+        assert_eq!(decider.ceiling(1), time::Duration::from_millis(100));
+        assert_eq!(decider.ceiling(3), time::Duration::from_millis(350));
+
+        let mut state = RetryState::new(0, 4, None);
+        while let Some(next) = decider.decide(state.clone()) {
+            assert!(next.wait.expect("should have wait") <= decider.ceiling(next.attempt));
+            state = next;
+        }
+        assert_eq!(state.attempt, 4);
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let result: Result<u32, RetryError<&str>> = fast_policy(3).run(|attempt| {
+            if attempt < 2 {
+                Err("down")
+            } else {
+                Ok(attempt)
+            }
+        });
+        assert_eq!(result.expect("should succeed"), 2);
+
+        let result: Result<(), RetryError<&str>> = fast_policy(3).run(|_| Err("down"));
+        assert!(matches!(
+            result,
+            Err(RetryError::Exhausted {
+                attempts: 3,
+                last: "down"
+            })
+        ));
+    }
+
+    #[test]
+    fn waits_between_attempts_on_the_giving_clock() {
+        let clock = TestClock::new();
+        let policy = RetryPolicy::new(4)
+            .with_decider(SameBackoffDecider::new(time::Duration::from_secs(10)))
+            .with_clock(clock.clone());
+
+        let result: Result<(), RetryError<&str>> = policy.run(|_| Err("down"));
+        assert_eq!(result.unwrap_err().attempts(), 4);
+        assert_eq!(clock.elapsed(), time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn async_waits_between_attempts_on_the_giving_clock() {
+        let clock = TestClock::new();
+        let policy = RetryPolicy::new(3)
+            .with_decider(SameBackoffDecider::new(time::Duration::from_secs(10)))
+            .with_clock(clock.clone());
+
+        let result: Result<(), RetryError<&str>> =
+            block_on(policy.run_async(|_| async { Err("down") }));
+        assert_eq!(result.unwrap_err().attempts(), 3);
+        assert_eq!(clock.elapsed(), time::Duration::from_secs(20));
+    }
+
+    #[test]
+    fn stops_on_errors_not_matching_predicate() {
+        let mut calls = 0;
+        let result: Result<(), RetryError<io::Error>> = fast_policy(5)
+            .with_predicate(retry_on_io_kinds(&[io::ErrorKind::ConnectionRefused]))
+            .run(|_| {
+                calls += 1;
+                Err(io::Error::from(io::ErrorKind::PermissionDenied))
+            });
+
+        assert_eq!(calls, 1);
+        assert!(matches!(result, Err(RetryError::NotRetryable { .. })));
+    }
+
+    #[test]
+    fn shared_budget_limits_retries() {
+        let budget = RetryBudget::new(0.0, 2);
+        let policy = fast_policy::<&str>(10).with_budget(budget.clone());
+
+        let result = policy.run(|_| Err::<(), _>("down"));
+        assert!(matches!(
+            result,
+            Err(RetryError::BudgetExhausted { attempts: 3, .. })
+        ));
+        assert!(budget.available() < 1.0);
+    }
+
+    #[test]
+    fn async_attempts_time_out() {
+        let result: Result<(), RetryError<&str>> = block_on(
+            fast_policy(2)
+                .with_attempt_timeout(time::Duration::from_millis(5))
+                .run_async(|_| async {
+                    tokio::time::sleep(time::Duration::from_millis(50)).await;
+                    Ok(())
+                }),
+        );
+        assert!(matches!(result, Err(RetryError::TimedOut { attempts: 2 })));
+    }
+
+    #[test]
+    fn timed_out_attempts_draw_from_budget() {
+        let budget = RetryBudget::new(0.0, 1);
+        let result: Result<(), RetryError<&str>> = block_on(
+            fast_policy(5)
+                .with_budget(budget.clone())
+                .with_attempt_timeout(time::Duration::from_millis(5))
+                .run_async(|_| async {
+                    tokio::time::sleep(time::Duration::from_millis(50)).await;
+                    Ok(())
+                }),
+        );
+        assert!(matches!(
+            result,
+            Err(RetryError::BudgetExhausted {
+                attempts: 2,
+                last: None
+            })
+        ));
+        assert!(budget.available() < 1.0);
+    }
+}
@@ -0,0 +1,249 @@
+/// `CronError` is returned when a cron expression can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CronError {
+    /// the expression did not have the five `minute hour day month weekday`
+    /// fields.
+    FieldCount(usize),
+
+    /// a field had a value out of its range or which was not understood.
+    InvalidField { field: &'static str, value: String },
+}
+
+impl std::error::Error for CronError {}
+
+impl core::fmt::Display for CronError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// `MAX_SEARCH_DAYS` bounds the search for the next matching time, long
+/// enough to find a February 29th across a skipped leap year.
+const MAX_SEARCH_DAYS: u64 = 366 * 9;
+
+/// `CronSchedule` is a parsed five field cron expression evaluated in UTC,
+/// `minute hour day-of-month month day-of-week`, where every field takes
+/// `*`, values, ranges `a-b`, lists `a,b` and steps `*/n` or `a-b/n`.
+/// `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are also
+/// understood.
+///
+/// Like cron, when both the day of month and the day of week are
+/// restricted a day matching either of them fires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+// -- Constructors
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(CronError::FieldCount(fields.len()));
+        }
+
+        let mut weekdays = parse_field("weekday", fields[4], 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: parse_field("minute", fields[0], 0, 59)?,
+            hours: parse_field("hour", fields[1], 0, 23)?,
+            days: parse_field("day", fields[2], 1, 31)?,
+            months: parse_field("month", fields[3], 1, 12)?,
+            weekdays,
+            days_restricted: !fields[2].starts_with('*'),
+            weekdays_restricted: !fields[4].starts_with('*'),
+        })
+    }
+}
+
+// -- Methods
+
+impl CronSchedule {
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// `next_after` returns the first matching time strictly after
+    /// `unix_seconds`, as seconds since the unix epoch.
+    pub fn next_after(&self, unix_seconds: u64) -> Option<u64> {
+        let start = (unix_seconds / 60 + 1) * 60;
+        let first_day = start / SECONDS_PER_DAY;
+
+        for day in first_day..first_day + MAX_SEARCH_DAYS {
+            if !self.matches_day(day) {
+                continue;
+            }
+
+            let day_start = day * SECONDS_PER_DAY;
+            for hour in 0..24 {
+                if self.hours & (1 << hour) == 0 {
+                    continue;
+                }
+                for minute in 0..60 {
+                    let at = day_start + hour * 3600 + minute * 60;
+                    if self.minutes & (1 << minute) != 0 && at >= start {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, days_since_epoch: u64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+
+        // 1970-01-01 was a thursday
+        let weekday = (days_since_epoch + 4) % 7;
+        let day_matches = self.days & (1 << day) != 0;
+        let weekday_matches = self.weekdays & (1 << weekday) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        }
+    }
+}
+
+impl std::str::FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn parse_field(field: &'static str, text: &str, min: u64, max: u64) -> Result<u64, CronError> {
+    let invalid = || CronError::InvalidField {
+        field,
+        value: text.to_string(),
+    };
+    let number = |value: &str| -> Result<u64, CronError> {
+        value
+            .parse::<u64>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(invalid)
+    };
+
+    let mut mask = 0;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (from, to) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((from, to)) => (number(from)?, number(to)?),
+                None if part.contains('/') => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if from > to {
+            return Err(invalid());
+        }
+
+        for value in (from..=to).step_by(step) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// `civil_from_days` turns days since the unix epoch into a
+/// `(year, month, day)` date of the proleptic gregorian calendar.
+fn civil_from_days(days_since_epoch: u64) -> (u64, u64, u64) {
+    let shifted = days_since_epoch + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod cron_tests {
+    use super::*;
+
+    // 2024-01-01T00:00:00Z, a monday
+    const NEW_YEAR_2024: u64 = 1_704_067_200;
+
+    #[test]
+    fn finds_the_next_matching_minute() {
+        let every_quarter = CronSchedule::parse("*/15 * * * *").expect("should parse");
+        assert_eq!(every_quarter.next_after(NEW_YEAR_2024), Some(1_704_068_100));
+
+        let weekday_mornings = CronSchedule::parse("30 9 * * 1-5").expect("should parse");
+        assert_eq!(
+            weekday_mornings.next_after(NEW_YEAR_2024),
+            Some(1_704_101_400)
+        );
+
+        // friday 2024-01-05T23:59:30Z fires on sunday 2024-01-07
+        let weekly: CronSchedule = "@weekly".parse().expect("should parse");
+        assert_eq!(weekly.next_after(1_704_499_170), Some(1_704_585_600));
+
+        // from 2024-03-01 the next february 29th is in 2028
+        let leap_day = CronSchedule::parse("0 0 29 2 *").expect("should parse");
+        assert_eq!(leap_day.next_after(1_709_251_200), Some(1_835_395_200));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(
+            CronSchedule::parse("* * * *"),
+            Err(CronError::FieldCount(4))
+        );
+        assert_eq!(
+            CronSchedule::parse("60 * * * *"),
+            Err(CronError::InvalidField {
+                field: "minute",
+                value: String::from("60"),
+            })
+        );
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+        assert!(CronSchedule::parse("* 5-2 * * *").is_err());
+    }
+}
@@ -0,0 +1,751 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::runtime::Handle;
+
+use crate::extensions::result_ext::BoxedError;
+use crate::synca::{SharedClock, SystemClock};
+
+use super::{CronError, CronSchedule};
+
+/// `JOB_HISTORY_LIMIT` is how many of its latest runs a job remembers.
+pub const JOB_HISTORY_LIMIT: usize = 32;
+
+/// `MAX_DISPATCH_WAIT` caps how long the dispatcher sleeps between checks,
+/// so clocks that don't follow the system time are still honoured.
+const MAX_DISPATCH_WAIT: Duration = Duration::from_secs(1);
+
+pub type JobFn = Arc<dyn Fn() -> Result<(), BoxedError> + Send + Sync>;
+
+/// `Trigger` decides when a job fires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// fires every giving duration, the first time one interval after being
+    /// scheduled, missed fires are coalesced into one. Zero intervals are
+    /// rejected by [`Scheduler::schedule`].
+    Interval(Duration),
+
+    /// fires on every time matching the cron schedule.
+    Cron(CronSchedule),
+}
+
+impl Trigger {
+    pub fn every(interval: Duration) -> Self {
+        Self::Interval(interval)
+    }
+
+    pub fn cron(expression: &str) -> Result<Self, CronError> {
+        CronSchedule::parse(expression).map(Self::Cron)
+    }
+}
+
+/// `OverlapPolicy` decides what happens when a job fires while a previous
+/// run of it is still going.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// the fire is skipped and recorded as such.
+    #[default]
+    Skip,
+
+    /// the fire is queued and runs once the previous runs are done.
+    Queue,
+
+    /// the job runs again alongside the previous run.
+    Concurrent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(u64);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobOutcome {
+    Succeeded,
+    Failed(String),
+    Panicked,
+
+    /// the job fired while running and [`OverlapPolicy::Skip`] applied.
+    Skipped,
+}
+
+/// `JobRun` is an entry of a job's run history.
+#[derive(Clone, Debug)]
+pub struct JobRun {
+    pub started: Instant,
+    pub elapsed: Duration,
+    pub outcome: JobOutcome,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SchedulerError {
+    /// the scheduler was shut down.
+    Stopped,
+
+    /// the dispatcher thread is already running.
+    AlreadyStarted,
+
+    /// the job was given a [`Trigger::Interval`] of zero, which would fire
+    /// on every check.
+    ZeroInterval,
+
+    /// jobs were still running when the shutdown grace period ended.
+    ShutdownTimedOut { running: usize },
+}
+
+impl std::error::Error for SchedulerError {}
+
+impl core::fmt::Display for SchedulerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `Scheduler` runs registered jobs whenever their [`Trigger`] fires,
+/// either driven by its own dispatcher thread through [`Scheduler::start`]
+/// or by calling [`Scheduler::run_pending`].
+///
+/// Jobs are blocking closures, so runs go to the blocking pool of the
+/// tokio runtime given to [`Scheduler::with_runtime`], or the one the
+/// scheduler was created in. Outside of any runtime each run gets its own
+/// thread.
+///
+/// Clones share the same jobs. Time is read from the scheduler's
+/// [`SharedClock`], cron triggers map it onto the system time taken when
+/// the scheduler was created.
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+struct SchedulerInner {
+    clock: SharedClock,
+    created: (SystemTime, Instant),
+    state: Mutex<SchedulerState>,
+    changed: Condvar,
+    idle: Condvar,
+    dispatcher: Mutex<Option<JoinHandle<()>>>,
+    runtime: Mutex<Option<Handle>>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    next_id: u64,
+    jobs: BTreeMap<JobId, Job>,
+    running: usize,
+    stopped: bool,
+}
+
+struct Job {
+    name: String,
+    trigger: Trigger,
+    overlap: OverlapPolicy,
+    run: JobFn,
+    next_fire: Option<Instant>,
+    running: usize,
+    queued: usize,
+    history: VecDeque<JobRun>,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.inner.lock();
+        f.debug_struct("Scheduler")
+            .field(
+                "jobs",
+                &state.jobs.values().map(|job| &job.name).collect::<Vec<_>>(),
+            )
+            .field("running", &state.running)
+            .field("stopped", &state.stopped)
+            .finish()
+    }
+}
+
+// -- Constructors
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::shared())
+    }
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                created: (SystemTime::now(), clock.now()),
+                clock,
+                state: Mutex::new(SchedulerState::default()),
+                changed: Condvar::new(),
+                idle: Condvar::new(),
+                dispatcher: Mutex::new(None),
+                runtime: Mutex::new(Handle::try_current().ok()),
+            }),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl Scheduler {
+    /// `with_runtime` runs jobs on the blocking pool of `handle`'s runtime.
+    #[must_use]
+    pub fn with_runtime(self, handle: Handle) -> Self {
+        *self
+            .inner
+            .runtime
+            .lock()
+            .expect("should acquire runtime lock") = Some(handle);
+        self
+    }
+}
+
+// -- Methods
+
+impl Scheduler {
+    /// `schedule` registers a job firing on `trigger`, returning its id for
+    /// the history and next fire queries.
+    pub fn schedule<F>(
+        &self,
+        name: impl Into<String>,
+        trigger: Trigger,
+        overlap: OverlapPolicy,
+        job: F,
+    ) -> Result<JobId, SchedulerError>
+    where
+        F: Fn() -> Result<(), BoxedError> + Send + Sync + 'static,
+    {
+        if trigger == Trigger::Interval(Duration::ZERO) {
+            return Err(SchedulerError::ZeroInterval);
+        }
+
+        let now = self.inner.clock.now();
+        let next_fire = self.inner.next_fire(&trigger, now, now);
+
+        let mut state = self.inner.lock();
+        if state.stopped {
+            return Err(SchedulerError::Stopped);
+        }
+
+        let id = JobId(state.next_id);
+        state.next_id += 1;
+        state.jobs.insert(
+            id,
+            Job {
+                name: name.into(),
+                trigger,
+                overlap,
+                run: Arc::new(job),
+                next_fire,
+                running: 0,
+                queued: 0,
+                history: VecDeque::new(),
+            },
+        );
+        drop(state);
+
+        self.inner.changed.notify_all();
+        Ok(id)
+    }
+
+    /// `unschedule` removes the job, a run in progress still completes.
+    pub fn unschedule(&self, id: JobId) -> bool {
+        self.inner.lock().jobs.remove(&id).is_some()
+    }
+
+    /// `next_fire` returns when the job fires next, `None` for unknown jobs
+    /// or cron schedules which never match again.
+    pub fn next_fire(&self, id: JobId) -> Option<Instant> {
+        self.inner
+            .lock()
+            .jobs
+            .get(&id)
+            .and_then(|job| job.next_fire)
+    }
+
+    /// `next_fire_time` is [`Self::next_fire`] as a system time.
+    pub fn next_fire_time(&self, id: JobId) -> Option<SystemTime> {
+        self.next_fire(id).map(|at| self.inner.system_time(at))
+    }
+
+    /// `history` returns the latest runs of the job, oldest first.
+    pub fn history(&self, id: JobId) -> Vec<JobRun> {
+        self.inner
+            .lock()
+            .jobs
+            .get(&id)
+            .map(|job| job.history.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// `running` returns how many job runs are currently in progress.
+    pub fn running(&self) -> usize {
+        self.inner.lock().running
+    }
+
+    /// `run_pending` fires every job which is due, returning how many fired.
+    pub fn run_pending(&self) -> usize {
+        self.inner.fire_due(&mut self.inner.lock())
+    }
+
+    /// `start` spawns the dispatcher thread firing jobs as they become due.
+    pub fn start(&self) -> Result<(), SchedulerError> {
+        let mut dispatcher = self
+            .inner
+            .dispatcher
+            .lock()
+            .expect("should acquire dispatcher lock");
+        if self.inner.lock().stopped {
+            return Err(SchedulerError::Stopped);
+        }
+        if dispatcher.is_some() {
+            return Err(SchedulerError::AlreadyStarted);
+        }
+
+        let inner = self.inner.clone();
+        *dispatcher = Some(thread::spawn(move || inner.dispatch()));
+        Ok(())
+    }
+
+    /// `shutdown` stops firing jobs, drops queued runs and waits up to
+    /// `grace` for the runs in progress to finish.
+    pub fn shutdown(&self, grace: Duration) -> Result<(), SchedulerError> {
+        self.inner.lock().stopped = true;
+        self.inner.changed.notify_all();
+
+        let dispatcher = self
+            .inner
+            .dispatcher
+            .lock()
+            .expect("should acquire dispatcher lock")
+            .take();
+        if let Some(dispatcher) = dispatcher {
+            dispatcher.join().expect("should join dispatcher thread");
+        }
+
+        let (state, _) = self
+            .inner
+            .idle
+            .wait_timeout_while(self.inner.lock(), grace, |state| state.running > 0)
+            .expect("should acquire scheduler lock");
+        match state.running {
+            0 => Ok(()),
+            running => Err(SchedulerError::ShutdownTimedOut { running }),
+        }
+    }
+}
+
+impl SchedulerInner {
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        self.state.lock().expect("should acquire scheduler lock")
+    }
+
+    fn system_time(&self, at: Instant) -> SystemTime {
+        let (system, instant) = self.created;
+        match at.checked_duration_since(instant) {
+            Some(after) => system + after,
+            None => system - instant.duration_since(at),
+        }
+    }
+
+    /// `next_fire` returns when the trigger fires after `previous`, skipping
+    /// any time already behind `now`.
+    fn next_fire(&self, trigger: &Trigger, previous: Instant, now: Instant) -> Option<Instant> {
+        match trigger {
+            Trigger::Interval(interval) => {
+                let next = previous + *interval;
+                if next > now {
+                    return Some(next);
+                }
+
+                // keep the interval's phase when catching up with missed fires
+                let late = now.duration_since(next).as_nanos() % interval.as_nanos();
+                let late = Duration::from_nanos(u64::try_from(late).unwrap_or(u64::MAX));
+                Some(now + interval.saturating_sub(late))
+            }
+            Trigger::Cron(schedule) => {
+                let after = self.system_time(previous.max(now));
+                let after_secs = after.duration_since(UNIX_EPOCH).ok()?.as_secs();
+                let fire_at = UNIX_EPOCH + Duration::from_secs(schedule.next_after(after_secs)?);
+                let wait = fire_at.duration_since(after).unwrap_or_default();
+                Some(previous.max(now) + wait)
+            }
+        }
+    }
+
+    fn fire_due(self: &Arc<Self>, state: &mut SchedulerState) -> usize {
+        if state.stopped {
+            return 0;
+        }
+
+        let now = self.clock.now();
+        let mut fired = 0;
+        let mut starting = Vec::new();
+        for (id, job) in &mut state.jobs {
+            let Some(due) = job.next_fire.filter(|due| *due <= now) else {
+                continue;
+            };
+            job.next_fire = self.next_fire(&job.trigger, due, now);
+            fired += 1;
+
+            match (job.running, job.overlap) {
+                (0, _) | (_, OverlapPolicy::Concurrent) => {
+                    job.running += 1;
+                    starting.push((*id, job.run.clone()));
+                }
+                (_, OverlapPolicy::Queue) => job.queued += 1,
+                (_, OverlapPolicy::Skip) => job.record(JobRun {
+                    started: now,
+                    elapsed: Duration::ZERO,
+                    outcome: JobOutcome::Skipped,
+                }),
+            }
+        }
+
+        state.running += starting.len();
+        let runtime = self
+            .runtime
+            .lock()
+            .expect("should acquire runtime lock")
+            .clone();
+        for (id, run) in starting {
+            let inner = self.clone();
+            let execute = move || inner.execute(id, &run);
+            match &runtime {
+                Some(runtime) => drop(runtime.spawn_blocking(execute)),
+                None => drop(thread::spawn(execute)),
+            }
+        }
+        fired
+    }
+
+    fn execute(&self, id: JobId, run: &JobFn) {
+        loop {
+            let started = self.clock.now();
+            let outcome = match catch_unwind(AssertUnwindSafe(|| run())) {
+                Ok(Ok(())) => JobOutcome::Succeeded,
+                Ok(Err(err)) => JobOutcome::Failed(err.to_string()),
+                Err(_) => JobOutcome::Panicked,
+            };
+            let elapsed = self.clock.now().saturating_duration_since(started);
+
+            let mut state = self.lock();
+            let stopped = state.stopped;
+            let Some(job) = state.jobs.get_mut(&id) else {
+                state.running -= 1;
+                break;
+            };
+
+            job.record(JobRun {
+                started,
+                elapsed,
+                outcome,
+            });
+            if job.queued > 0 && !stopped {
+                job.queued -= 1;
+                continue;
+            }
+
+            job.queued = 0;
+            job.running -= 1;
+            state.running -= 1;
+            break;
+        }
+        self.idle.notify_all();
+    }
+
+    fn dispatch(self: Arc<Self>) {
+        let mut state = self.lock();
+        while !state.stopped {
+            self.fire_due(&mut state);
+
+            let now = self.clock.now();
+            let wait = state
+                .jobs
+                .values()
+                .filter_map(|job| job.next_fire)
+                .min()
+                .map_or(MAX_DISPATCH_WAIT, |next| {
+                    next.saturating_duration_since(now)
+                })
+                .min(MAX_DISPATCH_WAIT);
+
+            state = self
+                .changed
+                .wait_timeout(state, wait)
+                .expect("should acquire scheduler lock")
+                .0;
+        }
+    }
+}
+
+impl Job {
+    fn record(&mut self, run: JobRun) {
+        if self.history.len() == JOB_HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(run);
+    }
+}
+
+#[cfg(test)]
+mod scheduler_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::synca::TestClock;
+
+    const GRACE: Duration = Duration::from_secs(5);
+
+    fn outcomes(scheduler: &Scheduler, id: JobId) -> Vec<JobOutcome> {
+        scheduler
+            .history(id)
+            .into_iter()
+            .map(|run| run.outcome)
+            .collect()
+    }
+
+    fn wait_idle(scheduler: &Scheduler) {
+        let started = Instant::now();
+        while scheduler.running() > 0 {
+            assert!(started.elapsed() < GRACE, "jobs should finish");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// `gated_job` returns a job which blocks until released through the
+    /// returned sender.
+    fn gated_job() -> (
+        mpsc::Sender<()>,
+        impl Fn() -> Result<(), BoxedError> + Send + Sync + 'static,
+    ) {
+        let (release, gate) = mpsc::channel::<()>();
+        let gate = Mutex::new(gate);
+        (release, move || {
+            gate.lock()
+                .expect("should acquire gate")
+                .recv()
+                .map_err(|err| Box::new(err) as BoxedError)
+        })
+    }
+
+    #[test]
+    fn fires_interval_jobs_and_records_history() {
+        let clock = TestClock::new();
+        let scheduler = Scheduler::with_clock(Arc::new(clock.clone()));
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        let id = scheduler
+            .schedule(
+                "cleanup",
+                Trigger::every(Duration::from_secs(10)),
+                OverlapPolicy::Skip,
+                move || {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 1 {
+                        return Err("disk full".into());
+                    }
+                    Ok(())
+                },
+            )
+            .expect("should schedule");
+
+        let first = scheduler.next_fire(id).expect("should have next fire");
+        assert_eq!(scheduler.run_pending(), 0);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(scheduler.run_pending(), 1);
+        assert_eq!(
+            scheduler.next_fire(id),
+            Some(first + Duration::from_secs(10))
+        );
+        wait_idle(&scheduler);
+
+        // missed fires are coalesced into a single run
+        clock.advance(Duration::from_secs(35));
+        assert_eq!(scheduler.run_pending(), 1);
+        assert_eq!(
+            scheduler.next_fire(id),
+            Some(first + Duration::from_secs(40))
+        );
+
+        assert_eq!(scheduler.shutdown(GRACE), Ok(()));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            outcomes(&scheduler, id),
+            vec![
+                JobOutcome::Succeeded,
+                JobOutcome::Failed(String::from("disk full"))
+            ]
+        );
+        assert_eq!(
+            scheduler.schedule(
+                "late",
+                Trigger::every(GRACE),
+                OverlapPolicy::Skip,
+                || Ok(())
+            ),
+            Err(SchedulerError::Stopped)
+        );
+    }
+
+    #[test]
+    fn applies_overlap_policies() {
+        let clock = TestClock::new();
+        let scheduler = Scheduler::with_clock(Arc::new(clock.clone()));
+        let every = Trigger::every(Duration::from_secs(1));
+
+        let (release_skip, skip_job) = gated_job();
+        let (release_queue, queue_job) = gated_job();
+        let (release_concurrent, concurrent_job) = gated_job();
+        let skip = scheduler
+            .schedule("skip", every.clone(), OverlapPolicy::Skip, skip_job)
+            .expect("should schedule");
+        let queue = scheduler
+            .schedule("queue", every.clone(), OverlapPolicy::Queue, queue_job)
+            .expect("should schedule");
+        let concurrent = scheduler
+            .schedule(
+                "concurrent",
+                every,
+                OverlapPolicy::Concurrent,
+                concurrent_job,
+            )
+            .expect("should schedule");
+
+        for _ in 0..2 {
+            clock.advance(Duration::from_secs(1));
+            assert_eq!(scheduler.run_pending(), 3);
+        }
+        // the concurrent job has two runs going
+        assert_eq!(scheduler.running(), 4);
+        assert_eq!(outcomes(&scheduler, skip), vec![JobOutcome::Skipped]);
+
+        for _ in 0..2 {
+            release_skip.send(()).expect("should release");
+            release_queue.send(()).expect("should release");
+            release_concurrent.send(()).expect("should release");
+        }
+        wait_idle(&scheduler);
+        assert_eq!(scheduler.shutdown(GRACE), Ok(()));
+
+        assert_eq!(
+            outcomes(&scheduler, skip),
+            vec![JobOutcome::Skipped, JobOutcome::Succeeded]
+        );
+        assert_eq!(outcomes(&scheduler, queue), vec![JobOutcome::Succeeded; 2]);
+        assert_eq!(
+            outcomes(&scheduler, concurrent),
+            vec![JobOutcome::Succeeded; 2]
+        );
+    }
+
+    #[test]
+    fn shutdown_waits_for_running_jobs() {
+        let scheduler = Scheduler::new();
+        let (release, job) = gated_job();
+        let (fired, was_fired) = mpsc::channel();
+        let id = scheduler
+            .schedule(
+                "refresh",
+                Trigger::every(Duration::from_millis(5)),
+                OverlapPolicy::Skip,
+                move || {
+                    let _ = fired.send(());
+                    job()
+                },
+            )
+            .expect("should schedule");
+        scheduler.start().expect("should start");
+        assert_eq!(scheduler.start(), Err(SchedulerError::AlreadyStarted));
+
+        was_fired.recv_timeout(GRACE).expect("should fire job");
+        assert_eq!(
+            scheduler.shutdown(Duration::from_millis(20)),
+            Err(SchedulerError::ShutdownTimedOut { running: 1 })
+        );
+
+        release.send(()).expect("should release");
+        assert_eq!(scheduler.shutdown(GRACE), Ok(()));
+        assert_eq!(
+            scheduler.history(id).last().map(|run| run.outcome.clone()),
+            Some(JobOutcome::Succeeded)
+        );
+        assert_eq!(scheduler.run_pending(), 0);
+    }
+
+    #[test]
+    fn rejects_zero_intervals() {
+        let scheduler = Scheduler::new();
+        assert_eq!(
+            scheduler.schedule(
+                "spin",
+                Trigger::every(Duration::ZERO),
+                OverlapPolicy::Skip,
+                || Ok(())
+            ),
+            Err(SchedulerError::ZeroInterval)
+        );
+        assert_eq!(format!("{scheduler:?}").matches("spin").count(), 0);
+    }
+
+    #[test]
+    fn runs_jobs_on_the_given_runtime() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .thread_name("scheduler-runtime")
+            .build()
+            .expect("should build runtime");
+        let clock = TestClock::new();
+        let scheduler =
+            Scheduler::with_clock(Arc::new(clock.clone())).with_runtime(runtime.handle().clone());
+
+        let (ran_on, thread_name) = mpsc::channel();
+        scheduler
+            .schedule(
+                "report",
+                Trigger::every(Duration::from_secs(1)),
+                OverlapPolicy::Skip,
+                move || {
+                    let _ = ran_on.send(thread::current().name().map(String::from));
+                    Ok(())
+                },
+            )
+            .expect("should schedule");
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.run_pending(), 1);
+        assert_eq!(
+            thread_name.recv_timeout(GRACE).expect("should run job"),
+            Some(String::from("scheduler-runtime"))
+        );
+        assert_eq!(scheduler.shutdown(GRACE), Ok(()));
+    }
+
+    #[test]
+    fn reports_cron_fire_times() {
+        let scheduler = Scheduler::new();
+        let id = scheduler
+            .schedule(
+                "hourly",
+                Trigger::cron("@hourly").expect("should parse"),
+                OverlapPolicy::Skip,
+                || Ok(()),
+            )
+            .expect("should schedule");
+
+        let next = scheduler.next_fire_time(id).expect("should have next fire");
+        let secs = next
+            .duration_since(UNIX_EPOCH)
+            .expect("should be after epoch")
+            .as_secs();
+        assert_eq!(secs % 3600, 0);
+        assert!(next > SystemTime::now());
+        assert!(next <= SystemTime::now() + Duration::from_secs(3600));
+    }
+}
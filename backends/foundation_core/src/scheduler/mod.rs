@@ -0,0 +1,5 @@
+mod cron;
+mod jobs;
+
+pub use cron::*;
+pub use jobs::*;
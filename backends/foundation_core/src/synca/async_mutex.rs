@@ -0,0 +1,151 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use super::{Semaphore, SemaphorePermit};
+
+/// `AsyncMutex` is a fair mutex whose `lock` waits asynchronously instead
+/// of blocking the thread, so a task holding it across an `.await` never
+/// deadlocks the executor it runs on.
+///
+/// Waiters get the lock in the order they asked for it.
+pub struct AsyncMutex<T: ?Sized> {
+    semaphore: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: access to `value` is serialized by the single semaphore permit.
+unsafe impl<T: ?Sized + Send> Send for AsyncMutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for AsyncMutex<T> {}
+
+// -- Constructors
+
+impl<T> AsyncMutex<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(1),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> Default for AsyncMutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// -- Methods
+
+impl<T: ?Sized> AsyncMutex<T> {
+    /// `lock` waits for the lock, releasing it when the guard drops.
+    pub async fn lock(&self) -> AsyncMutexGuard<'_, T> {
+        let permit = self.semaphore.acquire().await;
+        AsyncMutexGuard {
+            mutex: self,
+            _permit: permit,
+        }
+    }
+
+    pub fn try_lock(&self) -> Option<AsyncMutexGuard<'_, T>> {
+        self.semaphore.try_acquire().map(|permit| AsyncMutexGuard {
+            mutex: self,
+            _permit: permit,
+        })
+    }
+
+    /// `get_mut` needs no locking as the borrow proves exclusive access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AsyncMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Some(guard) => f
+                .debug_struct("AsyncMutex")
+                .field("value", &&*guard)
+                .finish(),
+            None => f
+                .debug_struct("AsyncMutex")
+                .field("value", &"<locked>")
+                .finish(),
+        }
+    }
+}
+
+/// `AsyncMutexGuard` gives access to the value of a locked [`AsyncMutex`].
+pub struct AsyncMutexGuard<'a, T: ?Sized> {
+    mutex: &'a AsyncMutex<T>,
+    _permit: SemaphorePermit<'a>,
+}
+
+// SAFETY: sharing the guard only shares `&T`.
+unsafe impl<T: ?Sized + Sync> Sync for AsyncMutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for AsyncMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the guard holds the only permit.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for AsyncMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the guard holds the only permit.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AsyncMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod test_async_mutex {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    #[test]
+    fn can_be_held_across_await_points() {
+        block_on(async {
+            let shared = Arc::new(AsyncMutex::new(Vec::new()));
+
+            let tasks: Vec<_> = (0..4)
+                .map(|worker| {
+                    let shared = shared.clone();
+                    tokio::spawn(async move {
+                        let mut guard = shared.lock().await;
+                        guard.push(worker);
+                        // a std mutex held here would block the only thread.
+                        tokio::task::yield_now().await;
+                        guard.push(worker);
+                    })
+                })
+                .collect();
+
+            tokio::task::yield_now().await;
+            assert!(shared.try_lock().is_none());
+
+            for task in tasks {
+                task.await.expect("should finish");
+            }
+
+            let values = shared.lock().await.clone();
+            assert_eq!(values, vec![0, 0, 1, 1, 2, 2, 3, 3]);
+        });
+    }
+}
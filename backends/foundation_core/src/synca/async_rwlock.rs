@@ -0,0 +1,234 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use super::{Semaphore, SemaphorePermit};
+
+/// Readers take a single permit and writers all of them, this bounds the
+/// number of concurrent readers.
+const MAX_READERS: usize = usize::MAX >> 3;
+
+/// `AsyncRwLock` is a reader-writer lock whose acquisitions wait
+/// asynchronously instead of blocking the thread.
+///
+/// By default it is fair: readers and writers get the lock in the order
+/// they asked for it, so a waiting writer holds back later readers. With
+/// [`AsyncRwLock::with_writer_preference`] waiting writers also go ahead
+/// of readers that queued before them.
+pub struct AsyncRwLock<T: ?Sized> {
+    semaphore: Semaphore,
+    prefer_writers: bool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: access to `value` is governed by the semaphore, writers hold
+// every permit and readers only ever get `&T`.
+unsafe impl<T: ?Sized + Send> Send for AsyncRwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for AsyncRwLock<T> {}
+
+// -- Constructors
+
+impl<T> AsyncRwLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(MAX_READERS),
+            prefer_writers: false,
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> Default for AsyncRwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// -- Builder methods
+
+impl<T> AsyncRwLock<T> {
+    /// `with_writer_preference` lets waiting writers go ahead of waiting
+    /// readers, for read heavy data whose updates should not lag.
+    #[must_use]
+    pub fn with_writer_preference(mut self) -> Self {
+        self.prefer_writers = true;
+        self
+    }
+}
+
+// -- Methods
+
+impl<T: ?Sized> AsyncRwLock<T> {
+    pub async fn read(&self) -> AsyncRwLockReadGuard<'_, T> {
+        let permit = self.semaphore.acquire().await;
+        AsyncRwLockReadGuard {
+            lock: self,
+            _permit: permit,
+        }
+    }
+
+    pub async fn write(&self) -> AsyncRwLockWriteGuard<'_, T> {
+        let permit = if self.prefer_writers {
+            self.semaphore.acquire_many_preferred(MAX_READERS).await
+        } else {
+            self.semaphore.acquire_many(MAX_READERS).await
+        };
+        AsyncRwLockWriteGuard {
+            lock: self,
+            _permit: permit,
+        }
+    }
+
+    pub fn try_read(&self) -> Option<AsyncRwLockReadGuard<'_, T>> {
+        self.semaphore
+            .try_acquire()
+            .map(|permit| AsyncRwLockReadGuard {
+                lock: self,
+                _permit: permit,
+            })
+    }
+
+    pub fn try_write(&self) -> Option<AsyncRwLockWriteGuard<'_, T>> {
+        self.semaphore
+            .try_acquire_many(MAX_READERS)
+            .map(|permit| AsyncRwLockWriteGuard {
+                lock: self,
+                _permit: permit,
+            })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for AsyncRwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_read() {
+            Some(guard) => f
+                .debug_struct("AsyncRwLock")
+                .field("value", &&*guard)
+                .finish(),
+            None => f
+                .debug_struct("AsyncRwLock")
+                .field("value", &"<locked>")
+                .finish(),
+        }
+    }
+}
+
+/// `AsyncRwLockReadGuard` gives shared access to the value of an
+/// [`AsyncRwLock`].
+pub struct AsyncRwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a AsyncRwLock<T>,
+    _permit: SemaphorePermit<'a>,
+}
+
+// SAFETY: a read guard only ever hands out `&T`.
+unsafe impl<T: ?Sized + Sync> Sync for AsyncRwLockReadGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for AsyncRwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: no writer can hold the lock while this permit is held.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+/// `AsyncRwLockWriteGuard` gives exclusive access to the value of an
+/// [`AsyncRwLock`].
+pub struct AsyncRwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a AsyncRwLock<T>,
+    _permit: SemaphorePermit<'a>,
+}
+
+// SAFETY: sharing the guard only shares `&T`.
+unsafe impl<T: ?Sized + Sync> Sync for AsyncRwLockWriteGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for AsyncRwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the guard holds every permit.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for AsyncRwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: the guard holds every permit.
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+#[cfg(test)]
+mod test_async_rwlock {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    /// `acquisition_order` holds a write lock while a reader and then a
+    /// writer queue up, returning who got the lock first once released.
+    fn acquisition_order(lock: AsyncRwLock<usize>) -> Vec<&'static str> {
+        block_on(async {
+            let lock = Arc::new(lock);
+            let order = Arc::new(Mutex::new(Vec::new()));
+            let held = lock.write().await;
+
+            let reader = {
+                let (lock, order) = (lock.clone(), order.clone());
+                tokio::spawn(async move {
+                    let _guard = lock.read().await;
+                    order.lock().unwrap().push("reader");
+                })
+            };
+            tokio::task::yield_now().await;
+
+            let writer = {
+                let (lock, order) = (lock.clone(), order.clone());
+                tokio::spawn(async move {
+                    let mut guard = lock.write().await;
+                    *guard += 1;
+                    order.lock().unwrap().push("writer");
+                })
+            };
+            tokio::task::yield_now().await;
+            assert!(lock.try_read().is_none());
+
+            drop(held);
+            reader.await.expect("should finish");
+            writer.await.expect("should finish");
+            assert_eq!(*lock.read().await, 1);
+
+            let order = order.lock().unwrap().clone();
+            order
+        })
+    }
+
+    #[test]
+    fn readers_share_and_writers_wait_their_turn() {
+        let lock = AsyncRwLock::new(0);
+        let first = lock.try_read().expect("should read");
+        let second = lock.try_read().expect("should read");
+        assert!(lock.try_write().is_none());
+        drop((first, second));
+        assert!(lock.try_write().is_some());
+
+        assert_eq!(
+            acquisition_order(AsyncRwLock::new(0)),
+            vec!["reader", "writer"]
+        );
+        assert_eq!(
+            acquisition_order(AsyncRwLock::new(0).with_writer_preference()),
+            vec!["writer", "reader"]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+extern crate test;
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use super::*;
+
+use self::test::Bencher;
+
+const WORKERS: usize = 4;
+const TASKS: usize = 10_000;
+
+/// `run_workers` has `WORKERS` threads drain `TASKS` tasks, each task
+/// spawning one follow up task until half of them were spawned.
+fn run_workers(pop: impl Fn(usize) -> Option<usize> + Sync, push: impl Fn(usize, usize) + Sync) {
+    let done = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for worker in 0..WORKERS {
+            let (pop, push, done) = (&pop, &push, &done);
+            scope.spawn(move || {
+                while done.load(Ordering::Relaxed) < TASKS {
+                    let Some(task) = pop(worker) else {
+                        thread::yield_now();
+                        continue;
+                    };
+                    done.fetch_add(1, Ordering::Relaxed);
+                    if task < TASKS / 2 {
+                        push(worker, task + TASKS / 2);
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[bench]
+fn bench_mutex_vecdeque_queue(b: &mut Bencher) {
+    b.iter(|| {
+        let queue = Mutex::new((0..TASKS / 2).collect::<VecDeque<_>>());
+        run_workers(
+            |_| queue.lock().unwrap().pop_front(),
+            |_, task| queue.lock().unwrap().push_back(task),
+        );
+    })
+}
+
+#[bench]
+fn bench_work_stealing_queue(b: &mut Bencher) {
+    b.iter(|| {
+        let queue = WorkStealingQueue::new(WORKERS, 256);
+        (0..TASKS / 2).for_each(|task| queue.push(task));
+        let workers: Vec<_> = (0..WORKERS).map(|index| queue.worker(index)).collect();
+        run_workers(
+            |worker| workers[worker].pop(),
+            |worker, task| workers[worker].push(task),
+        );
+    })
+}
@@ -0,0 +1,167 @@
+use std::fmt::Debug;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::{thread, time};
+
+/// `Sleeping` is the future returned by [`Clock::sleep_async`].
+pub type Sleeping = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// `Clock` is where time-based primitives like the rate limiters, the
+/// circuit breaker and retry policies read the time from, letting tests swap
+/// in a [`TestClock`] instead of sleeping.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> time::Instant;
+
+    /// `sleep` blocks the current thread for `duration` as seen by this clock.
+    fn sleep(&self, duration: time::Duration);
+
+    /// `sleep_async` resolves once `duration` passed as seen by this clock,
+    /// without blocking the thread.
+    fn sleep_async(&self, duration: time::Duration) -> Sleeping;
+}
+
+/// `SharedClock` is how a [`Clock`] is held by the types using one.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// `SystemClock` is the real monotonic clock, the default everywhere.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(Self)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> time::Instant {
+        time::Instant::now()
+    }
+
+    fn sleep(&self, duration: time::Duration) {
+        thread::sleep(duration);
+    }
+
+    fn sleep_async(&self, duration: time::Duration) -> Sleeping {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// `TestClock` is a [`Clock`] that only moves when told to, either through
+/// [`TestClock::advance`] or by a fixed step on every reading when
+/// auto-advance is set. Sleeping on it advances it instantly.
+///
+/// Clones share the same time, keep one to control a clock handed out.
+#[derive(Clone, Debug)]
+pub struct TestClock {
+    state: Arc<Mutex<TestClockState>>,
+}
+
+#[derive(Debug)]
+struct TestClockState {
+    started: time::Instant,
+    now: time::Instant,
+    auto_advance: Option<time::Duration>,
+}
+
+// -- Constructors
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        let started = time::Instant::now();
+        Self {
+            state: Arc::new(Mutex::new(TestClockState {
+                started,
+                now: started,
+                auto_advance: None,
+            })),
+        }
+    }
+
+    /// `with_auto_advance` moves the clock forward by `step` after every
+    /// reading of it.
+    #[must_use]
+    pub fn with_auto_advance(self, step: time::Duration) -> Self {
+        self.set_auto_advance(Some(step));
+        self
+    }
+}
+
+// -- Methods
+
+impl TestClock {
+    pub fn advance(&self, duration: time::Duration) {
+        let mut state = self.state.lock().expect("should acquire clock lock");
+        state.now += duration;
+    }
+
+    pub fn set_auto_advance(&self, step: Option<time::Duration>) {
+        let mut state = self.state.lock().expect("should acquire clock lock");
+        state.auto_advance = step;
+    }
+
+    /// `elapsed` returns how far the clock moved since it was created.
+    pub fn elapsed(&self) -> time::Duration {
+        let state = self.state.lock().expect("should acquire clock lock");
+        state.now.duration_since(state.started)
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> time::Instant {
+        let mut state = self.state.lock().expect("should acquire clock lock");
+        let now = state.now;
+        if let Some(step) = state.auto_advance {
+            state.now += step;
+        }
+        now
+    }
+
+    fn sleep(&self, duration: time::Duration) {
+        self.advance(duration);
+    }
+
+    fn sleep_async(&self, duration: time::Duration) -> Sleeping {
+        self.advance(duration);
+        Box::pin(future::ready(()))
+    }
+}
+
+#[cfg(test)]
+mod test_clock_tests {
+    use super::*;
+
+    #[test]
+    fn moves_only_when_advanced() {
+        let clock = TestClock::new();
+        let started = clock.now();
+        assert_eq!(clock.now(), started);
+
+        clock.advance(time::Duration::from_secs(5));
+        clock.sleep(time::Duration::from_secs(1));
+        assert_eq!(clock.now() - started, time::Duration::from_secs(6));
+
+        let shared: SharedClock = Arc::new(
+            clock
+                .clone()
+                .with_auto_advance(time::Duration::from_millis(10)),
+        );
+        let first = shared.now();
+        assert_eq!(shared.now() - first, time::Duration::from_millis(10));
+        assert_eq!(clock.elapsed(), time::Duration::from_millis(6020));
+    }
+
+    #[test]
+    fn sleeping_async_advances_right_away() {
+        let clock = TestClock::new();
+        crate::extensions::tokio_ext::block_on(clock.sleep_async(time::Duration::from_secs(60)));
+        assert_eq!(clock.elapsed(), time::Duration::from_secs(60));
+    }
+}
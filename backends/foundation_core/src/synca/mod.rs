@@ -1,11 +1,25 @@
+mod async_mutex;
+mod async_rwlock;
+mod clock;
 mod entrylist;
 mod event;
 mod idleman;
+mod semaphore;
 mod signals;
 mod sleepers;
+mod stealing;
 
+pub use async_mutex::*;
+pub use async_rwlock::*;
+pub use clock::*;
 pub use entrylist::*;
 pub use event::*;
 pub use idleman::*;
+pub use semaphore::*;
 pub use signals::*;
 pub use sleepers::*;
+pub use stealing::*;
+
+#[cfg(test)]
+#[cfg(feature = "nightly")]
+mod bench;
@@ -0,0 +1,369 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(not(feature = "web_spin_lock"))]
+use std::sync::{Mutex, MutexGuard};
+
+#[cfg(feature = "web_spin_lock")]
+use wasm_sync::{Mutex, MutexGuard};
+
+struct Waiting {
+    id: u64,
+    needed: usize,
+    preferred: bool,
+    waker: Waker,
+}
+
+#[derive(Default)]
+struct SemaphoreState {
+    permits: usize,
+    total: usize,
+    next_id: u64,
+    waiting: VecDeque<Waiting>,
+    granted: HashSet<u64>,
+}
+
+impl SemaphoreState {
+    /// `grant_waiting` hands permits to waiters in queue order, stopping
+    /// at the first one that cannot be satisfied so no waiter is ever
+    /// overtaken. The returned wakers are woken once the lock is released.
+    fn grant_waiting(&mut self) -> Vec<Waker> {
+        let mut wakers = Vec::new();
+        while let Some(front) = self.waiting.front() {
+            if front.needed > self.permits {
+                break;
+            }
+            let waiting = self.waiting.pop_front().expect("should have front waiter");
+            self.permits -= waiting.needed;
+            self.granted.insert(waiting.id);
+            wakers.push(waiting.waker);
+        }
+        wakers
+    }
+
+    fn enqueue(&mut self, needed: usize, preferred: bool, waker: Waker) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let waiting = Waiting {
+            id,
+            needed,
+            preferred,
+            waker,
+        };
+
+        // preferred waiters queue behind other preferred waiters but
+        // ahead of everyone else.
+        if preferred {
+            let position = self
+                .waiting
+                .iter()
+                .position(|waiting| !waiting.preferred)
+                .unwrap_or(self.waiting.len());
+            self.waiting.insert(position, waiting);
+        } else {
+            self.waiting.push_back(waiting);
+        }
+        id
+    }
+}
+
+/// `Semaphore` is an async counting semaphore.
+///
+/// It is fair: permits are handed out in the order they were asked for,
+/// so a large `acquire_many` is never starved by a stream of smaller
+/// acquisitions. It never blocks the thread, which makes it safe to use
+/// from tasks on the valtron executor and on wasm.
+pub struct Semaphore {
+    state: Mutex<SemaphoreState>,
+}
+
+// -- Constructors
+
+impl Semaphore {
+    #[must_use]
+    pub fn new(permits: usize) -> Self {
+        Self {
+            state: Mutex::new(SemaphoreState {
+                permits,
+                total: permits,
+                ..SemaphoreState::default()
+            }),
+        }
+    }
+}
+
+// -- Methods
+
+impl Semaphore {
+    pub fn available_permits(&self) -> usize {
+        self.lock_state().permits
+    }
+
+    /// `add_permits` adds `permits` new permits to the semaphore, waking
+    /// any waiters that can now proceed.
+    pub fn add_permits(&self, permits: usize) {
+        self.lock_state().total += permits;
+        self.release(permits);
+    }
+
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.try_acquire_many(1)
+    }
+
+    /// `try_acquire_many` takes `permits` permits if they are available
+    /// and nobody is waiting ahead.
+    pub fn try_acquire_many(&self, permits: usize) -> Option<SemaphorePermit<'_>> {
+        let mut state = self.lock_state();
+        if state.waiting.is_empty() && state.permits >= permits {
+            state.permits -= permits;
+            return Some(SemaphorePermit {
+                semaphore: self,
+                permits,
+            });
+        }
+        None
+    }
+
+    pub fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// `acquire_many` waits until `permits` permits can be taken at once.
+    ///
+    /// Asking for more than the semaphore has in total waits, holding up
+    /// the waiters queued behind it, until [`Semaphore::add_permits`]
+    /// raises the total far enough.
+    pub fn acquire_many(&self, permits: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            permits,
+            preferred: false,
+            id: None,
+        }
+    }
+
+    /// `acquire_many_preferred` is `acquire_many` but queues ahead of
+    /// waiters that are not preferred, used for writer preference.
+    pub(crate) fn acquire_many_preferred(&self, permits: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            permits,
+            preferred: true,
+            id: None,
+        }
+    }
+
+    /// `release` returns `permits` taken from the semaphore, waking any
+    /// waiters that can now proceed.
+    fn release(&self, permits: usize) {
+        let wakers = {
+            let mut state = self.lock_state();
+            state.permits += permits;
+            state.grant_waiting()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, SemaphoreState> {
+        self.state.lock().expect("should acquire semaphore lock")
+    }
+}
+
+impl std::fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock_state();
+        f.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("total", &state.total)
+            .field("waiting", &state.waiting.len())
+            .finish()
+    }
+}
+
+/// `SemaphorePermit` holds permits of a [`Semaphore`] until dropped.
+#[derive(Debug)]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.release(self.permits);
+    }
+}
+
+/// `Acquire` is the future returned by [`Semaphore::acquire_many`].
+///
+/// Dropping it before completion gives up its place in the queue.
+#[derive(Debug)]
+pub struct Acquire<'a> {
+    semaphore: &'a Semaphore,
+    permits: usize,
+    preferred: bool,
+    id: Option<u64>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SemaphorePermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore = self.semaphore;
+        let permits = self.permits;
+        let mut state = semaphore.lock_state();
+
+        match self.id {
+            None => {
+                let can_skip_queue = state.waiting.is_empty()
+                    || (self.preferred && !state.waiting.iter().any(|waiting| waiting.preferred));
+                if can_skip_queue && state.permits >= permits {
+                    state.permits -= permits;
+                    return Poll::Ready(SemaphorePermit { semaphore, permits });
+                }
+
+                let id = state.enqueue(permits, self.preferred, cx.waker().clone());
+                drop(state);
+                self.id = Some(id);
+                Poll::Pending
+            }
+            Some(id) => {
+                if state.granted.remove(&id) {
+                    drop(state);
+                    self.id = None;
+                    return Poll::Ready(SemaphorePermit { semaphore, permits });
+                }
+
+                if let Some(waiting) = state.waiting.iter_mut().find(|waiting| waiting.id == id) {
+                    waiting.waker.clone_from(cx.waker());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.id else {
+            return;
+        };
+
+        let wakers = {
+            let mut state = self.semaphore.lock_state();
+            if state.granted.remove(&id) {
+                // permits were handed over but never picked up.
+                state.permits += self.permits;
+            } else {
+                state.waiting.retain(|waiting| waiting.id != id);
+            }
+
+            // leaving the queue may unblock the waiters behind us.
+            state.grant_waiting()
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+#[cfg(test)]
+mod test_semaphore {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    #[test]
+    fn hands_out_permits_in_fifo_order() {
+        block_on(async {
+            let semaphore = Arc::new(Semaphore::new(3));
+            let held = semaphore.acquire_many(2).await;
+            assert_eq!(semaphore.available_permits(), 1);
+
+            let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let big = {
+                let (semaphore, order) = (semaphore.clone(), order.clone());
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire_many(3).await;
+                    order.lock().unwrap().push("big");
+                })
+            };
+            tokio::task::yield_now().await;
+
+            // a permit is free, but the queued acquire_many(3) goes first.
+            assert!(semaphore.try_acquire().is_none());
+            let small = {
+                let (semaphore, order) = (semaphore.clone(), order.clone());
+                tokio::spawn(async move {
+                    let _permit = semaphore.acquire().await;
+                    order.lock().unwrap().push("small");
+                })
+            };
+            tokio::task::yield_now().await;
+
+            drop(held);
+            big.await.expect("should finish");
+            small.await.expect("should finish");
+            assert_eq!(*order.lock().unwrap(), vec!["big", "small"]);
+            assert_eq!(semaphore.available_permits(), 3);
+        });
+    }
+
+    #[test]
+    fn dropped_acquire_leaves_the_queue() {
+        block_on(async {
+            let semaphore = Semaphore::new(1);
+            let held = semaphore.acquire().await;
+
+            let pending = tokio::time::timeout(
+                std::time::Duration::from_millis(10),
+                semaphore.acquire_many(1),
+            )
+            .await;
+            assert!(pending.is_err());
+
+            drop(held);
+            let permit = semaphore.try_acquire().expect("should acquire");
+            assert_eq!(permit.permits(), 1);
+        });
+    }
+
+    #[test]
+    fn acquiring_more_than_the_total_waits_for_added_permits() {
+        block_on(async {
+            let semaphore = Arc::new(Semaphore::new(2));
+            let big = {
+                let semaphore = semaphore.clone();
+                tokio::spawn(async move { semaphore.acquire_many(3).await.permits() })
+            };
+            tokio::task::yield_now().await;
+            assert!(!big.is_finished());
+            assert!(semaphore.try_acquire().is_none());
+
+            semaphore.add_permits(1);
+            assert_eq!(big.await.expect("should finish"), 3);
+            assert_eq!(semaphore.available_permits(), 3);
+        });
+    }
+
+    #[test]
+    fn added_permits_raise_the_total() {
+        block_on(async {
+            let semaphore = Semaphore::new(1);
+            semaphore.add_permits(2);
+            let permit = semaphore.acquire_many(3).await;
+            assert_eq!(permit.permits(), 3);
+            drop(permit);
+            assert_eq!(semaphore.available_permits(), 3);
+        });
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use concurrent_queue::{ConcurrentQueue, PushError};
+
+struct StealingShared<T> {
+    injector: ConcurrentQueue<T>,
+    locals: Vec<ConcurrentQueue<T>>,
+}
+
+/// `WorkStealingQueue` spreads tasks over per-worker local queues plus a
+/// global injector.
+///
+/// Workers push and pop on their own queue without contending with each
+/// other, refill from the injector in batches when it runs dry and only
+/// then steal half of a sibling's queue. Every queue is a lock-free
+/// `ConcurrentQueue`, so no mutex is taken on any path.
+///
+/// Clones share the same queues.
+pub struct WorkStealingQueue<T> {
+    shared: Arc<StealingShared<T>>,
+}
+
+impl<T> Clone for WorkStealingQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+// -- Constructors
+
+impl<T> WorkStealingQueue<T> {
+    /// `new` creates queues for `workers` workers, each local queue
+    /// holding up to `local_capacity` tasks before spilling into the
+    /// injector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` or `local_capacity` is zero.
+    #[must_use]
+    pub fn new(workers: usize, local_capacity: usize) -> Self {
+        assert!(workers > 0, "should have at least one worker");
+        assert!(local_capacity > 0, "local queues should hold a task");
+        Self {
+            shared: Arc::new(StealingShared {
+                injector: ConcurrentQueue::unbounded(),
+                locals: (0..workers)
+                    .map(|_| ConcurrentQueue::bounded(local_capacity))
+                    .collect(),
+            }),
+        }
+    }
+}
+
+// -- Methods
+
+impl<T> WorkStealingQueue<T> {
+    pub fn workers(&self) -> usize {
+        self.shared.locals.len()
+    }
+
+    /// `worker` returns the handle for worker `index`, there should be a
+    /// single handle per index in use at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`Self::workers`].
+    #[must_use]
+    pub fn worker(&self, index: usize) -> StealingWorker<T> {
+        assert!(index < self.workers(), "worker index out of range");
+        StealingWorker {
+            index,
+            next_victim: AtomicUsize::new(index + 1),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// `push` hands `task` to whichever worker gets to it first.
+    pub fn push(&self, task: T) {
+        push_unbounded(&self.shared.injector, task);
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.injector.len()
+            + self
+                .shared
+                .locals
+                .iter()
+                .map(ConcurrentQueue::len)
+                .sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn push_unbounded<T>(queue: &ConcurrentQueue<T>, task: T) {
+    if let Err(PushError::Closed(_) | PushError::Full(_)) = queue.push(task) {
+        unreachable!("injector is unbounded and never closed");
+    }
+}
+
+/// `StealingWorker` is a worker's handle on a [`WorkStealingQueue`].
+pub struct StealingWorker<T> {
+    index: usize,
+    next_victim: AtomicUsize,
+    shared: Arc<StealingShared<T>>,
+}
+
+impl<T> StealingWorker<T> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn local(&self) -> &ConcurrentQueue<T> {
+        &self.shared.locals[self.index]
+    }
+
+    /// `push` queues `task` on this worker, spilling into the injector
+    /// when the local queue is full so other workers can pick it up.
+    pub fn push(&self, task: T) {
+        if let Err(PushError::Full(task) | PushError::Closed(task)) = self.local().push(task) {
+            push_unbounded(&self.shared.injector, task);
+        }
+    }
+
+    /// `pop` returns the next task: from the local queue, else a batch
+    /// from the injector, else half of a sibling's queue.
+    pub fn pop(&self) -> Option<T> {
+        if let Ok(task) = self.local().pop() {
+            return Some(task);
+        }
+
+        self.refill_from(&self.shared.injector)
+            .or_else(|| self.steal())
+    }
+
+    /// `refill_from` takes a task to return plus up to half of what is
+    /// left in `source` into the local queue.
+    fn refill_from(&self, source: &ConcurrentQueue<T>) -> Option<T> {
+        let task = source.pop().ok()?;
+
+        let batch = (source.len() / 2).min(self.local().capacity().unwrap_or(0));
+        for _ in 0..batch {
+            let Ok(extra) = source.pop() else {
+                break;
+            };
+            if let Err(PushError::Full(extra) | PushError::Closed(extra)) = self.local().push(extra)
+            {
+                push_unbounded(&self.shared.injector, extra);
+                break;
+            }
+        }
+        Some(task)
+    }
+
+    fn steal(&self) -> Option<T> {
+        let workers = self.shared.locals.len();
+        let start = self.next_victim.fetch_add(1, Ordering::Relaxed);
+        (0..workers)
+            .map(|offset| (start + offset) % workers)
+            .filter(|victim| *victim != self.index)
+            .find_map(|victim| self.refill_from(&self.shared.locals[victim]))
+    }
+}
+
+#[cfg(test)]
+mod test_work_stealing_queue {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn idle_workers_steal_from_busy_ones() {
+        let queue = WorkStealingQueue::new(2, 8);
+        let busy = queue.worker(0);
+        let idle = queue.worker(1);
+
+        for task in 0..10 {
+            busy.push(task);
+        }
+        // two tasks did not fit and went to the injector.
+        assert_eq!(queue.len(), 10);
+
+        assert_eq!(idle.pop(), Some(8));
+        assert_eq!(idle.pop(), Some(9));
+        let stolen = idle.pop().expect("should steal");
+        assert!(stolen < 8);
+
+        let mut rest: Vec<usize> =
+            std::iter::from_fn(|| busy.pop().or_else(|| idle.pop())).collect();
+        rest.push(stolen);
+        rest.sort_unstable();
+        assert_eq!(rest, (0..8).collect::<Vec<_>>());
+        assert!(queue.is_empty());
+    }
+
+    /// Each worker spawns follow up tasks onto its own queue, the way an
+    /// executor does, every task should still run exactly once.
+    #[test]
+    fn scenario_concurrent_workers_run_every_task_once() {
+        const WORKERS: usize = 4;
+        const ROOTS: usize = 2_000;
+
+        let queue = WorkStealingQueue::new(WORKERS, 64);
+        for root in 0..ROOTS {
+            queue.push(root * 4);
+        }
+
+        let seen: Vec<AtomicUsize> = (0..ROOTS * 4).map(|_| AtomicUsize::new(0)).collect();
+        let remaining = AtomicUsize::new(ROOTS * 4);
+        thread::scope(|scope| {
+            for index in 0..WORKERS {
+                let (worker, seen, remaining) = (queue.worker(index), &seen, &remaining);
+                scope.spawn(move || {
+                    while remaining.load(Ordering::SeqCst) > 0 {
+                        let Some(task) = worker.pop() else {
+                            thread::yield_now();
+                            continue;
+                        };
+                        seen[task].fetch_add(1, Ordering::SeqCst);
+                        remaining.fetch_sub(1, Ordering::SeqCst);
+                        if task % 4 == 0 {
+                            (1..4).for_each(|child| worker.push(task + child));
+                        }
+                    }
+                });
+            }
+        });
+
+        assert!(seen.iter().all(|count| count.load(Ordering::SeqCst) == 1));
+        assert!(queue.is_empty());
+    }
+}
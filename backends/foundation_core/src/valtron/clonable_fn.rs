@@ -1,3 +1,6 @@
+use std::future::Future;
+use std::pin::Pin;
+
 /// ClonableFnMut implements a cloning for your FnMut/Fn types
 /// which allows you define a Fn/FnMut that can be owned and
 /// wholely Send as well without concerns on Sync.
@@ -48,3 +51,161 @@ impl<I: 'static, R: 'static> Clone for WrappedClonableFnMut<I, R> {
         Self(self.0.clone_box())
     }
 }
+
+/// `BoxedSendFuture` is the future returned by a [`ClonableAsyncFn`].
+pub type BoxedSendFuture<R> = Pin<Box<dyn Future<Output = R> + Send>>;
+
+/// `ClonableStatefulFn` is the `FnMut` counterpart of [`ClonableFn`], for
+/// closures that keep state between calls. Each clone carries its own copy
+/// of that state.
+///
+/// Despite its name, [`WrappedClonableFnMut`] only holds `Fn` closures,
+/// use [`WrappedClonableStatefulFn`] for ones that mutate what they capture.
+pub trait ClonableStatefulFn<I, R>: FnMut(I) -> R + Send {
+    fn clone_box_stateful(&self) -> Box<dyn ClonableStatefulFn<I, R>>;
+}
+
+impl<F, I, R> ClonableStatefulFn<I, R> for F
+where
+    F: FnMut(I) -> R + Send + Clone + 'static,
+{
+    fn clone_box_stateful(&self) -> Box<dyn ClonableStatefulFn<I, R>> {
+        Box::new(self.clone())
+    }
+}
+
+/// `WrappedClonableStatefulFn` wraps a [`ClonableStatefulFn`] for cases
+/// where the compiler wants the stored type to implement Clone. A
+/// [`WrappedClonableFnMut`] converts into one.
+pub struct WrappedClonableStatefulFn<I, R>(Box<dyn ClonableStatefulFn<I, R>>);
+
+impl<I, R> WrappedClonableStatefulFn<I, R> {
+    pub fn new(elem: Box<dyn ClonableStatefulFn<I, R>>) -> Self {
+        Self(elem)
+    }
+
+    pub fn call(&mut self, input: I) -> R {
+        (self.0)(input)
+    }
+}
+
+impl<I: 'static, R: 'static> Clone for WrappedClonableStatefulFn<I, R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box_stateful())
+    }
+}
+
+impl<I: 'static, R: 'static> From<WrappedClonableFnMut<I, R>> for WrappedClonableStatefulFn<I, R> {
+    fn from(wrapped: WrappedClonableFnMut<I, R>) -> Self {
+        Self(Box::new(move |input| {
+            let wrapped = &wrapped;
+            (wrapped.0)(input)
+        }))
+    }
+}
+
+/// `ClonableAsyncFn` is a boxed async closure that can be cloned and sent
+/// across threads, letting handlers be stored and shared without each
+/// caller defining its own trait object.
+///
+/// Closures returning plain futures are boxed through
+/// [`WrappedClonableAsyncFn::from_fn`].
+pub trait ClonableAsyncFn<I, R>: Fn(I) -> BoxedSendFuture<R> + Send {
+    fn clone_box_async(&self) -> Box<dyn ClonableAsyncFn<I, R>>;
+}
+
+impl<F, I, R> ClonableAsyncFn<I, R> for F
+where
+    F: Fn(I) -> BoxedSendFuture<R> + Send + Clone + 'static,
+{
+    fn clone_box_async(&self) -> Box<dyn ClonableAsyncFn<I, R>> {
+        Box::new(self.clone())
+    }
+}
+
+/// `WrappedClonableAsyncFn` wraps a [`ClonableAsyncFn`] so it implements
+/// Clone.
+pub struct WrappedClonableAsyncFn<I, R>(Box<dyn ClonableAsyncFn<I, R>>);
+
+impl<I: 'static, R: 'static> WrappedClonableAsyncFn<I, R> {
+    pub fn new(elem: Box<dyn ClonableAsyncFn<I, R>>) -> Self {
+        Self(elem)
+    }
+
+    /// `from_fn` boxes the futures returned by `handler`, e.g
+    /// `WrappedClonableAsyncFn::from_fn(|req| async move { handle(req).await })`.
+    pub fn from_fn<F, Fut>(handler: F) -> Self
+    where
+        F: Fn(I) -> Fut + Send + Clone + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+    {
+        Self(Box::new(move |input| {
+            Box::pin(handler(input)) as BoxedSendFuture<R>
+        }))
+    }
+
+    pub fn call(&self, input: I) -> BoxedSendFuture<R> {
+        (self.0)(input)
+    }
+}
+
+impl<I: 'static, R: 'static> Clone for WrappedClonableAsyncFn<I, R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone_box_async())
+    }
+}
+
+/// Lifts a synchronous [`ClonableFn`] into an async one whose futures
+/// resolve immediately.
+impl<I: 'static, R: Send + 'static> From<WrappedClonableFnMut<I, R>>
+    for WrappedClonableAsyncFn<I, R>
+{
+    fn from(wrapped: WrappedClonableFnMut<I, R>) -> Self {
+        Self(Box::new(move |input| {
+            let wrapped = &wrapped;
+            Box::pin(std::future::ready((wrapped.0)(input))) as BoxedSendFuture<R>
+        }))
+    }
+}
+
+#[cfg(test)]
+mod clonable_fn_tests {
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    #[test]
+    fn mut_fn_clones_carry_their_own_state() {
+        let mut count = 0;
+        let mut counter = WrappedClonableStatefulFn::new(Box::new(move |step: usize| {
+            count += step;
+            count
+        }));
+
+        assert_eq!(counter.call(1), 1);
+        let mut cloned = counter.clone();
+        assert_eq!(counter.call(1), 2);
+        assert_eq!(cloned.call(5), 6);
+
+        let mut doubled: WrappedClonableStatefulFn<usize, usize> =
+            WrappedClonableFnMut::new(Box::new(|value: usize| value * 2)).into();
+        assert_eq!(doubled.call(4), 8);
+    }
+
+    #[test]
+    fn async_fn_can_be_cloned_and_sent() {
+        let prefix = String::from("hello");
+        let greet = WrappedClonableAsyncFn::from_fn(move |name: String| {
+            let prefix = prefix.clone();
+            async move { format!("{prefix} {name}") }
+        });
+
+        let cloned = greet.clone();
+        let handle = std::thread::spawn(move || block_on(cloned.call(String::from("thread"))));
+        assert_eq!(handle.join().unwrap(), "hello thread");
+        assert_eq!(block_on(greet.call(String::from("main"))), "hello main");
+
+        let lifted: WrappedClonableAsyncFn<u8, u8> =
+            WrappedClonableFnMut::new(Box::new(|value: u8| value + 1)).into();
+        assert_eq!(block_on(lifted.clone().call(1)), 2);
+    }
+}
@@ -123,3 +123,190 @@ impl<T> Iterator for CanCloneSendIterator<T> {
         self.0.next()
     }
 }
+
+/// `ClonableIteratorExt` adds combinators whose adapters stay Clone as long
+/// as the iterators (and closures) they wrap are, so clones of the result
+/// advance independently without collecting into a Vec first.
+pub trait ClonableIteratorExt: Iterator + Clone + Sized {
+    /// `peekable_clone` is [`Iterator::peekable`] for clonable iterators,
+    /// the peeked item is cloned along with the iterator.
+    fn peekable_clone(self) -> std::iter::Peekable<Self>
+    where
+        Self::Item: Clone,
+    {
+        self.peekable()
+    }
+
+    /// `chunks` yields the items in `Vec`s of `size`, the last one holding
+    /// whatever is left.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `size` is zero.
+    fn chunks(self, size: usize) -> Chunks<Self> {
+        assert!(size > 0, "chunk size should be greater than zero");
+        Chunks { iter: self, size }
+    }
+
+    /// `merge_by` merges two iterators, taking from `self` while `first`
+    /// returns true for its next item against the next item of `other`.
+    /// Merging two sorted iterators keeps the result sorted.
+    fn merge_by<J, F>(self, other: J, first: F) -> MergeBy<Self, J::IntoIter, F>
+    where
+        J: IntoIterator<Item = Self::Item>,
+        J::IntoIter: Clone,
+        Self::Item: Clone,
+        F: FnMut(&Self::Item, &Self::Item) -> bool + Clone,
+    {
+        MergeBy {
+            left: self.peekable(),
+            right: other.into_iter().peekable(),
+            first,
+        }
+    }
+
+    /// `interleave` alternates between the items of `self` and `other`,
+    /// continuing with the remaining one once either runs out.
+    fn interleave<J>(self, other: J) -> Interleave<Self, J::IntoIter>
+    where
+        J: IntoIterator<Item = Self::Item>,
+        J::IntoIter: Clone,
+    {
+        Interleave {
+            left: self,
+            right: other.into_iter(),
+            take_right: false,
+        }
+    }
+}
+
+impl<T: Iterator + Clone> ClonableIteratorExt for T {}
+
+/// `Chunks` is returned by [`ClonableIteratorExt::chunks`].
+#[derive(Clone, Debug)]
+pub struct Chunks<I> {
+    iter: I,
+    size: usize,
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<I::Item> = self.iter.by_ref().take(self.size).collect();
+        if chunk.is_empty() {
+            return None;
+        }
+        Some(chunk)
+    }
+}
+
+/// `MergeBy` is returned by [`ClonableIteratorExt::merge_by`].
+pub struct MergeBy<I: Iterator, J: Iterator, F> {
+    left: std::iter::Peekable<I>,
+    right: std::iter::Peekable<J>,
+    first: F,
+}
+
+impl<I, J, F> Clone for MergeBy<I, J, F>
+where
+    I: Iterator + Clone,
+    J: Iterator<Item = I::Item> + Clone,
+    I::Item: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            left: self.left.clone(),
+            right: self.right.clone(),
+            first: self.first.clone(),
+        }
+    }
+}
+
+impl<I, J, F> Iterator for MergeBy<I, J, F>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+    F: FnMut(&I::Item, &I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.left.peek(), self.right.peek()) {
+            (Some(left), Some(right)) => {
+                if (self.first)(left, right) {
+                    self.left.next()
+                } else {
+                    self.right.next()
+                }
+            }
+            (Some(_), None) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+}
+
+/// `Interleave` is returned by [`ClonableIteratorExt::interleave`].
+#[derive(Clone, Debug)]
+pub struct Interleave<I, J> {
+    left: I,
+    right: J,
+    take_right: bool,
+}
+
+impl<I, J> Iterator for Interleave<I, J>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.take_right = !self.take_right;
+        if self.take_right {
+            self.left.next().or_else(|| self.right.next())
+        } else {
+            self.right.next().or_else(|| self.left.next())
+        }
+    }
+}
+
+#[cfg(test)]
+mod clone_iterator_tests {
+    use super::*;
+
+    #[test]
+    fn peekable_and_chunks_clones_advance_independently() {
+        let mut peekable = (1..=3).peekable_clone();
+        assert_eq!(peekable.peek(), Some(&1));
+        let cloned = peekable.clone();
+        assert_eq!(peekable.by_ref().skip(1).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(cloned.collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        let mut chunks = (1..=5).chunks(2);
+        assert_eq!(chunks.next(), Some(vec![1, 2]));
+        let cloned = chunks.clone();
+        assert_eq!(chunks.next(), Some(vec![3, 4]));
+        assert_eq!(chunks.next(), Some(vec![5]));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(cloned.collect::<Vec<_>>(), vec![vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn merge_and_interleave_clones_advance_independently() {
+        let mut merged = vec![1, 4, 6]
+            .into_iter()
+            .merge_by(vec![2, 3, 7], |a, b| a <= b);
+        assert_eq!(merged.next(), Some(1));
+        let cloned = merged.clone();
+        assert_eq!(merged.collect::<Vec<_>>(), vec![2, 3, 4, 6, 7]);
+        assert_eq!(cloned.collect::<Vec<_>>(), vec![2, 3, 4, 6, 7]);
+
+        let mut interleaved = "ab".chars().interleave("xyz".chars());
+        assert_eq!(interleaved.next(), Some('a'));
+        let cloned = interleaved.clone();
+        assert_eq!(interleaved.collect::<String>(), "xbyz");
+        assert_eq!(cloned.collect::<String>(), "xbyz");
+    }
+}
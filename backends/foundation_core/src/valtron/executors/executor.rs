@@ -7,7 +7,7 @@ use crate::{
     valtron::{AnyResult, GenericResult},
 };
 
-use super::{task::TaskStatus, DoNext, LocalExecutorEngine, OnNext, TaskIterator};
+use super::{task::TaskStatus, DoNext, LocalExecutorEngine, OnNext, TaskIterator, TaskPriority};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum State {
@@ -60,6 +60,54 @@ pub trait ExecutionIterator {
     type Executor: ExecutionEngine;
 
     fn next(&mut self, entry: Entry, executor: Self::Executor) -> Option<State>;
+
+    /// name returns the name given to the task if any, used by
+    /// executors when reporting on the task.
+    fn name(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// `NamedExecutionIterator` attaches a name to an `ExecutionIterator`.
+pub struct NamedExecutionIterator<M> {
+    name: String,
+    inner: M,
+}
+
+impl<M> NamedExecutionIterator<M> {
+    pub fn new(name: impl Into<String>, inner: M) -> Self {
+        Self {
+            name: name.into(),
+            inner,
+        }
+    }
+}
+
+impl<M, Executor> ExecutionIterator for NamedExecutionIterator<M>
+where
+    Executor: ExecutionEngine,
+    M: ExecutionIterator<Executor = Executor>,
+{
+    type Executor = M::Executor;
+
+    fn next(&mut self, entry: Entry, executor: Self::Executor) -> Option<State> {
+        self.inner.next(entry, executor)
+    }
+
+    fn name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
+
+fn boxed_task<E, M>(name: Option<String>, task: M) -> Box<dyn ExecutionIterator<Executor = E>>
+where
+    E: ExecutionEngine,
+    M: ExecutionIterator<Executor = E> + 'static,
+{
+    match name {
+        Some(name) => Box::new(NamedExecutionIterator::new(name, task)),
+        None => Box::new(task),
+    }
 }
 
 pub trait IntoBoxedExecutionIterator<Executor> {
@@ -138,6 +186,10 @@ impl<E: ExecutionEngine> ExecutionIterator for CanCloneExecutionIterator<E> {
     fn next(&mut self, entry: Entry, executor: Self::Executor) -> Option<State> {
         self.0.next(entry, executor)
     }
+
+    fn name(&self) -> Option<&str> {
+        self.0.name()
+    }
 }
 
 impl<'a, M, Executor> ExecutionIterator for &'a mut M
@@ -150,6 +202,10 @@ where
     fn next(&mut self, entry: Entry, executor: Self::Executor) -> Option<State> {
         (**self).next(entry, executor)
     }
+
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
 }
 
 impl<M, Executor> ExecutionIterator for Box<M>
@@ -162,6 +218,10 @@ where
     fn next(&mut self, entry: Entry, executor: Self::Executor) -> Option<State> {
         (**self).next(entry, executor)
     }
+
+    fn name(&self) -> Option<&str> {
+        (**self).name()
+    }
 }
 
 #[derive(Clone, Debug, From)]
@@ -217,7 +277,9 @@ pub struct ExecutionTaskIteratorBuilder<
 > {
     engine: Engine,
     task: Option<Task>,
+    name: Option<String>,
     parent: Option<Entry>,
+    priority: TaskPriority,
     resolver: Option<Resolver>,
     mappers: Option<Vec<Mapper>>,
     _marker: PhantomData<(Done, Pending, Action)>,
@@ -237,7 +299,9 @@ impl<
         Self {
             engine,
             task: None,
+            name: None,
             parent: None,
+            priority: TaskPriority::default(),
             mappers: None,
             resolver: None,
             _marker: PhantomData::default(),
@@ -261,6 +325,22 @@ impl<
         self
     }
 
+    /// `with_name` names the task, the name is handed to the executor's
+    /// instrumentation.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// `with_priority` sets the global lane the task is queued in when
+    /// broadcast, lifted and scheduled tasks run locally regardless.
+    #[must_use]
+    pub fn with_priority(mut self, priority: TaskPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub fn with_task(mut self, task: Task) -> Self {
         self.task = Some(task);
         self
@@ -272,20 +352,21 @@ impl<
     }
 
     pub fn lift(self) -> AnyResult<(), ExecutorError> {
+        let name = self.name;
         let parent = self.parent;
         match self.task {
             Some(task) => match (self.resolver, self.mappers) {
                 (Some(resolver), Some(mappers)) => {
                     let task_iter = OnNext::new(task, resolver, mappers);
-                    self.engine.lift(Box::new(task_iter), parent)
+                    self.engine.lift(boxed_task(name, task_iter), parent)
                 }
                 (Some(resolver), None) => {
                     let task_iter = OnNext::new(task, resolver, Vec::<Mapper>::new());
-                    self.engine.lift(Box::new(task_iter), parent)
+                    self.engine.lift(boxed_task(name, task_iter), parent)
                 }
                 (None, None) => {
                     let task_iter = DoNext::new(task);
-                    self.engine.lift(Box::new(task_iter), parent)
+                    self.engine.lift(boxed_task(name, task_iter), parent)
                 }
                 (None, Some(_)) => Err(ExecutorError::FailedToCreate),
             },
@@ -294,19 +375,20 @@ impl<
     }
 
     pub fn schedule(self) -> AnyResult<(), ExecutorError> {
+        let name = self.name;
         match self.task {
             Some(task) => match (self.resolver, self.mappers) {
                 (Some(resolver), Some(mappers)) => {
                     let task_iter = OnNext::new(task, resolver, mappers);
-                    self.engine.schedule(Box::new(task_iter))
+                    self.engine.schedule(boxed_task(name, task_iter))
                 }
                 (Some(resolver), None) => {
                     let task_iter = OnNext::new(task, resolver, Vec::<Mapper>::new());
-                    self.engine.schedule(Box::new(task_iter))
+                    self.engine.schedule(boxed_task(name, task_iter))
                 }
                 (None, None) => {
                     let task_iter = DoNext::new(task);
-                    self.engine.schedule(Box::new(task_iter))
+                    self.engine.schedule(boxed_task(name, task_iter))
                 }
                 (None, Some(_)) => Err(ExecutorError::FailedToCreate),
             },
@@ -315,19 +397,24 @@ impl<
     }
 
     pub fn broadcast(self) -> AnyResult<(), ExecutorError> {
+        let name = self.name;
+        let priority = self.priority;
         match self.task {
             Some(task) => match (self.resolver, self.mappers) {
                 (Some(resolver), Some(mappers)) => {
                     let task_iter = OnNext::new(task, resolver, mappers);
-                    self.engine.broadcast(Box::new(task_iter))
+                    self.engine
+                        .broadcast_with_priority(boxed_task(name, task_iter), priority)
                 }
                 (Some(resolver), None) => {
                     let task_iter = OnNext::new(task, resolver, Vec::<Mapper>::new());
-                    self.engine.broadcast(Box::new(task_iter))
+                    self.engine
+                        .broadcast_with_priority(boxed_task(name, task_iter), priority)
                 }
                 (None, None) => {
                     let task_iter = DoNext::new(task);
-                    self.engine.broadcast(Box::new(task_iter))
+                    self.engine
+                        .broadcast_with_priority(boxed_task(name, task_iter), priority)
                 }
                 (None, Some(_)) => Err(ExecutorError::FailedToCreate),
             },
@@ -431,6 +518,17 @@ pub trait ExecutionEngine {
         &self,
         task: Box<dyn ExecutionIterator<Executor = Self::Executor>>,
     ) -> AnyResult<(), ExecutorError>;
+
+    /// `broadcast_with_priority` is `broadcast` into the global lane for
+    /// `priority`, engines without lanes treat every task the same.
+    fn broadcast_with_priority(
+        &self,
+        task: Box<dyn ExecutionIterator<Executor = Self::Executor>>,
+        priority: TaskPriority,
+    ) -> AnyResult<(), ExecutorError> {
+        let _ = priority;
+        self.broadcast(task)
+    }
 }
 
 pub type BoxedExecutionIterator<M> = Box<dyn ExecutionIterator<Executor = M>>;
@@ -0,0 +1,131 @@
+use std::{
+    sync::{
+        self,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time,
+};
+
+use crate::synca::Entry;
+
+use super::QueueDepths;
+
+/// `ExecutorInstrumentation` receives scheduling events from an executor,
+/// every method defaults to doing nothing so implementations only pick
+/// the events they care about.
+///
+/// Hooks are called inline on the executor's thread, so they should
+/// be cheap.
+pub trait ExecutorInstrumentation {
+    /// `on_queue_depth` is called each time the executor pulls a task from
+    /// the global lanes.
+    fn on_queue_depth(&self, _depths: QueueDepths) {}
+
+    /// `on_poll` is called after each call to a task's `next`.
+    fn on_poll(&self, _entry: &Entry, _name: Option<&str>, _duration: time::Duration) {}
+
+    /// `on_task_finished` is called once a task is done, `lifetime` being
+    /// the time since it entered the executor.
+    fn on_task_finished(&self, _entry: &Entry, _name: Option<&str>, _lifetime: time::Duration) {}
+}
+
+pub type SharedInstrumentation = sync::Arc<dyn ExecutorInstrumentation + Send + Sync>;
+
+/// `TracingInstrumentation` logs every event through `tracing` at trace
+/// level.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingInstrumentation;
+
+impl ExecutorInstrumentation for TracingInstrumentation {
+    fn on_queue_depth(&self, depths: QueueDepths) {
+        tracing::trace!(
+            high = depths.high,
+            normal = depths.normal,
+            low = depths.low,
+            local = depths.local,
+            "executor queue depth"
+        );
+    }
+
+    fn on_poll(&self, entry: &Entry, name: Option<&str>, duration: time::Duration) {
+        tracing::trace!(
+            "Task {:?} ({}) polled in {:?}",
+            entry,
+            name.unwrap_or("unnamed"),
+            duration
+        );
+    }
+
+    fn on_task_finished(&self, entry: &Entry, name: Option<&str>, lifetime: time::Duration) {
+        tracing::trace!(
+            "Task {:?} ({}) finished after {:?}",
+            entry,
+            name.unwrap_or("unnamed"),
+            lifetime
+        );
+    }
+}
+
+/// `ExecutorStats` aggregates instrumentation events into counters, useful
+/// to observe scheduler behaviour in stress tests.
+#[derive(Debug, Default)]
+pub struct ExecutorStats {
+    polls: AtomicU64,
+    poll_nanos: AtomicU64,
+    max_poll_nanos: AtomicU64,
+    finished: AtomicU64,
+    lifetime_nanos: AtomicU64,
+    max_queue_depth: AtomicUsize,
+}
+
+/// `ExecutorStatsSnapshot` is a point in time copy of [`ExecutorStats`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ExecutorStatsSnapshot {
+    pub polls: u64,
+    pub total_poll_time: time::Duration,
+    pub max_poll_time: time::Duration,
+    pub finished: u64,
+    pub total_lifetime: time::Duration,
+    pub max_queue_depth: usize,
+}
+
+impl ExecutorStats {
+    pub fn shared() -> sync::Arc<Self> {
+        sync::Arc::new(Self::default())
+    }
+
+    pub fn snapshot(&self) -> ExecutorStatsSnapshot {
+        ExecutorStatsSnapshot {
+            polls: self.polls.load(Ordering::Relaxed),
+            total_poll_time: time::Duration::from_nanos(self.poll_nanos.load(Ordering::Relaxed)),
+            max_poll_time: time::Duration::from_nanos(self.max_poll_nanos.load(Ordering::Relaxed)),
+            finished: self.finished.load(Ordering::Relaxed),
+            total_lifetime: time::Duration::from_nanos(self.lifetime_nanos.load(Ordering::Relaxed)),
+            max_queue_depth: self.max_queue_depth.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn as_nanos(duration: time::Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+impl ExecutorInstrumentation for ExecutorStats {
+    fn on_queue_depth(&self, depths: QueueDepths) {
+        let depth = depths.high + depths.normal + depths.low + depths.local;
+        self.max_queue_depth.fetch_max(depth, Ordering::Relaxed);
+    }
+
+    fn on_poll(&self, _entry: &Entry, _name: Option<&str>, duration: time::Duration) {
+        let nanos = as_nanos(duration);
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        self.poll_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_poll_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    fn on_task_finished(&self, _entry: &Entry, _name: Option<&str>, lifetime: time::Duration) {
+        self.finished.fetch_add(1, Ordering::Relaxed);
+        self.lifetime_nanos
+            .fetch_add(as_nanos(lifetime), Ordering::Relaxed);
+    }
+}
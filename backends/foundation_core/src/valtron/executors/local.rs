@@ -16,11 +16,10 @@ use crate::{
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 
-use concurrent_queue::{ConcurrentQueue, PushError};
-
 use super::{
     BoxedLocalExecutionIterator, ExecutionAction, ExecutionTaskIteratorBuilder, ExecutorError,
-    ProcessController, TaskIterator, TaskReadyResolver, TaskStatusMapper,
+    ProcessController, SharedInstrumentation, SharedTaskQueue, TaskIterator, TaskLanes,
+    TaskPriority, TaskReadyResolver, TaskStatusMapper,
 };
 
 /// PriorityOrder defines how wake up tasks should placed once woken up.
@@ -66,9 +65,17 @@ pub struct ExecutorState<T: ExecutionIterator> {
     pub(crate) priority: PriorityOrder,
 
     /// global_tasks are the shared tasks coming from the main thread
-    /// they generally will always come in fifo order and will be processed
-    /// in the order received.
-    pub(crate) global_tasks: sync::Arc<ConcurrentQueue<T>>,
+    /// they generally will always come in fifo order within their
+    /// priority lane and will be processed in the order received.
+    pub(crate) global_tasks: TaskLanes<T>,
+
+    /// instrumentation when set receives queue depths, poll durations
+    /// and task lifetimes.
+    pub(crate) instrumentation: rc::Rc<cell::RefCell<Option<SharedInstrumentation>>>,
+
+    /// when each task entered the executor, only tracked while
+    /// instrumentation is set.
+    pub(crate) task_started: rc::Rc<cell::RefCell<HashMap<Entry, time::Instant>>>,
 
     /// indicates to us which if any spawn operation occurred.
     pub(crate) spawn_op: rc::Rc<cell::RefCell<Option<SpawnType>>>,
@@ -131,8 +138,10 @@ pub struct ExecutorState<T: ExecutionIterator> {
 static DEQUEUE_CAPACITY: usize = 10;
 
 impl<T: ExecutionIterator> ExecutorState<T> {
+    /// new creates the state over the shared global lanes, executors
+    /// given clones of the same lanes share all their priorities.
     pub fn new(
-        global_tasks: sync::Arc<ConcurrentQueue<T>>,
+        global_tasks: TaskLanes<T>,
         priority: PriorityOrder,
         rng: ChaCha8Rng,
         idler: IdleMan,
@@ -140,6 +149,8 @@ impl<T: ExecutionIterator> ExecutorState<T> {
         Self {
             priority,
             global_tasks,
+            instrumentation: rc::Rc::new(cell::RefCell::new(None)),
+            task_started: rc::Rc::new(cell::RefCell::new(HashMap::new())),
             sleepers: Sleepers::new(),
             rng: rc::Rc::new(cell::RefCell::new(rng)),
             idler: rc::Rc::new(cell::RefCell::new(idler)),
@@ -204,6 +215,8 @@ impl<T: ExecutionIterator> Clone for ExecutorState<T> {
             spawn_op: self.spawn_op.clone(),
             current_task: self.current_task.clone(),
             global_tasks: self.global_tasks.clone(),
+            instrumentation: self.instrumentation.clone(),
+            task_started: self.task_started.clone(),
             local_tasks: self.local_tasks.clone(),
             task_graph: self.task_graph.clone(),
             packed_tasks: self.packed_tasks.clone(),
@@ -391,12 +404,20 @@ impl<T: ExecutionIterator> ExecutorState<T> {
         }
 
         match self.global_tasks.pop() {
-            Ok(task) => {
+            Some((task, lane)) => {
                 let task_entry = self.local_tasks.borrow_mut().insert(task);
                 self.processing.borrow_mut().push_front(task_entry.clone());
+                tracing::debug!("Acquired task {:?} from {:?} lane", &task_entry, lane);
+
+                self.track_task_start(&task_entry);
+                if let Some(instrumentation) = self.instrumentation.borrow().as_ref() {
+                    let mut depths = self.global_tasks.depths();
+                    depths.local = self.processing.borrow().len();
+                    instrumentation.on_queue_depth(depths);
+                }
                 ScheduleOutcome::GlobalTaskAcquired
             }
-            Err(_) => ScheduleOutcome::NoTaskRunningOrAcquired,
+            None => ScheduleOutcome::NoTaskRunningOrAcquired,
         }
     }
 
@@ -541,7 +562,19 @@ impl<T: ExecutionIterator> ExecutorState<T> {
         let iter_container = self.local_tasks.borrow_mut().park(&top_entry);
         match iter_container {
             Some(mut iter) => {
-                match iter.next(top_entry.clone(), engine) {
+                let poll_started = self
+                    .instrumentation
+                    .borrow()
+                    .as_ref()
+                    .map(|_| time::Instant::now());
+                let next_state = iter.next(top_entry.clone(), engine);
+                if let Some(started) = poll_started {
+                    if let Some(instrumentation) = self.instrumentation.borrow().as_ref() {
+                        instrumentation.on_poll(&top_entry, iter.name(), started.elapsed());
+                    }
+                }
+
+                match next_state {
                     Some(state) => {
                         tracing::debug!("Task delivered state: {:?}", &state);
                         match state {
@@ -576,6 +609,8 @@ impl<T: ExecutionIterator> ExecutorState<T> {
                                     remaining_tasks
                                 );
 
+                                self.track_task_finished(&top_entry, iter.name());
+
                                 // now unpack and take entry out of local tasks
                                 self.local_tasks.borrow_mut().unpark(&top_entry, iter);
                                 self.local_tasks.borrow_mut().take(&top_entry);
@@ -670,6 +705,7 @@ impl<T: ExecutionIterator> ExecutorState<T> {
                             "Task returned None (has finished) (rem_tasks: {})",
                             remaining_tasks
                         );
+                        self.track_task_finished(&top_entry, iter.name());
                         // Task Iterator is really done
                         if remaining_tasks == 0 {
                             ProgressIndicator::NoWork
@@ -686,6 +722,38 @@ impl<T: ExecutionIterator> ExecutorState<T> {
 
 // --- End of: Task Dependences, Rng and Helper methods
 
+// --- Instrumentation
+
+impl<T: ExecutionIterator> ExecutorState<T> {
+    /// `set_instrumentation` replaces the hooks receiving this executor's
+    /// scheduling events.
+    pub fn set_instrumentation(&self, instrumentation: Option<SharedInstrumentation>) {
+        if instrumentation.is_none() {
+            self.task_started.borrow_mut().clear();
+        }
+        *self.instrumentation.borrow_mut() = instrumentation;
+    }
+
+    fn track_task_start(&self, entry: &Entry) {
+        if self.instrumentation.borrow().is_some() {
+            self.task_started
+                .borrow_mut()
+                .insert(entry.clone(), time::Instant::now());
+        }
+    }
+
+    fn track_task_finished(&self, entry: &Entry, name: Option<&str>) {
+        let Some(started) = self.task_started.borrow_mut().remove(entry) else {
+            return;
+        };
+        if let Some(instrumentation) = self.instrumentation.borrow().as_ref() {
+            instrumentation.on_task_finished(entry, name, started.elapsed());
+        }
+    }
+}
+
+// --- End of: Instrumentation
+
 // --- Task spawn methods: Lift, Schedule & Broadcast
 
 impl<T: ExecutionIterator> ExecutorState<T> {
@@ -734,6 +802,7 @@ impl<T: ExecutionIterator> ExecutorState<T> {
         }
 
         self.processing.borrow_mut().push_front(task_entry.clone());
+        self.track_task_start(&task_entry);
 
         // create dependent graph map.
         if let Some(parent_handle) = &parent {
@@ -760,6 +829,7 @@ impl<T: ExecutionIterator> ExecutorState<T> {
         let task_entry = self.local_tasks.borrow_mut().insert(task);
         self.processing.borrow_mut().push_back(task_entry.clone());
         self.spawn_op.borrow_mut().replace(SpawnType::Scheduled);
+        self.track_task_start(&task_entry);
         Ok(task_entry)
     }
 
@@ -767,16 +837,19 @@ impl<T: ExecutionIterator> ExecutorState<T> {
     /// and in such a case you do not have an handle to the task as we
     /// no more have control as to where it gets allocated.
     pub fn broadcast(&self, task: T) -> AnyResult<(), ExecutorError> {
-        match self.global_tasks.push(task) {
-            Ok(_) => {
-                self.spawn_op.borrow_mut().replace(SpawnType::Broadcast);
-                Ok(())
-            }
-            Err(err) => match err {
-                PushError::Full(_) => Err(ExecutorError::QueueFull),
-                PushError::Closed(_) => Err(ExecutorError::QueueClosed),
-            },
-        }
+        self.broadcast_with_priority(task, TaskPriority::Normal)
+    }
+
+    /// Delivers a task to the global lane for `priority`, executors
+    /// pull from higher priority lanes first.
+    pub fn broadcast_with_priority(
+        &self,
+        task: T,
+        priority: TaskPriority,
+    ) -> AnyResult<(), ExecutorError> {
+        self.global_tasks.push(task, priority)?;
+        self.spawn_op.borrow_mut().replace(SpawnType::Broadcast);
+        Ok(())
     }
 }
 
@@ -942,6 +1015,16 @@ impl ExecutionEngine for LocalExecutorEngine {
         tracing::debug!("broadcast: new task into Executor");
         Ok(())
     }
+
+    fn broadcast_with_priority(
+        &self,
+        task: Box<dyn ExecutionIterator<Executor = Self::Executor>>,
+        priority: TaskPriority,
+    ) -> AnyResult<(), ExecutorError> {
+        self.inner.broadcast_with_priority(task, priority)?;
+        tracing::debug!("broadcast: new {:?} task into Executor", priority);
+        Ok(())
+    }
 }
 
 impl ReferencedExecutorState<BoxedLocalExecutionIterator> {
@@ -982,7 +1065,7 @@ impl<T: ProcessController> Clone for LocalThreadExecutor<T> {
 #[allow(unused)]
 impl<T: ProcessController> LocalThreadExecutor<T> {
     pub fn new(
-        tasks: sync::Arc<ConcurrentQueue<BoxedLocalExecutionIterator>>,
+        tasks: SharedTaskQueue,
         rng: ChaCha8Rng,
         idler: IdleMan,
         priority: PriorityOrder,
@@ -998,7 +1081,7 @@ impl<T: ProcessController> LocalThreadExecutor<T> {
     /// seed for ChaCha8Rng generator.
     pub fn from_seed(
         seed: u64,
-        tasks: sync::Arc<ConcurrentQueue<BoxedLocalExecutionIterator>>,
+        tasks: SharedTaskQueue,
         idler: IdleMan,
         priority: PriorityOrder,
         yielder: T,
@@ -1015,7 +1098,7 @@ impl<T: ProcessController> LocalThreadExecutor<T> {
     /// Allows supplying a custom Rng generator for creating the initial
     /// ChaCha8Rng seed.
     pub fn from_rng<R: rand::Rng>(
-        tasks: sync::Arc<ConcurrentQueue<BoxedLocalExecutionIterator>>,
+        tasks: SharedTaskQueue,
         rng: &mut R,
         idler: IdleMan,
         priority: PriorityOrder,
@@ -1025,6 +1108,20 @@ impl<T: ProcessController> LocalThreadExecutor<T> {
     }
 }
 
+// -- Builder methods
+
+impl<T: ProcessController> LocalThreadExecutor<T> {
+    /// `with_instrumentation` reports queue depths, poll durations and
+    /// task lifetimes of this executor to `instrumentation`.
+    #[must_use]
+    pub fn with_instrumentation(self, instrumentation: SharedInstrumentation) -> Self {
+        self.state
+            .clone_state()
+            .set_instrumentation(Some(instrumentation));
+        self
+    }
+}
+
 // -- LocalExecutor builder
 
 #[allow(unused)]
@@ -1134,12 +1231,15 @@ mod test_local_thread_executor {
         thread,
     };
 
+    use concurrent_queue::ConcurrentQueue;
+
     use crate::{
         panic_if_failed,
         retries::ExponentialBackoffDecider,
         synca::SleepyMan,
         valtron::{
-            ExecutionAction, NoSpawner, OnNext, ProcessController, TaskIterator, TaskStatus,
+            ExecutionAction, ExecutorInstrumentation, NoSpawner, OnNext, ProcessController,
+            QueueDepths, TaskIterator, TaskStatus,
         },
     };
 
@@ -1207,7 +1307,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1251,7 +1351,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1293,7 +1393,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1352,7 +1452,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1481,7 +1581,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1745,7 +1845,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1826,7 +1926,7 @@ mod test_local_thread_executor {
 
         let executor = LocalThreadExecutor::from_seed(
             seed,
-            global.clone(),
+            TaskLanes::new(global.clone()),
             IdleMan::new(
                 3,
                 None,
@@ -1960,4 +2060,124 @@ mod test_local_thread_executor {
             ]
         );
     }
+
+    #[derive(Default)]
+    struct FinishedTasks(sync::Mutex<Vec<String>>, AtomicUsize);
+
+    impl ExecutorInstrumentation for FinishedTasks {
+        fn on_queue_depth(&self, depths: QueueDepths) {
+            self.1.fetch_max(depths.high + depths.low, Ordering::SeqCst);
+        }
+
+        fn on_task_finished(&self, _entry: &Entry, name: Option<&str>, _: time::Duration) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(name.unwrap_or("unnamed").to_string());
+        }
+    }
+
+    type NamedStatus = (&'static str, TaskStatus<usize, time::Duration, NoSpawner>);
+
+    #[test]
+    #[traced_test]
+    fn scenario_6_high_priority_tasks_run_before_low_priority_tasks() {
+        let lanes: TaskLanes<BoxedLocalExecutionIterator> = TaskLanes::unbounded();
+        let finished = Arc::new(FinishedTasks::default());
+
+        let counts: Rc<RefCell<Vec<NamedStatus>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let executor = LocalThreadExecutor::new(
+            lanes,
+            ChaCha8Rng::seed_from_u64(rand::thread_rng().next_u64()),
+            IdleMan::new(
+                3,
+                None,
+                SleepyMan::new(3, ExponentialBackoffDecider::default()),
+            ),
+            PriorityOrder::Bottom,
+            NoYielder,
+        )
+        .with_instrumentation(finished.clone());
+
+        let background = Rc::clone(&counts);
+        panic_if_failed!(executor
+            .typed_task()
+            .with_task(Counter("Rebuild", 0, 3, 3))
+            .with_name("rebuild")
+            .with_priority(TaskPriority::Low)
+            .on_next(move |next, _| background.borrow_mut().push(("rebuild", next)))
+            .broadcast());
+
+        let user_facing = Rc::clone(&counts);
+        panic_if_failed!(executor
+            .typed_task()
+            .with_task(Counter("Request", 0, 3, 3))
+            .with_name("request")
+            .with_priority(TaskPriority::High)
+            .on_next(move |next, _| user_facing.borrow_mut().push(("request", next)))
+            .broadcast());
+
+        for _ in 0..10 {
+            executor.run_once();
+        }
+
+        let order: Vec<&str> = counts.borrow().iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["request", "request", "rebuild", "rebuild"]);
+        assert_eq!(
+            finished.0.lock().unwrap().clone(),
+            vec!["request".to_string(), "rebuild".to_string()]
+        );
+        assert_eq!(finished.1.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[traced_test]
+    fn scenario_7_executors_sharing_lanes_pick_up_each_others_priority_tasks() {
+        let lanes: TaskLanes<BoxedLocalExecutionIterator> = TaskLanes::unbounded();
+        let counts: Rc<RefCell<Vec<NamedStatus>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let new_executor = |lanes: TaskLanes<BoxedLocalExecutionIterator>| {
+            LocalThreadExecutor::new(
+                lanes,
+                ChaCha8Rng::seed_from_u64(rand::thread_rng().next_u64()),
+                IdleMan::new(
+                    3,
+                    None,
+                    SleepyMan::new(3, ExponentialBackoffDecider::default()),
+                ),
+                PriorityOrder::Bottom,
+                NoYielder,
+            )
+        };
+
+        let sender = new_executor(lanes.clone());
+        let worker = new_executor(lanes.clone());
+
+        let background = Rc::clone(&counts);
+        panic_if_failed!(sender
+            .typed_task()
+            .with_task(Counter("Rebuild", 0, 3, 3))
+            .with_priority(TaskPriority::Low)
+            .on_next(move |next, _| background.borrow_mut().push(("rebuild", next)))
+            .broadcast());
+
+        let user_facing = Rc::clone(&counts);
+        panic_if_failed!(sender
+            .typed_task()
+            .with_task(Counter("Request", 0, 3, 3))
+            .with_priority(TaskPriority::High)
+            .on_next(move |next, _| user_facing.borrow_mut().push(("request", next)))
+            .broadcast());
+
+        assert_eq!(lanes.len(), 2);
+
+        for _ in 0..10 {
+            worker.run_once();
+        }
+
+        let order: Vec<&str> = counts.borrow().iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["request", "request", "rebuild", "rebuild"]);
+        assert!(lanes.is_empty());
+    }
 }
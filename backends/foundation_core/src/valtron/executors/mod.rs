@@ -3,8 +3,10 @@ mod controller;
 mod do_next;
 mod executor;
 mod hot;
+mod instrumentation;
 mod local;
 mod on_next;
+mod priority;
 mod task;
 mod threads;
 
@@ -13,8 +15,10 @@ pub use controller::*;
 pub use do_next::*;
 pub use executor::*;
 pub use hot::*;
+pub use instrumentation::*;
 pub use local::*;
 pub use on_next::*;
+pub use priority::*;
 pub use rand::SeedableRng;
 pub use task::*;
 pub use threads::*;
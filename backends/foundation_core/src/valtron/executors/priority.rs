@@ -0,0 +1,193 @@
+use std::sync::{
+    self,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use concurrent_queue::{ConcurrentQueue, PushError};
+
+use super::ExecutorError;
+
+/// `TaskPriority` selects the global lane a broadcast task is queued in,
+/// executors always pull from higher lanes first.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TaskPriority {
+    /// High is for user-facing work that should never wait behind
+    /// background jobs.
+    High,
+
+    #[default]
+    Normal,
+
+    /// Low is for background work like rebuilds or cleanups.
+    Low,
+}
+
+/// `QueueDepths` is a snapshot of how many tasks wait in each lane
+/// and in an executor's local processing queue.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QueueDepths {
+    pub high: usize,
+    pub normal: usize,
+    pub low: usize,
+    pub local: usize,
+}
+
+/// `DEFAULT_STARVATION_LIMIT` is how many tasks can be taken from higher
+/// lanes in a row while lower lanes have waiting tasks.
+pub const DEFAULT_STARVATION_LIMIT: usize = 32;
+
+/// `TaskLanes` are the global queues executors pull tasks from, one per
+/// [`TaskPriority`].
+///
+/// To keep background work from starving under constant high priority
+/// load, every `starvation_limit` pops the lowest non-empty lane is
+/// served first.
+///
+/// Clones share the same lanes, which is how multiple executors share
+/// their global queue.
+pub struct TaskLanes<T> {
+    high: sync::Arc<ConcurrentQueue<T>>,
+    normal: sync::Arc<ConcurrentQueue<T>>,
+    low: sync::Arc<ConcurrentQueue<T>>,
+    streak: sync::Arc<AtomicUsize>,
+    starvation_limit: usize,
+}
+
+impl<T> Clone for TaskLanes<T> {
+    fn clone(&self) -> Self {
+        Self {
+            high: self.high.clone(),
+            normal: self.normal.clone(),
+            low: self.low.clone(),
+            streak: self.streak.clone(),
+            starvation_limit: self.starvation_limit,
+        }
+    }
+}
+
+// -- Constructors
+
+impl<T> TaskLanes<T> {
+    /// new uses `normal` as the normal lane, creating unbounded high and
+    /// low lanes around it, so existing global queues keep working as is.
+    pub fn new(normal: sync::Arc<ConcurrentQueue<T>>) -> Self {
+        Self {
+            normal,
+            high: sync::Arc::new(ConcurrentQueue::unbounded()),
+            low: sync::Arc::new(ConcurrentQueue::unbounded()),
+            streak: sync::Arc::new(AtomicUsize::new(0)),
+            starvation_limit: DEFAULT_STARVATION_LIMIT,
+        }
+    }
+
+    pub fn unbounded() -> Self {
+        Self::new(sync::Arc::new(ConcurrentQueue::unbounded()))
+    }
+}
+
+// -- Builder methods
+
+impl<T> TaskLanes<T> {
+    #[must_use]
+    pub fn with_starvation_limit(mut self, starvation_limit: usize) -> Self {
+        self.starvation_limit = starvation_limit.max(1);
+        self
+    }
+}
+
+// -- Methods
+
+impl<T> TaskLanes<T> {
+    fn lane(&self, priority: TaskPriority) -> &ConcurrentQueue<T> {
+        match priority {
+            TaskPriority::High => &self.high,
+            TaskPriority::Normal => &self.normal,
+            TaskPriority::Low => &self.low,
+        }
+    }
+
+    /// push queues `task` in the lane for `priority`.
+    pub fn push(&self, task: T, priority: TaskPriority) -> Result<(), ExecutorError> {
+        match self.lane(priority).push(task) {
+            Ok(()) => Ok(()),
+            Err(err) => match err {
+                PushError::Full(_) => Err(ExecutorError::QueueFull),
+                PushError::Closed(_) => Err(ExecutorError::QueueClosed),
+            },
+        }
+    }
+
+    /// pop takes the next task, highest lane first, returning the lane
+    /// it came from.
+    pub fn pop(&self) -> Option<(T, TaskPriority)> {
+        let streak = self.streak.fetch_add(1, Ordering::SeqCst) + 1;
+        let order = if streak >= self.starvation_limit {
+            [TaskPriority::Low, TaskPriority::Normal, TaskPriority::High]
+        } else {
+            [TaskPriority::High, TaskPriority::Normal, TaskPriority::Low]
+        };
+
+        for priority in order {
+            if let Ok(task) = self.lane(priority).pop() {
+                // the streak only matters while lower lanes are waiting.
+                if priority == TaskPriority::Low
+                    || streak >= self.starvation_limit
+                    || (self.low.is_empty() && self.normal.is_empty())
+                {
+                    self.streak.store(0, Ordering::SeqCst);
+                }
+                return Some((task, priority));
+            }
+        }
+
+        self.streak.store(0, Ordering::SeqCst);
+        None
+    }
+
+    pub fn len(&self) -> usize {
+        self.high.len() + self.normal.len() + self.low.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// depths returns the number of tasks in each lane, `local` is left
+    /// for the executor to fill in.
+    pub fn depths(&self) -> QueueDepths {
+        QueueDepths {
+            high: self.high.len(),
+            normal: self.normal.len(),
+            low: self.low.len(),
+            local: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_task_lanes {
+    use super::*;
+
+    #[test]
+    fn pops_higher_lanes_first_without_starving_lower_ones() {
+        let lanes: TaskLanes<usize> = TaskLanes::unbounded().with_starvation_limit(3);
+        lanes.push(1, TaskPriority::Low).expect("should push");
+        lanes.push(2, TaskPriority::Normal).expect("should push");
+        for task in 10..15 {
+            lanes.push(task, TaskPriority::High).expect("should push");
+        }
+        assert_eq!(
+            lanes.depths(),
+            QueueDepths {
+                high: 5,
+                normal: 1,
+                low: 1,
+                local: 0
+            }
+        );
+
+        let order: Vec<usize> = std::iter::from_fn(|| lanes.pop().map(|(task, _)| task)).collect();
+        assert_eq!(order, vec![10, 11, 1, 12, 13, 2, 14]);
+        assert!(lanes.is_empty());
+    }
+}
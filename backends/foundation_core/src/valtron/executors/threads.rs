@@ -11,7 +11,7 @@ use std::str::FromStr;
 
 use crate::synca::{ActivitySignal, DurationStore, Entry, EntryList, LockSignal, OnSignal};
 
-use super::{BoxedLocalExecutionIterator, ProcessController};
+use super::{BoxedLocalExecutionIterator, ProcessController, TaskLanes};
 
 #[cfg(not(feature = "web_spin_lock"))]
 use std::sync::{Condvar, Mutex};
@@ -42,7 +42,7 @@ pub enum ThreadActivity {
 }
 
 pub(crate) type SharedThreadRegister = sync::Arc<Mutex<ThreadPoolRegistryInner>>;
-pub type SharedTaskQueue = sync::Arc<ConcurrentQueue<BoxedLocalExecutionIterator>>;
+pub type SharedTaskQueue = TaskLanes<BoxedLocalExecutionIterator>;
 pub type SharedActivityQueue = sync::Arc<ConcurrentQueue<ThreadActivity>>;
 
 #[derive(Clone)]
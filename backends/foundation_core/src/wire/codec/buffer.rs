@@ -0,0 +1,138 @@
+use std::ops::Deref;
+
+use crate::io::buffers::{BufferPool, PooledBuffer};
+
+/// `FrameBuffer` holds the bytes waiting to be decoded by a
+/// [`super::FrameCodec`], it derefs to the bytes not consumed yet.
+///
+/// Decoding a frame only moves a cursor forward, the consumed bytes are
+/// compacted away in one go when more input is appended and they make up
+/// at least half of the buffer, so a read holding many frames does not
+/// shift the rest of it once per frame.
+#[derive(Debug)]
+pub struct FrameBuffer {
+    bytes: PooledBuffer,
+    start: usize,
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Vec<u8>> for FrameBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: PooledBuffer::unpooled(bytes),
+            start: 0,
+        }
+    }
+}
+
+// -- Constructors
+
+impl FrameBuffer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+// -- Methods
+
+impl FrameBuffer {
+    /// `consume` drops the first `count` unread bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is more than the unread bytes.
+    pub fn consume(&mut self, count: usize) {
+        assert!(count <= self.len(), "should consume only unread bytes");
+        self.start += count;
+        if self.start == self.bytes.len() {
+            self.bytes.clear();
+            self.start = 0;
+        }
+    }
+
+    /// `take` returns the first `count` unread bytes and consumes them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is more than the unread bytes.
+    pub fn take(&mut self, count: usize) -> Vec<u8> {
+        let taken = self[..count].to_vec();
+        self.consume(count);
+        taken
+    }
+
+    /// `extend_from_slice` appends `bytes` after the unread ones.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if self.start > 0 && self.start >= self.bytes.len() / 2 {
+            self.compact();
+        }
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// `compact` moves the unread bytes to the front of the buffer.
+    pub fn compact(&mut self) {
+        if self.start > 0 {
+            self.bytes.drain(..self.start);
+            self.start = 0;
+        }
+    }
+
+    /// `lease_from` moves the unread bytes into a buffer leased from `pool`.
+    pub(crate) fn lease_from(&mut self, pool: &BufferPool) {
+        let mut leased = pool.lease();
+        leased.extend_from_slice(self);
+        self.bytes = leased;
+        self.start = 0;
+    }
+
+    /// `into_unread` returns the unread bytes, taking the buffer out of any
+    /// pool it was leased from.
+    #[must_use]
+    pub fn into_unread(mut self) -> Vec<u8> {
+        self.compact();
+        self.bytes.detach()
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.bytes[self.start..]
+    }
+}
+
+#[cfg(test)]
+mod test_frame_buffer {
+    use super::*;
+
+    #[test]
+    fn compacts_consumed_bytes_in_batches() {
+        let mut buffer = FrameBuffer::from(b"aabbccdd".to_vec());
+        assert_eq!(buffer.take(2), b"aa");
+        buffer.consume(2);
+        assert_eq!(&buffer[..], b"ccdd");
+        assert_eq!(buffer.bytes.len(), 8);
+
+        // half of the buffer is consumed, appending compacts it first.
+        buffer.extend_from_slice(b"ee");
+        assert_eq!(buffer.start, 0);
+        assert_eq!(&buffer.bytes[..], b"ccddee");
+
+        buffer.consume(1);
+        buffer.extend_from_slice(b"f");
+        assert_eq!(buffer.start, 1);
+        assert_eq!(&buffer[..], b"cddeef");
+
+        buffer.consume(6);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.bytes.len(), 0);
+        assert_eq!(FrameBuffer::from(b"xyz".to_vec()).into_unread(), b"xyz");
+    }
+}
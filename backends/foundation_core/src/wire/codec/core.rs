@@ -0,0 +1,71 @@
+use derive_more::From;
+use std::io;
+
+use super::FrameBuffer;
+
+pub type CodecResult<T> = std::result::Result<T, CodecError>;
+
+#[derive(From, Debug)]
+pub enum CodecError {
+    IO(io::Error),
+
+    #[from(ignore)]
+    FrameTooLarge {
+        size: usize,
+        max: usize,
+    },
+
+    #[from(ignore)]
+    InvalidFrame(String),
+
+    /// `UnexpectedEof` is returned when the stream ended in the middle of
+    /// a frame, holding how many bytes of it were received.
+    #[from(ignore)]
+    UnexpectedEof(usize),
+}
+
+impl std::error::Error for CodecError {}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `Decoded` is the outcome of decoding from a buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decoded<T> {
+    /// `Frame` is a complete frame, its bytes were consumed from the buffer.
+    Frame(T),
+
+    /// `Incomplete` means more input is needed, the buffer is left as is.
+    /// `needed` is how many more bytes are required when the codec knows.
+    Incomplete { needed: Option<usize> },
+}
+
+/// `FrameCodec` turns a byte stream into frames and back, so protocols
+/// only describe their framing and leave buffering to [`super::FramedRead`]
+/// and [`super::FramedWrite`].
+pub trait FrameCodec {
+    type Item;
+
+    /// `encode` appends the wire form of `item` to `dst`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if `item` cannot be framed, e.g when it is
+    /// larger than the codec allows.
+    fn encode(&mut self, item: Self::Item, dst: &mut Vec<u8>) -> CodecResult<()>;
+
+    /// `decode` takes the first complete frame off the front of `src`, or
+    /// signals that more input is needed without consuming anything.
+    ///
+    /// `src` may be a different buffer between calls, codecs must not
+    /// assume it only grew since the last one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] when the buffered input can never form a
+    /// valid frame.
+    fn decode(&mut self, src: &mut FrameBuffer) -> CodecResult<Decoded<Self::Item>>;
+}
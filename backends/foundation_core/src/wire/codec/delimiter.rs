@@ -0,0 +1,169 @@
+use super::{CodecError, CodecResult, Decoded, FrameBuffer, FrameCodec};
+
+/// `DelimiterCodec` frames payloads terminated by a delimiter, e.g
+/// newline separated JSON or `\r\n` terminated commands.
+///
+/// Payloads must not contain the delimiter themselves.
+#[derive(Clone, Debug)]
+pub struct DelimiterCodec {
+    delimiter: Vec<u8>,
+    strip_carriage_return: bool,
+    max_frame_len: usize,
+
+    /// `searched` is how much of the buffer is known to not contain the
+    /// delimiter, so partial frames are not scanned again on every read.
+    /// It is reset when the next buffer decoded is shorter than that.
+    searched: usize,
+}
+
+// -- Constructors
+
+impl DelimiterCodec {
+    /// `new` creates a codec splitting on `delimiter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `delimiter` is empty.
+    pub fn new(delimiter: impl Into<Vec<u8>>) -> Self {
+        let delimiter = delimiter.into();
+        assert!(!delimiter.is_empty(), "delimiter should not be empty");
+        Self {
+            delimiter,
+            strip_carriage_return: false,
+            max_frame_len: 64 * 1024,
+            searched: 0,
+        }
+    }
+
+    /// `lines` splits on `\n`, dropping a `\r` right before it.
+    #[must_use]
+    pub fn lines() -> Self {
+        let mut codec = Self::new(b"\n".to_vec());
+        codec.strip_carriage_return = true;
+        codec
+    }
+}
+
+// -- Builder methods
+
+impl DelimiterCodec {
+    #[must_use]
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+}
+
+impl FrameCodec for DelimiterCodec {
+    type Item = Vec<u8>;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut Vec<u8>) -> CodecResult<()> {
+        if item.len() > self.max_frame_len {
+            return Err(CodecError::FrameTooLarge {
+                size: item.len(),
+                max: self.max_frame_len,
+            });
+        }
+        if memchr::memmem::find(&item, &self.delimiter).is_some() {
+            return Err(CodecError::InvalidFrame(
+                "payload contains the delimiter".into(),
+            ));
+        }
+
+        dst.extend_from_slice(&item);
+        dst.extend_from_slice(&self.delimiter);
+        Ok(())
+    }
+
+    fn decode(&mut self, src: &mut FrameBuffer) -> CodecResult<Decoded<Vec<u8>>> {
+        // a buffer shorter than what was searched is not the one searched.
+        if self.searched > src.len() {
+            self.searched = 0;
+        }
+
+        // the delimiter may straddle the already searched part.
+        let start = self.searched.saturating_sub(self.delimiter.len() - 1);
+        let Some(found) = memchr::memmem::find(&src[start..], &self.delimiter) else {
+            self.searched = src.len();
+            if src.len() > self.max_frame_len {
+                return Err(CodecError::FrameTooLarge {
+                    size: src.len(),
+                    max: self.max_frame_len,
+                });
+            }
+            return Ok(Decoded::Incomplete { needed: None });
+        };
+
+        let end = start + found;
+        self.searched = 0;
+        if end > self.max_frame_len {
+            return Err(CodecError::FrameTooLarge {
+                size: end,
+                max: self.max_frame_len,
+            });
+        }
+
+        let mut frame = src.take(end);
+        src.consume(self.delimiter.len());
+        if self.strip_carriage_return && frame.last() == Some(&b'\r') {
+            frame.pop();
+        }
+        Ok(Decoded::Frame(frame))
+    }
+}
+
+#[cfg(test)]
+mod test_delimiter_codec {
+    use super::*;
+
+    #[test]
+    fn splits_on_delimiter_across_reads() {
+        let mut codec = DelimiterCodec::new(b"\r\n\r\n".to_vec());
+        let mut buffer = FrameBuffer::from(b"first\r\n".to_vec());
+        assert_eq!(
+            codec.decode(&mut buffer).expect("should decode"),
+            Decoded::Incomplete { needed: None }
+        );
+
+        buffer.extend_from_slice(b"\r\nsecond");
+        assert_eq!(
+            codec.decode(&mut buffer).expect("should decode"),
+            Decoded::Frame(b"first".to_vec())
+        );
+        assert_eq!(&buffer[..], b"second");
+
+        let mut lines = DelimiterCodec::lines().with_max_frame_len(8);
+        let mut buffer = FrameBuffer::from(b"one\r\ntwo\n".to_vec());
+        assert_eq!(
+            lines.decode(&mut buffer).expect("should decode"),
+            Decoded::Frame(b"one".to_vec())
+        );
+        assert_eq!(
+            lines.decode(&mut buffer).expect("should decode"),
+            Decoded::Frame(b"two".to_vec())
+        );
+
+        let mut too_long = FrameBuffer::from(b"123456789".to_vec());
+        assert!(matches!(
+            lines.decode(&mut too_long),
+            Err(CodecError::FrameTooLarge { .. })
+        ));
+        assert!(lines.encode(b"a\nb".to_vec(), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn searched_offset_does_not_outlive_a_shorter_buffer() {
+        let mut codec = DelimiterCodec::lines();
+        let mut long = FrameBuffer::from(b"a long partial line".to_vec());
+        assert_eq!(
+            codec.decode(&mut long).expect("should decode"),
+            Decoded::Incomplete { needed: None }
+        );
+
+        let mut short = FrameBuffer::from(b"hi\n".to_vec());
+        assert_eq!(
+            codec.decode(&mut short).expect("should decode"),
+            Decoded::Frame(b"hi".to_vec())
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::io::{self, Read, Write};
+
+use super::{CodecError, CodecResult, Decoded, FrameBuffer, FrameCodec};
+use crate::io::buffers::{BufferPool, PooledBuffer};
+
+pub(super) const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// `FramedRead` reads frames of `C` out of any [`Read`], keeping partial
+/// frames buffered between reads.
+#[derive(Debug)]
+pub struct FramedRead<R, C> {
+    reader: R,
+    codec: C,
+    buffer: FrameBuffer,
+}
+
+// -- Constructors
+
+impl<R: Read, C: FrameCodec> FramedRead<R, C> {
+    pub fn new(reader: R, codec: C) -> Self {
+        Self {
+            reader,
+            codec,
+            buffer: FrameBuffer::new(),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<R: Read, C: FrameCodec> FramedRead<R, C> {
+    /// `with_buffer_pool` leases the read buffer from `pool`, returning it
+    /// once the reader is dropped.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer.lease_from(pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<R: Read, C: FrameCodec> FramedRead<R, C> {
+    /// `read_frame` returns the next frame, reading as much as needed to
+    /// complete it, or `None` once the stream ended cleanly between frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] on read or decode failures, and
+    /// [`CodecError::UnexpectedEof`] if the stream ended mid-frame.
+    pub fn read_frame(&mut self) -> CodecResult<Option<C::Item>> {
+        loop {
+            if let Decoded::Frame(frame) = self.codec.decode(&mut self.buffer)? {
+                return Ok(Some(frame));
+            }
+
+            let mut chunk = [0; READ_CHUNK_SIZE];
+            let read = match self.reader.read(&mut chunk) {
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if read == 0 {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                return Err(CodecError::UnexpectedEof(self.buffer.len()));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// `pump` hands every frame to `sink` until the stream ends or `sink`
+    /// returns false, e.g to feed a channel:
+    /// `framed.pump(|frame| sender.block_send(frame).is_ok())`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`CodecError`] met while reading.
+    pub fn pump(mut self, mut sink: impl FnMut(C::Item) -> bool) -> CodecResult<()> {
+        while let Some(frame) = self.read_frame()? {
+            if !sink(frame) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// `into_parts` returns the reader, codec and any bytes read but not
+    /// yet decoded.
+    pub fn into_parts(self) -> (R, C, Vec<u8>) {
+        (self.reader, self.codec, self.buffer.into_unread())
+    }
+}
+
+impl<R: Read, C: FrameCodec> Iterator for FramedRead<R, C> {
+    type Item = CodecResult<C::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// `FramedWrite` writes frames of `C` to any [`Write`].
+#[derive(Debug)]
+pub struct FramedWrite<W, C> {
+    writer: W,
+    codec: C,
+    buffer: PooledBuffer,
+}
+
+// -- Constructors
+
+impl<W: Write, C: FrameCodec> FramedWrite<W, C> {
+    pub fn new(writer: W, codec: C) -> Self {
+        Self {
+            writer,
+            codec,
+            buffer: PooledBuffer::unpooled(Vec::new()),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<W: Write, C: FrameCodec> FramedWrite<W, C> {
+    /// `with_buffer_pool` leases the encoding buffer from `pool`.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer = swap_into_lease(&mut self.buffer, pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<W: Write, C: FrameCodec> FramedWrite<W, C> {
+    /// `send` encodes `item` and writes it out in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if encoding or writing fails.
+    pub fn send(&mut self, item: C::Item) -> CodecResult<()> {
+        self.buffer.clear();
+        self.codec.encode(item, &mut self.buffer)?;
+        self.writer.write_all(&self.buffer)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// `Framed` reads and writes frames of `C` over a single duplex stream.
+#[derive(Debug)]
+pub struct Framed<S, C> {
+    inner: FramedRead<S, C>,
+    write_buffer: PooledBuffer,
+}
+
+// -- Constructors
+
+impl<S: Read + Write, C: FrameCodec> Framed<S, C> {
+    pub fn new(stream: S, codec: C) -> Self {
+        Self {
+            inner: FramedRead::new(stream, codec),
+            write_buffer: PooledBuffer::unpooled(Vec::new()),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<S: Read + Write, C: FrameCodec> Framed<S, C> {
+    /// `with_buffer_pool` leases both the read and write buffers from
+    /// `pool`.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.inner = self.inner.with_buffer_pool(pool);
+        self.write_buffer = swap_into_lease(&mut self.write_buffer, pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<S: Read + Write, C: FrameCodec> Framed<S, C> {
+    /// `send` encodes `item` and writes it out in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if encoding or writing fails.
+    pub fn send(&mut self, item: C::Item) -> CodecResult<()> {
+        self.write_buffer.clear();
+        self.inner.codec.encode(item, &mut self.write_buffer)?;
+        self.inner.reader.write_all(&self.write_buffer)?;
+        self.inner.reader.flush()?;
+        Ok(())
+    }
+
+    /// `recv` returns the next frame, see [`FramedRead::read_frame`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] on read or decode failures.
+    pub fn recv(&mut self) -> CodecResult<Option<C::Item>> {
+        self.inner.read_frame()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref()
+    }
+
+    /// `into_parts` returns the stream, codec and any bytes read but not
+    /// yet decoded.
+    pub fn into_parts(self) -> (S, C, Vec<u8>) {
+        self.inner.into_parts()
+    }
+}
+
+/// `swap_into_lease` moves any bytes already in `buffer` into a buffer
+/// leased from `pool`.
+pub(super) fn swap_into_lease(buffer: &mut PooledBuffer, pool: &BufferPool) -> PooledBuffer {
+    let mut leased = pool.lease();
+    leased.append(buffer);
+    leased
+}
+
+#[cfg(test)]
+mod test_framed {
+    use std::io::Cursor;
+
+    use super::super::{DelimiterCodec, LengthPrefixedCodec};
+    use super::*;
+
+    /// `Trickle` hands out a single byte per read to exercise partial frames.
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let limit = buf.len().min(1);
+            self.0.read(&mut buf[..limit])
+        }
+    }
+
+    #[test]
+    fn frames_round_trip_over_streams() {
+        let mut writer = FramedWrite::new(Vec::new(), LengthPrefixedCodec::default());
+        writer.send(b"alpha".to_vec()).expect("should send");
+        writer.send(b"beta".to_vec()).expect("should send");
+
+        let reader = FramedRead::new(
+            Trickle(Cursor::new(writer.into_inner())),
+            LengthPrefixedCodec::default(),
+        );
+        let frames: Vec<Vec<u8>> = reader
+            .collect::<CodecResult<_>>()
+            .expect("should read frames");
+        assert_eq!(frames, vec![b"alpha".to_vec(), b"beta".to_vec()]);
+
+        let mut truncated =
+            FramedRead::new(Cursor::new(b"partial".to_vec()), DelimiterCodec::lines());
+        assert!(matches!(
+            truncated.read_frame(),
+            Err(CodecError::UnexpectedEof(7))
+        ));
+
+        let mut received = Vec::new();
+        FramedRead::new(Cursor::new(b"a\nb\nc\n".to_vec()), DelimiterCodec::lines())
+            .pump(|frame| {
+                received.push(frame);
+                received.len() < 2
+            })
+            .expect("should pump");
+        assert_eq!(received, vec![b"a".to_vec(), b"b".to_vec()]);
+
+        let mut duplex = Framed::new(Cursor::new(Vec::new()), DelimiterCodec::lines());
+        duplex.send(b"ping".to_vec()).expect("should send");
+        assert_eq!(duplex.get_ref().get_ref(), b"ping\n");
+    }
+
+    #[test]
+    fn framed_buffers_are_leased_from_the_pool() {
+        let pool = BufferPool::new(64, 4);
+
+        for _ in 0..3 {
+            let mut reader =
+                FramedRead::new(Cursor::new(b"a\nb\n".to_vec()), DelimiterCodec::lines())
+                    .with_buffer_pool(&pool);
+            assert_eq!(
+                reader.read_frame().expect("should read"),
+                Some(b"a".to_vec())
+            );
+        }
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.misses, 1);
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.idle, 1);
+    }
+}
@@ -0,0 +1,282 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::framed::{swap_into_lease, READ_CHUNK_SIZE};
+use super::{CodecError, CodecResult, Decoded, FrameBuffer, FrameCodec};
+use crate::io::buffers::{BufferPool, PooledBuffer};
+
+/// `AsyncFramedRead` is the tokio counterpart of [`super::FramedRead`],
+/// reading frames of `C` out of any [`AsyncRead`].
+///
+/// Reading is cancel safe, bytes are only buffered once a read completed
+/// so dropping a pending [`AsyncFramedRead::read_frame`] loses nothing.
+#[derive(Debug)]
+pub struct AsyncFramedRead<R, C> {
+    reader: R,
+    codec: C,
+    buffer: FrameBuffer,
+}
+
+// -- Constructors
+
+impl<R: AsyncRead + Unpin, C: FrameCodec> AsyncFramedRead<R, C> {
+    pub fn new(reader: R, codec: C) -> Self {
+        Self {
+            reader,
+            codec,
+            buffer: FrameBuffer::new(),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<R: AsyncRead + Unpin, C: FrameCodec> AsyncFramedRead<R, C> {
+    /// `with_buffer_pool` leases the read buffer from `pool`, returning it
+    /// once the reader is dropped.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer.lease_from(pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<R: AsyncRead + Unpin, C: FrameCodec> AsyncFramedRead<R, C> {
+    /// `read_frame` returns the next frame, reading as much as needed to
+    /// complete it, or `None` once the stream ended cleanly between frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] on read or decode failures, and
+    /// [`CodecError::UnexpectedEof`] if the stream ended mid-frame.
+    pub async fn read_frame(&mut self) -> CodecResult<Option<C::Item>> {
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        loop {
+            if let Decoded::Frame(frame) = self.codec.decode(&mut self.buffer)? {
+                return Ok(Some(frame));
+            }
+
+            let read = self.reader.read(&mut chunk).await?;
+            if read == 0 {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                return Err(CodecError::UnexpectedEof(self.buffer.len()));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    /// `pump` hands every frame to `sink` until the stream ends or `sink`
+    /// returns false, e.g to feed a channel:
+    /// `framed.pump(|frame| sender.try_send(frame).is_ok()).await`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`CodecError`] met while reading.
+    pub async fn pump(mut self, mut sink: impl FnMut(C::Item) -> bool) -> CodecResult<()> {
+        while let Some(frame) = self.read_frame().await? {
+            if !sink(frame) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// `into_parts` returns the reader, codec and any bytes read but not
+    /// yet decoded.
+    pub fn into_parts(self) -> (R, C, Vec<u8>) {
+        (self.reader, self.codec, self.buffer.into_unread())
+    }
+}
+
+/// `AsyncFramedWrite` is the tokio counterpart of [`super::FramedWrite`],
+/// writing frames of `C` to any [`AsyncWrite`].
+#[derive(Debug)]
+pub struct AsyncFramedWrite<W, C> {
+    writer: W,
+    codec: C,
+    buffer: PooledBuffer,
+}
+
+// -- Constructors
+
+impl<W: AsyncWrite + Unpin, C: FrameCodec> AsyncFramedWrite<W, C> {
+    pub fn new(writer: W, codec: C) -> Self {
+        Self {
+            writer,
+            codec,
+            buffer: PooledBuffer::unpooled(Vec::new()),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<W: AsyncWrite + Unpin, C: FrameCodec> AsyncFramedWrite<W, C> {
+    /// `with_buffer_pool` leases the encoding buffer from `pool`.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.buffer = swap_into_lease(&mut self.buffer, pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<W: AsyncWrite + Unpin, C: FrameCodec> AsyncFramedWrite<W, C> {
+    /// `send` encodes `item` and writes it out in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if encoding or writing fails.
+    pub async fn send(&mut self, item: C::Item) -> CodecResult<()> {
+        self.buffer.clear();
+        self.codec.encode(item, &mut self.buffer)?;
+        self.writer.write_all(&self.buffer).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// `AsyncFramed` reads and writes frames of `C` over a single tokio duplex
+/// stream, see [`super::Framed`].
+#[derive(Debug)]
+pub struct AsyncFramed<S, C> {
+    inner: AsyncFramedRead<S, C>,
+    write_buffer: PooledBuffer,
+}
+
+// -- Constructors
+
+impl<S: AsyncRead + AsyncWrite + Unpin, C: FrameCodec> AsyncFramed<S, C> {
+    pub fn new(stream: S, codec: C) -> Self {
+        Self {
+            inner: AsyncFramedRead::new(stream, codec),
+            write_buffer: PooledBuffer::unpooled(Vec::new()),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl<S: AsyncRead + AsyncWrite + Unpin, C: FrameCodec> AsyncFramed<S, C> {
+    /// `with_buffer_pool` leases both the read and write buffers from
+    /// `pool`.
+    #[must_use]
+    pub fn with_buffer_pool(mut self, pool: &BufferPool) -> Self {
+        self.inner = self.inner.with_buffer_pool(pool);
+        self.write_buffer = swap_into_lease(&mut self.write_buffer, pool);
+        self
+    }
+}
+
+// -- Methods
+
+impl<S: AsyncRead + AsyncWrite + Unpin, C: FrameCodec> AsyncFramed<S, C> {
+    /// `send` encodes `item` and writes it out in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] if encoding or writing fails.
+    pub async fn send(&mut self, item: C::Item) -> CodecResult<()> {
+        self.write_buffer.clear();
+        self.inner.codec.encode(item, &mut self.write_buffer)?;
+        self.inner.reader.write_all(&self.write_buffer).await?;
+        self.inner.reader.flush().await?;
+        Ok(())
+    }
+
+    /// `recv` returns the next frame, see [`AsyncFramedRead::read_frame`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodecError`] on read or decode failures.
+    pub async fn recv(&mut self) -> CodecResult<Option<C::Item>> {
+        self.inner.read_frame().await
+    }
+
+    pub fn get_ref(&self) -> &S {
+        self.inner.get_ref()
+    }
+
+    /// `into_parts` returns the stream, codec and any bytes read but not
+    /// yet decoded.
+    pub fn into_parts(self) -> (S, C, Vec<u8>) {
+        self.inner.into_parts()
+    }
+}
+
+#[cfg(test)]
+mod test_async_framed {
+    use super::super::{DelimiterCodec, LengthPrefixedCodec};
+    use super::*;
+    use crate::extensions::tokio_ext::block_on;
+
+    #[test]
+    fn frames_round_trip_over_async_streams() {
+        block_on(async {
+            let mut writer = AsyncFramedWrite::new(Vec::new(), LengthPrefixedCodec::default());
+            writer.send(b"alpha".to_vec()).await.expect("should send");
+            writer.send(b"beta".to_vec()).await.expect("should send");
+            let wire = writer.into_inner();
+
+            let mut received = Vec::new();
+            AsyncFramedRead::new(wire.as_slice(), LengthPrefixedCodec::default())
+                .pump(|frame| {
+                    received.push(frame);
+                    true
+                })
+                .await
+                .expect("should pump");
+            assert_eq!(received, vec![b"alpha".to_vec(), b"beta".to_vec()]);
+
+            let mut truncated =
+                AsyncFramedRead::new(b"partial".as_slice(), DelimiterCodec::lines());
+            assert!(matches!(
+                truncated.read_frame().await,
+                Err(CodecError::UnexpectedEof(7))
+            ));
+        });
+    }
+
+    #[test]
+    fn duplex_frames_arrive_in_small_reads() {
+        block_on(async {
+            // a 2 byte pipe splits every frame across reads and writes.
+            let (client, server) = tokio::io::duplex(2);
+            let echo = tokio::spawn(async move {
+                let mut server = AsyncFramed::new(server, DelimiterCodec::lines());
+                while let Some(frame) = server.recv().await.expect("should recv") {
+                    server.send(frame).await.expect("should send");
+                }
+            });
+
+            let mut client = AsyncFramed::new(client, DelimiterCodec::lines());
+            for message in [b"ping".to_vec(), b"pong pong".to_vec()] {
+                client.send(message.clone()).await.expect("should send");
+                assert_eq!(client.recv().await.expect("should recv"), Some(message));
+            }
+
+            drop(client);
+            echo.await.expect("should finish echoing");
+        });
+    }
+}
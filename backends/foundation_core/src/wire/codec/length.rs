@@ -0,0 +1,137 @@
+use super::{CodecError, CodecResult, Decoded, FrameBuffer, FrameCodec};
+
+/// `LengthPrefixedCodec` frames payloads behind a big-endian length header
+/// of `header_len` bytes, the usual framing of binary protocols.
+#[derive(Clone, Debug)]
+pub struct LengthPrefixedCodec {
+    header_len: usize,
+    max_frame_len: usize,
+}
+
+impl Default for LengthPrefixedCodec {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+// -- Constructors
+
+impl LengthPrefixedCodec {
+    /// `new` creates a codec with a `header_len` byte length header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `header_len` is not between 1 and 8.
+    #[must_use]
+    pub fn new(header_len: usize) -> Self {
+        assert!(
+            (1..=8).contains(&header_len),
+            "header_len should be between 1 and 8 bytes"
+        );
+        Self {
+            header_len,
+            max_frame_len: 8 * 1024 * 1024,
+        }
+    }
+}
+
+// -- Builder methods
+
+impl LengthPrefixedCodec {
+    #[must_use]
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+}
+
+impl FrameCodec for LengthPrefixedCodec {
+    type Item = Vec<u8>;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut Vec<u8>) -> CodecResult<()> {
+        let max_for_header = if self.header_len == 8 {
+            u64::MAX
+        } else {
+            (1u64 << (self.header_len * 8)) - 1
+        };
+        let len = item.len() as u64;
+        if item.len() > self.max_frame_len || len > max_for_header {
+            return Err(CodecError::FrameTooLarge {
+                size: item.len(),
+                max: self.max_frame_len,
+            });
+        }
+
+        dst.extend_from_slice(&len.to_be_bytes()[8 - self.header_len..]);
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+
+    fn decode(&mut self, src: &mut FrameBuffer) -> CodecResult<Decoded<Vec<u8>>> {
+        if src.len() < self.header_len {
+            return Ok(Decoded::Incomplete {
+                needed: Some(self.header_len - src.len()),
+            });
+        }
+
+        let mut header = [0u8; 8];
+        header[8 - self.header_len..].copy_from_slice(&src[..self.header_len]);
+        let len = usize::try_from(u64::from_be_bytes(header)).unwrap_or(usize::MAX);
+        if len > self.max_frame_len {
+            return Err(CodecError::FrameTooLarge {
+                size: len,
+                max: self.max_frame_len,
+            });
+        }
+
+        let total = self.header_len + len;
+        if src.len() < total {
+            return Ok(Decoded::Incomplete {
+                needed: Some(total - src.len()),
+            });
+        }
+
+        src.consume(self.header_len);
+        Ok(Decoded::Frame(src.take(len)))
+    }
+}
+
+#[cfg(test)]
+mod test_length_prefixed_codec {
+    use super::*;
+
+    #[test]
+    fn decodes_partial_and_multiple_frames() {
+        let mut codec = LengthPrefixedCodec::new(2);
+        let mut wire = Vec::new();
+        codec
+            .encode(b"hello".to_vec(), &mut wire)
+            .expect("should encode");
+        codec.encode(Vec::new(), &mut wire).expect("should encode");
+        assert_eq!(&wire[..2], &[0, 5]);
+
+        let mut buffer = FrameBuffer::from(wire[..4].to_vec());
+        assert_eq!(
+            codec.decode(&mut buffer).expect("should decode"),
+            Decoded::Incomplete { needed: Some(3) }
+        );
+
+        buffer.extend_from_slice(&wire[4..]);
+        assert_eq!(
+            codec.decode(&mut buffer).expect("should decode"),
+            Decoded::Frame(b"hello".to_vec())
+        );
+        assert_eq!(
+            codec.decode(&mut buffer).expect("should decode"),
+            Decoded::Frame(Vec::new())
+        );
+        assert_eq!(buffer.len(), 0);
+
+        let mut limited = LengthPrefixedCodec::new(1).with_max_frame_len(4);
+        assert!(matches!(
+            limited.decode(&mut FrameBuffer::from(vec![200])),
+            Err(CodecError::FrameTooLarge { size: 200, max: 4 })
+        ));
+        assert!(limited.encode(vec![0; 5], &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+mod buffer;
+mod core;
+mod delimiter;
+mod framed;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod framed_async;
+
+mod length;
+
+pub use buffer::*;
+pub use core::*;
+pub use delimiter::*;
+pub use framed::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use framed_async::*;
+
+pub use length::*;
@@ -1,3 +1,6 @@
+pub mod codec;
 pub mod event_source;
+pub mod rpc;
 pub mod simple_http;
 pub mod tcp;
+pub mod websocket;
@@ -0,0 +1,210 @@
+use super::{RpcError, RpcFrame, RpcMethod, RpcResult, RpcTransport};
+
+/// `RpcClient` calls methods registered on a remote
+/// [`super::RpcServices`], one call at a time.
+#[derive(Debug)]
+pub struct RpcClient<T: RpcTransport> {
+    transport: T,
+    next_id: u64,
+}
+
+// -- Constructors
+
+impl<T: RpcTransport> RpcClient<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            next_id: 1,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RpcClient<crate::wire::codec::Framed<crate::wire::tcp::TransportStream, super::RpcCodec>> {
+    /// `connect` opens a stream connection to `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RpcError::Codec`] wrapping the connection failure.
+    pub fn connect(addr: &crate::wire::tcp::TransportAddr) -> RpcResult<Self> {
+        let stream = crate::wire::tcp::TransportStream::connect(
+            addr,
+            std::time::Duration::ZERO,
+            &crate::wire::tcp::SystemResolver,
+        )
+        .map_err(crate::wire::codec::CodecError::from)?;
+        Ok(Self::new(crate::wire::codec::Framed::new(
+            stream,
+            super::RpcCodec::default(),
+        )))
+    }
+}
+
+// -- Methods
+
+impl<T: RpcTransport> RpcClient<T> {
+    /// `call` sends a request for method `M` and waits for its events.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RpcError::Remote`] when the handler failed,
+    /// [`RpcError::ConnectionClosed`] if the peer went away before
+    /// answering, or the transport and serialization errors met.
+    pub fn call<M: RpcMethod>(&mut self, request: &M::Request) -> RpcResult<Vec<M::Event>> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.transport.send_frame(RpcFrame::Request {
+            id,
+            method: M::NAME.to_string(),
+            payload: serde_json::to_vec(request)?,
+        })?;
+
+        loop {
+            match self.transport.recv_frame()? {
+                None => return Err(RpcError::ConnectionClosed),
+                Some(frame) if frame.id() != id => {
+                    tracing::warn!("Dropping rpc frame for an earlier call: {:?}", frame);
+                }
+                Some(RpcFrame::Events { payloads, .. }) => {
+                    return payloads
+                        .iter()
+                        .map(|payload| serde_json::from_slice(payload).map_err(RpcError::from))
+                        .collect();
+                }
+                Some(RpcFrame::Failed { message, .. }) => return Err(RpcError::Remote(message)),
+                Some(frame @ RpcFrame::Request { .. }) => {
+                    return Err(RpcError::UnexpectedMessage(format!("{frame:?}")));
+                }
+            }
+        }
+    }
+
+    /// `call_one` is [`Self::call`] for methods answering with a single
+    /// event.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::call`], it also returns [`RpcError::UnexpectedMessage`]
+    /// when the handler sent no events.
+    pub fn call_one<M: RpcMethod>(&mut self, request: &M::Request) -> RpcResult<M::Event> {
+        self.call::<M>(request)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| RpcError::UnexpectedMessage(format!("{} sent no events", M::NAME)))
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+}
+
+#[cfg(test)]
+mod test_rpc_client {
+    use std::{net::TcpListener, sync::Arc, thread};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::wire::{
+        codec::Framed,
+        tcp::{TransportAddr, TransportStream},
+    };
+
+    use super::super::{RpcCodec, RpcServices};
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Countdown {
+        from: u32,
+    }
+
+    struct CountDown;
+
+    impl RpcMethod for CountDown {
+        const NAME: &'static str = "counter.countdown";
+        type Request = Countdown;
+        type Event = u32;
+    }
+
+    struct Greet;
+
+    impl RpcMethod for Greet {
+        const NAME: &'static str = "greeter.greet";
+        type Request = String;
+        type Event = String;
+    }
+
+    fn services() -> RpcServices {
+        RpcServices::new()
+            .with_method::<CountDown, _>(|request| {
+                if request.from > 10 {
+                    return Err(format!("{} is too high", request.from));
+                }
+                Ok((0..=request.from).rev().collect())
+            })
+            .with_method::<Greet, _>(|name| Ok(vec![format!("hello {name}")]))
+    }
+
+    #[test]
+    fn calls_registered_methods_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("should bind");
+        let port = listener.local_addr().expect("should have address").port();
+        let services = Arc::new(services());
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("should accept");
+            services
+                .serve_transport(Framed::new(
+                    TransportStream::Tcp(stream),
+                    RpcCodec::default(),
+                ))
+                .expect("should serve");
+        });
+
+        let mut client =
+            RpcClient::connect(&TransportAddr::tcp("127.0.0.1", port)).expect("should connect");
+        assert_eq!(
+            client
+                .call::<CountDown>(&Countdown { from: 3 })
+                .expect("should call"),
+            vec![3, 2, 1, 0]
+        );
+        assert_eq!(
+            client
+                .call_one::<Greet>(&"alex".to_string())
+                .expect("should call"),
+            "hello alex"
+        );
+        assert!(matches!(
+            client.call::<CountDown>(&Countdown { from: 11 }),
+            Err(RpcError::Remote(message)) if message == "11 is too high"
+        ));
+
+        drop(client);
+        server.join().expect("should finish");
+    }
+
+    #[test]
+    fn unknown_methods_are_reported_to_the_caller() {
+        struct Missing;
+
+        impl RpcMethod for Missing {
+            const NAME: &'static str = "missing";
+            type Request = ();
+            type Event = ();
+        }
+
+        let answer = services().dispatch(RpcFrame::Request {
+            id: 4,
+            method: Missing::NAME.into(),
+            payload: serde_json::to_vec(&()).expect("should serialize"),
+        });
+        assert_eq!(
+            answer,
+            Some(RpcFrame::Failed {
+                id: 4,
+                message: RpcError::UnknownMethod("missing".into()).to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use derive_more::From;
+
+use crate::wire::codec::{
+    CodecError, CodecResult, Decoded, FrameBuffer, FrameCodec, LengthPrefixedCodec,
+};
+
+pub type RpcResult<T> = std::result::Result<T, RpcError>;
+
+#[derive(From, Debug)]
+pub enum RpcError {
+    Codec(CodecError),
+    Json(serde_json::Error),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    WebSocket(crate::wire::websocket::WebSocketError),
+
+    /// `UnknownMethod` is returned by the server for calls to a method
+    /// that was never registered.
+    #[from(ignore)]
+    UnknownMethod(String),
+
+    /// `Remote` is a failure reported by the remote handler.
+    #[from(ignore)]
+    Remote(String),
+
+    #[from(ignore)]
+    UnexpectedMessage(String),
+
+    ConnectionClosed,
+}
+
+impl std::error::Error for RpcError {}
+
+impl core::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+const TAG_REQUEST: u8 = 1;
+const TAG_EVENTS: u8 = 2;
+const TAG_FAILED: u8 = 3;
+
+/// `RpcFrame` is a single message of the RPC protocol.
+///
+/// It mirrors the `NamedRequest`/`NamedEvent` envelopes of domain shells:
+/// a request carries an id, the answer reuses that id and holds any
+/// number of events, so a shell can be moved to another process without
+/// changing how it pairs requests with events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcFrame {
+    Request {
+        id: u64,
+        method: String,
+        payload: Vec<u8>,
+    },
+    Events {
+        id: u64,
+        payloads: Vec<Vec<u8>>,
+    },
+    Failed {
+        id: u64,
+        message: String,
+    },
+}
+
+impl RpcFrame {
+    pub fn id(&self) -> u64 {
+        match self {
+            Self::Request { id, .. } | Self::Events { id, .. } | Self::Failed { id, .. } => *id,
+        }
+    }
+
+    /// `to_bytes` encodes the frame without the length header, as sent in
+    /// a websocket binary message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::FrameTooLarge`] if the method name or an
+    /// event does not fit its length field.
+    pub fn to_bytes(&self) -> CodecResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        match self {
+            Self::Request {
+                id,
+                method,
+                payload,
+            } => {
+                bytes.push(TAG_REQUEST);
+                bytes.extend_from_slice(&id.to_be_bytes());
+                let method_len =
+                    u16::try_from(method.len()).map_err(|_| CodecError::FrameTooLarge {
+                        size: method.len(),
+                        max: u16::MAX as usize,
+                    })?;
+                bytes.extend_from_slice(&method_len.to_be_bytes());
+                bytes.extend_from_slice(method.as_bytes());
+                bytes.extend_from_slice(payload);
+            }
+            Self::Events { id, payloads } => {
+                bytes.push(TAG_EVENTS);
+                bytes.extend_from_slice(&id.to_be_bytes());
+                bytes.extend_from_slice(&encode_u32(payloads.len())?);
+                for payload in payloads {
+                    bytes.extend_from_slice(&encode_u32(payload.len())?);
+                    bytes.extend_from_slice(payload);
+                }
+            }
+            Self::Failed { id, message } => {
+                bytes.push(TAG_FAILED);
+                bytes.extend_from_slice(&id.to_be_bytes());
+                bytes.extend_from_slice(message.as_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// `from_bytes` decodes a frame produced by [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CodecError::InvalidFrame`] if `bytes` is not a valid frame.
+    pub fn from_bytes(bytes: &[u8]) -> CodecResult<Self> {
+        let mut reader = FrameReader(bytes);
+        let tag = reader.take(1)?[0];
+        let id = u64::from_be_bytes(reader.take(8)?.try_into().expect("should be 8 bytes"));
+
+        match tag {
+            TAG_REQUEST => {
+                let method_len =
+                    u16::from_be_bytes(reader.take(2)?.try_into().expect("should be 2 bytes"));
+                let method = reader.take_string(method_len as usize)?;
+                Ok(Self::Request {
+                    id,
+                    method,
+                    payload: reader.0.to_vec(),
+                })
+            }
+            TAG_EVENTS => {
+                let count = reader.take_u32()?;
+                let payloads = (0..count)
+                    .map(|_| {
+                        let len = reader.take_u32()?;
+                        reader.take(len).map(<[u8]>::to_vec)
+                    })
+                    .collect::<CodecResult<Vec<_>>>()?;
+                Ok(Self::Events { id, payloads })
+            }
+            TAG_FAILED => Ok(Self::Failed {
+                id,
+                message: reader.take_string(reader.0.len())?,
+            }),
+            other => Err(CodecError::InvalidFrame(format!(
+                "unknown rpc frame tag: {other}"
+            ))),
+        }
+    }
+}
+
+fn encode_u32(value: usize) -> CodecResult<[u8; 4]> {
+    u32::try_from(value)
+        .map(u32::to_be_bytes)
+        .map_err(|_| CodecError::FrameTooLarge {
+            size: value,
+            max: u32::MAX as usize,
+        })
+}
+
+struct FrameReader<'a>(&'a [u8]);
+
+impl<'a> FrameReader<'a> {
+    fn take(&mut self, len: usize) -> CodecResult<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(CodecError::InvalidFrame("truncated rpc frame".into()));
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn take_u32(&mut self) -> CodecResult<usize> {
+        let value = u32::from_be_bytes(self.take(4)?.try_into().expect("should be 4 bytes"));
+        Ok(value as usize)
+    }
+
+    fn take_string(&mut self, len: usize) -> CodecResult<String> {
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|err| CodecError::InvalidFrame(err.to_string()))
+    }
+}
+
+/// `RpcCodec` frames [`RpcFrame`]s behind a 4 byte length header, for
+/// stream transports like TCP or Unix sockets.
+#[derive(Clone, Debug, Default)]
+pub struct RpcCodec {
+    inner: LengthPrefixedCodec,
+}
+
+// -- Builder methods
+
+impl RpcCodec {
+    #[must_use]
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.inner = self.inner.with_max_frame_len(max_frame_len);
+        self
+    }
+}
+
+impl FrameCodec for RpcCodec {
+    type Item = RpcFrame;
+
+    fn encode(&mut self, item: RpcFrame, dst: &mut Vec<u8>) -> CodecResult<()> {
+        self.inner.encode(item.to_bytes()?, dst)
+    }
+
+    fn decode(&mut self, src: &mut FrameBuffer) -> CodecResult<Decoded<RpcFrame>> {
+        match self.inner.decode(src)? {
+            Decoded::Frame(bytes) => RpcFrame::from_bytes(&bytes).map(Decoded::Frame),
+            Decoded::Incomplete { needed } => Ok(Decoded::Incomplete { needed }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_rpc_frame {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip_through_the_codec() {
+        let frames = vec![
+            RpcFrame::Request {
+                id: 7,
+                method: "users.get".into(),
+                payload: br#"{"id":1}"#.to_vec(),
+            },
+            RpcFrame::Events {
+                id: 7,
+                payloads: vec![b"a".to_vec(), Vec::new(), b"ccc".to_vec()],
+            },
+            RpcFrame::Failed {
+                id: 8,
+                message: "boom".into(),
+            },
+        ];
+
+        let mut codec = RpcCodec::default();
+        let mut wire = Vec::new();
+        for frame in frames.clone() {
+            codec.encode(frame, &mut wire).expect("should encode");
+        }
+
+        let mut wire = FrameBuffer::from(wire);
+        let mut decoded = Vec::new();
+        while let Decoded::Frame(frame) = codec.decode(&mut wire).expect("should decode") {
+            decoded.push(frame);
+        }
+        assert_eq!(decoded, frames);
+
+        assert!(matches!(
+            RpcFrame::from_bytes(&[TAG_EVENTS, 0, 0]),
+            Err(CodecError::InvalidFrame(_))
+        ));
+    }
+}
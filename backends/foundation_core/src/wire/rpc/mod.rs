@@ -0,0 +1,9 @@
+mod client;
+mod frame;
+mod service;
+mod transport;
+
+pub use client::*;
+pub use frame::*;
+pub use service::*;
+pub use transport::*;
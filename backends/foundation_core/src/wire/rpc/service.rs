@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{RpcError, RpcFrame, RpcResult, RpcTransport};
+
+/// `RpcMethod` describes a remote method: its name on the wire and the
+/// types of its request and events, shared by the service registering a
+/// handler and the client calling it.
+///
+/// ```ignore
+/// struct GetUser;
+///
+/// impl RpcMethod for GetUser {
+///     const NAME: &'static str = "users.get";
+///     type Request = UserId;
+///     type Event = User;
+/// }
+/// ```
+pub trait RpcMethod {
+    const NAME: &'static str;
+    type Request: Serialize + DeserializeOwned;
+    type Event: Serialize + DeserializeOwned;
+}
+
+type RpcHandler = Box<dyn Fn(&[u8]) -> RpcResult<Vec<Vec<u8>>> + Send + Sync>;
+
+/// `RpcServices` is the server side registry of method handlers, it
+/// answers each request frame with the events of its handler.
+#[derive(Default)]
+pub struct RpcServices {
+    handlers: HashMap<String, RpcHandler>,
+}
+
+// -- Constructors
+
+impl RpcServices {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// -- Builder methods
+
+impl RpcServices {
+    #[must_use]
+    pub fn with_method<M, F>(mut self, handler: F) -> Self
+    where
+        M: RpcMethod,
+        F: Fn(M::Request) -> Result<Vec<M::Event>, String> + Send + Sync + 'static,
+    {
+        self.register::<M, F>(handler);
+        self
+    }
+}
+
+// -- Methods
+
+impl RpcServices {
+    /// `register` sets the handler of method `M`, replacing any previous
+    /// one. Errors returned by `handler` are sent back to the caller as
+    /// [`RpcError::Remote`].
+    pub fn register<M, F>(&mut self, handler: F)
+    where
+        M: RpcMethod,
+        F: Fn(M::Request) -> Result<Vec<M::Event>, String> + Send + Sync + 'static,
+    {
+        let handler: RpcHandler = Box::new(move |payload| {
+            let request: M::Request = serde_json::from_slice(payload)?;
+            handler(request)
+                .map_err(RpcError::Remote)?
+                .iter()
+                .map(|event| serde_json::to_vec(event).map_err(RpcError::from))
+                .collect()
+        });
+        self.handlers.insert(M::NAME.to_string(), handler);
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
+    /// `dispatch` runs the handler for a request frame and returns the
+    /// frame to answer with, frames that are not requests get no answer.
+    pub fn dispatch(&self, frame: RpcFrame) -> Option<RpcFrame> {
+        let RpcFrame::Request {
+            id,
+            method,
+            payload,
+        } = frame
+        else {
+            tracing::warn!("Ignoring rpc frame that is not a request: {:?}", frame);
+            return None;
+        };
+
+        let result = match self.handlers.get(&method) {
+            Some(handler) => handler(&payload),
+            None => Err(RpcError::UnknownMethod(method)),
+        };
+
+        Some(match result {
+            Ok(payloads) => RpcFrame::Events { id, payloads },
+            Err(RpcError::Remote(message)) => RpcFrame::Failed { id, message },
+            Err(err) => RpcFrame::Failed {
+                id,
+                message: err.to_string(),
+            },
+        })
+    }
+
+    /// `serve_transport` answers requests arriving on `transport` until the
+    /// peer disconnects.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`RpcError`] of the transport itself, failing
+    /// handlers are reported to the caller instead.
+    pub fn serve_transport<T: RpcTransport>(&self, mut transport: T) -> RpcResult<()> {
+        while let Some(frame) = transport.recv_frame()? {
+            if let Some(answer) = self.dispatch(frame) {
+                transport.send_frame(answer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for RpcServices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcServices")
+            .field("methods", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod listener {
+    use std::{sync::Arc, thread};
+
+    use crate::wire::{codec::Framed, tcp::TransportListener};
+
+    use super::super::RpcCodec;
+    use super::RpcServices;
+
+    impl RpcServices {
+        /// `serve_listener` accepts connections on `listener` forever,
+        /// serving each on its own thread.
+        ///
+        /// # Errors
+        ///
+        /// Returns the [`std::io::Error`] of a failed accept.
+        pub fn serve_listener(
+            self: Arc<Self>,
+            listener: &TransportListener,
+        ) -> std::io::Result<()> {
+            loop {
+                let stream = listener.accept()?;
+                let services = self.clone();
+                thread::spawn(move || {
+                    let peer = stream.peer_addr();
+                    if let Err(err) =
+                        services.serve_transport(Framed::new(stream, RpcCodec::default()))
+                    {
+                        tracing::error!("Rpc connection from {:?} failed: {:?}", peer, err);
+                    }
+                });
+            }
+        }
+    }
+}
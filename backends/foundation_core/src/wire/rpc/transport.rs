@@ -0,0 +1,65 @@
+use std::io::{Read, Write};
+
+use crate::wire::codec::Framed;
+
+use super::{RpcCodec, RpcFrame, RpcResult};
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::RpcError;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::wire::websocket::{WebSocketClient, WebSocketError, WebSocketMessage};
+
+/// `RpcTransport` moves [`RpcFrame`]s between two peers, letting clients
+/// and services work the same over a raw stream or a websocket.
+pub trait RpcTransport {
+    /// `send_frame` writes `frame` out in full.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`super::RpcError`] if encoding or writing fails.
+    fn send_frame(&mut self, frame: RpcFrame) -> RpcResult<()>;
+
+    /// `recv_frame` blocks for the next frame, returning `None` once the
+    /// peer closed the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`super::RpcError`] if reading or decoding fails.
+    fn recv_frame(&mut self) -> RpcResult<Option<RpcFrame>>;
+}
+
+impl<S: Read + Write> RpcTransport for Framed<S, RpcCodec> {
+    fn send_frame(&mut self, frame: RpcFrame) -> RpcResult<()> {
+        Ok(self.send(frame)?)
+    }
+
+    fn recv_frame(&mut self) -> RpcResult<Option<RpcFrame>> {
+        Ok(self.recv()?)
+    }
+}
+
+/// Frames travel as binary messages, pings are answered by the client and
+/// skipped here.
+#[cfg(not(target_arch = "wasm32"))]
+impl<S: Read + Write> RpcTransport for WebSocketClient<S> {
+    fn send_frame(&mut self, frame: RpcFrame) -> RpcResult<()> {
+        Ok(self.send_binary(frame.to_bytes()?)?)
+    }
+
+    fn recv_frame(&mut self) -> RpcResult<Option<RpcFrame>> {
+        loop {
+            match self.recv() {
+                Ok(WebSocketMessage::Binary(data)) => {
+                    return Ok(Some(RpcFrame::from_bytes(&data)?))
+                }
+                Ok(WebSocketMessage::Ping(_) | WebSocketMessage::Pong(_)) => {}
+                Ok(WebSocketMessage::Close(_)) | Err(WebSocketError::ConnectionClosed) => {
+                    return Ok(None)
+                }
+                Ok(WebSocketMessage::Text(text)) => return Err(RpcError::UnexpectedMessage(text)),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::{SimpleBody, SimpleHeader, SimpleHeaders, SimpleMethod, Status};
+
+/// `TRACEPARENT` is the W3C trace context header carrying the trace a
+/// request belongs to.
+pub const TRACEPARENT: &str = "traceparent";
+
+/// `traceparent_header` returns the [`SimpleHeader`] key `traceparent`
+/// is stored under once parsed.
+#[must_use]
+pub fn traceparent_header() -> SimpleHeader {
+    SimpleHeader::from(TRACEPARENT.to_string())
+}
+
+/// `TraceContext` is a W3C trace context: the trace a request belongs to,
+/// the span that issued it and the trace flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub flags: u8,
+}
+
+// -- Constructors
+
+impl TraceContext {
+    /// `new_root` starts a new, sampled trace.
+    #[must_use]
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: Self::random_id(),
+            span_id: Self::random_id(),
+            flags: 0x01,
+        }
+    }
+
+    /// `parse` reads a `traceparent` header value, returning `None` when
+    /// it is malformed or uses the invalid all zero ids.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let version = decode_hex::<1>(parts.next()?)?;
+        let trace_id = decode_hex::<16>(parts.next()?)?;
+        let span_id = decode_hex::<8>(parts.next()?)?;
+        let flags = decode_hex::<1>(parts.next()?)?;
+
+        // version 00 has exactly four fields, later versions may append more.
+        if version[0] == 0xFF || (version[0] == 0x00 && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == [0; 16] || span_id == [0; 8] {
+            return None;
+        }
+
+        Some(Self {
+            trace_id,
+            span_id,
+            flags: flags[0],
+        })
+    }
+
+    /// `from_headers` continues the trace of an incoming request, starting
+    /// a new one when the request carries none. The returned context is
+    /// the span handling the request.
+    #[must_use]
+    pub fn from_headers(headers: &SimpleHeaders) -> Self {
+        headers
+            .get(&traceparent_header())
+            .and_then(|value| Self::parse(value))
+            .map_or_else(Self::new_root, |parent| parent.child())
+    }
+
+    fn random_id<const N: usize>() -> [u8; N] {
+        loop {
+            let id: [u8; N] = std::array::from_fn(|_| fastrand::u8(..));
+            if id != [0; N] {
+                return id;
+            }
+        }
+    }
+}
+
+// -- Methods
+
+impl TraceContext {
+    /// `child` returns a new span within the same trace.
+    #[must_use]
+    pub fn child(&self) -> Self {
+        Self {
+            trace_id: self.trace_id,
+            span_id: Self::random_id(),
+            flags: self.flags,
+        }
+    }
+
+    #[must_use]
+    pub fn is_sampled(&self) -> bool {
+        self.flags & 0x01 == 0x01
+    }
+
+    #[must_use]
+    pub fn trace_id_hex(&self) -> String {
+        encode_hex(&self.trace_id)
+    }
+
+    #[must_use]
+    pub fn span_id_hex(&self) -> String {
+        encode_hex(&self.span_id)
+    }
+
+    /// `to_header` renders the context as a `traceparent` value.
+    #[must_use]
+    pub fn to_header(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id_hex(),
+            self.span_id_hex(),
+            self.flags
+        )
+    }
+
+    /// `apply_to_headers` sets `traceparent` on outgoing headers so the
+    /// receiver continues this trace.
+    pub fn apply_to_headers(&self, headers: &mut SimpleHeaders) {
+        headers.insert(traceparent_header(), self.to_header());
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = write!(out, "{byte:02x}");
+        out
+    })
+}
+
+fn decode_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    if value.len() != N * 2
+        || !value
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return None;
+    }
+
+    let mut out = [0; N];
+    for (index, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+/// `AccessLogEntry` describes one handled request, it is emitted as a
+/// single structured `tracing` event from this module so access logs can
+/// be filtered on their own.
+#[derive(Clone, Debug)]
+pub struct AccessLogEntry {
+    pub method: SimpleMethod,
+    pub path: String,
+    pub status: Status,
+    pub duration: Duration,
+
+    /// `bytes` is the size of the response body, when known.
+    pub bytes: Option<u64>,
+    pub peer: Option<String>,
+    pub trace: TraceContext,
+}
+
+impl AccessLogEntry {
+    /// `response_bytes` returns the size of a response body, `None` for
+    /// streamed bodies whose size is only known once sent.
+    #[must_use]
+    pub fn response_bytes(body: Option<&SimpleBody>) -> Option<u64> {
+        match body {
+            None | Some(SimpleBody::None) => Some(0),
+            Some(SimpleBody::Text(text)) => Some(text.len() as u64),
+            Some(SimpleBody::Bytes(bytes)) => Some(bytes.len() as u64),
+            Some(_) => None,
+        }
+    }
+
+    pub fn emit(&self) {
+        // the status line always starts with the numeric code.
+        let status_line = self.status.status_line();
+        let status = status_line.split(' ').next().unwrap_or_default();
+        let duration_ms = self.duration.as_secs_f64() * 1000.0;
+        let method = self.method.to_string();
+        let method = method.as_str();
+        let peer = self.peer.as_deref().unwrap_or("-");
+        let trace_id = self.trace.trace_id_hex();
+        let span_id = self.trace.span_id_hex();
+
+        if let Some(bytes) = self.bytes {
+            tracing::info!(
+                method,
+                path = self.path.as_str(),
+                status,
+                duration_ms,
+                bytes,
+                peer,
+                trace_id = trace_id.as_str(),
+                span_id = span_id.as_str(),
+                "{method} {} {status}",
+                self.path
+            );
+        } else {
+            tracing::info!(
+                method,
+                path = self.path.as_str(),
+                status,
+                duration_ms,
+                peer,
+                trace_id = trace_id.as_str(),
+                span_id = span_id.as_str(),
+                "{method} {} {status}",
+                self.path
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_trace_context {
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[test]
+    fn parses_and_renders_traceparent() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let context = TraceContext::parse(header).expect("should parse");
+        assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert!(context.is_sampled());
+        assert_eq!(context.to_header(), header);
+
+        assert!(
+            TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01")
+                .is_none()
+        );
+        assert!(
+            TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none()
+        );
+    }
+
+    #[test]
+    fn continues_incoming_trace() {
+        let mut headers = SimpleHeaders::new();
+        headers.insert(
+            SimpleHeader::from("TraceParent".to_string()),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00".into(),
+        );
+
+        let context = TraceContext::from_headers(&headers);
+        assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_ne!(context.span_id_hex(), "00f067aa0ba902b7");
+        assert!(!context.is_sampled());
+
+        let root = TraceContext::from_headers(&SimpleHeaders::new());
+        assert_ne!(root.trace_id, [0; 16]);
+        assert!(root.is_sampled());
+    }
+
+    #[test]
+    #[traced_test]
+    fn emits_one_structured_event_per_request() {
+        let trace = TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+            .expect("should parse");
+
+        AccessLogEntry {
+            method: SimpleMethod::POST,
+            path: "/v1/items".into(),
+            status: Status::Created,
+            duration: Duration::from_millis(12),
+            bytes: AccessLogEntry::response_bytes(Some(&SimpleBody::Text("done".into()))),
+            peer: Some("127.0.0.1:40000".into()),
+            trace,
+        }
+        .emit();
+
+        assert!(logs_contain("POST /v1/items 201"));
+        assert!(logs_contain("bytes=4"));
+        assert!(logs_contain("peer=\"127.0.0.1:40000\""));
+        assert!(logs_contain(
+            "trace_id=\"4bf92f3577b34da6a3ce929d0e0e4736\""
+        ));
+    }
+}
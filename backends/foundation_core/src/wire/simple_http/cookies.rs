@@ -0,0 +1,607 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{header_values, SimpleHeader, SimpleHeaders};
+
+/// `Cookie` is a cookie as stored by a [`CookieJar`], after the
+/// `Set-Cookie` attributes were resolved against the url that sent it
+/// (RFC 6265 section 5.3).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+
+    /// `domain` is the host the cookie was set by, or the domain given by
+    /// its `Domain` attribute, always lowercase and without a leading dot.
+    pub domain: String,
+
+    /// `host_only` is true when no `Domain` attribute was given, in which
+    /// case the cookie is only sent back to the exact same host.
+    pub host_only: bool,
+    pub path: String,
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<String>,
+}
+
+impl Cookie {
+    /// `new` creates a host only cookie for `domain` valid on every path,
+    /// mostly useful for injecting fixed cookies into a store.
+    pub fn new<N, V, D>(name: N, value: V, domain: D) -> Self
+    where
+        N: Into<String>,
+        V: Into<String>,
+        D: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            domain: domain.into().to_ascii_lowercase(),
+            host_only: true,
+            path: String::from("/"),
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// `parse` parses a `Set-Cookie` header value received from `url`,
+    /// returning `None` if the cookie is malformed or must be ignored,
+    /// e.g when its `Domain` does not cover the url's host or is a single
+    /// label like `com`.
+    #[must_use]
+    pub fn parse(set_cookie: &str, url: &url::Url, now: SystemTime) -> Option<Self> {
+        Self::parse_with_suffixes(set_cookie, url, now, &SingleLabelSuffixes)
+    }
+
+    /// `parse_with_suffixes` is [`Cookie::parse`] checking the `Domain`
+    /// attribute against `suffixes`, a cookie for a public suffix is only
+    /// kept as a host only cookie when the url's host is that suffix
+    /// (RFC 6265 section 5.3 step 5).
+    #[must_use]
+    pub fn parse_with_suffixes(
+        set_cookie: &str,
+        url: &url::Url,
+        now: SystemTime,
+        suffixes: &dyn PublicSuffixList,
+    ) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut attributes = set_cookie.split(';');
+        let (name, value) = attributes.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self::new(name, value.trim(), host.as_str());
+        cookie.path = default_path(url.path());
+
+        let mut max_age = None;
+        for attribute in attributes {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+
+            if key.eq_ignore_ascii_case("expires") {
+                if let Some(expires) = parse_cookie_date(value) {
+                    cookie.expires = Some(expires);
+                }
+            } else if key.eq_ignore_ascii_case("max-age") {
+                if let Ok(seconds) = value.parse::<i64>() {
+                    max_age = Some(seconds);
+                }
+            } else if key.eq_ignore_ascii_case("domain") {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                if !domain.is_empty() {
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+            } else if key.eq_ignore_ascii_case("path") {
+                if value.starts_with('/') {
+                    cookie.path = value.to_string();
+                }
+            } else if key.eq_ignore_ascii_case("secure") {
+                cookie.secure = true;
+            } else if key.eq_ignore_ascii_case("httponly") {
+                cookie.http_only = true;
+            } else if key.eq_ignore_ascii_case("samesite") {
+                cookie.same_site = Some(value.to_string());
+            }
+        }
+
+        // Max-Age takes precedence over Expires, a zero or negative value
+        // expires the cookie right away.
+        if let Some(seconds) = max_age {
+            cookie.expires = Some(match u64::try_from(seconds) {
+                Ok(seconds) if seconds > 0 => now + Duration::from_secs(seconds),
+                _ => UNIX_EPOCH,
+            });
+        }
+
+        if !cookie.host_only && suffixes.is_public_suffix(&cookie.domain) {
+            if cookie.domain != host {
+                return None;
+            }
+            cookie.host_only = true;
+        }
+
+        if !cookie.host_only && !domain_matches(&host, &cookie.domain) {
+            return None;
+        }
+        Some(cookie)
+    }
+
+    #[must_use]
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// `matches` returns true if the cookie should be sent with a request
+    /// to `url`.
+    #[must_use]
+    pub fn matches(&self, url: &url::Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+
+        let host_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+
+        host_matches
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https" || url.scheme() == "wss")
+    }
+
+    fn same_identity(&self, other: &Self) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+}
+
+/// `domain_matches` implements the domain matching of RFC 6265 section
+/// 5.1.3, ip addresses only ever match themselves.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    let is_ip = host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[');
+    !is_ip
+        && host.len() > domain.len()
+        && host.ends_with(domain)
+        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+}
+
+/// `path_matches` implements the path matching of RFC 6265 section 5.1.4.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    if request_path == cookie_path {
+        return true;
+    }
+    request_path.starts_with(cookie_path)
+        && (cookie_path.ends_with('/')
+            || request_path.as_bytes().get(cookie_path.len()) == Some(&b'/'))
+}
+
+/// `default_path` computes the path a cookie applies to when it has no
+/// `Path` attribute, which is the request path up to its last `/`.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => String::from("/"),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+/// `parse_cookie_date` parses the dates found in the `Expires` attribute
+/// following the lenient algorithm of RFC 6265 section 5.1.1, so both
+/// `Wed, 21 Oct 2015 07:28:00 GMT` and `Wed, 21-Oct-15 07:28:00 GMT` work.
+#[must_use]
+pub fn parse_cookie_date(value: &str) -> Option<SystemTime> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    let tokens = value
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|token| !token.is_empty());
+    for token in tokens {
+        if time.is_none() && token.contains(':') {
+            let parts: Vec<u64> = token
+                .split(':')
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .ok()?;
+            if let [hours, minutes, seconds] = parts[..] {
+                time = Some((hours, minutes, seconds));
+                continue;
+            }
+        }
+
+        let lower = token.to_ascii_lowercase();
+        if month.is_none() {
+            if let Some(index) = MONTHS.iter().position(|name| lower.starts_with(name)) {
+                month = Some(index as u64 + 1);
+                continue;
+            }
+        }
+
+        if let Ok(number) = token.parse::<u64>() {
+            if day.is_none() && token.len() <= 2 {
+                day = Some(number);
+            } else if year.is_none() && (token.len() == 2 || token.len() == 4) {
+                year = Some(number);
+            }
+        }
+    }
+
+    let (hours, minutes, seconds) = time?;
+    let (day, month) = (day?, month?);
+    let year = match year? {
+        year @ 70..=99 => year + 1900,
+        year @ 0..=69 => year + 2000,
+        year => year,
+    };
+
+    if !(1..=31).contains(&day) || year < 1970 || hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let days = days_since_epoch(year, month, day);
+    let seconds = days * 86_400 + hours * 3_600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// `days_since_epoch` returns the days between 1970-01-01 and the given
+/// date of the proleptic gregorian calendar, valid from 1970 onwards.
+fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// -- Public suffixes
+
+/// `PublicSuffixList` decides which domains are public suffixes, i.e
+/// domains like `com` or `co.uk` under which anyone can register names
+/// and that must never be given cookies.
+///
+/// Plain closures taking the domain implement it, so a list like the
+/// one from publicsuffix.org can be plugged in without a wrapper type.
+pub trait PublicSuffixList: Send + Sync {
+    /// `is_public_suffix` returns true if cookies must not be set for
+    /// `domain`, which is lowercase and without a leading dot.
+    fn is_public_suffix(&self, domain: &str) -> bool;
+}
+
+impl<F> PublicSuffixList for F
+where
+    F: Fn(&str) -> bool + Send + Sync,
+{
+    fn is_public_suffix(&self, domain: &str) -> bool {
+        self(domain)
+    }
+}
+
+/// `SingleLabelSuffixes` treats every single label domain (`com`,
+/// `local`) as a public suffix, it is the default when no list is given
+/// and still lets multi label suffixes like `co.uk` through.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SingleLabelSuffixes;
+
+impl PublicSuffixList for SingleLabelSuffixes {
+    fn is_public_suffix(&self, domain: &str) -> bool {
+        !domain.contains('.')
+    }
+}
+
+// -- Stores
+
+/// `CookieStore` is where a [`CookieJar`] keeps its cookies, implement it
+/// to persist cookies or to inject fixed cookies in tests.
+pub trait CookieStore: Send + Sync {
+    /// `insert` stores a cookie, replacing one with the same name,
+    /// domain and path.
+    fn insert(&self, cookie: Cookie);
+
+    /// `remove` deletes the cookie with the same name, domain and path.
+    fn remove(&self, cookie: &Cookie);
+
+    /// `cookies` returns all stored cookies, expired ones included.
+    fn cookies(&self) -> Vec<Cookie>;
+}
+
+/// `MemoryCookieStore` keeps cookies in memory for the lifetime of the
+/// process.
+#[derive(Default)]
+pub struct MemoryCookieStore {
+    cookies: Mutex<Vec<Cookie>>,
+}
+
+impl MemoryCookieStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_cookies(cookies: Vec<Cookie>) -> Self {
+        Self {
+            cookies: Mutex::new(cookies),
+        }
+    }
+}
+
+impl CookieStore for MemoryCookieStore {
+    fn insert(&self, cookie: Cookie) {
+        let mut cookies = self
+            .cookies
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        cookies.retain(|existing| !existing.same_identity(&cookie));
+        cookies.push(cookie);
+    }
+
+    fn remove(&self, cookie: &Cookie) {
+        self.cookies
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .retain(|existing| !existing.same_identity(cookie));
+    }
+
+    fn cookies(&self) -> Vec<Cookie> {
+        self.cookies
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+// -- Jar
+
+/// `CookieJar` records cookies from responses and produces the `Cookie`
+/// header for requests, applying the domain, path, secure and expiry
+/// rules of RFC 6265.
+///
+/// Clones share the same store, so a jar can be handed to every request
+/// made as part of one session, redirects included.
+#[derive(Clone)]
+pub struct CookieJar {
+    store: Arc<dyn CookieStore>,
+    suffixes: Arc<dyn PublicSuffixList>,
+    suffixes_type: &'static str,
+}
+
+impl core::fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CookieJar")
+            .field("cookies", &self.store.cookies().len())
+            .field("suffixes", &self.suffixes_type)
+            .finish()
+    }
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        Self::new(MemoryCookieStore::new())
+    }
+}
+
+impl CookieJar {
+    pub fn new<S: CookieStore + 'static>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            suffixes: Arc::new(SingleLabelSuffixes),
+            suffixes_type: std::any::type_name::<SingleLabelSuffixes>(),
+        }
+    }
+
+    /// `with_public_suffixes` sets the list `Domain` attributes are
+    /// checked against, replacing the default [`SingleLabelSuffixes`].
+    #[must_use]
+    pub fn with_public_suffixes<P: PublicSuffixList + 'static>(mut self, suffixes: P) -> Self {
+        self.suffixes = Arc::new(suffixes);
+        self.suffixes_type = std::any::type_name::<P>();
+        self
+    }
+
+    /// `store_set_cookie` records a single `Set-Cookie` header value
+    /// received from `url`, cookies that are already expired remove any
+    /// stored cookie they replace.
+    pub fn store_set_cookie(&self, url: &url::Url, set_cookie: &str) {
+        let now = SystemTime::now();
+        let Some(cookie) = Cookie::parse_with_suffixes(set_cookie, url, now, &*self.suffixes)
+        else {
+            tracing::debug!("Ignoring invalid cookie from {url}: {set_cookie}");
+            return;
+        };
+
+        if cookie.is_expired(now) {
+            self.store.remove(&cookie);
+            return;
+        }
+        self.store.insert(cookie);
+    }
+
+    /// `store_response_headers` records every `Set-Cookie` header of a
+    /// response received from `url`.
+    pub fn store_response_headers(&self, url: &url::Url, headers: &SimpleHeaders) {
+        for set_cookie in header_values(headers, &SimpleHeader::SET_COOKIE) {
+            self.store_set_cookie(url, set_cookie);
+        }
+    }
+
+    /// `cookies_for` returns the live cookies to send to `url`, longest
+    /// paths first as recommended by RFC 6265 section 5.4.
+    #[must_use]
+    pub fn cookies_for(&self, url: &url::Url) -> Vec<Cookie> {
+        let now = SystemTime::now();
+        let mut cookies: Vec<Cookie> = self
+            .store
+            .cookies()
+            .into_iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.matches(url))
+            .collect();
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        cookies
+    }
+
+    /// `cookie_header` returns the `Cookie` header value for a request to
+    /// `url`, if any cookie applies.
+    #[must_use]
+    pub fn cookie_header(&self, url: &url::Url) -> Option<String> {
+        let cookies = self.cookies_for(url);
+        if cookies.is_empty() {
+            return None;
+        }
+        Some(
+            cookies
+                .iter()
+                .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    /// `apply_to_headers` sets the `Cookie` header of a request to `url`,
+    /// leaving the headers untouched when no cookie applies.
+    pub fn apply_to_headers(&self, url: &url::Url, headers: &mut SimpleHeaders) {
+        if let Some(value) = self.cookie_header(url) {
+            headers.insert(SimpleHeader::COOKIE, value);
+        }
+    }
+
+    /// `clear_expired` drops the expired cookies from the store.
+    pub fn clear_expired(&self) {
+        let now = SystemTime::now();
+        for cookie in self.store.cookies() {
+            if cookie.is_expired(now) {
+                self.store.remove(&cookie);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cookie_jar {
+    use super::*;
+    use crate::wire::simple_http::append_header_value;
+
+    fn url(value: &str) -> url::Url {
+        url::Url::parse(value).expect("should be valid url")
+    }
+
+    #[test]
+    fn cookie_dates_are_parsed() {
+        let expected = UNIX_EPOCH + Duration::from_secs(1_445_412_480);
+        assert_eq!(
+            parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_cookie_date("Wed, 21-Oct-15 07:28:00 GMT"),
+            Some(expected)
+        );
+        assert_eq!(parse_cookie_date("not a date"), None);
+    }
+
+    #[test]
+    fn cookies_follow_domain_and_path_rules() {
+        let jar = CookieJar::default();
+        let origin = url("https://api.example.com/auth/login");
+
+        jar.store_set_cookie(&origin, "session=abc; Path=/; Domain=example.com; Secure");
+        jar.store_set_cookie(&origin, "csrf=xyz");
+        jar.store_set_cookie(&origin, "stolen=1; Domain=other.com");
+
+        assert_eq!(
+            jar.cookie_header(&url("https://api.example.com/auth/refresh"))
+                .as_deref(),
+            Some("csrf=xyz; session=abc")
+        );
+        assert_eq!(
+            jar.cookie_header(&url("https://www.example.com/"))
+                .as_deref(),
+            Some("session=abc")
+        );
+        assert_eq!(jar.cookie_header(&url("http://www.example.com/")), None);
+        assert_eq!(jar.cookie_header(&url("https://other.com/")), None);
+    }
+
+    #[test]
+    fn cookies_for_public_suffixes_are_ignored() {
+        let jar = CookieJar::default();
+        let origin = url("https://api.example.com/");
+        jar.store_set_cookie(&origin, "tld=1; Domain=com");
+        jar.store_set_cookie(&origin, "site=1; Domain=example.com");
+        assert_eq!(jar.cookie_header(&url("https://other.com/")), None);
+        assert_eq!(jar.cookie_header(&origin).as_deref(), Some("site=1"));
+
+        let local = url("http://localhost/");
+        jar.store_set_cookie(&local, "dev=1; Domain=localhost");
+        let cookie = jar.cookies_for(&local).pop().expect("should keep cookie");
+        assert!(cookie.host_only);
+
+        let jar = CookieJar::default()
+            .with_public_suffixes(|domain: &str| domain == "co.uk" || !domain.contains('.'));
+        let origin = url("https://shop.example.co.uk/");
+        jar.store_set_cookie(&origin, "suffix=1; Domain=co.uk");
+        jar.store_set_cookie(&origin, "site=1; Domain=example.co.uk");
+        assert_eq!(
+            jar.cookie_header(&url("https://www.example.co.uk/"))
+                .as_deref(),
+            Some("site=1")
+        );
+        assert_eq!(jar.cookie_header(&url("https://other.co.uk/")), None);
+    }
+
+    #[test]
+    fn every_set_cookie_header_is_stored() {
+        let jar = CookieJar::default();
+        let origin = url("https://example.com/");
+
+        let mut headers = SimpleHeaders::new();
+        append_header_value(
+            &mut headers,
+            SimpleHeader::SET_COOKIE,
+            "first=1; Expires=Wed, 21 Oct 2099 07:28:00 GMT".into(),
+        );
+        append_header_value(&mut headers, SimpleHeader::SET_COOKIE, "second=2".into());
+        jar.store_response_headers(&origin, &headers);
+
+        assert_eq!(
+            jar.cookie_header(&origin).as_deref(),
+            Some("first=1; second=2")
+        );
+        assert!(format!("{jar:?}").contains("SingleLabelSuffixes"));
+    }
+
+    #[test]
+    fn expired_cookies_are_removed() {
+        let store =
+            MemoryCookieStore::with_cookies(vec![Cookie::new("token", "fixed", "localhost")]);
+        let jar = CookieJar::new(store);
+        let origin = url("http://localhost:8080/");
+
+        assert_eq!(jar.cookie_header(&origin).as_deref(), Some("token=fixed"));
+
+        jar.store_set_cookie(&origin, "token=; Max-Age=0");
+        assert_eq!(jar.cookie_header(&origin), None);
+
+        jar.store_set_cookie(&origin, "old=1; Expires=Thu, 01 Jan 1970 00:00:01 GMT");
+        assert_eq!(jar.cookie_header(&origin), None);
+    }
+}
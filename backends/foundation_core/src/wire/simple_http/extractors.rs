@@ -0,0 +1,368 @@
+use derive_more::From;
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    SimpleBody, SimpleHeader, SimpleHeaders, SimpleIncomingRequest, SimpleOutgoingResponse, Status,
+    FORM_URLENCODED_CONTENT_TYPE,
+};
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// `DEFAULT_BODY_LIMIT` is the largest body the typed extractors accept
+/// unless a handler asks for another limit.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+pub type ExtractResult<T> = std::result::Result<T, ExtractError>;
+
+#[derive(From, Debug)]
+pub enum ExtractError {
+    MissingBody,
+
+    /// `StreamedBody` is returned for bodies that were not read into memory
+    /// yet, extractors never consume a stream.
+    #[from(ignore)]
+    StreamedBody,
+
+    #[from(ignore)]
+    UnsupportedMediaType(String),
+
+    #[from(ignore)]
+    PayloadTooLarge {
+        size: usize,
+        limit: usize,
+    },
+
+    Json(serde_json::Error),
+    Form(serde_urlencoded::de::Error),
+    FormEncoding(serde_urlencoded::ser::Error),
+}
+
+impl ExtractError {
+    /// `status` is the response status a handler should answer with when
+    /// extraction failed.
+    pub fn status(&self) -> Status {
+        match self {
+            Self::MissingBody | Self::StreamedBody => Status::BadRequest,
+            Self::UnsupportedMediaType(_) => Status::UnsupportedMediaType,
+            Self::PayloadTooLarge { .. } => Status::PayloadTooLarge,
+            Self::Json(_) | Self::Form(_) => Status::UnprocessableEntity,
+            Self::FormEncoding(_) => Status::InternalServerError,
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl core::fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `media_type_essence` returns the lowercased `type/subtype` of a
+/// `Content-Type` or `Accept` entry, without its parameters.
+pub fn media_type_essence(value: &str) -> String {
+    value
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+fn is_json_media_type(essence: &str) -> bool {
+    essence == JSON_CONTENT_TYPE
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// `negotiate` picks the entry of `offered` the `accept` header prefers,
+/// honouring `q` weights and `*/*` or `type/*` wildcards. Ties go to the
+/// earlier entry of `offered`, and a missing header accepts the first.
+pub fn negotiate<'a>(accept: Option<&str>, offered: &[&'a str]) -> Option<&'a str> {
+    let Some(accept) = accept else {
+        return offered.first().copied();
+    };
+
+    let ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .map(|entry| {
+            let quality = entry
+                .split(';')
+                .skip(1)
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|value| value.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (media_type_essence(entry), quality)
+        })
+        .filter(|(essence, _)| !essence.is_empty())
+        .collect();
+
+    let quality_of = |offer: &str| -> f32 {
+        let offer = media_type_essence(offer);
+        let (offer_type, _) = offer.split_once('/').unwrap_or((offer.as_str(), ""));
+
+        // the most specific matching range decides the quality.
+        ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                let specificity = if *range == offer {
+                    3
+                } else if range.strip_suffix("/*") == Some(offer_type) {
+                    2
+                } else if range == "*/*" {
+                    1
+                } else {
+                    return None;
+                };
+                Some((specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, quality)| quality)
+    };
+
+    let mut best: Option<(&'a str, f32)> = None;
+    for offer in offered {
+        let quality = quality_of(offer);
+        if quality > 0.0 && best.map_or(true, |(_, best_quality)| quality > best_quality) {
+            best = Some((offer, quality));
+        }
+    }
+    best.map(|(offer, _)| offer)
+}
+
+/// `RequestBodyExt` reads typed values out of a request body that was
+/// already read into memory, checking its `Content-Type` and size first.
+pub trait RequestBodyExt {
+    fn headers(&self) -> &SimpleHeaders;
+    fn body(&self) -> Option<&SimpleBody>;
+
+    /// `content_type` is the essence of the `Content-Type` header, e.g
+    /// `application/json`.
+    fn content_type(&self) -> Option<String> {
+        self.headers()
+            .get(&SimpleHeader::CONTENT_TYPE)
+            .map(|value| media_type_essence(value))
+    }
+
+    /// `body_bytes` returns the body if it holds at most `limit` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExtractError::MissingBody`] or
+    /// [`ExtractError::StreamedBody`] when no buffered body is present and
+    /// [`ExtractError::PayloadTooLarge`] when it is over `limit`.
+    fn body_bytes(&self, limit: usize) -> ExtractResult<&[u8]> {
+        let bytes = match self.body() {
+            Some(SimpleBody::Bytes(bytes)) => bytes.as_slice(),
+            Some(SimpleBody::Text(text)) => text.as_bytes(),
+            Some(
+                SimpleBody::Stream(_)
+                | SimpleBody::ChunkedStream(_)
+                | SimpleBody::LimitedChunkedStream(_),
+            ) => return Err(ExtractError::StreamedBody),
+            Some(SimpleBody::None) | None => return Err(ExtractError::MissingBody),
+        };
+
+        if bytes.len() > limit {
+            return Err(ExtractError::PayloadTooLarge {
+                size: bytes.len(),
+                limit,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// `json` deserializes a JSON body of at most [`DEFAULT_BODY_LIMIT`]
+    /// bytes, a request without `Content-Type` is assumed to be JSON.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::json_with_limit`].
+    fn json<T: DeserializeOwned>(&self) -> ExtractResult<T> {
+        self.json_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// # Errors
+    ///
+    /// Returns [`ExtractError::UnsupportedMediaType`] for bodies that are
+    /// not JSON, [`ExtractError::Json`] if it does not deserialize into `T`
+    /// and the errors of [`Self::body_bytes`].
+    fn json_with_limit<T: DeserializeOwned>(&self, limit: usize) -> ExtractResult<T> {
+        if let Some(content_type) = self.content_type() {
+            if !is_json_media_type(&content_type) {
+                return Err(ExtractError::UnsupportedMediaType(content_type));
+            }
+        }
+        Ok(serde_json::from_slice(self.body_bytes(limit)?)?)
+    }
+
+    /// `form` deserializes an url encoded form body of at most
+    /// [`DEFAULT_BODY_LIMIT`] bytes.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::form_with_limit`].
+    fn form<T: DeserializeOwned>(&self) -> ExtractResult<T> {
+        self.form_with_limit(DEFAULT_BODY_LIMIT)
+    }
+
+    /// # Errors
+    ///
+    /// Returns [`ExtractError::UnsupportedMediaType`] unless the body is
+    /// `application/x-www-form-urlencoded`, [`ExtractError::Form`] if it
+    /// does not deserialize into `T` and the errors of
+    /// [`Self::body_bytes`].
+    fn form_with_limit<T: DeserializeOwned>(&self, limit: usize) -> ExtractResult<T> {
+        let content_type = self.content_type().unwrap_or_default();
+        if content_type != FORM_URLENCODED_CONTENT_TYPE {
+            return Err(ExtractError::UnsupportedMediaType(content_type));
+        }
+        Ok(serde_urlencoded::from_bytes(self.body_bytes(limit)?)?)
+    }
+
+    /// `accepts` is true when the `Accept` header allows `media_type`.
+    fn accepts(&self, media_type: &str) -> bool {
+        self.negotiate(&[media_type]).is_some()
+    }
+
+    /// `negotiate` picks the media type to answer with out of `offered`,
+    /// see [`negotiate`].
+    fn negotiate<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        negotiate(
+            self.headers()
+                .get(&SimpleHeader::ACCEPT)
+                .map(String::as_str),
+            offered,
+        )
+    }
+}
+
+impl RequestBodyExt for SimpleIncomingRequest {
+    fn headers(&self) -> &SimpleHeaders {
+        &self.headers
+    }
+
+    fn body(&self) -> Option<&SimpleBody> {
+        self.body.as_ref()
+    }
+}
+
+/// `ResponseBodyExt` sets typed bodies on a response along with their
+/// `Content-Type` and `Content-Length`.
+pub trait ResponseBodyExt {
+    /// `set_body` replaces the body with `bytes` of `content_type`.
+    fn set_body(&mut self, content_type: &str, bytes: Vec<u8>);
+
+    /// # Errors
+    ///
+    /// Returns [`ExtractError::Json`] if `value` cannot be serialized.
+    fn set_json<T: Serialize + ?Sized>(&mut self, value: &T) -> ExtractResult<()> {
+        self.set_body(JSON_CONTENT_TYPE, serde_json::to_vec(value)?);
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Returns [`ExtractError::FormEncoding`] if `value` cannot be
+    /// serialized as a form.
+    fn set_form<T: Serialize + ?Sized>(&mut self, value: &T) -> ExtractResult<()> {
+        self.set_body(
+            FORM_URLENCODED_CONTENT_TYPE,
+            serde_urlencoded::to_string(value)?.into_bytes(),
+        );
+        Ok(())
+    }
+}
+
+impl ResponseBodyExt for SimpleOutgoingResponse {
+    fn set_body(&mut self, content_type: &str, bytes: Vec<u8>) {
+        self.headers
+            .insert(SimpleHeader::CONTENT_TYPE, content_type.to_string());
+        self.headers
+            .insert(SimpleHeader::CONTENT_LENGTH, bytes.len().to_string());
+        self.body = Some(SimpleBody::Bytes(bytes));
+    }
+}
+
+#[cfg(test)]
+mod test_extractors {
+    use serde::Deserialize;
+
+    use super::super::SimpleUrl;
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Signup {
+        name: String,
+        age: u32,
+    }
+
+    fn request(content_type: Option<&str>, body: &str) -> SimpleIncomingRequest {
+        let mut builder = SimpleIncomingRequest::builder()
+            .with_url(SimpleUrl::url_only("/signup"))
+            .with_body_string(body);
+        if let Some(content_type) = content_type {
+            builder = builder.add_header(SimpleHeader::CONTENT_TYPE, content_type);
+        }
+        builder.build().expect("should build request")
+    }
+
+    #[test]
+    fn extracts_typed_bodies() {
+        let expected = Signup {
+            name: "alex".into(),
+            age: 30,
+        };
+
+        let json = request(
+            Some("application/json; charset=utf-8"),
+            r#"{"name":"alex","age":30}"#,
+        );
+        assert_eq!(json.json::<Signup>().expect("should parse"), expected);
+        assert!(matches!(
+            json.form::<Signup>(),
+            Err(ExtractError::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            json.json_with_limit::<Signup>(4),
+            Err(ExtractError::PayloadTooLarge { size: 24, limit: 4 })
+        ));
+
+        let form = request(Some(FORM_URLENCODED_CONTENT_TYPE), "name=alex&age=30");
+        assert_eq!(form.form::<Signup>().expect("should parse"), expected);
+
+        let invalid = request(None, r#"{"name":"alex"}"#);
+        let err = invalid.json::<Signup>().expect_err("should be missing age");
+        assert!(matches!(err.status(), Status::UnprocessableEntity));
+
+        let mut response = SimpleOutgoingResponse::empty();
+        response.set_json(&expected).expect("should serialize");
+        assert_eq!(
+            response.headers.get(&SimpleHeader::CONTENT_TYPE),
+            Some(&JSON_CONTENT_TYPE.to_string())
+        );
+        assert_eq!(
+            response.headers.get(&SimpleHeader::CONTENT_LENGTH),
+            Some(&"24".to_string())
+        );
+    }
+
+    #[test]
+    fn negotiates_accept_headers() {
+        let offered = [JSON_CONTENT_TYPE, "text/html"];
+        assert_eq!(negotiate(None, &offered), Some(JSON_CONTENT_TYPE));
+        assert_eq!(
+            negotiate(Some("text/html, application/json;q=0.9"), &offered),
+            Some("text/html")
+        );
+        assert_eq!(
+            negotiate(Some("text/*;q=0.5, */*;q=0.1"), &offered),
+            Some("text/html")
+        );
+        assert_eq!(
+            negotiate(Some("*/*, application/json;q=0"), &offered),
+            Some("text/html")
+        );
+        assert_eq!(negotiate(Some("image/png"), &offered), None);
+    }
+}
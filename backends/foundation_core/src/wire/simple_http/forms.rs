@@ -0,0 +1,633 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::io::{self, Read};
+use std::string::FromUtf8Error;
+use std::sync::{Arc, Mutex};
+
+use derive_more::From;
+
+use crate::extensions::result_ext::BoxedError;
+use crate::valtron::ClonableSendVecIterator;
+
+use super::{SimpleBody, SimpleHeader, SimpleHeaders};
+
+pub const FORM_URLENCODED_CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+pub const MULTIPART_FORM_DATA_CONTENT_TYPE: &str = "multipart/form-data";
+
+/// `MULTIPART_CHUNK_SIZE` is the most bytes a streamed file part reads
+/// from its source per chunk.
+const MULTIPART_CHUNK_SIZE: usize = 8 * 1024;
+
+pub type FormResult<T> = std::result::Result<T, FormError>;
+
+#[derive(From, Debug)]
+pub enum FormError {
+    #[from(ignore)]
+    MissingBoundary,
+
+    #[from(ignore)]
+    MissingDelimiter,
+
+    #[from(ignore)]
+    MissingHeaders,
+
+    #[from(ignore)]
+    MissingContentDisposition,
+
+    #[from(ignore)]
+    InvalidHeader(String),
+
+    #[from(ignore)]
+    UnexpectedEnd,
+
+    Utf8(FromUtf8Error),
+}
+
+impl std::error::Error for FormError {}
+
+impl core::fmt::Display for FormError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+// -- Url encoded forms
+
+/// `FormUrlEncoded` holds the ordered fields of an
+/// `application/x-www-form-urlencoded` body, it is used both to build
+/// request bodies and to read them on the server side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormUrlEncoded {
+    fields: Vec<(String, String)>,
+}
+
+impl FormUrlEncoded {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `parse` decodes an url encoded body, keys may repeat and their
+    /// order is kept.
+    #[must_use]
+    pub fn parse(body: &[u8]) -> Self {
+        let fields = url::form_urlencoded::parse(body)
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        Self { fields }
+    }
+
+    #[must_use]
+    pub fn with_field<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.add_field(key, value);
+        self
+    }
+
+    pub fn add_field<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.fields.push((key.into(), value.into()));
+    }
+
+    #[must_use]
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+
+    /// `get` returns the first value for `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `get_all` returns every value for `key` in the order they appeared.
+    #[must_use]
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    #[must_use]
+    pub fn encode(&self) -> String {
+        let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+        for (key, value) in &self.fields {
+            serializer.append_pair(key, value);
+        }
+        serializer.finish()
+    }
+
+    /// `headers` returns the content type and length headers matching
+    /// the encoded body.
+    #[must_use]
+    pub fn headers(&self) -> SimpleHeaders {
+        let mut headers = SimpleHeaders::new();
+        headers.insert(
+            SimpleHeader::CONTENT_TYPE,
+            FORM_URLENCODED_CONTENT_TYPE.into(),
+        );
+        headers.insert(
+            SimpleHeader::CONTENT_LENGTH,
+            self.encode().len().to_string(),
+        );
+        headers
+    }
+
+    #[must_use]
+    pub fn into_body(self) -> SimpleBody {
+        SimpleBody::Text(self.encode())
+    }
+}
+
+// -- Multipart forms
+
+/// `MultipartForm` builds a `multipart/form-data` body.
+///
+/// File parts added with [`MultipartForm::with_file`] are read from their
+/// source in chunks as the body is written, so uploads never hold the whole
+/// file in memory.
+pub struct MultipartForm {
+    boundary: String,
+    parts: VecDeque<OutgoingPart>,
+}
+
+struct OutgoingPart {
+    name: String,
+    filename: Option<String>,
+    content_type: Option<String>,
+    source: PartSource,
+}
+
+enum PartSource {
+    Bytes(Vec<u8>),
+    Reader(Box<dyn Read + Send>),
+}
+
+impl core::fmt::Debug for MultipartForm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartForm")
+            .field("boundary", &self.boundary)
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
+
+impl Default for MultipartForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MultipartForm {
+    /// `new` creates an empty form with a random boundary.
+    #[must_use]
+    pub fn new() -> Self {
+        let token: String = std::iter::repeat_with(fastrand::alphanumeric)
+            .take(24)
+            .collect();
+        Self::with_boundary(format!("----ewe-form-{token}"))
+    }
+
+    pub fn with_boundary<S: Into<String>>(boundary: S) -> Self {
+        Self {
+            boundary: boundary.into(),
+            parts: VecDeque::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// `content_type` returns the `Content-Type` header value announcing
+    /// the form's boundary.
+    #[must_use]
+    pub fn content_type(&self) -> String {
+        format!(
+            "{MULTIPART_FORM_DATA_CONTENT_TYPE}; boundary={}",
+            self.boundary
+        )
+    }
+
+    #[must_use]
+    pub fn with_text<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.parts.push_back(OutgoingPart {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            source: PartSource::Bytes(value.into().into_bytes()),
+        });
+        self
+    }
+
+    /// `with_bytes` adds a file part whose content is already in memory.
+    #[must_use]
+    pub fn with_bytes<K, F, C, B>(mut self, name: K, filename: F, content_type: C, data: B) -> Self
+    where
+        K: Into<String>,
+        F: Into<String>,
+        C: Into<String>,
+        B: Into<Vec<u8>>,
+    {
+        self.parts.push_back(OutgoingPart {
+            name: name.into(),
+            filename: Some(filename.into()),
+            content_type: Some(content_type.into()),
+            source: PartSource::Bytes(data.into()),
+        });
+        self
+    }
+
+    /// `with_file` adds a file part streamed from `reader` until it
+    /// reports end of file.
+    #[must_use]
+    pub fn with_file<K, F, C, R>(mut self, name: K, filename: F, content_type: C, reader: R) -> Self
+    where
+        K: Into<String>,
+        F: Into<String>,
+        C: Into<String>,
+        R: Read + Send + 'static,
+    {
+        self.parts.push_back(OutgoingPart {
+            name: name.into(),
+            filename: Some(filename.into()),
+            content_type: Some(content_type.into()),
+            source: PartSource::Reader(Box::new(reader)),
+        });
+        self
+    }
+
+    /// `headers` returns the content type and transfer encoding headers
+    /// for sending the form as a chunked stream.
+    #[must_use]
+    pub fn headers(&self) -> SimpleHeaders {
+        let mut headers = SimpleHeaders::new();
+        headers.insert(SimpleHeader::CONTENT_TYPE, self.content_type());
+        headers.insert(SimpleHeader::TRANSFER_ENCODING, "chunked".into());
+        headers
+    }
+
+    /// `into_stream` returns an iterator producing the encoded body in
+    /// chunks, clones of the iterator share its progress since the file
+    /// sources can not be rewound.
+    #[must_use]
+    pub fn into_stream(self) -> ClonableSendVecIterator<BoxedError> {
+        Box::new(MultipartStream {
+            state: Arc::new(Mutex::new(MultipartStreamState {
+                boundary: self.boundary,
+                parts: self.parts,
+                current: None,
+                finished: false,
+            })),
+        })
+    }
+
+    #[must_use]
+    pub fn into_body(self) -> SimpleBody {
+        SimpleBody::Stream(Some(self.into_stream()))
+    }
+}
+
+#[derive(Clone)]
+struct MultipartStream {
+    state: Arc<Mutex<MultipartStreamState>>,
+}
+
+struct MultipartStreamState {
+    boundary: String,
+    parts: VecDeque<OutgoingPart>,
+    current: Option<Box<dyn Read + Send>>,
+    finished: bool,
+}
+
+impl MultipartStreamState {
+    fn next_chunk(&mut self) -> Option<Result<Vec<u8>, BoxedError>> {
+        if let Some(reader) = self.current.as_mut() {
+            let mut buffer = vec![0; MULTIPART_CHUNK_SIZE];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) => {
+                        self.current = None;
+                        return Some(Ok(b"\r\n".to_vec()));
+                    }
+                    Ok(read) => {
+                        buffer.truncate(read);
+                        return Some(Ok(buffer));
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        self.current = None;
+                        self.finished = true;
+                        return Some(Err(Box::new(err)));
+                    }
+                }
+            }
+        }
+
+        if let Some(part) = self.parts.pop_front() {
+            let mut chunk = part_head(&self.boundary, &part);
+            match part.source {
+                PartSource::Bytes(data) => {
+                    chunk.extend_from_slice(&data);
+                    chunk.extend_from_slice(b"\r\n");
+                }
+                PartSource::Reader(reader) => self.current = Some(reader),
+            }
+            return Some(Ok(chunk));
+        }
+
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+        Some(Ok(format!("--{}--\r\n", self.boundary).into_bytes()))
+    }
+}
+
+impl Iterator for MultipartStream {
+    type Item = Result<Vec<u8>, BoxedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        state.next_chunk()
+    }
+}
+
+fn part_head(boundary: &str, part: &OutgoingPart) -> Vec<u8> {
+    let mut head = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"",
+        escape_quoted(&part.name)
+    );
+    if let Some(filename) = &part.filename {
+        let _ = write!(head, "; filename=\"{}\"", escape_quoted(filename));
+    }
+    head.push_str("\r\n");
+    if let Some(content_type) = &part.content_type {
+        let _ = write!(head, "Content-Type: {content_type}\r\n");
+    }
+    head.push_str("\r\n");
+    head.into_bytes()
+}
+
+/// `escape_quoted` percent encodes the characters that would end a quoted
+/// parameter or header line, as browsers do for form field and file names.
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// -- Multipart parsing
+
+/// `MultipartField` is a single part of a parsed `multipart/form-data` body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultipartField {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub data: Vec<u8>,
+}
+
+impl MultipartField {
+    pub fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+
+    /// `text` returns the field's content as a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FormError::Utf8`] if the content is not valid utf-8.
+    pub fn text(&self) -> FormResult<String> {
+        Ok(String::from_utf8(self.data.clone())?)
+    }
+}
+
+/// `multipart_boundary` extracts the boundary from a `multipart/form-data`
+/// content type header value.
+#[must_use]
+pub fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = split_params(content_type).into_iter();
+    let mime = params.next()?;
+    if !mime
+        .trim()
+        .eq_ignore_ascii_case(MULTIPART_FORM_DATA_CONTENT_TYPE)
+    {
+        return None;
+    }
+    params
+        .filter_map(|param| parse_param(&param))
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// `parse_multipart` splits a `multipart/form-data` body into its fields.
+///
+/// # Errors
+///
+/// Returns a [`FormError`] if the body does not match the boundary or a
+/// part is missing its `Content-Disposition` header.
+pub fn parse_multipart(body: &[u8], boundary: &str) -> FormResult<Vec<MultipartField>> {
+    if boundary.is_empty() {
+        return Err(FormError::MissingBoundary);
+    }
+
+    let delimiter = format!("--{boundary}");
+    let part_end = format!("\r\n--{boundary}");
+
+    let mut position = memchr::memmem::find(body, delimiter.as_bytes())
+        .ok_or(FormError::MissingDelimiter)?
+        + delimiter.len();
+
+    let mut fields = Vec::new();
+    loop {
+        let rest = &body[position..];
+        if rest.starts_with(b"--") {
+            return Ok(fields);
+        }
+
+        let rest = rest.strip_prefix(b"\r\n").ok_or(FormError::UnexpectedEnd)?;
+        let headers_end =
+            memchr::memmem::find(rest, b"\r\n\r\n").ok_or(FormError::MissingHeaders)?;
+        let headers = parse_part_headers(&rest[..headers_end])?;
+
+        let content = &rest[headers_end + 4..];
+        let content_end =
+            memchr::memmem::find(content, part_end.as_bytes()).ok_or(FormError::UnexpectedEnd)?;
+
+        fields.push(build_field(headers, content[..content_end].to_vec())?);
+
+        let consumed = body.len() - content.len() + content_end;
+        position = consumed + part_end.len();
+    }
+}
+
+fn parse_part_headers(block: &[u8]) -> FormResult<Vec<(String, String)>> {
+    let block = String::from_utf8(block.to_vec())?;
+    block
+        .split("\r\n")
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| FormError::InvalidHeader(line.to_string()))?;
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn build_field(headers: Vec<(String, String)>, data: Vec<u8>) -> FormResult<MultipartField> {
+    let disposition = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-disposition"))
+        .map(|(_, value)| value.clone())
+        .ok_or(FormError::MissingContentDisposition)?;
+
+    let mut name = None;
+    let mut filename = None;
+    for (key, value) in split_params(&disposition)
+        .iter()
+        .skip(1)
+        .filter_map(|param| parse_param(param))
+    {
+        if key.eq_ignore_ascii_case("name") {
+            name = Some(value);
+        } else if key.eq_ignore_ascii_case("filename") {
+            filename = Some(value);
+        }
+    }
+
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone());
+
+    Ok(MultipartField {
+        name: name.ok_or(FormError::MissingContentDisposition)?,
+        filename,
+        content_type,
+        headers,
+        data,
+    })
+}
+
+/// `split_params` splits a header value on `;` while keeping quoted
+/// strings intact.
+fn split_params(value: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for character in value.chars() {
+        match character {
+            '"' => {
+                quoted = !quoted;
+                current.push(character);
+            }
+            ';' if !quoted => params.push(std::mem::take(&mut current)),
+            _ => current.push(character),
+        }
+    }
+    params.push(current);
+    params
+}
+
+fn parse_param(param: &str) -> Option<(String, String)> {
+    let (key, value) = param.split_once('=')?;
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((key.trim().to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod test_forms {
+    use super::*;
+
+    #[test]
+    fn url_encoded_form_round_trips() {
+        let form = FormUrlEncoded::new()
+            .with_field("name", "ewe platform")
+            .with_field("tags", "a&b")
+            .with_field("tags", "c=d");
+
+        let encoded = form.encode();
+        assert_eq!(encoded, "name=ewe+platform&tags=a%26b&tags=c%3Dd");
+
+        let parsed = FormUrlEncoded::parse(encoded.as_bytes());
+        assert_eq!(parsed, form);
+        assert_eq!(parsed.get("name"), Some("ewe platform"));
+        assert_eq!(parsed.get_all("tags"), vec!["a&b", "c=d"]);
+    }
+
+    #[test]
+    fn multipart_streams_file_parts_in_chunks() {
+        let file = vec![b'x'; MULTIPART_CHUNK_SIZE * 2 + 10];
+        let form = MultipartForm::with_boundary("XBOUNDARY")
+            .with_text("title", "report")
+            .with_file(
+                "upload",
+                "report.bin",
+                "application/octet-stream",
+                io::Cursor::new(file.clone()),
+            );
+
+        let chunks: Vec<Vec<u8>> = form
+            .into_stream()
+            .collect::<Result<_, _>>()
+            .expect("should encode form");
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.len() <= MULTIPART_CHUNK_SIZE));
+
+        let body = chunks.concat();
+        let fields = parse_multipart(&body, "XBOUNDARY").expect("should parse form");
+        assert_eq!(fields.len(), 2);
+
+        assert_eq!(fields[0].name, "title");
+        assert_eq!(fields[0].text().expect("should be text"), "report");
+        assert!(!fields[0].is_file());
+
+        assert_eq!(fields[1].name, "upload");
+        assert_eq!(fields[1].filename.as_deref(), Some("report.bin"));
+        assert_eq!(
+            fields[1].content_type.as_deref(),
+            Some("application/octet-stream")
+        );
+        assert_eq!(fields[1].data, file);
+    }
+
+    #[test]
+    fn multipart_parses_quoted_parameters() {
+        let content_type = "multipart/form-data; boundary=\"abc\"";
+        assert_eq!(multipart_boundary(content_type).as_deref(), Some("abc"));
+        assert_eq!(multipart_boundary("text/plain; boundary=abc"), None);
+
+        let body = b"preamble\r\n--abc\r\n\
+            Content-Disposition: form-data; name=\"doc\"; filename=\"a;b.txt\"\r\n\
+            \r\n\
+            hello\r\n--abc--\r\n";
+        let fields = parse_multipart(body, "abc").expect("should parse form");
+        assert_eq!(fields[0].filename.as_deref(), Some("a;b.txt"));
+        assert_eq!(fields[0].data, b"hello");
+
+        assert!(matches!(
+            parse_multipart(
+                b"--abc\r\nContent-Type: text/plain\r\n\r\nx\r\n--abc--",
+                "abc"
+            ),
+            Err(FormError::MissingContentDisposition)
+        ));
+    }
+}
@@ -0,0 +1,579 @@
+use std::cell::OnceCell;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::runtime::{Handle, Runtime};
+
+use crate::eventbus::{EventBus, OverflowPolicy, Topic};
+use crate::extensions::result_ext::BoxedError;
+use crate::valtron::BoxedSendFuture;
+
+use super::{
+    FuncSimpleServer, ResponseBodyExt, ServiceAction, SimpleHttpResult, SimpleMethod,
+    SimpleOutgoingResponse, Status,
+};
+
+pub const HEALTHZ_ROUTE: &str = "/healthz";
+pub const READYZ_ROUTE: &str = "/readyz";
+
+/// `DEFAULT_PROBE_TIMEOUT` is how long a single probe may take before it
+/// is reported as failed.
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `HEALTH_PING` is the topic the event bus probe publishes on.
+pub const HEALTH_PING: Topic<HealthPing> = Topic::new("health.ping");
+
+pub type ProbeResult = Result<(), BoxedError>;
+
+/// `HealthPing` is the event published by the event bus probe on every
+/// readiness check, subscribers of [`HEALTH_PING`] other than the probe
+/// receive it too and should ignore it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HealthPing;
+
+/// `ProbeKind` tells which endpoint a probe is reported on, liveness probes
+/// on `/healthz` and readiness probes on `/readyz`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbeKind {
+    Liveness,
+    Readiness,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Pass,
+    Fail,
+}
+
+/// `CheckReport` is the outcome of a single probe.
+#[derive(Clone, Debug, Serialize)]
+pub struct CheckReport {
+    pub name: String,
+    pub status: HealthStatus,
+    pub duration_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `HealthReport` is the JSON body of the health endpoints, failing when
+/// any of its checks failed.
+#[derive(Clone, Debug, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub duration_ms: f64,
+    pub checks: Vec<CheckReport>,
+}
+
+impl HealthReport {
+    pub fn is_healthy(&self) -> bool {
+        self.status == HealthStatus::Pass
+    }
+}
+
+#[derive(Clone)]
+enum Probe {
+    Sync(Arc<dyn Fn() -> ProbeResult + Send + Sync>),
+    Async(Arc<dyn Fn() -> BoxedSendFuture<ProbeResult> + Send + Sync>),
+}
+
+struct RegisteredProbe {
+    name: String,
+    kind: ProbeKind,
+    probe: Probe,
+}
+
+/// `HealthChecks` is where components register their liveness and
+/// readiness probes, served through [`HealthChecks::service_actions`] as
+/// the `/healthz` and `/readyz` endpoints answering `200 Ok` or
+/// `503 Service Unavailable` with a [`HealthReport`].
+///
+/// Probes run one after the other on the request's thread. Async probes
+/// are driven on the runtime given to [`HealthChecks::with_runtime`], or
+/// on a tokio runtime created for the check, so they can use tokio's
+/// timers.
+///
+/// Each probe fails once it runs past the probe timeout, async probes are
+/// cancelled then while sync ones can't be interrupted and are only
+/// reported as failed when they return.
+///
+/// A `HealthChecks` is cheap to clone, all clones share the same probes.
+#[derive(Clone)]
+pub struct HealthChecks {
+    probes: Arc<Mutex<Vec<RegisteredProbe>>>,
+    runtime: Option<Handle>,
+    probe_timeout: Duration,
+}
+
+impl std::fmt::Debug for HealthChecks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let probes = self.probes.lock().expect("should acquire probes lock");
+        f.debug_struct("HealthChecks")
+            .field(
+                "probes",
+                &probes
+                    .iter()
+                    .map(|probe| (&probe.name, probe.kind))
+                    .collect::<Vec<_>>(),
+            )
+            .field("runtime", &self.runtime)
+            .field("probe_timeout", &self.probe_timeout)
+            .finish()
+    }
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self {
+            probes: Arc::default(),
+            runtime: None,
+            probe_timeout: DEFAULT_PROBE_TIMEOUT,
+        }
+    }
+}
+
+// -- Constructors
+
+impl HealthChecks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `with_runtime` runs async probes on the runtime of `handle` instead
+    /// of a runtime created for each check. It must have timers enabled
+    /// for the probe timeout, and they only make progress there when it is
+    /// a multi thread runtime, or a current thread one driven by another
+    /// thread.
+    #[must_use]
+    pub fn with_runtime(mut self, handle: Handle) -> Self {
+        self.runtime = Some(handle);
+        self
+    }
+
+    /// `with_probe_timeout` sets how long each probe may take, replacing
+    /// [`DEFAULT_PROBE_TIMEOUT`].
+    #[must_use]
+    pub fn with_probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+}
+
+// -- Methods
+
+impl HealthChecks {
+    pub fn register<F>(&self, kind: ProbeKind, name: impl Into<String>, probe: F)
+    where
+        F: Fn() -> ProbeResult + Send + Sync + 'static,
+    {
+        self.add(kind, name.into(), Probe::Sync(Arc::new(probe)));
+    }
+
+    pub fn register_async<F, Fut>(&self, kind: ProbeKind, name: impl Into<String>, probe: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ProbeResult> + Send + 'static,
+    {
+        self.add(
+            kind,
+            name.into(),
+            Probe::Async(Arc::new(move || Box::pin(probe()))),
+        );
+    }
+
+    pub fn register_liveness<F>(&self, name: impl Into<String>, probe: F)
+    where
+        F: Fn() -> ProbeResult + Send + Sync + 'static,
+    {
+        self.register(ProbeKind::Liveness, name, probe);
+    }
+
+    pub fn register_readiness<F>(&self, name: impl Into<String>, probe: F)
+    where
+        F: Fn() -> ProbeResult + Send + Sync + 'static,
+    {
+        self.register(ProbeKind::Readiness, name, probe);
+    }
+
+    /// `register_pool` adds a `connection_pool` readiness probe failing
+    /// when the pool rejected checkouts since the previous check, as its
+    /// hosts were at their connection limit.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn register_pool(&self, pool: crate::wire::tcp::ConnectionPool) {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let last_rejected = AtomicU64::new(pool.metrics().rejected);
+        self.register_readiness("connection_pool", move || {
+            // concurrent checks may read the metrics out of order, only
+            // moving forward counts each rejection once.
+            let rejected = pool.metrics().rejected;
+            let previous = last_rejected.fetch_max(rejected, Ordering::SeqCst);
+            match rejected.saturating_sub(previous) {
+                0 => Ok(()),
+                count => Err(format!("{count} checkouts rejected since the last check").into()),
+            }
+        });
+    }
+
+    /// `register_event_bus` adds an `event_bus` readiness probe publishing
+    /// a [`HealthPing`] on [`HEALTH_PING`] and failing unless it is
+    /// delivered back.
+    pub fn register_event_bus(&self, bus: &EventBus) {
+        let bus = bus.clone();
+        let pings = Mutex::new(bus.subscribe_with::<HealthPing>(
+            HEALTH_PING.name(),
+            1,
+            OverflowPolicy::DropOldest,
+        ));
+        self.register_readiness("event_bus", move || {
+            // concurrent checks would take each other's ping otherwise.
+            let pings = pings.lock().expect("should acquire pings lock");
+            while pings.try_recv().is_some() {}
+
+            bus.try_publish(&HEALTH_PING, HealthPing);
+            match pings.try_recv() {
+                Some(_) => Ok(()),
+                None => Err("health ping was not delivered".into()),
+            }
+        });
+    }
+
+    /// `check` runs the probes of the giving kind, a panicking probe is
+    /// reported as failed.
+    ///
+    /// Async probes can not be driven from within an async task, calling
+    /// `check` from one reports them as failed.
+    pub fn check(&self, kind: ProbeKind) -> HealthReport {
+        let probes: Vec<(String, Probe)> = self
+            .probes
+            .lock()
+            .expect("should acquire probes lock")
+            .iter()
+            .filter(|registered| registered.kind == kind)
+            .map(|registered| (registered.name.clone(), registered.probe.clone()))
+            .collect();
+
+        let started = Instant::now();
+        let runner = OnceCell::new();
+        let checks: Vec<CheckReport> = probes
+            .into_iter()
+            .map(|(name, probe)| self.run_probe(name, &probe, &runner))
+            .collect();
+
+        let failed = checks
+            .iter()
+            .any(|check| check.status == HealthStatus::Fail);
+        HealthReport {
+            status: if failed {
+                HealthStatus::Fail
+            } else {
+                HealthStatus::Pass
+            },
+            duration_ms: millis(started.elapsed()),
+            checks,
+        }
+    }
+
+    /// `respond` renders the report of the giving kind as a response.
+    pub fn respond(&self, kind: ProbeKind) -> Result<SimpleOutgoingResponse, BoxedError> {
+        let report = self.check(kind);
+        let mut response = SimpleOutgoingResponse::builder()
+            .with_status(if report.is_healthy() {
+                Status::OK
+            } else {
+                Status::ServiceUnavailable
+            })
+            .build()
+            .map_err(|err| Box::new(err) as BoxedError)?;
+
+        response
+            .set_json(&report)
+            .map_err(|err| Box::new(err) as BoxedError)?;
+        Ok(response)
+    }
+
+    /// `service_actions` returns the `GET /healthz` and `GET /readyz`
+    /// endpoints to serve alongside a service's own.
+    pub fn service_actions(&self) -> SimpleHttpResult<Vec<ServiceAction>> {
+        [
+            (HEALTHZ_ROUTE, ProbeKind::Liveness),
+            (READYZ_ROUTE, ProbeKind::Readiness),
+        ]
+        .into_iter()
+        .map(|(route, kind)| {
+            let checks = self.clone();
+            ServiceAction::builder()
+                .with_route(route)
+                .with_method(SimpleMethod::GET)
+                .with_body(FuncSimpleServer::new(move |_| checks.respond(kind)))
+                .build()
+        })
+        .collect()
+    }
+
+    fn add(&self, kind: ProbeKind, name: String, probe: Probe) {
+        self.probes
+            .lock()
+            .expect("should acquire probes lock")
+            .push(RegisteredProbe { name, kind, probe });
+    }
+
+    fn run_probe(
+        &self,
+        name: String,
+        probe: &Probe,
+        runner: &OnceCell<Result<AsyncRunner, String>>,
+    ) -> CheckReport {
+        let started = Instant::now();
+        let timeout = self.probe_timeout;
+        let result = catch_unwind(AssertUnwindSafe(|| match probe {
+            Probe::Sync(probe) => probe(),
+            Probe::Async(probe) => match runner.get_or_init(|| self.async_runner()) {
+                Ok(runner) => runner
+                    .block_on(async { tokio::time::timeout(timeout, probe()).await })
+                    .unwrap_or_else(|_| Err(timed_out(timeout))),
+                Err(err) => Err(err.clone().into()),
+            },
+        }));
+        let elapsed = started.elapsed();
+        let duration_ms = millis(elapsed);
+
+        let error = match result {
+            Ok(Ok(())) if elapsed > timeout => Some(timed_out(timeout).to_string()),
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(err.to_string()),
+            Err(_) => Some(String::from("probe panicked")),
+        };
+        CheckReport {
+            name,
+            status: if error.is_some() {
+                HealthStatus::Fail
+            } else {
+                HealthStatus::Pass
+            },
+            duration_ms,
+            error,
+        }
+    }
+
+    fn async_runner(&self) -> Result<AsyncRunner, String> {
+        // blocking on a future from within a runtime panics
+        if Handle::try_current().is_ok() {
+            return Err(String::from(
+                "async probes can not run from within an async task",
+            ));
+        }
+        if let Some(handle) = &self.runtime {
+            return Ok(AsyncRunner::Shared(handle.clone()));
+        }
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .map(AsyncRunner::Owned)
+            .map_err(|err| format!("failed to start a runtime for async probes: {err}"))
+    }
+}
+
+/// `AsyncRunner` is the runtime async probes of one check are driven on.
+enum AsyncRunner {
+    Shared(Handle),
+    Owned(Runtime),
+}
+
+impl AsyncRunner {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        match self {
+            Self::Shared(handle) => handle.block_on(future),
+            Self::Owned(runtime) => runtime.block_on(future),
+        }
+    }
+}
+
+fn timed_out(timeout: Duration) -> BoxedError {
+    format!("probe timed out after {timeout:?}").into()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod healthcheck_tests {
+    use serde_json::Value;
+
+    use super::super::{SimpleBody, SimpleIncomingRequest};
+    use super::*;
+
+    fn body_json(response: &SimpleOutgoingResponse) -> Value {
+        match &response.body {
+            Some(SimpleBody::Bytes(bytes)) => {
+                serde_json::from_slice(bytes).expect("should be json")
+            }
+            _ => panic!("should have a bytes body"),
+        }
+    }
+
+    #[test]
+    fn reports_probes_by_kind_with_timing() {
+        let checks = HealthChecks::new();
+        checks.register_liveness("process", || Ok(()));
+        checks.register_readiness("database", || Err("connection refused".into()));
+        checks.register_async(ProbeKind::Readiness, "cache", || async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(())
+        });
+        checks.register_readiness("migrations", || panic!("migrations table missing"));
+
+        let live = checks.check(ProbeKind::Liveness);
+        assert!(live.is_healthy());
+        assert_eq!(live.checks.len(), 1);
+
+        let ready = checks.check(ProbeKind::Readiness);
+        assert!(!ready.is_healthy());
+        let outcomes: Vec<(&str, HealthStatus, Option<&str>)> = ready
+            .checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.status, check.error.as_deref()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("database", HealthStatus::Fail, Some("connection refused")),
+                ("cache", HealthStatus::Pass, None),
+                ("migrations", HealthStatus::Fail, Some("probe panicked")),
+            ]
+        );
+        assert!(ready.checks.iter().all(|check| check.duration_ms >= 0.0));
+        assert!(ready.checks[1].duration_ms >= 5.0);
+    }
+
+    #[test]
+    fn async_probes_fail_clearly_inside_a_runtime() {
+        let checks = HealthChecks::new();
+        checks.register_async(ProbeKind::Liveness, "cache", || async { Ok(()) });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("should build runtime");
+        let report = runtime.block_on(async { checks.check(ProbeKind::Liveness) });
+        assert!(!report.is_healthy());
+        assert_eq!(
+            report.checks[0].error.as_deref(),
+            Some("async probes can not run from within an async task")
+        );
+
+        let checks = checks.with_runtime(runtime.handle().clone());
+        assert!(checks.check(ProbeKind::Liveness).is_healthy());
+    }
+
+    #[test]
+    fn serves_healthz_and_readyz_as_json() {
+        let checks = HealthChecks::new();
+        checks.register_liveness("process", || Ok(()));
+        checks.register_readiness("warmup", || Err("still loading".into()));
+
+        let actions = checks.service_actions().expect("should build actions");
+        let serve = |route: &str| {
+            let action = actions
+                .iter()
+                .find(|action| action.match_head(route, SimpleMethod::GET))
+                .expect("should have route");
+            let request = SimpleIncomingRequest::builder()
+                .with_plain_url(route)
+                .with_method(SimpleMethod::GET)
+                .build()
+                .expect("should build request");
+            action.body.handle(request).expect("should respond")
+        };
+
+        let healthz = serve(HEALTHZ_ROUTE);
+        assert!(matches!(healthz.status, Status::OK));
+        let body = body_json(&healthz);
+        assert_eq!(body["status"], "pass");
+        assert_eq!(body["checks"][0]["name"], "process");
+        assert!(body["checks"][0]["duration_ms"].is_number());
+        assert!(body["checks"][0].get("error").is_none());
+
+        let readyz = serve(READYZ_ROUTE);
+        assert!(matches!(readyz.status, Status::ServiceUnavailable));
+        let body = body_json(&readyz);
+        assert_eq!(body["status"], "fail");
+        assert_eq!(body["checks"][0]["error"], "still loading");
+    }
+
+    #[test]
+    fn default_probes_check_the_pool_and_event_bus() {
+        let checks = HealthChecks::new();
+        let bus = EventBus::new();
+        checks.register_event_bus(&bus);
+        checks.register_pool(crate::wire::tcp::ConnectionPool::default());
+
+        let report = checks.check(ProbeKind::Readiness);
+        let names: Vec<&str> = report
+            .checks
+            .iter()
+            .map(|check| check.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["event_bus", "connection_pool"]);
+        assert!(report.is_healthy());
+
+        // other ping subscribers see every ping without starving the probe
+        let everything = bus.subscribe::<HealthPing>("**");
+        assert!(checks.check(ProbeKind::Readiness).is_healthy());
+        let ping = everything.try_recv().expect("should see the ping");
+        assert_eq!(&*ping.topic, HEALTH_PING.name());
+        assert!(everything.try_recv().is_none());
+    }
+
+    #[test]
+    fn concurrent_checks_do_not_take_each_others_ping() {
+        let checks = HealthChecks::new();
+        let bus = EventBus::new();
+        checks.register_event_bus(&bus);
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let checks = checks.clone();
+                std::thread::spawn(move || {
+                    (0..50).all(|_| checks.check(ProbeKind::Readiness).is_healthy())
+                })
+            })
+            .collect();
+        for worker in workers {
+            assert!(worker.join().expect("should finish checking"));
+        }
+    }
+
+    #[test]
+    fn probes_running_past_the_timeout_fail() {
+        let checks = HealthChecks::new().with_probe_timeout(Duration::from_millis(20));
+        checks.register_async(ProbeKind::Liveness, "hung", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        checks.register_liveness("slow", || {
+            std::thread::sleep(Duration::from_millis(40));
+            Ok(())
+        });
+
+        let report = checks.check(ProbeKind::Liveness);
+        assert!(report.duration_ms < 1000.0);
+        let errors: Vec<Option<&str>> = report
+            .checks
+            .iter()
+            .map(|check| check.error.as_deref())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                Some("probe timed out after 20ms"),
+                Some("probe timed out after 20ms")
+            ]
+        );
+        assert!(format!("{checks:?}").contains("runtime: None"));
+    }
+}
@@ -57,6 +57,8 @@ pub enum HttpReaderError {
     #[from(ignore)]
     HeaderValueGreaterThanLimit(usize),
     BodyContentSizeIsGreaterThanLimit(usize),
+    #[from(ignore)]
+    ChunkSizeIsGreaterThanLimit(u64),
     InvalidHeaderLine,
 
     #[from(ignore)]
@@ -476,6 +478,59 @@ pub fn is_sub_set_of_other_header(this: &SimpleHeaders, other: &SimpleHeaders) -
     true
 }
 
+/// `SET_COOKIE_SEPARATOR` keeps apart the values of a `Set-Cookie` header
+/// received more than once, they can't be folded with a comma like other
+/// headers as cookie dates contain commas. A line break never appears in
+/// a header value so it splits them back unambiguously.
+pub const SET_COOKIE_SEPARATOR: char = '\n';
+
+/// `append_header_value` records `value` for `key`, folding it into any
+/// value already present as RFC 9110 section 5.3 describes, with `, ` or
+/// [`SET_COOKIE_SEPARATOR`] for `Set-Cookie`.
+pub fn append_header_value(headers: &mut SimpleHeaders, key: SimpleHeader, value: String) {
+    match headers.entry(key) {
+        std::collections::btree_map::Entry::Vacant(entry) => {
+            entry.insert(value);
+        }
+        std::collections::btree_map::Entry::Occupied(mut entry) => {
+            let separator = if *entry.key() == SimpleHeader::SET_COOKIE {
+                SET_COOKIE_SEPARATOR.to_string()
+            } else {
+                String::from(", ")
+            };
+            let existing = entry.get_mut();
+            existing.push_str(&separator);
+            existing.push_str(&value);
+        }
+    }
+}
+
+/// `header_values` returns every value received for `key`, only
+/// `Set-Cookie` can hold more than one, other headers were folded into a
+/// single value by [`append_header_value`].
+pub fn header_values<'a>(headers: &'a SimpleHeaders, key: &SimpleHeader) -> Vec<&'a str> {
+    match headers.get(key) {
+        Some(value) if *key == SimpleHeader::SET_COOKIE => {
+            value.split(SET_COOKIE_SEPARATOR).collect()
+        }
+        Some(value) => vec![value.as_str()],
+        None => Vec::new(),
+    }
+}
+
+/// `encode_header_lines` renders `headers` as `key: value` lines, values
+/// holding line breaks, like repeated `Set-Cookie` ones, get a line each.
+fn encode_header_lines(headers: &SimpleHeaders) -> Vec<String> {
+    headers
+        .iter()
+        .flat_map(|(key, value)| {
+            value
+                .split(SET_COOKIE_SEPARATOR)
+                .map(move |value| format!("{key}: {value}\r\n"))
+        })
+        .collect()
+}
+
 /// HTTP Headers
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1842,10 +1897,7 @@ impl Iterator for Http11RequestIterator {
 
                 let borrowed_headers = &request.headers;
 
-                let mut encoded_headers: Vec<String> = borrowed_headers
-                    .into_iter()
-                    .map(|(key, value)| format!("{}: {}\r\n", key, value))
-                    .collect();
+                let mut encoded_headers: Vec<String> = encode_header_lines(borrowed_headers);
 
                 // add CLRF for ending header
                 encoded_headers.push("\r\n".into());
@@ -2103,10 +2155,7 @@ impl Iterator for Http11ResponseIterator {
 
                 let borrowed_headers = &response.headers;
 
-                let mut encoded_headers: Vec<String> = borrowed_headers
-                    .into_iter()
-                    .map(|(key, value)| format!("{}: {}\r\n", key, value))
-                    .collect();
+                let mut encoded_headers: Vec<String> = encode_header_lines(borrowed_headers);
 
                 // add CLRF for ending header
                 encoded_headers.push("\r\n".into());
@@ -2647,6 +2696,11 @@ where
 }
 
 const MAX_HEADER_NAME_LEN: usize = (1 << 16) - 1;
+
+/// `MAX_CHUNK_SIZE` is the largest chunk [`SimpleHttpChunkIterator`]
+/// accepts, the size comes from the peer so it must be capped before any
+/// memory is set aside for it.
+const MAX_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
 static SPACE_CHARS: &[char] = &[' ', '\n', '\t', '\r'];
 
 impl<F, T> Iterator for HttpReader<F, T>
@@ -2807,7 +2861,7 @@ where
                     // check if there is any funny business with headers
                     // for header_value_part in header_value.split(','). {}
 
-                    headers.insert(SimpleHeader::from(header_key), header_value);
+                    append_header_value(&mut headers, SimpleHeader::from(header_key), header_value);
 
                     line.clear();
                 }
@@ -3030,7 +3084,7 @@ impl ChunkState {
         let mut total_bytes = 0;
 
         // are we starting out with a CRLF, if so, count and skip it
-        if data_pointer.peek(2) != Some(b"\r\n") {
+        if data_pointer.peek(2) == Some(b"\r\n") {
             data_pointer.peek_next_by(2);
             data_pointer.skip();
 
@@ -3450,6 +3504,55 @@ mod test_chunk_parser {
             Ok(ChunkState::Chunk(198765, _, _))
         ));
     }
+
+    fn chunk_iterator(
+        content: &str,
+    ) -> SimpleHttpChunkIterator<ioutils::BufferedReader<std::io::Cursor<Vec<u8>>>> {
+        let reader = ioutils::BufferedReader::new(ioutils::BufferedReader::new(
+            std::io::Cursor::new(content.as_bytes().to_vec()),
+        ));
+        SimpleHttpChunkIterator::new(
+            "chunked".into(),
+            SimpleHeaders::new(),
+            std::sync::Arc::new(std::sync::Mutex::new(reader)),
+        )
+    }
+
+    #[test]
+    fn chunk_iterator_reads_valid_chunks() {
+        let mut chunks = chunk_iterator("5\r\nhello\r\n6\r\n world\r\n0\r\n");
+
+        let mut body = Vec::new();
+        for chunk in chunks.by_ref() {
+            match chunk.expect("should read chunk") {
+                ChunkedData::Data(data, _) => body.extend(data),
+                ChunkedData::DataEnded => break,
+                ChunkedData::Trailer(..) => panic!("should not have trailers"),
+            }
+        }
+        assert_eq!(body, b"hello world");
+    }
+
+    #[test]
+    fn chunk_iterator_rejects_oversized_and_truncated_chunks() {
+        let err = chunk_iterator("ffffffffffff\r\nhello")
+            .next()
+            .expect("should yield")
+            .expect_err("should reject chunk size");
+        assert!(matches!(
+            err.downcast_ref::<HttpReaderError>(),
+            Some(HttpReaderError::ChunkSizeIsGreaterThanLimit(MAX_CHUNK_SIZE))
+        ));
+
+        let err = chunk_iterator("a\r\nhello")
+            .next()
+            .expect("should yield")
+            .expect_err("should fail on missing data");
+        assert!(matches!(
+            err.downcast_ref::<HttpReaderError>(),
+            Some(HttpReaderError::ReadFailed)
+        ));
+    }
 }
 
 pub struct SimpleHttpChunkIterator<T: PeekableReadStream + Send>(
@@ -3505,17 +3608,31 @@ impl<T: PeekableReadStream + Send> Iterator for SimpleHttpChunkIterator<T> {
                 match ChunkState::parse_http_chunk_from_pointer(&mut head_pointer) {
                     Ok(chunk) => match chunk {
                         ChunkState::Chunk(size, _, opt_exts) => {
-                            let size_to_read = (total_bytes_before_body as u64) + size;
-                            let mut read_buffer = Vec::with_capacity(size_to_read as usize);
+                            if size > MAX_CHUNK_SIZE {
+                                return Some(Err(Box::new(
+                                    HttpReaderError::ChunkSizeIsGreaterThanLimit(MAX_CHUNK_SIZE),
+                                )));
+                            }
 
-                            if let Err(err) = reader.read_exact(&mut read_buffer) {
+                            // the chunk header was only peeked, skip over it.
+                            let mut chunk_header = [0; 128];
+                            if let Err(err) =
+                                reader.read_exact(&mut chunk_header[..total_bytes_before_body])
+                            {
                                 return Some(Err(Box::new(err)));
                             }
 
-                            let chunk_data: &[u8] =
-                                &read_buffer[total_bytes_before_body..read_buffer.len()];
-
-                            Some(Ok(ChunkedData::Data(Vec::from(chunk_data), opt_exts)))
+                            // read_to_end only grows the vec as data arrives, so
+                            // a peer announcing a large chunk it never sends costs
+                            // nothing.
+                            let mut chunk_data = Vec::new();
+                            match reader.by_ref().take(size).read_to_end(&mut chunk_data) {
+                                Ok(read) if read as u64 == size => {
+                                    Some(Ok(ChunkedData::Data(chunk_data, opt_exts)))
+                                }
+                                Ok(_) => Some(Err(Box::new(HttpReaderError::ReadFailed))),
+                                Err(err) => Some(Err(Box::new(err))),
+                            }
                         }
                         ChunkState::LastChunk => Some(Ok(ChunkedData::DataEnded)),
                         ChunkState::Trailer(mut inner) => match inner.find(":") {
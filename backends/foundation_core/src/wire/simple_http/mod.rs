@@ -1,4 +1,16 @@
+mod access_log;
+mod cookies;
+mod extractors;
+mod forms;
+mod healthcheck;
 mod impls;
+mod redirects;
 mod tests;
 
+pub use access_log::*;
+pub use cookies::*;
+pub use extractors::*;
+pub use forms::*;
+pub use healthcheck::*;
 pub use impls::*;
+pub use redirects::*;
@@ -0,0 +1,389 @@
+use super::{SimpleHeader, SimpleHeaders, SimpleMethod, Status};
+
+#[derive(Debug)]
+pub enum RedirectError {
+    TooManyRedirects(RedirectChain),
+    CrossOriginBlocked {
+        from: Box<url::Url>,
+        to: Box<url::Url>,
+    },
+    MissingLocation,
+    InvalidLocation(url::ParseError),
+    UnsupportedScheme(String),
+}
+
+impl std::error::Error for RedirectError {}
+
+impl core::fmt::Display for RedirectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `RedirectPolicy` decides whether and how a redirect response is
+/// followed.
+#[derive(Clone, Debug)]
+pub struct RedirectPolicy {
+    /// `max_hops` is how many redirects may be followed for one request,
+    /// zero disables following redirects.
+    pub max_hops: usize,
+
+    /// `allow_cross_origin` allows following redirects to another scheme,
+    /// host or port.
+    pub allow_cross_origin: bool,
+
+    /// `preserve_method` keeps the method and body on 307 and 308 as
+    /// RFC 9110 requires, when false those are always turned into a `GET`.
+    /// Whatever this is set to, 301 and 302 only turn a `POST` into a
+    /// `GET` and 303 turns every method but `HEAD` into one.
+    pub preserve_method: bool,
+
+    /// `strip_auth_on_host_change` drops the `Authorization` and `Cookie`
+    /// headers when a redirect leads to another origin (scheme, host or
+    /// port), so credentials are never leaked to a server they were not
+    /// meant for. They are always dropped when a redirect downgrades from
+    /// `https` to `http`, whatever this is set to.
+    pub strip_auth_on_host_change: bool,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_hops: 10,
+            allow_cross_origin: true,
+            preserve_method: true,
+            strip_auth_on_host_change: true,
+        }
+    }
+}
+
+// -- Builder methods
+
+impl RedirectPolicy {
+    /// `none` returns a policy that never follows redirects.
+    #[must_use]
+    pub fn none() -> Self {
+        Self::default().with_max_hops(0)
+    }
+
+    #[must_use]
+    pub fn with_max_hops(mut self, max_hops: usize) -> Self {
+        self.max_hops = max_hops;
+        self
+    }
+
+    #[must_use]
+    pub fn with_cross_origin(mut self, allow: bool) -> Self {
+        self.allow_cross_origin = allow;
+        self
+    }
+
+    #[must_use]
+    pub fn with_preserve_method(mut self, preserve: bool) -> Self {
+        self.preserve_method = preserve;
+        self
+    }
+
+    #[must_use]
+    pub fn with_strip_auth_on_host_change(mut self, strip: bool) -> Self {
+        self.strip_auth_on_host_change = strip;
+        self
+    }
+}
+
+/// `RedirectHop` records a single redirect that was followed.
+#[derive(Clone, Debug)]
+pub struct RedirectHop {
+    pub status: Status,
+    pub from: url::Url,
+    pub to: url::Url,
+}
+
+/// `RedirectChain` lists the redirects followed for a request in order,
+/// it is meant to be kept alongside the final response for debugging.
+#[derive(Clone, Debug, Default)]
+pub struct RedirectChain(Vec<RedirectHop>);
+
+impl RedirectChain {
+    #[must_use]
+    pub fn hops(&self) -> &[RedirectHop] {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `final_url` returns the url the last redirect pointed at.
+    #[must_use]
+    pub fn final_url(&self) -> Option<&url::Url> {
+        self.0.last().map(|hop| &hop.to)
+    }
+}
+
+/// `RedirectedRequest` describes the request to make next when a redirect
+/// is followed.
+#[derive(Clone, Debug)]
+pub struct RedirectedRequest {
+    pub method: SimpleMethod,
+    pub url: url::Url,
+    pub headers: SimpleHeaders,
+
+    /// `keep_body` is false when the redirect turned the request into a
+    /// `GET`, in which case the original body must not be sent again.
+    pub keep_body: bool,
+}
+
+impl RedirectPolicy {
+    /// `is_redirect` returns true for the statuses this policy follows.
+    #[must_use]
+    pub fn is_redirect(status: &Status) -> bool {
+        matches!(
+            status,
+            Status::MovedPermanently
+                | Status::Found
+                | Status::SeeOther
+                | Status::TemporaryRedirect
+                | Status::PermanentRedirect
+        )
+    }
+
+    /// `follow` returns the request to make for a redirect response to
+    /// `method` on `url`, recording the hop into `chain`.
+    ///
+    /// Returns `Ok(None)` when the response is not a redirect or the policy
+    /// does not follow redirects at all, in which case the response should
+    /// be handed to the caller as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RedirectError`] when the redirect is invalid or breaks
+    /// the policy, e.g once more than `max_hops` redirects were followed.
+    pub fn follow(
+        &self,
+        chain: &mut RedirectChain,
+        method: &SimpleMethod,
+        url: &url::Url,
+        headers: &SimpleHeaders,
+        status: &Status,
+        response_headers: &SimpleHeaders,
+    ) -> Result<Option<RedirectedRequest>, RedirectError> {
+        if self.max_hops == 0 || !Self::is_redirect(status) {
+            return Ok(None);
+        }
+
+        let location = response_headers
+            .get(&SimpleHeader::LOCATION)
+            .ok_or(RedirectError::MissingLocation)?;
+        let target = url
+            .join(location.trim())
+            .map_err(RedirectError::InvalidLocation)?;
+
+        if target.scheme() != "http" && target.scheme() != "https" {
+            return Err(RedirectError::UnsupportedScheme(
+                target.scheme().to_string(),
+            ));
+        }
+
+        let same_origin = url.scheme() == target.scheme()
+            && url.host_str() == target.host_str()
+            && url.port_or_known_default() == target.port_or_known_default();
+        if !same_origin && !self.allow_cross_origin {
+            return Err(RedirectError::CrossOriginBlocked {
+                from: Box::new(url.clone()),
+                to: Box::new(target),
+            });
+        }
+
+        chain.0.push(RedirectHop {
+            status: status.clone(),
+            from: url.clone(),
+            to: target.clone(),
+        });
+        if chain.len() > self.max_hops {
+            return Err(RedirectError::TooManyRedirects(chain.clone()));
+        }
+
+        let is_head = method.equal("HEAD");
+        let keep_method = match status {
+            Status::TemporaryRedirect | Status::PermanentRedirect => self.preserve_method,
+            Status::SeeOther => is_head,
+            _ => *method != SimpleMethod::POST,
+        };
+
+        let mut headers = headers.clone();
+        let method = if keep_method {
+            method.clone()
+        } else {
+            headers.remove(&SimpleHeader::CONTENT_LENGTH);
+            headers.remove(&SimpleHeader::CONTENT_TYPE);
+            headers.remove(&SimpleHeader::TRANSFER_ENCODING);
+            SimpleMethod::GET
+        };
+
+        headers.remove(&SimpleHeader::HOST);
+        let downgraded = url.scheme() == "https" && target.scheme() == "http";
+        if downgraded || (self.strip_auth_on_host_change && !same_origin) {
+            headers.remove(&SimpleHeader::AUTHORIZATION);
+            headers.remove(&SimpleHeader::COOKIE);
+        }
+
+        Ok(Some(RedirectedRequest {
+            keep_body: keep_method && !is_head && method != SimpleMethod::GET,
+            method,
+            url: target,
+            headers,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test_redirect_policy {
+    use super::*;
+
+    fn url(value: &str) -> url::Url {
+        url::Url::parse(value).expect("should be valid url")
+    }
+
+    fn location(value: &str) -> SimpleHeaders {
+        let mut headers = SimpleHeaders::new();
+        headers.insert(SimpleHeader::LOCATION, value.into());
+        headers
+    }
+
+    fn request_headers() -> SimpleHeaders {
+        let mut headers = SimpleHeaders::new();
+        headers.insert(SimpleHeader::AUTHORIZATION, "Bearer secret".into());
+        headers.insert(SimpleHeader::CONTENT_TYPE, "application/json".into());
+        headers
+    }
+
+    #[test]
+    fn methods_follow_the_redirect_status() {
+        let policy = RedirectPolicy::default();
+        let origin = url("http://example.com/api/items");
+        let mut chain = RedirectChain::default();
+
+        let next = policy
+            .follow(
+                &mut chain,
+                &SimpleMethod::POST,
+                &origin,
+                &request_headers(),
+                &Status::SeeOther,
+                &location("/items/1"),
+            )
+            .expect("should follow")
+            .expect("should redirect");
+        assert_eq!(next.method, SimpleMethod::GET);
+        assert_eq!(next.url.as_str(), "http://example.com/items/1");
+        assert!(!next.keep_body);
+        assert!(!next.headers.contains_key(&SimpleHeader::CONTENT_TYPE));
+        assert!(next.headers.contains_key(&SimpleHeader::AUTHORIZATION));
+
+        let next = policy
+            .follow(
+                &mut chain,
+                &SimpleMethod::POST,
+                &origin,
+                &request_headers(),
+                &Status::PermanentRedirect,
+                &location("https://other.example.org/items"),
+            )
+            .expect("should follow")
+            .expect("should redirect");
+        assert_eq!(next.method, SimpleMethod::POST);
+        assert!(next.keep_body);
+        assert!(!next.headers.contains_key(&SimpleHeader::AUTHORIZATION));
+
+        assert_eq!(chain.len(), 2);
+        assert_eq!(
+            chain.final_url().map(url::Url::as_str),
+            Some("https://other.example.org/items")
+        );
+    }
+
+    #[test]
+    fn credentials_are_stripped_when_the_origin_changes() {
+        let origin = url("https://example.com/api");
+        let follow = |policy: &RedirectPolicy, target: &str| {
+            policy
+                .follow(
+                    &mut RedirectChain::default(),
+                    &SimpleMethod::GET,
+                    &origin,
+                    &request_headers(),
+                    &Status::Found,
+                    &location(target),
+                )
+                .expect("should follow")
+                .expect("should redirect")
+                .headers
+                .contains_key(&SimpleHeader::AUTHORIZATION)
+        };
+
+        let policy = RedirectPolicy::default();
+        assert!(follow(&policy, "https://example.com:443/other"));
+        assert!(!follow(&policy, "https://example.com:8443/other"));
+        assert!(!follow(&policy, "http://example.com/other"));
+
+        let keep_auth = RedirectPolicy::default().with_strip_auth_on_host_change(false);
+        assert!(follow(&keep_auth, "https://example.com:8443/other"));
+        assert!(!follow(&keep_auth, "http://example.com/other"));
+    }
+
+    #[test]
+    fn policy_limits_are_enforced() {
+        let origin = url("http://example.com/");
+        let headers = SimpleHeaders::new();
+
+        let same_origin_only = RedirectPolicy::default().with_cross_origin(false);
+        assert!(matches!(
+            same_origin_only.follow(
+                &mut RedirectChain::default(),
+                &SimpleMethod::GET,
+                &origin,
+                &headers,
+                &Status::Found,
+                &location("http://evil.example.net/"),
+            ),
+            Err(RedirectError::CrossOriginBlocked { .. })
+        ));
+
+        let single_hop = RedirectPolicy::default().with_max_hops(1);
+        let mut chain = RedirectChain::default();
+        let hop = |chain: &mut RedirectChain| {
+            single_hop.follow(
+                chain,
+                &SimpleMethod::GET,
+                &origin,
+                &headers,
+                &Status::Found,
+                &location("/next"),
+            )
+        };
+        assert!(hop(&mut chain).expect("should follow").is_some());
+        assert!(matches!(
+            hop(&mut chain),
+            Err(RedirectError::TooManyRedirects(_))
+        ));
+
+        assert!(RedirectPolicy::none()
+            .follow(
+                &mut RedirectChain::default(),
+                &SimpleMethod::GET,
+                &origin,
+                &headers,
+                &Status::Found,
+                &location("/next"),
+            )
+            .expect("should not fail")
+            .is_none());
+    }
+}
@@ -9,6 +9,11 @@ pub enum TlsError {
     Handshake,
     ConnectorCreation,
 
+    /// `InvalidCertificate` is a certificate or private key that could not
+    /// be parsed or is not supported.
+    #[from(ignore)]
+    InvalidCertificate(String),
+
     #[from(ignore)]
     IO(io::Error),
 }
@@ -21,6 +26,7 @@ impl PartialEq for TlsError {
             (Self::IO(m1), Self::IO(m2)) => m1.kind() == m2.kind(),
             (Self::Handshake, Self::Handshake) => true,
             (Self::ConnectorCreation, Self::ConnectorCreation) => true,
+            (Self::InvalidCertificate(m1), Self::InvalidCertificate(m2)) => m1 == m2,
             _ => false,
         }
     }
@@ -46,6 +52,7 @@ pub type DataStreamResult<T> = std::result::Result<T, DataStreamError>;
 pub enum DataStreamError {
     ConnectionFailed,
     ReconnectionError,
+    PoolExhausted,
 
     #[from(ignore)]
     IO(io::Error),
@@ -55,6 +62,10 @@ pub enum DataStreamError {
 
     #[from(ignore)]
     SocketAddrError(AddrParseError),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[from(ignore)]
+    Proxy(super::ProxyError),
 }
 
 impl Eq for DataStreamError {}
@@ -67,8 +78,11 @@ impl PartialEq for DataStreamError {
             (Self::SocketAddrError(m1), Self::SocketAddrError(m2)) => {
                 m1.to_string() == m2.to_string()
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            (Self::Proxy(m1), Self::Proxy(m2)) => m1.to_string() == m2.to_string(),
             (Self::ConnectionFailed, Self::ConnectionFailed) => true,
             (Self::ReconnectionError, Self::ReconnectionError) => true,
+            (Self::PoolExhausted, Self::PoolExhausted) => true,
             _ => false,
         }
     }
@@ -80,6 +94,16 @@ impl From<AddrParseError> for DataStreamError {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl From<super::ProxyError> for DataStreamError {
+    fn from(value: super::ProxyError) -> Self {
+        match value {
+            super::ProxyError::IO(err) => Self::IO(err),
+            other => Self::Proxy(other),
+        }
+    }
+}
+
 impl From<TlsError> for DataStreamError {
     fn from(value: TlsError) -> Self {
         Self::TLS(value)
@@ -7,9 +7,39 @@ mod no_wasm;
 #[cfg(not(target_arch = "wasm32"))]
 pub use no_wasm::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod pool;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use pool::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod proxy;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use proxy::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod resolver;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use resolver::*;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod server;
 
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+mod tls_server;
+
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+pub use tls_server::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod transport;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use transport::*;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub use server::*;
 
@@ -10,7 +10,6 @@ use crate::valtron::delayed_iterators::{DelayedIterator, SleepIterator};
 use crate::native_tls::{Identity, TlsConnector, TlsStream};
 use crate::wire::simple_http::{self};
 use core::net;
-use std::net::SocketAddr;
 use std::time::Duration;
 use std::{net::TcpStream, time};
 
@@ -207,29 +206,48 @@ impl RawStream {
         endpoint: super::Endpoint<T>,
         timeout: time::Duration,
     ) -> super::DataStreamResult<Self> {
-        let host = endpoint.host();
+        Self::from_endpoint_with_resolver(&endpoint, timeout, &super::SystemResolver)
+    }
+
+    /// `from_endpoint_with_resolver` works like [`RawStream::from_endpoint_timeout`]
+    /// but looks up the endpoint's host through the provided [`super::Resolver`],
+    /// trying each resolved address in turn.
+    pub fn from_endpoint_with_resolver<T: Clone>(
+        endpoint: &super::Endpoint<T>,
+        timeout: time::Duration,
+        resolver: &dyn super::Resolver,
+    ) -> super::DataStreamResult<Self> {
+        Self::from_endpoint_through(endpoint, timeout, resolver, &super::ProxyConfig::default())
+    }
+
+    /// `from_endpoint_through` works like [`RawStream::from_endpoint_with_resolver`]
+    /// but tunnels through the proxy `proxies` picks for the endpoint, if any.
+    /// TLS is negotiated with the endpoint itself, inside the tunnel.
+    pub fn from_endpoint_through<T: Clone>(
+        endpoint: &super::Endpoint<T>,
+        timeout: time::Duration,
+        resolver: &dyn super::Resolver,
+        proxies: &super::ProxyConfig,
+    ) -> super::DataStreamResult<Self> {
+        let url = endpoint.url();
+        let host = url.host_str().unwrap_or("localhost");
+        let port = url.port_or_known_default().unwrap_or(80);
 
-        let host_socket_addr: SocketAddr = host.parse()?;
+        let plain_stream = proxies.connect(endpoint.scheme(), host, port, timeout, resolver)?;
 
         #[cfg(feature = "native-tls")]
-        let stream = {
-            let plain_stream = TcpStream::connect_timeout(&host_socket_addr, timeout)?;
-            let encrypted_stream = if endpoint.scheme() == "https" {
-                RawStream::try_wrap_tls(plain_stream, &endpoint.host())?
-            } else {
-                RawStream::wrap_plain(plain_stream)
-            };
-            encrypted_stream
+        let stream = if endpoint.scheme() == "https" {
+            RawStream::try_wrap_tls(plain_stream, host)?
+        } else {
+            RawStream::wrap_plain(plain_stream)
         };
 
         #[cfg(not(feature = "native-tls"))]
-        let mut stream = {
-            let plain_stream = TcpStream::connect_timeout(&host_socket_addr, timeout)?;
-            RawStream::wrap_plain(plain_stream)
-        };
+        let stream = RawStream::wrap_plain(plain_stream);
 
         Ok(stream)
     }
+
     /// from_endpoint creates a naked RawStream which is not mapped to a specific
     /// protocol version and simply is a TCPStream connected to the relevant Endpoint
     /// upgrade to TLS if required.
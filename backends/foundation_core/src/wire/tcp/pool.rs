@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use super::{
+    DataStreamError, DataStreamResult, Endpoint, ProxyConfig, RawStream, SharedResolver,
+    SystemResolver,
+};
+
+/// `PoolConfig` defines how a `ConnectionPool` manages the connections
+/// it keeps for each host.
+#[derive(Clone, Debug)]
+pub struct PoolConfig {
+    /// `max_per_host` is the maximum number of connections (idle and in use)
+    /// the pool allows to a single host.
+    pub max_per_host: usize,
+
+    /// `idle_timeout` is how long a connection may sit unused in the pool
+    /// before it gets evicted.
+    pub idle_timeout: Duration,
+
+    /// `max_lifetime` is how long a connection may live in total before it
+    /// gets evicted, regardless of use.
+    pub max_lifetime: Option<Duration>,
+
+    /// `connect_timeout` is used when a new connection must be created.
+    pub connect_timeout: Duration,
+
+    /// `resolver` looks up the addresses of a host when a new connection
+    /// must be created.
+    pub resolver: SharedResolver,
+
+    /// `proxy` picks the proxy new connections are tunneled through.
+    pub proxy: ProxyConfig,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_per_host: 8,
+            idle_timeout: Duration::from_secs(90),
+            max_lifetime: None,
+            connect_timeout: Duration::from_secs(10),
+            resolver: Arc::new(SystemResolver),
+            proxy: ProxyConfig::default(),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl PoolConfig {
+    #[must_use]
+    pub fn with_max_per_host(mut self, max_per_host: usize) -> Self {
+        self.max_per_host = max_per_host.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+
+    #[must_use]
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    #[must_use]
+    pub fn with_resolver(mut self, resolver: SharedResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// `PoolMetrics` is a snapshot of the activity of a `ConnectionPool`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// created is the number of new connections made.
+    pub created: u64,
+
+    /// reused is the number of checkouts served from an idle connection.
+    pub reused: u64,
+
+    /// evicted is the number of connections dropped due to the idle
+    /// timeout, max lifetime or a full pool.
+    pub evicted: u64,
+
+    /// rejected is the number of checkouts refused as the host was at
+    /// its connection limit.
+    pub rejected: u64,
+
+    /// idle is the number of connections currently waiting in the pool.
+    pub idle: usize,
+
+    /// active is the number of connections currently checked out.
+    pub active: usize,
+}
+
+impl PoolMetrics {
+    /// `reuse_rate` returns the fraction of checkouts served from the pool.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn reuse_rate(&self) -> f64 {
+        let checkouts = self.created + self.reused;
+        if checkouts == 0 {
+            return 0.0;
+        }
+        self.reused as f64 / checkouts as f64
+    }
+}
+
+/// `PooledStream` is a `RawStream` checked out of a `ConnectionPool`,
+/// it should be returned with `ConnectionPool::checkin` when the
+/// request/response exchange is done or `ConnectionPool::discard`
+/// when the connection is no longer usable.
+///
+/// A `PooledStream` dropped without either still frees its slot in the
+/// pool, e.g when the caller returns early on an error.
+pub struct PooledStream {
+    key: String,
+    stream: RawStream,
+    created_at: Instant,
+
+    /// `pool` is set while checked out, releasing the slot on drop.
+    pool: Option<Weak<Mutex<PoolState>>>,
+}
+
+impl PooledStream {
+    #[inline]
+    pub fn stream(&self) -> &RawStream {
+        &self.stream
+    }
+
+    #[inline]
+    pub fn stream_mut(&mut self) -> &mut RawStream {
+        &mut self.stream
+    }
+
+    #[inline]
+    pub fn age(&self) -> Duration {
+        self.created_at.elapsed()
+    }
+}
+
+impl Drop for PooledStream {
+    fn drop(&mut self) {
+        let Some(pool) = self.pool.take().and_then(|pool| pool.upgrade()) else {
+            return;
+        };
+        let Ok(mut state) = pool.lock() else {
+            return;
+        };
+        state.release(&self.key);
+    }
+}
+
+impl core::fmt::Debug for PooledStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PooledStream")
+            .field("key", &self.key)
+            .field("stream", &self.stream)
+            .finish_non_exhaustive()
+    }
+}
+
+impl io::Read for PooledStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl io::Write for PooledStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+struct IdleStream {
+    stream: PooledStream,
+    idle_since: Instant,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<String, Vec<IdleStream>>,
+    active: HashMap<String, usize>,
+    metrics: PoolMetrics,
+}
+
+impl PoolState {
+    fn release(&mut self, key: &str) {
+        if let Some(count) = self.active.get_mut(key) {
+            *count = count.saturating_sub(1);
+        }
+        self.metrics.active = self.metrics.active.saturating_sub(1);
+    }
+}
+
+/// `ConnectionPool` keeps connections to hosts alive between requests,
+/// letting repeated requests to the same host reuse sockets.
+///
+/// A `ConnectionPool` is cheap to clone, all clones share the same pool.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    config: PoolConfig,
+    state: Arc<Mutex<PoolState>>,
+}
+
+// -- Constructors
+
+impl Default for ConnectionPool {
+    fn default() -> Self {
+        Self::new(PoolConfig::default())
+    }
+}
+
+impl ConnectionPool {
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(PoolState::default())),
+        }
+    }
+}
+
+// -- Methods
+
+impl ConnectionPool {
+    /// `pool_key` returns the key connections to the giving endpoint are pooled under,
+    /// plain and encrypted connections to the same host are kept apart.
+    pub fn pool_key<T: Clone>(endpoint: &Endpoint<T>) -> String {
+        format!("{}://{}", endpoint.scheme(), endpoint.host())
+    }
+
+    /// checkout returns an idle connection to the endpoint's host if one is
+    /// available, else creates a new one as long as the host is within
+    /// the pool's `max_per_host` limit.
+    pub fn checkout<T: Clone>(&self, endpoint: &Endpoint<T>) -> DataStreamResult<PooledStream> {
+        let key = Self::pool_key(endpoint);
+
+        {
+            let mut guard = self.state.lock().expect("should acquire pool lock");
+            let state = &mut *guard;
+            self.evict_expired_for(state, &key);
+
+            if let Some(idle) = state.idle.get_mut(&key).and_then(Vec::pop) {
+                state.metrics.idle -= 1;
+                state.metrics.reused += 1;
+                state.metrics.active += 1;
+                *state.active.entry(key).or_default() += 1;
+
+                let mut stream = idle.stream;
+                stream.pool = Some(Arc::downgrade(&self.state));
+                return Ok(stream);
+            }
+
+            let active = state.active.entry(key.clone()).or_default();
+            if *active >= self.config.max_per_host {
+                state.metrics.rejected += 1;
+                return Err(DataStreamError::PoolExhausted);
+            }
+
+            // reserve the slot before connecting so concurrent checkouts
+            // can't exceed the host limit.
+            *active += 1;
+            state.metrics.active += 1;
+        }
+
+        match RawStream::from_endpoint_through(
+            endpoint,
+            self.config.connect_timeout,
+            &self.config.resolver,
+            &self.config.proxy,
+        ) {
+            Ok(stream) => {
+                let mut state = self.state.lock().expect("should acquire pool lock");
+                state.metrics.created += 1;
+                Ok(PooledStream {
+                    key,
+                    stream,
+                    created_at: Instant::now(),
+                    pool: Some(Arc::downgrade(&self.state)),
+                })
+            }
+            Err(err) => {
+                let mut state = self.state.lock().expect("should acquire pool lock");
+                state.release(&key);
+                Err(err)
+            }
+        }
+    }
+
+    /// checkin returns a connection to the pool for reuse, connections past
+    /// their max lifetime or beyond the host's limit are dropped instead.
+    pub fn checkin(&self, mut stream: PooledStream) {
+        stream.pool = None;
+        let mut guard = self.state.lock().expect("should acquire pool lock");
+        let state = &mut *guard;
+        state.release(&stream.key);
+
+        let expired = self
+            .config
+            .max_lifetime
+            .is_some_and(|lifetime| stream.age() >= lifetime);
+
+        let idle = state.idle.entry(stream.key.clone()).or_default();
+        if expired || idle.len() >= self.config.max_per_host {
+            state.metrics.evicted += 1;
+            return;
+        }
+
+        idle.push(IdleStream {
+            stream,
+            idle_since: Instant::now(),
+        });
+        state.metrics.idle += 1;
+    }
+
+    /// discard drops a checked out connection that is no longer usable
+    /// (e.g the peer closed it), freeing its slot in the pool.
+    pub fn discard(&self, mut stream: PooledStream) {
+        stream.pool = None;
+        let mut state = self.state.lock().expect("should acquire pool lock");
+        state.release(&stream.key);
+        drop(stream);
+    }
+
+    /// `evict_expired` drops all idle connections past their idle timeout
+    /// or max lifetime, returning how many were dropped.
+    pub fn evict_expired(&self) -> usize {
+        let mut state = self.state.lock().expect("should acquire pool lock");
+        let keys: Vec<String> = state.idle.keys().cloned().collect();
+
+        keys.iter()
+            .map(|key| self.evict_expired_for(&mut state, key))
+            .sum()
+    }
+
+    /// metrics returns a snapshot of the pool's activity.
+    pub fn metrics(&self) -> PoolMetrics {
+        let state = self.state.lock().expect("should acquire pool lock");
+        state.metrics.clone()
+    }
+
+    fn evict_expired_for(&self, state: &mut PoolState, key: &str) -> usize {
+        let Some(idle) = state.idle.get_mut(key) else {
+            return 0;
+        };
+
+        let before = idle.len();
+        idle.retain(|entry| {
+            let idle_expired = entry.idle_since.elapsed() >= self.config.idle_timeout;
+            let lifetime_expired = self
+                .config
+                .max_lifetime
+                .is_some_and(|lifetime| entry.stream.age() >= lifetime);
+            !idle_expired && !lifetime_expired
+        });
+
+        let evicted = before - idle.len();
+        state.metrics.idle -= evicted;
+        state.metrics.evicted += evicted as u64;
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod test_connection_pool {
+    use crate::{panic_if_failed, wire::tcp::Endpoint};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    fn accept_all(listener: TcpListener) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            let mut clients = Vec::new();
+            while let Ok((client, _)) = listener.accept() {
+                clients.push(client);
+                if clients.len() >= 3 {
+                    return;
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn reuses_connections_to_the_same_host() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3811"));
+        let server = accept_all(listener);
+
+        let pool = ConnectionPool::new(PoolConfig::default().with_max_per_host(2));
+        let endpoint = panic_if_failed!(Endpoint::plain_string("http://127.0.0.1:3811"));
+
+        let first = panic_if_failed!(pool.checkout(&endpoint));
+        let first_addr = first.stream().local_addr();
+        pool.checkin(first);
+
+        let second = panic_if_failed!(pool.checkout(&endpoint));
+        assert_eq!(second.stream().local_addr(), first_addr);
+
+        let third = panic_if_failed!(pool.checkout(&endpoint));
+        assert!(matches!(
+            pool.checkout(&endpoint),
+            Err(DataStreamError::PoolExhausted)
+        ));
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.created, 2);
+        assert_eq!(metrics.reused, 1);
+        assert_eq!(metrics.rejected, 1);
+        assert_eq!(metrics.active, 2);
+
+        pool.discard(second);
+        pool.checkin(third);
+        assert_eq!(pool.metrics().active, 0);
+        assert_eq!(pool.metrics().idle, 1);
+
+        drop(pool);
+        let _ = std::net::TcpStream::connect("127.0.0.1:3811");
+        server.join().expect("should close server");
+    }
+
+    #[test]
+    fn dropped_streams_free_their_slot() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3820"));
+        let server = accept_all(listener);
+
+        let pool = ConnectionPool::new(PoolConfig::default().with_max_per_host(1));
+        let endpoint = panic_if_failed!(Endpoint::plain_string("http://127.0.0.1:3820"));
+
+        let abandoned = panic_if_failed!(pool.checkout(&endpoint));
+        drop(abandoned);
+        assert_eq!(pool.metrics().active, 0);
+
+        let stream = panic_if_failed!(pool.checkout(&endpoint));
+        pool.checkin(stream);
+        let reused = panic_if_failed!(pool.checkout(&endpoint));
+        pool.discard(reused);
+
+        let metrics = pool.metrics();
+        assert_eq!((metrics.active, metrics.idle, metrics.rejected), (0, 0, 0));
+
+        drop(pool);
+        let _ = std::net::TcpStream::connect("127.0.0.1:3820");
+        server.join().expect("should close server");
+    }
+
+    #[test]
+    fn evicts_idle_connections_after_timeout() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3812"));
+        let server = accept_all(listener);
+
+        let pool =
+            ConnectionPool::new(PoolConfig::default().with_idle_timeout(Duration::from_millis(20)));
+        let endpoint = panic_if_failed!(Endpoint::plain_string("http://127.0.0.1:3812"));
+
+        let stream = panic_if_failed!(pool.checkout(&endpoint));
+        pool.checkin(stream);
+        assert_eq!(pool.metrics().idle, 1);
+
+        thread::sleep(Duration::from_millis(40));
+        assert_eq!(pool.evict_expired(), 1);
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.idle, 0);
+        assert_eq!(metrics.evicted, 1);
+
+        drop(pool);
+        let _ = std::net::TcpStream::connect("127.0.0.1:3812");
+        let _ = std::net::TcpStream::connect("127.0.0.1:3812");
+        server.join().expect("should close server");
+    }
+
+    #[test]
+    fn connects_through_the_configured_resolver() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3816"));
+        let server = accept_all(listener);
+
+        let resolver = crate::wire::tcp::StaticResolver::default()
+            .with_host("pinned.internal", std::net::Ipv4Addr::LOCALHOST.into());
+        let pool = ConnectionPool::new(PoolConfig::default().with_resolver(Arc::new(resolver)));
+        let endpoint = panic_if_failed!(Endpoint::plain_string("http://pinned.internal:3816"));
+
+        let stream = panic_if_failed!(pool.checkout(&endpoint));
+        assert_eq!(stream.stream().peer_addr().port(), 3816);
+        pool.discard(stream);
+
+        let _ = std::net::TcpStream::connect("127.0.0.1:3816");
+        let _ = std::net::TcpStream::connect("127.0.0.1:3816");
+        server.join().expect("should close server");
+    }
+}
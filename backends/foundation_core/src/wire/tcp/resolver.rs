@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// `Resolver` turns a host name and port into the socket addresses a
+/// connection should be attempted against, in order of preference.
+pub trait Resolver: Send + Sync + Debug {
+    /// `resolve` returns the addresses for `host` and `port`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] when the host cannot be resolved or resolves
+    /// to no address at all.
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+pub type SharedResolver = Arc<dyn Resolver>;
+
+impl<R: Resolver + ?Sized> Resolver for Arc<R> {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        (**self).resolve(host, port)
+    }
+}
+
+/// `SystemResolver` resolves through the operating system, i.e
+/// `getaddrinfo` on most platforms.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        if let Ok(ip) = host.trim_matches(['[', ']']).parse::<IpAddr>() {
+            return Ok(vec![SocketAddr::new(ip, port)]);
+        }
+
+        let addrs: Vec<SocketAddr> = (host, port).to_socket_addrs()?.collect();
+        if addrs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no addresses found for {host}"),
+            ));
+        }
+        Ok(addrs)
+    }
+}
+
+type CachedAddrs = HashMap<(String, u16), (Instant, Vec<SocketAddr>)>;
+
+/// `CachingResolver` keeps the answers of another resolver for `ttl`, so
+/// repeated connections to a host do not pay for a lookup each time.
+///
+/// Failed lookups are never cached.
+#[derive(Debug)]
+pub struct CachingResolver<R: Resolver> {
+    inner: R,
+    ttl: Duration,
+    entries: Mutex<CachedAddrs>,
+}
+
+// -- Constructors
+
+impl<R: Resolver> CachingResolver<R> {
+    pub fn new(inner: R, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CachingResolver<SystemResolver> {
+    #[must_use]
+    pub fn system(ttl: Duration) -> Self {
+        Self::new(SystemResolver, ttl)
+    }
+}
+
+// -- Methods
+
+impl<R: Resolver> CachingResolver<R> {
+    /// `clear` drops every cached entry.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .expect("should acquire resolver lock")
+            .clear();
+    }
+
+    /// `evict_expired` drops the entries older than the ttl, returning how
+    /// many were dropped.
+    pub fn evict_expired(&self) -> usize {
+        let mut entries = self.entries.lock().expect("should acquire resolver lock");
+        let before = entries.len();
+        entries.retain(|_, (resolved_at, _)| resolved_at.elapsed() < self.ttl);
+        before - entries.len()
+    }
+}
+
+impl<R: Resolver> Resolver for CachingResolver<R> {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = (host.to_ascii_lowercase(), port);
+        {
+            let entries = self.entries.lock().expect("should acquire resolver lock");
+            if let Some((resolved_at, addrs)) = entries.get(&key) {
+                if resolved_at.elapsed() < self.ttl {
+                    return Ok(addrs.clone());
+                }
+            }
+        }
+
+        // the lock is not held during the lookup so a slow host does not
+        // block lookups of every other host.
+        let addrs = self.inner.resolve(host, port)?;
+        self.entries
+            .lock()
+            .expect("should acquire resolver lock")
+            .insert(key, (Instant::now(), addrs.clone()));
+        Ok(addrs)
+    }
+}
+
+/// `StaticResolver` answers from a fixed map of host names to addresses,
+/// falling back to another resolver for hosts it does not know.
+///
+/// Useful to pin a host to a known address or to point a client at a
+/// local server in tests.
+#[derive(Clone, Debug, Default)]
+pub struct StaticResolver {
+    overrides: HashMap<String, Vec<IpAddr>>,
+    fallback: Option<SharedResolver>,
+}
+
+// -- Builder methods
+
+impl StaticResolver {
+    /// `with_host` maps `host` to `addr`, calling it again for the same
+    /// host adds another address.
+    #[must_use]
+    pub fn with_host(mut self, host: impl Into<String>, addr: IpAddr) -> Self {
+        self.overrides
+            .entry(host.into().to_ascii_lowercase())
+            .or_default()
+            .push(addr);
+        self
+    }
+
+    /// `with_fallback` sets the resolver used for hosts without an
+    /// override, without one such hosts fail to resolve.
+    #[must_use]
+    pub fn with_fallback(mut self, fallback: impl Resolver + 'static) -> Self {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        if let Some(addrs) = self.overrides.get(&host.to_ascii_lowercase()) {
+            return Ok(addrs.iter().map(|ip| SocketAddr::new(*ip, port)).collect());
+        }
+
+        match &self.fallback {
+            Some(fallback) => fallback.resolve(host, port),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no override for {host}"),
+            )),
+        }
+    }
+}
+
+/// `connect_resolved` resolves `host` with `resolver` and connects to the
+/// first address that accepts the connection. A zero `timeout` uses the
+/// operating system's default connect timeout.
+///
+/// # Errors
+///
+/// Returns the resolution error, or the error of the last address tried
+/// when none of them accepted the connection.
+pub fn connect_resolved(
+    resolver: &dyn Resolver,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::from(io::ErrorKind::AddrNotAvailable);
+    for addr in resolver.resolve(host, port)? {
+        let connected = if timeout.is_zero() {
+            TcpStream::connect(addr)
+        } else {
+            TcpStream::connect_timeout(&addr, timeout)
+        };
+        match connected {
+            Ok(stream) => return Ok(stream),
+            Err(err) => {
+                tracing::debug!("Failed to connect to {addr} for {host}: {err}");
+                last_error = err;
+            }
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod test_resolver {
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct CountingResolver(AtomicUsize);
+
+    impl Resolver for CountingResolver {
+        fn resolve(&self, _host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+            let calls = self.0.fetch_add(1, Ordering::SeqCst);
+            let last = u8::try_from(calls % 250).expect("should fit") + 1;
+            Ok(vec![SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)),
+                port,
+            )])
+        }
+    }
+
+    #[test]
+    fn static_overrides_take_precedence_over_fallback() {
+        let resolver = StaticResolver::default()
+            .with_host("api.example.com", IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .with_fallback(SystemResolver);
+
+        assert_eq!(
+            resolver
+                .resolve("API.example.com", 8080)
+                .expect("should resolve"),
+            vec![SocketAddr::from(([127, 0, 0, 1], 8080))]
+        );
+        assert_eq!(
+            resolver.resolve("10.1.2.3", 443).expect("should resolve"),
+            vec![SocketAddr::from(([10, 1, 2, 3], 443))]
+        );
+        assert!(StaticResolver::default()
+            .resolve("api.example.com", 80)
+            .is_err());
+    }
+
+    #[test]
+    fn caching_resolver_reuses_answers_until_the_ttl_expires() {
+        let resolver = CachingResolver::new(CountingResolver::default(), Duration::from_millis(30));
+
+        let first = resolver.resolve("example.com", 80).expect("should resolve");
+        assert_eq!(
+            resolver.resolve("EXAMPLE.com", 80).expect("should resolve"),
+            first
+        );
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 1);
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(resolver.evict_expired(), 1);
+        assert_ne!(
+            resolver.resolve("example.com", 80).expect("should resolve"),
+            first
+        );
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 2);
+    }
+}
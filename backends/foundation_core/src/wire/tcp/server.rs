@@ -1,20 +1,23 @@
 use derive_more::From;
 use std::{
     io::Write,
-    net::{TcpListener, TcpStream},
     sync::mpsc,
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use crate::{
     extensions::result_ext::{BoxedError, BoxedResult},
-    io::ioutils,
+    io::ioutils::{self, PeekableReadStream},
     wire::simple_http::{
-        self, Http11, IncomingRequestParts, Proto, RenderHttp, ServiceAction, ServiceActionList,
-        SimpleIncomingRequest, SimpleOutgoingResponse, Status, WrappedTcpStream,
+        self, AccessLogEntry, Http11, IncomingRequestParts, Proto, RenderHttp, ServiceAction,
+        ServiceActionList, SimpleHttpBody, SimpleIncomingRequest, SimpleOutgoingResponse, Status,
+        TraceContext,
     },
 };
 
+use super::{SystemResolver, TransportAddr, TransportListener, TransportStream};
+
 pub type TestServerResult<T> = std::result::Result<T, TestServerError>;
 
 #[derive(From, Debug)]
@@ -31,24 +34,43 @@ impl core::fmt::Display for TestServerError {
 }
 
 pub struct TestServer {
-    port: usize,
-    address: String,
+    addr: TransportAddr,
     actions: Vec<ServiceAction>,
+
+    #[cfg(feature = "rustls")]
+    tls: Option<super::TlsAcceptor>,
 }
 
 impl TestServer {
     pub fn new(port: usize, address: String, actions: Vec<ServiceAction>) -> Self {
+        let port = u16::try_from(port).expect("port should be a valid tcp port");
+        Self::with_transport(TransportAddr::tcp(address, port), actions)
+    }
+
+    /// `with_transport` creates a server listening on any supported transport,
+    /// e.g a Unix domain socket instead of a TCP port.
+    pub fn with_transport(addr: TransportAddr, actions: Vec<ServiceAction>) -> Self {
         Self {
-            port,
-            address,
+            addr,
             actions,
+            #[cfg(feature = "rustls")]
+            tls: None,
         }
     }
 
+    /// `with_tls_acceptor` serves https, every accepted connection goes
+    /// through the TLS handshake before its requests are read.
+    ///
+    /// [`Self::close`] still connects in plaintext.
+    #[cfg(feature = "rustls")]
+    #[must_use]
+    pub fn with_tls_acceptor(mut self, acceptor: super::TlsAcceptor) -> Self {
+        self.tls = Some(acceptor);
+        self
+    }
+
     pub fn close(&self) -> Result<(), BoxedError> {
-        let port = self.port;
-        let address = self.address.clone();
-        let mut client = TcpStream::connect(format!("{}:{}", address, port))
+        let mut client = TransportStream::connect(&self.addr, Duration::ZERO, &SystemResolver)
             .map_err(|err| err.into_boxed_error())?;
 
         client
@@ -64,21 +86,21 @@ impl TestServer {
         mpsc::Receiver<SimpleIncomingRequest>,
         mpsc::Receiver<JoinHandle<()>>,
     ) {
-        let port = self.port;
-        let address = self.address.clone();
         let actions = self.actions.clone();
 
+        #[cfg(feature = "rustls")]
+        let tls = self.tls.clone();
+
         let (tx, rx) = mpsc::channel::<SimpleIncomingRequest>();
         let (workers_tx, workers_rx) = mpsc::channel::<JoinHandle<()>>();
 
-        let listener =
-            TcpListener::bind(format!("{}:{}", address, port)).expect("create tcp listener");
+        let listener = TransportListener::bind(&self.addr).expect("create listener");
 
         (
             thread::spawn(move || {
                 for stream_result in listener.incoming() {
                     match stream_result {
-                        Ok(stream) => {
+                        Ok(mut stream) => {
                             let mut buffer = [0; 512];
                             stream.peek(&mut buffer).unwrap();
 
@@ -86,9 +108,15 @@ impl TestServer {
                                 break;
                             }
 
-                            workers_tx
-                                .send(Self::serve_connection(stream, actions.clone(), tx.clone()))
-                                .expect("should save worker handler");
+                            let worker = Self::serve_connection(
+                                stream,
+                                actions.clone(),
+                                tx.clone(),
+                                #[cfg(feature = "rustls")]
+                                tls.clone(),
+                            );
+
+                            workers_tx.send(worker).expect("should save worker handler");
                         }
                         Err(err) => return Err(err.into_boxed_error()),
                     }
@@ -101,25 +129,43 @@ impl TestServer {
     }
 
     fn serve_connection(
-        read_stream: TcpStream,
+        read_stream: TransportStream,
         actions: Vec<ServiceAction>,
         sender: mpsc::Sender<SimpleIncomingRequest>,
+        #[cfg(feature = "rustls")] tls: Option<super::TlsAcceptor>,
     ) -> JoinHandle<()> {
         let action_list = ServiceActionList::new(actions);
 
         thread::spawn(move || {
+            // the handshake happens here rather than in the accept loop so
+            // a slow client does not hold up other connections.
+            #[cfg(feature = "rustls")]
+            let read_stream = match tls {
+                Some(acceptor) => match acceptor.accept_transport(read_stream) {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        tracing::error!("Failed TLS handshake: {:?}", err);
+                        return;
+                    }
+                },
+                None => read_stream,
+            };
+
+            let peer = read_stream.peer_addr();
             let mut write_stream = read_stream
                 .try_clone()
                 .expect("should be able to clone connection");
 
-            let mut request_reader = simple_http::HttpReader::simple_tcp_stream(
-                ioutils::BufferedReader::new(WrappedTcpStream::new(read_stream)),
+            let mut request_reader = simple_http::HttpReader::new(
+                ioutils::BufferedReader::new(read_stream),
+                SimpleHttpBody,
             );
 
             loop {
                 // fetch the intro portion and validate we have resources for processing request
                 // if not, just break and return an error
 
+                let started_at = Instant::now();
                 let (method, url, proto) =
                     if let Some(Ok(IncomingRequestParts::Intro(method, url, proto))) =
                         request_reader.next()
@@ -169,6 +215,9 @@ impl TestServer {
                     break;
                 };
 
+                let trace = TraceContext::from_headers(&headers);
+                let path = url.url.clone();
+
                 if let Ok(request) = SimpleIncomingRequest::builder()
                     .with_headers(headers)
                     .with_url(url)
@@ -178,6 +227,7 @@ impl TestServer {
                 {
                     let mut cloned_request = request.clone();
                     cloned_request.body = body;
+                    let request_method = request.method.clone();
 
                     sender.send(request).expect("should sent request");
 
@@ -186,6 +236,9 @@ impl TestServer {
                         Err(err) => Self::internal_server_error_response(err),
                     };
 
+                    let status = outgoing_response.status.clone();
+                    let bytes = AccessLogEntry::response_bytes(outgoing_response.body.as_ref());
+
                     let response = Http11::response(outgoing_response);
                     match response.http_render() {
                         Ok(renderer) => {
@@ -203,6 +256,17 @@ impl TestServer {
                             return;
                         }
                     }
+
+                    AccessLogEntry {
+                        method: request_method,
+                        path,
+                        status,
+                        duration: started_at.elapsed(),
+                        bytes,
+                        peer: peer.clone(),
+                        trace,
+                    }
+                    .emit();
                 }
 
                 // if we ever get here, just break.
@@ -431,4 +495,103 @@ Hello buster!";
         let sent_requests: Vec<SimpleIncomingRequest> = requests.iter().collect();
         assert_eq!(sent_requests.len(), 0);
     }
+
+    #[test]
+    #[cfg(unix)]
+    #[traced_test]
+    fn test_can_use_test_server_over_unix_socket() {
+        let resource = ServiceAction::builder()
+            .with_route("/v1/info")
+            .with_method(SimpleMethod::GET)
+            .with_body(FuncSimpleServer::new(|_| {
+                SimpleOutgoingResponse::builder()
+                    .with_status(Status::OK)
+                    .with_body_string("ready")
+                    .build()
+                    .map_err(BoxedResult::into_boxed_error)
+            }))
+            .build()
+            .expect("should generate service action");
+
+        let path =
+            std::env::temp_dir().join(format!("ewe-test-server-{}.sock", std::process::id()));
+        let addr = super::TransportAddr::unix(&path);
+        let test_server = TestServer::with_transport(addr.clone(), vec![resource]);
+        let (handler, requests, workers) = test_server.serve();
+
+        let mut client = t!(super::TransportStream::connect(
+            &addr,
+            std::time::Duration::ZERO,
+            &super::SystemResolver
+        ));
+        t!(client.write(
+            b"GET /v1/info HTTP/1.1\r\nHost: localhost\r\ntraceparent: 00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01\r\n\r\n"
+        ));
+
+        let mut response = [0; 64];
+        let read = t!(client.read(&mut response));
+        assert!(String::from_utf8_lossy(&response[..read]).starts_with("HTTP/1.1 200 Ok\r\n"));
+        drop(client);
+
+        test_server.close().expect("should close server");
+        t!(handler.join().expect("should join server"));
+        for worker_handler in workers {
+            worker_handler.join().expect("should have closed");
+        }
+
+        assert_eq!(requests.iter().count(), 1);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    #[cfg(all(feature = "rustls", feature = "native-tls"))]
+    #[traced_test]
+    fn test_can_use_test_server_over_tls() {
+        let resource = ServiceAction::builder()
+            .with_route("/v1/info")
+            .with_method(SimpleMethod::GET)
+            .with_body(FuncSimpleServer::new(|_| {
+                SimpleOutgoingResponse::builder()
+                    .with_status(Status::OK)
+                    .with_body_string("secure")
+                    .build()
+                    .map_err(BoxedResult::into_boxed_error)
+            }))
+            .build()
+            .expect("should generate service action");
+
+        let store = std::sync::Arc::new(super::super::CertificateStore::new());
+        store
+            .load_pem_files(
+                "localhost",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/test_certs/localhost.cert.pem"),
+                concat!(env!("CARGO_MANIFEST_DIR"), "/test_certs/localhost.key.pem"),
+            )
+            .expect("should load certificate");
+        let acceptor = t!(super::super::TlsAcceptor::new(store));
+
+        let test_server =
+            TestServer::new(3831, "127.0.0.1".into(), vec![resource]).with_tls_acceptor(acceptor);
+        let (handler, requests, workers) = test_server.serve();
+
+        let connector = t!(crate::native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .build());
+        let mut client =
+            t!(connector.connect("localhost", t!(TcpStream::connect("127.0.0.1:3831"))));
+        t!(client.write(b"GET /v1/info HTTP/1.1\r\nHost: localhost\r\n\r\n"));
+
+        let mut response = [0; 64];
+        let read = t!(client.read(&mut response));
+        assert!(String::from_utf8_lossy(&response[..read]).starts_with("HTTP/1.1 200 Ok\r\n"));
+        drop(client);
+
+        test_server.close().expect("should close server");
+        t!(handler.join().expect("should join server"));
+        for worker_handler in workers {
+            worker_handler.join().expect("should have closed");
+        }
+
+        assert_eq!(requests.iter().count(), 1);
+    }
 }
@@ -0,0 +1,438 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard, RwLock},
+    time::Duration,
+};
+
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    ServerConfig, ServerConnection, StreamOwned,
+};
+
+use crate::io::ioutils::BufferedReader;
+
+use super::{TlsError, TlsResult, TransportStream};
+
+/// `CertificateStore` holds the certificates a [`TlsAcceptor`] serves,
+/// picking one per connection from the SNI host name the client sent.
+///
+/// Certificates can be inserted, replaced or removed while the server
+/// runs, new handshakes pick up the change and established connections
+/// keep the certificate they negotiated.
+#[derive(Debug, Default)]
+pub struct CertificateStore {
+    hosts: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+    default: RwLock<Option<Arc<CertifiedKey>>>,
+}
+
+// -- Constructors
+
+impl CertificateStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `certified_key_from_pem` parses a PEM certificate chain and its
+    /// private key (PKCS#8, PKCS#1 or SEC1).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::InvalidCertificate`] when either PEM block is
+    /// missing or the key type is not supported.
+    pub fn certified_key_from_pem(cert_pem: &[u8], key_pem: &[u8]) -> TlsResult<CertifiedKey> {
+        let certs = rustls_pemfile::certs(&mut io::Cursor::new(cert_pem))
+            .collect::<Result<Vec<CertificateDer<'static>>, io::Error>>()
+            .map_err(|err| TlsError::InvalidCertificate(err.to_string()))?;
+        if certs.is_empty() {
+            return Err(TlsError::InvalidCertificate(
+                "no certificate found in pem".into(),
+            ));
+        }
+
+        let key: PrivateKeyDer<'static> =
+            rustls_pemfile::private_key(&mut io::Cursor::new(key_pem))
+                .map_err(|err| TlsError::InvalidCertificate(err.to_string()))?
+                .ok_or_else(|| {
+                    TlsError::InvalidCertificate("no private key found in pem".into())
+                })?;
+
+        let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+            .map_err(|err| TlsError::InvalidCertificate(err.to_string()))?;
+
+        Ok(CertifiedKey::new(certs, signing_key))
+    }
+}
+
+// -- Methods
+
+impl CertificateStore {
+    /// `insert` serves `key` to clients asking for `host`, replacing the
+    /// previous certificate of that host if any.
+    ///
+    /// `host` can be a wildcard like `*.example.com`, which matches any
+    /// direct subdomain that has no exact entry of its own.
+    pub fn insert(&self, host: &str, key: CertifiedKey) {
+        self.hosts
+            .write()
+            .expect("should acquire certificate store lock")
+            .insert(host.to_ascii_lowercase(), Arc::new(key));
+    }
+
+    /// `load_pem_files` reads the certificate chain and key at the given
+    /// paths and serves them for `host`, calling it again with renewed
+    /// files hot swaps the certificate.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::IO`] if a file cannot be read or
+    /// [`TlsError::InvalidCertificate`] if its content is not usable.
+    pub fn load_pem_files(
+        &self,
+        host: &str,
+        cert_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> TlsResult<()> {
+        let cert_pem = std::fs::read(cert_path)?;
+        let key_pem = std::fs::read(key_path)?;
+        self.insert(host, Self::certified_key_from_pem(&cert_pem, &key_pem)?);
+        Ok(())
+    }
+
+    pub fn remove(&self, host: &str) -> bool {
+        self.hosts
+            .write()
+            .expect("should acquire certificate store lock")
+            .remove(&host.to_ascii_lowercase())
+            .is_some()
+    }
+
+    /// `set_default` sets the certificate served when the client sent no
+    /// SNI host name or one that matches no entry.
+    pub fn set_default(&self, key: CertifiedKey) {
+        *self
+            .default
+            .write()
+            .expect("should acquire certificate store lock") = Some(Arc::new(key));
+    }
+
+    /// `resolve_host` returns the certificate for `host`: an exact match
+    /// first, then a wildcard for its parent domain, then the default.
+    pub fn resolve_host(&self, host: Option<&str>) -> Option<Arc<CertifiedKey>> {
+        if let Some(host) = host.map(str::to_ascii_lowercase) {
+            let hosts = self
+                .hosts
+                .read()
+                .expect("should acquire certificate store lock");
+
+            if let Some(key) = hosts.get(&host) {
+                return Some(key.clone());
+            }
+
+            if let Some((_, parent)) = host.split_once('.') {
+                if let Some(key) = hosts.get(&format!("*.{parent}")) {
+                    return Some(key.clone());
+                }
+            }
+        }
+
+        self.default
+            .read()
+            .expect("should acquire certificate store lock")
+            .clone()
+    }
+}
+
+impl ResolvesServerCert for CertificateStore {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let key = self.resolve_host(client_hello.server_name());
+        if key.is_none() {
+            tracing::warn!(
+                "No certificate found for server name: {:?}",
+                client_hello.server_name()
+            );
+        }
+        key
+    }
+}
+
+/// `TlsAcceptor` performs the server side of TLS handshakes with rustls,
+/// serving certificates from a shared [`CertificateStore`].
+#[derive(Clone, Debug)]
+pub struct TlsAcceptor {
+    config: Arc<ServerConfig>,
+}
+
+// -- Constructors
+
+impl TlsAcceptor {
+    /// `new` creates an acceptor for TLS 1.2 and 1.3 using the ring crypto
+    /// provider, without client authentication.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::ConnectorCreation`] if the provider cannot
+    /// support the default protocol versions.
+    pub fn new(store: Arc<CertificateStore>) -> TlsResult<Self> {
+        let config =
+            ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .map_err(|err| {
+                    tracing::error!("Failed to create TLS server config: {:?}", err);
+                    TlsError::ConnectorCreation
+                })?
+                .with_no_client_auth()
+                .with_cert_resolver(store);
+
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+}
+
+// -- Builder methods
+
+impl TlsAcceptor {
+    /// `with_alpn_protocols` sets the protocols offered during ALPN, in
+    /// order of preference, e.g `b"h2"` then `b"http/1.1"`.
+    #[must_use]
+    pub fn with_alpn_protocols(mut self, protocols: Vec<Vec<u8>>) -> Self {
+        Arc::make_mut(&mut self.config).alpn_protocols = protocols;
+        self
+    }
+}
+
+// -- Methods
+
+impl TlsAcceptor {
+    /// `accept` completes the handshake on `stream`, blocking until it is
+    /// done or the stream's read timeout expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Handshake`] if the handshake failed.
+    pub fn accept(&self, mut stream: TcpStream) -> TlsResult<ServerTlsStream> {
+        let mut connection = ServerConnection::new(self.config.clone()).map_err(|err| {
+            tracing::error!("Failed to create TLS server connection: {:?}", err);
+            TlsError::Handshake
+        })?;
+
+        while connection.is_handshaking() {
+            if let Err(err) = connection.complete_io(&mut stream) {
+                tracing::debug!("TLS handshake failed: {:?}", err);
+                return Err(TlsError::Handshake);
+            }
+        }
+
+        Ok(ServerTlsStream {
+            inner: Arc::new(Mutex::new(StreamOwned::new(connection, stream))),
+        })
+    }
+
+    /// `accept_transport` wraps an accepted [`TransportStream`] in TLS, only
+    /// TCP streams can be upgraded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TlsError::Handshake`] if the handshake failed and
+    /// [`TlsError::IO`] for streams that are not plain TCP.
+    pub fn accept_transport(&self, stream: TransportStream) -> TlsResult<TransportStream> {
+        match stream {
+            TransportStream::Tcp(stream) => self
+                .accept(stream)
+                .map(|stream| TransportStream::Tls(BufferedReader::new(stream))),
+            other => Err(TlsError::IO(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("cannot upgrade {other:?} to TLS"),
+            ))),
+        }
+    }
+}
+
+/// `ServerTlsStream` is the server end of an established TLS connection.
+///
+/// Clones share the same connection, they take turns on it so a clone
+/// should not write while another is blocked reading.
+#[derive(Clone)]
+pub struct ServerTlsStream {
+    inner: Arc<Mutex<StreamOwned<ServerConnection, TcpStream>>>,
+}
+
+impl ServerTlsStream {
+    fn lock(&self) -> MutexGuard<'_, StreamOwned<ServerConnection, TcpStream>> {
+        self.inner.lock().expect("should acquire tls stream lock")
+    }
+
+    /// `server_name` is the SNI host name the client asked for.
+    pub fn server_name(&self) -> Option<String> {
+        self.lock().conn.server_name().map(String::from)
+    }
+
+    /// `alpn_protocol` is the protocol agreed on during ALPN, if any.
+    pub fn alpn_protocol(&self) -> Option<Vec<u8>> {
+        self.lock().conn.alpn_protocol().map(<[u8]>::to_vec)
+    }
+
+    /// # Errors
+    ///
+    /// Returns the [`io::Error`] of the underlying socket.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.lock().sock.peer_addr()
+    }
+
+    /// # Errors
+    ///
+    /// Returns the [`io::Error`] of the underlying socket.
+    pub fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+        self.lock().sock.set_read_timeout(duration)
+    }
+}
+
+impl core::fmt::Debug for ServerTlsStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerTlsStream")
+            .field("server_name", &self.server_name())
+            .field("peer_addr", &self.peer_addr().ok())
+            .finish()
+    }
+}
+
+impl Read for ServerTlsStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.lock().read(buf)
+    }
+}
+
+impl Write for ServerTlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+#[cfg(test)]
+mod test_tls_server {
+    use std::net::TcpListener;
+
+    use super::*;
+
+    const LOCALHOST_CERT: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_certs/localhost.cert.pem"
+    ));
+    const LOCALHOST_KEY: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_certs/localhost.key.pem"
+    ));
+    const API_CERT: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_certs/api.localhost.cert.pem"
+    ));
+    const API_KEY: &[u8] = include_bytes!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_certs/api.localhost.key.pem"
+    ));
+
+    fn key(cert: &[u8], key: &[u8]) -> CertifiedKey {
+        CertificateStore::certified_key_from_pem(cert, key).expect("should parse pem")
+    }
+
+    fn leaf(key: &CertifiedKey) -> Vec<u8> {
+        key.cert[0].to_vec()
+    }
+
+    #[test]
+    fn resolves_exact_then_wildcard_then_default() {
+        let store = CertificateStore::new();
+        assert!(store.resolve_host(Some("localhost")).is_none());
+
+        store.insert("API.localhost", key(API_CERT, API_KEY));
+        store.insert("*.localhost", key(LOCALHOST_CERT, LOCALHOST_KEY));
+        store.set_default(key(LOCALHOST_CERT, LOCALHOST_KEY));
+
+        let api = store
+            .resolve_host(Some("api.LOCALHOST"))
+            .expect("should match");
+        assert_eq!(leaf(&api), leaf(&key(API_CERT, API_KEY)));
+
+        let wildcard = store
+            .resolve_host(Some("web.localhost"))
+            .expect("should match");
+        assert_eq!(leaf(&wildcard), leaf(&key(LOCALHOST_CERT, LOCALHOST_KEY)));
+
+        assert!(store.remove("api.localhost"));
+        let replaced = store
+            .resolve_host(Some("api.localhost"))
+            .expect("should match");
+        assert_eq!(leaf(&replaced), leaf(&key(LOCALHOST_CERT, LOCALHOST_KEY)));
+        assert!(store.resolve_host(None).is_some());
+
+        assert_eq!(
+            CertificateStore::certified_key_from_pem(b"", LOCALHOST_KEY).err(),
+            Some(TlsError::InvalidCertificate(
+                "no certificate found in pem".into()
+            ))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "native-tls")]
+    fn serves_certificate_matching_sni() {
+        let store = Arc::new(CertificateStore::new());
+        store.set_default(key(LOCALHOST_CERT, LOCALHOST_KEY));
+        store.insert("api.localhost", key(API_CERT, API_KEY));
+        let acceptor = TlsAcceptor::new(store.clone()).expect("should create acceptor");
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("should bind");
+        let addr = listener.local_addr().expect("should have address");
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().expect("should accept");
+                let mut stream = acceptor.accept(stream).expect("should handshake");
+
+                let mut request = [0; 4];
+                stream.read_exact(&mut request).expect("should read");
+                assert_eq!(&request, b"PING");
+                let name = stream.server_name().unwrap_or_default();
+                stream.write_all(name.as_bytes()).expect("should write");
+                stream.flush().expect("should flush");
+            }
+        });
+
+        let connector = crate::native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()
+            .expect("should build connector");
+
+        for (domain, expected) in [
+            ("api.localhost", key(API_CERT, API_KEY)),
+            ("localhost", key(LOCALHOST_CERT, LOCALHOST_KEY)),
+        ] {
+            let tcp = TcpStream::connect(addr).expect("should connect");
+            let mut client = connector.connect(domain, tcp).expect("should handshake");
+            let presented = client
+                .peer_certificate()
+                .expect("should read certificate")
+                .expect("should have certificate")
+                .to_der()
+                .expect("should encode certificate");
+            assert_eq!(presented, leaf(&expected));
+
+            client.write_all(b"PING").expect("should write");
+            let mut name = vec![0; domain.len()];
+            client.read_exact(&mut name).expect("should read");
+            assert_eq!(name, domain.as_bytes());
+        }
+
+        server.join().expect("should finish");
+    }
+}
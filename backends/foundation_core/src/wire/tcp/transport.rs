@@ -0,0 +1,442 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use crate::io::ioutils::{BufferedReader, PeekError, PeekableReadStream};
+
+use super::{connect_resolved, Resolver};
+
+/// `TransportAddr` is where a connection is made to or accepted from,
+/// which is not necessarily a TCP socket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransportAddr {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+
+    /// `Unix` is the path of a Unix domain socket, e.g
+    /// `/var/run/docker.sock`.
+    #[cfg(unix)]
+    Unix(PathBuf),
+
+    /// `NamedPipe` is the full name of a Windows named pipe, e.g
+    /// `\\.\pipe\docker_engine`.
+    #[cfg(all(windows, feature = "named-pipes"))]
+    NamedPipe(String),
+}
+
+// -- Constructors
+
+impl TransportAddr {
+    pub fn tcp(host: impl Into<String>, port: u16) -> Self {
+        Self::Tcp {
+            host: host.into(),
+            port,
+        }
+    }
+
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::Unix(path.into())
+    }
+
+    #[cfg(all(windows, feature = "named-pipes"))]
+    pub fn named_pipe(name: impl Into<String>) -> Self {
+        Self::NamedPipe(name.into())
+    }
+
+    /// `from_url` picks the transport for `url`:
+    ///
+    /// - `unix:///var/run/docker.sock` uses the url's path as the socket.
+    /// - `http+unix://%2Fvar%2Frun%2Fdocker.sock/v1/info` uses the percent
+    ///   encoded host as the socket, leaving the path for the request.
+    /// - `npipe:////./pipe/docker_engine` uses the url's path as the pipe name.
+    /// - anything else connects over TCP to the url's host and port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::Unsupported`] error when the url asks for a
+    /// transport that is not available on this platform.
+    pub fn from_url(url: &url::Url) -> io::Result<Self> {
+        let scheme = url.scheme();
+
+        #[cfg(unix)]
+        match scheme {
+            "unix" => return Ok(Self::Unix(PathBuf::from(url.path()))),
+            "http+unix" | "https+unix" => {
+                let host = url.host_str().unwrap_or_default();
+                let path = url::percent_encoding::percent_decode(host.as_bytes())
+                    .decode_utf8_lossy()
+                    .to_string();
+                return Ok(Self::Unix(PathBuf::from(path)));
+            }
+            _ => {}
+        }
+
+        #[cfg(all(windows, feature = "named-pipes"))]
+        if scheme == "npipe" {
+            return Ok(Self::NamedPipe(url.path().replace('/', "\\")));
+        }
+
+        if matches!(scheme, "unix" | "http+unix" | "https+unix" | "npipe") {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{scheme} transport is not supported here"),
+            ));
+        }
+
+        Ok(Self::Tcp {
+            host: url.host_str().unwrap_or("localhost").to_string(),
+            port: url.port_or_known_default().unwrap_or(80),
+        })
+    }
+}
+
+impl core::fmt::Display for TransportAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp { host, port } => write!(f, "{host}:{port}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(name) => write!(f, "npipe:{name}"),
+        }
+    }
+}
+
+/// `TransportStream` is a connected stream over any of the supported
+/// transports, it reads and writes the same regardless of which one.
+///
+/// Streams that cannot peek natively are kept behind a [`BufferedReader`]
+/// the same way TLS streams are in [`super::RawStream`].
+pub enum TransportStream {
+    Tcp(TcpStream),
+
+    #[cfg(unix)]
+    Unix(BufferedReader<UnixStream>),
+
+    #[cfg(all(windows, feature = "named-pipes"))]
+    NamedPipe(BufferedReader<std::fs::File>),
+
+    /// `Tls` is a TCP connection accepted through a
+    /// [`super::TlsAcceptor`].
+    #[cfg(feature = "rustls")]
+    Tls(BufferedReader<super::ServerTlsStream>),
+}
+
+// -- Constructors
+
+impl TransportStream {
+    /// `connect` opens a stream to `addr`, resolving TCP hosts with
+    /// `resolver`. A zero `timeout` uses the operating system's default.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`io::Error`] of the failed resolution or connection.
+    pub fn connect(
+        addr: &TransportAddr,
+        timeout: Duration,
+        resolver: &dyn Resolver,
+    ) -> io::Result<Self> {
+        match addr {
+            TransportAddr::Tcp { host, port } => {
+                connect_resolved(resolver, host, *port, timeout).map(Self::Tcp)
+            }
+            #[cfg(unix)]
+            TransportAddr::Unix(path) => {
+                UnixStream::connect(path).map(|stream| Self::Unix(BufferedReader::new(stream)))
+            }
+            #[cfg(all(windows, feature = "named-pipes"))]
+            TransportAddr::NamedPipe(name) => std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(name)
+                .map(|pipe| Self::NamedPipe(BufferedReader::new(pipe))),
+        }
+    }
+}
+
+// -- Methods
+
+impl TransportStream {
+    /// `try_clone` returns another handle to the same connection, e.g to
+    /// write from while this one reads.
+    ///
+    /// Data buffered by this handle is not visible to the clone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if the underlying handle cannot be cloned.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Self::Tcp(inner) => inner.try_clone().map(Self::Tcp),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner
+                .get_inner_ref()
+                .try_clone()
+                .map(|stream| Self::Unix(BufferedReader::new(stream))),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(inner) => inner
+                .get_inner_ref()
+                .try_clone()
+                .map(|pipe| Self::NamedPipe(BufferedReader::new(pipe))),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => Ok(Self::Tls(BufferedReader::new(
+                inner.get_inner_ref().clone(),
+            ))),
+        }
+    }
+
+    /// `peer_addr` describes the remote end of the connection, Unix domain
+    /// socket clients are usually unnamed and named pipes have none.
+    #[must_use]
+    pub fn peer_addr(&self) -> Option<String> {
+        match self {
+            Self::Tcp(inner) => inner.peer_addr().ok().map(|addr| addr.to_string()),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner
+                .get_inner_ref()
+                .peer_addr()
+                .ok()
+                .and_then(|addr| addr.as_pathname().map(|path| path.display().to_string())),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(_) => None,
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner
+                .get_inner_ref()
+                .peer_addr()
+                .ok()
+                .map(|addr| addr.to_string()),
+        }
+    }
+
+    /// `set_read_timeout` sets the read timeout, it is a no-op for named
+    /// pipes which do not support one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if the timeout cannot be set.
+    pub fn set_read_timeout(&self, duration: Option<Duration>) -> io::Result<()> {
+        match self {
+            Self::Tcp(inner) => inner.set_read_timeout(duration),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner.get_inner_ref().set_read_timeout(duration),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(_) => Ok(()),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner.get_inner_ref().set_read_timeout(duration),
+        }
+    }
+}
+
+impl core::fmt::Debug for TransportStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(inner) => f.debug_tuple("TransportStream::Tcp").field(inner).finish(),
+            #[cfg(unix)]
+            Self::Unix(inner) => f
+                .debug_tuple("TransportStream::Unix")
+                .field(inner.get_inner_ref())
+                .finish(),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(_) => f.debug_tuple("TransportStream::NamedPipe").finish(),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => f
+                .debug_tuple("TransportStream::Tls")
+                .field(inner.get_inner_ref())
+                .finish(),
+        }
+    }
+}
+
+impl Read for TransportStream {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(inner) => inner.read(buf),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner.read(buf),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(inner) => inner.read(buf),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner.read(buf),
+        }
+    }
+}
+
+impl Write for TransportStream {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(inner) => inner.write(buf),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner.get_inner_mut().write(buf),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(inner) => inner.get_inner_mut().write(buf),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner.get_inner_mut().write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(inner) => inner.flush(),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner.get_inner_mut().flush(),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(inner) => inner.get_inner_mut().flush(),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner.get_inner_mut().flush(),
+        }
+    }
+}
+
+impl PeekableReadStream for TransportStream {
+    fn peek(&mut self, buf: &mut [u8]) -> std::result::Result<usize, PeekError> {
+        match self {
+            Self::Tcp(inner) => inner.peek(buf).map_err(PeekError::IOError),
+            #[cfg(unix)]
+            Self::Unix(inner) => inner.peek(buf),
+            #[cfg(all(windows, feature = "named-pipes"))]
+            Self::NamedPipe(inner) => inner.peek(buf),
+            #[cfg(feature = "rustls")]
+            Self::Tls(inner) => inner.peek(buf),
+        }
+    }
+}
+
+/// `TransportListener` accepts connections on a [`TransportAddr`].
+///
+/// Unix domain socket listeners remove their socket file when dropped.
+/// Named pipes are client only, binding one returns an error.
+#[derive(Debug)]
+pub enum TransportListener {
+    Tcp(TcpListener),
+
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+impl TransportListener {
+    /// `bind` starts listening on `addr`, a stale Unix socket file left
+    /// behind by a previous listener is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if the address cannot be bound.
+    pub fn bind(addr: &TransportAddr) -> io::Result<Self> {
+        match addr {
+            TransportAddr::Tcp { host, port } => {
+                TcpListener::bind((host.as_str(), *port)).map(Self::Tcp)
+            }
+            #[cfg(unix)]
+            TransportAddr::Unix(path) => {
+                if path.exists() && UnixStream::connect(path).is_err() {
+                    std::fs::remove_file(path)?;
+                }
+                UnixListener::bind(path).map(|listener| Self::Unix(listener, path.clone()))
+            }
+            #[cfg(all(windows, feature = "named-pipes"))]
+            TransportAddr::NamedPipe(name) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("listening on named pipe {name} is not supported"),
+            )),
+        }
+    }
+
+    /// `accept` waits for the next connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if accepting the connection failed.
+    pub fn accept(&self) -> io::Result<TransportStream> {
+        match self {
+            Self::Tcp(listener) => listener
+                .accept()
+                .map(|(stream, _)| TransportStream::Tcp(stream)),
+            #[cfg(unix)]
+            Self::Unix(listener, _) => listener
+                .accept()
+                .map(|(stream, _)| TransportStream::Unix(BufferedReader::new(stream))),
+        }
+    }
+
+    /// `incoming` returns an iterator over accepted connections which
+    /// never returns `None`.
+    pub fn incoming(&self) -> impl Iterator<Item = io::Result<TransportStream>> + '_ {
+        std::iter::repeat_with(move || self.accept())
+    }
+}
+
+#[cfg(unix)]
+impl Drop for TransportListener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test_transport {
+    use super::super::SystemResolver;
+    use super::*;
+
+    #[test]
+    fn transport_addr_from_url() {
+        let parse = |value: &str| {
+            TransportAddr::from_url(&url::Url::parse(value).expect("should be valid url"))
+                .expect("should be supported")
+        };
+
+        assert_eq!(
+            parse("http+unix://%2Fvar%2Frun%2Fdocker.sock/v1.43/info"),
+            TransportAddr::unix("/var/run/docker.sock")
+        );
+        assert_eq!(
+            parse("unix:///tmp/backend.sock"),
+            TransportAddr::unix("/tmp/backend.sock")
+        );
+        assert_eq!(
+            parse("https://example.com/path"),
+            TransportAddr::tcp("example.com", 443)
+        );
+    }
+
+    #[test]
+    fn unix_socket_round_trip() {
+        let path = std::env::temp_dir().join(format!("ewe-transport-{}.sock", std::process::id()));
+        let addr = TransportAddr::unix(&path);
+
+        let listener = TransportListener::bind(&addr).expect("should bind");
+        let server = std::thread::spawn(move || {
+            let mut stream = listener.accept().expect("should accept");
+            let mut peeked = [0; 4];
+            assert_eq!(stream.peek(&mut peeked).expect("should peek"), 4);
+
+            let mut request = [0; 4];
+            stream.read_exact(&mut request).expect("should read");
+            assert_eq!(&request, b"PING");
+            stream.write_all(b"PONG").expect("should write");
+        });
+
+        let mut client = TransportStream::connect(&addr, Duration::ZERO, &SystemResolver)
+            .expect("should connect");
+        client.write_all(b"PING").expect("should write");
+        let mut response = String::new();
+        client
+            .read_to_string(&mut response)
+            .expect("should read response");
+        assert_eq!(response, "PONG");
+
+        server.join().expect("should finish");
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,491 @@
+use std::io;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::wire::simple_http::SimpleHeaders;
+use crate::wire::tcp::TlsError;
+
+use super::client::{
+    connect_plain, handshake_request, new_mask, response_head_line, target, Session,
+    MAX_HANDSHAKE_SIZE,
+};
+use super::{
+    generate_key, CloseFrame, Frame, FrameHead, Role, WebSocketConfig, WebSocketError,
+    WebSocketMessage, WebSocketResult,
+};
+
+/// `AsyncStream` is any tokio stream an [`AsyncWebSocketClient`] can run
+/// over.
+pub trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for T {}
+
+pub type BoxedAsyncStream = Box<dyn AsyncStream>;
+
+/// `AsyncWebSocketClient` is the tokio counterpart of
+/// [`super::WebSocketClient`], speaking the same protocol over any
+/// [`AsyncStream`], usually one opened by [`AsyncWebSocketClient::connect`].
+///
+/// Pings are answered automatically, they are still returned from
+/// [`AsyncWebSocketClient::recv`] so callers can track liveness.
+pub struct AsyncWebSocketClient<S: AsyncRead + AsyncWrite + Unpin = BoxedAsyncStream> {
+    stream: BufReader<S>,
+    session: Session,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> core::fmt::Debug for AsyncWebSocketClient<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncWebSocketClient")
+            .field("protocol", &self.session.protocol)
+            .field("deflate", &self.session.inflater.is_some())
+            .field("closed", &self.session.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+// -- Constructors
+
+impl AsyncWebSocketClient<BoxedAsyncStream> {
+    /// `connect` opens a websocket connection to a `ws://` or `wss://` url,
+    /// it must be called from within a tokio runtime with IO enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WebSocketError`] if the connection or the opening
+    /// handshake fails.
+    pub async fn connect(url: &str) -> WebSocketResult<Self> {
+        Self::connect_with(url, WebSocketConfig::default()).await
+    }
+
+    /// `connect_with` opens a websocket connection using `config`.
+    ///
+    /// Resolving the host and tunneling through a proxy reuse the
+    /// blocking transports, they run on tokio's blocking pool.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WebSocketError`] if the connection or the opening
+    /// handshake fails.
+    pub async fn connect_with(url: &str, config: WebSocketConfig) -> WebSocketResult<Self> {
+        let url = url::Url::parse(url)?;
+        let (secure, host, port) = target(&url)?;
+
+        let plain = {
+            let (url, host, config) = (url.clone(), host.clone(), config.clone());
+            tokio::task::spawn_blocking(move || connect_plain(&url, &host, port, &config))
+                .await
+                .map_err(io::Error::other)??
+        };
+        plain.set_nonblocking(true)?;
+        let plain = tokio::net::TcpStream::from_std(plain)?;
+
+        let stream: BoxedAsyncStream = if secure {
+            let connector = crate::native_tls::TlsConnector::new()
+                .map_err(|_| WebSocketError::Tls(TlsError::ConnectorCreation))?;
+            let stream = tokio_native_tls::TlsConnector::from(connector)
+                .connect(&host, plain)
+                .await
+                .map_err(|_| WebSocketError::Tls(TlsError::Handshake))?;
+            Box::new(stream)
+        } else {
+            Box::new(plain)
+        };
+
+        Self::handshake(stream, &url, config).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWebSocketClient<S> {
+    /// `handshake` performs the opening handshake for `url` over an
+    /// already connected stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::HandshakeFailed`] if the server does not
+    /// upgrade the connection or [`WebSocketError::InvalidAcceptKey`] if it
+    /// answers with the wrong key.
+    pub async fn handshake(
+        stream: S,
+        url: &url::Url,
+        config: WebSocketConfig,
+    ) -> WebSocketResult<Self> {
+        let key = generate_key();
+        let request = handshake_request(url, &config, &key)?;
+
+        let mut stream = BufReader::new(stream);
+        stream.get_mut().write_all(request.as_bytes()).await?;
+        stream.get_mut().flush().await?;
+
+        let (status_line, response_headers) = read_response_head(&mut stream).await?;
+        let session = Session::accept(&status_line, &response_headers, &key, config)?;
+        Ok(Self { stream, session })
+    }
+}
+
+async fn read_response_head<S: AsyncRead + Unpin>(
+    stream: &mut BufReader<S>,
+) -> WebSocketResult<(String, SimpleHeaders)> {
+    let mut status_line = String::new();
+    let mut headers = SimpleHeaders::new();
+    let mut consumed = 0;
+
+    loop {
+        let mut line = String::new();
+        let read = stream.read_line(&mut line).await?;
+        if read == 0 {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+
+        consumed += read;
+        if consumed > MAX_HANDSHAKE_SIZE {
+            return Err(WebSocketError::HandshakeFailed(
+                "response headers too large".into(),
+            ));
+        }
+
+        if !response_head_line(&line, &mut status_line, &mut headers) {
+            return Ok((status_line, headers));
+        }
+    }
+}
+
+/// `read_frame` is [`Frame::decode`] over an async reader.
+async fn read_frame<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_payload: u64,
+    role: Role,
+) -> WebSocketResult<Frame> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head).await?;
+    let head = FrameHead::parse(head, role)?;
+
+    let mut extended = [0u8; 8];
+    let extended = &mut extended[..head.extended_length()];
+    reader.read_exact(extended).await?;
+    let length = head.payload_length(extended, max_payload)?;
+
+    let mask = if head.masked {
+        let mut key = [0u8; 4];
+        reader.read_exact(&mut key).await?;
+        Some(key)
+    } else {
+        None
+    };
+
+    let mut payload = vec![0u8; length];
+    reader.read_exact(&mut payload).await?;
+    Ok(head.into_frame(payload, mask))
+}
+
+// -- Methods
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWebSocketClient<S> {
+    /// `protocol` returns the sub-protocol the server selected.
+    #[must_use]
+    pub fn protocol(&self) -> Option<&str> {
+        self.session.protocol.as_deref()
+    }
+
+    /// `is_deflate_enabled` returns true if permessage-deflate was
+    /// negotiated.
+    #[must_use]
+    pub fn is_deflate_enabled(&self) -> bool {
+        self.session.inflater.is_some()
+    }
+
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.session.closed
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &S {
+        self.stream.get_ref()
+    }
+
+    /// `send` sends a message as a single frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::ConnectionClosed`] once a close frame was
+    /// sent or received, or an IO error if writing fails.
+    pub async fn send(&mut self, message: WebSocketMessage) -> WebSocketResult<()> {
+        let frame = self.session.outgoing(message)?;
+        self.write_frame(&frame).await
+    }
+
+    /// `send_text` sends a text message.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncWebSocketClient::send`].
+    pub async fn send_text<T: Into<String>>(&mut self, text: T) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Text(text.into())).await
+    }
+
+    /// `send_binary` sends a binary message.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncWebSocketClient::send`].
+    pub async fn send_binary<B: Into<Vec<u8>>>(&mut self, data: B) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Binary(data.into())).await
+    }
+
+    /// `ping` sends a ping, the matching pong is returned by `recv`.
+    ///
+    /// # Errors
+    ///
+    /// See [`AsyncWebSocketClient::send`].
+    pub async fn ping<B: Into<Vec<u8>>>(&mut self, data: B) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Ping(data.into())).await
+    }
+
+    /// `recv` waits for the next message, reassembling fragmented
+    /// messages and answering pings and close frames. The configured
+    /// read timeout applies to each frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::ConnectionClosed`] once the close
+    /// handshake completed, or an error if the peer breaks the protocol.
+    pub async fn recv(&mut self) -> WebSocketResult<WebSocketMessage> {
+        if self.session.closed {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+
+        loop {
+            let mut replies = Vec::new();
+            let received = self
+                .read_frame()
+                .await
+                .and_then(|frame| self.session.incoming(frame, &mut replies))
+                .map_err(|err| self.session.fail(err, &mut replies));
+
+            for reply in &replies {
+                match &received {
+                    Ok(_) => self.write_frame(reply).await?,
+                    Err(_) => drop(self.write_frame(reply).await),
+                }
+            }
+            if let Some(message) = received? {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// `close` starts the closing handshake and waits for the server to
+    /// answer it, messages received in between are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an IO error if the close frame can not be sent.
+    pub async fn close(&mut self, close: Option<CloseFrame>) -> WebSocketResult<()> {
+        if self.session.closed {
+            return Ok(());
+        }
+        if !self.session.close_sent {
+            self.send(WebSocketMessage::Close(close)).await?;
+        }
+
+        loop {
+            match self.recv().await {
+                Ok(WebSocketMessage::Close(_)) | Err(WebSocketError::ConnectionClosed) => {
+                    self.session.closed = true;
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    self.session.closed = true;
+                    tracing::debug!("Websocket closed without close handshake: {err:?}");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn read_frame(&mut self) -> WebSocketResult<Frame> {
+        let max_payload = self.session.config.max_message_size;
+        let read = read_frame(&mut self.stream, max_payload, Role::Client);
+        match self.session.config.read_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, read)
+                .await
+                .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?,
+            None => read.await,
+        }
+    }
+
+    async fn write_frame(&mut self, frame: &Frame) -> WebSocketResult<()> {
+        let writer = self.stream.get_mut();
+        writer.write_all(&frame.encode(Some(new_mask()))).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_async_websocket_client {
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::panic_if_failed;
+    use crate::wire::simple_http::SimpleHeader;
+    use crate::wire::websocket::{accept_key, OpCode, DEFLATE_TRAILER};
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("should build runtime")
+            .block_on(future)
+    }
+
+    /// `serve_compressed` answers the handshake accepting permessage-deflate
+    /// with context takeover, then sends `messages` compressed by a single
+    /// compressor and returns the frames the client sent back.
+    fn serve_compressed(
+        listener: TcpListener,
+        messages: Vec<&'static str>,
+    ) -> thread::JoinHandle<Vec<Frame>> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("should accept");
+            let mut reader = std::io::BufReader::new(stream);
+
+            let mut status_line = String::new();
+            let mut headers = SimpleHeaders::new();
+            loop {
+                let mut line = String::new();
+                std::io::BufRead::read_line(&mut reader, &mut line).expect("should read");
+                if !response_head_line(&line, &mut status_line, &mut headers) {
+                    break;
+                }
+            }
+            let key = headers
+                .get(&SimpleHeader::SEC_WEBSOCKET_KEY)
+                .expect("should send key");
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\nSec-WebSocket-Extensions: permessage-deflate; client_no_context_takeover\r\n\r\n",
+                accept_key(key)
+            );
+            reader
+                .get_mut()
+                .write_all(response.as_bytes())
+                .expect("should respond");
+
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            for message in messages {
+                encoder
+                    .write_all(message.as_bytes())
+                    .expect("should compress");
+                encoder.flush().expect("should flush");
+                let mut payload = std::mem::take(encoder.get_mut());
+                payload.truncate(payload.len() - DEFLATE_TRAILER.len());
+
+                let mut frame = Frame::new(OpCode::Text, payload);
+                frame.compressed = true;
+                reader
+                    .get_mut()
+                    .write_all(&frame.encode(None))
+                    .expect("should send message");
+            }
+
+            let mut received = Vec::new();
+            loop {
+                let frame =
+                    Frame::decode(&mut reader, 1 << 20, Role::Server).expect("should decode");
+                received.push(frame.clone());
+                if frame.opcode == OpCode::Close {
+                    reader
+                        .get_mut()
+                        .write_all(&Frame::new(OpCode::Close, frame.payload).encode(None))
+                        .expect("should answer close");
+                    return received;
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn async_client_keeps_the_server_deflate_context() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3821"));
+        let message = "repeated words, repeated words, repeated words";
+        let server = serve_compressed(listener, vec![message, message]);
+
+        let received = block_on(async {
+            let config = WebSocketConfig::default().with_deflate(true);
+            let mut client = panic_if_failed!(
+                AsyncWebSocketClient::connect_with("ws://127.0.0.1:3821/", config).await
+            );
+            assert!(client.is_deflate_enabled());
+
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                received.push(panic_if_failed!(client.recv().await));
+            }
+            panic_if_failed!(client.send_text("thanks").await);
+            panic_if_failed!(client.close(None).await);
+            assert!(client.is_closed());
+            received
+        });
+
+        assert_eq!(received, vec![WebSocketMessage::Text(message.into()); 2]);
+        let frames = server.join().expect("server should finish");
+        let opcodes: Vec<OpCode> = frames.iter().map(|frame| frame.opcode).collect();
+        assert_eq!(opcodes, vec![OpCode::Text, OpCode::Close]);
+        assert!(frames[0].compressed);
+    }
+
+    #[test]
+    fn async_client_fails_on_masked_server_frames() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3822"));
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("should accept");
+            let mut reader = std::io::BufReader::new(stream);
+
+            let mut status_line = String::new();
+            let mut headers = SimpleHeaders::new();
+            loop {
+                let mut line = String::new();
+                std::io::BufRead::read_line(&mut reader, &mut line).expect("should read");
+                if !response_head_line(&line, &mut status_line, &mut headers) {
+                    break;
+                }
+            }
+            let key = headers
+                .get(&SimpleHeader::SEC_WEBSOCKET_KEY)
+                .expect("should send key");
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key)
+            );
+            let masked = Frame::new(OpCode::Text, b"hello".to_vec()).encode(Some([1, 2, 3, 4]));
+            let writer = reader.get_mut();
+            writer
+                .write_all(response.as_bytes())
+                .expect("should respond");
+            writer.write_all(&masked).expect("should send frame");
+
+            Frame::decode(&mut reader, 1 << 20, Role::Server).expect("should receive close")
+        });
+
+        block_on(async {
+            let mut client =
+                panic_if_failed!(AsyncWebSocketClient::connect("ws://127.0.0.1:3822/").await);
+            assert!(matches!(
+                client.recv().await,
+                Err(WebSocketError::MaskedFrame)
+            ));
+            assert!(client.is_closed());
+        });
+
+        let close = server.join().expect("server should finish");
+        assert_eq!(close.opcode, OpCode::Close);
+        assert_eq!(
+            close.payload,
+            CloseFrame::PROTOCOL_ERROR.to_be_bytes().to_vec()
+        );
+    }
+}
@@ -0,0 +1,874 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::wire::simple_http::{
+    Http11, RenderHttp, SimpleHeader, SimpleHeaders, SimpleIncomingRequest, SimpleMethod,
+};
+use crate::wire::tcp::{ProxyConfig, ProxyError, RawStream, SharedResolver, SystemResolver};
+
+use super::{
+    accept_key, deflate_message, generate_key, CloseFrame, Frame, MessageInflater, OpCode, Role,
+    WebSocketError, WebSocketMessage, WebSocketResult,
+};
+
+/// `PERMESSAGE_DEFLATE_OFFER` requests compression without context
+/// takeover in either direction. The server may still keep its context,
+/// [`MessageInflater`] follows whichever it answers with.
+pub(crate) const PERMESSAGE_DEFLATE_OFFER: &str =
+    "permessage-deflate; client_no_context_takeover; server_no_context_takeover";
+
+/// `MAX_HANDSHAKE_SIZE` bounds how many bytes of response headers are
+/// read during the opening handshake.
+pub(crate) const MAX_HANDSHAKE_SIZE: usize = 16 * 1024;
+
+/// `WebSocketConfig` defines how a `WebSocketClient` connects and the
+/// limits it enforces on received messages.
+#[derive(Clone, Debug)]
+pub struct WebSocketConfig {
+    /// `max_message_size` is the largest message, after reassembly and
+    /// decompression, the client accepts.
+    pub max_message_size: u64,
+    pub connect_timeout: Duration,
+    pub read_timeout: Option<Duration>,
+
+    /// `protocols` are offered through `Sec-WebSocket-Protocol`.
+    pub protocols: Vec<String>,
+
+    /// `headers` are added to the opening handshake request, e.g for
+    /// authentication.
+    pub headers: SimpleHeaders,
+
+    /// `deflate` offers permessage-deflate, it is only used if the server
+    /// accepts it.
+    pub deflate: bool,
+
+    /// `resolver` looks up the addresses of the server's host.
+    pub resolver: SharedResolver,
+
+    /// `proxy` picks the proxy the connection is tunneled through.
+    pub proxy: ProxyConfig,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            max_message_size: 64 * 1024 * 1024,
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: None,
+            protocols: Vec::new(),
+            headers: SimpleHeaders::new(),
+            deflate: false,
+            resolver: Arc::new(SystemResolver),
+            proxy: ProxyConfig::default(),
+        }
+    }
+}
+
+// -- Builder methods
+
+impl WebSocketConfig {
+    #[must_use]
+    pub fn with_max_message_size(mut self, max_message_size: u64) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    #[must_use]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    #[must_use]
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn with_protocol<S: Into<String>>(mut self, protocol: S) -> Self {
+        self.protocols.push(protocol.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_header<H: Into<SimpleHeader>, S: Into<String>>(mut self, key: H, value: S) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_deflate(mut self, deflate: bool) -> Self {
+        self.deflate = deflate;
+        self
+    }
+
+    #[must_use]
+    pub fn with_resolver(mut self, resolver: SharedResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
+
+/// `WebSocketClient` is a blocking RFC 6455 client over any stream,
+/// usually a `RawStream` created by [`WebSocketClient::connect`].
+///
+/// Pings are answered automatically, they are still returned from
+/// [`WebSocketClient::recv`] so callers can track liveness.
+pub struct WebSocketClient<S: Read + Write = RawStream> {
+    stream: BufReader<S>,
+    session: Session,
+}
+
+impl<S: Read + Write> core::fmt::Debug for WebSocketClient<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketClient")
+            .field("protocol", &self.session.protocol)
+            .field("deflate", &self.session.inflater.is_some())
+            .field("closed", &self.session.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `Session` is the protocol state shared by [`WebSocketClient`] and
+/// [`super::AsyncWebSocketClient`]. It turns received frames into messages
+/// and the frames answering them, leaving the IO to the clients.
+pub(crate) struct Session {
+    pub config: WebSocketConfig,
+    pub protocol: Option<String>,
+    pub inflater: Option<MessageInflater>,
+    pub close_sent: bool,
+    pub closed: bool,
+
+    /// `fragments` holds the message being reassembled, kept across
+    /// received frames as control frames may arrive between its
+    /// fragments.
+    fragments: Option<(OpCode, bool, Vec<u8>)>,
+}
+
+// -- Constructors
+
+impl WebSocketClient<RawStream> {
+    /// `connect` opens a websocket connection to a `ws://` or `wss://` url.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WebSocketError`] if the connection or the opening
+    /// handshake fails.
+    pub fn connect(url: &str) -> WebSocketResult<Self> {
+        Self::connect_with(url, WebSocketConfig::default())
+    }
+
+    /// `connect_with` opens a websocket connection using `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WebSocketError`] if the connection or the opening
+    /// handshake fails.
+    pub fn connect_with(url: &str, config: WebSocketConfig) -> WebSocketResult<Self> {
+        let url = url::Url::parse(url)?;
+        let (secure, host, port) = target(&url)?;
+
+        let plain = connect_plain(&url, &host, port, &config)?;
+        plain.set_read_timeout(config.read_timeout)?;
+
+        let stream = if secure {
+            RawStream::try_wrap_tls(plain, &host).map_err(WebSocketError::Tls)?
+        } else {
+            RawStream::wrap_plain(plain)
+        };
+
+        Self::handshake(stream, &url, config)
+    }
+}
+
+impl<S: Read + Write> WebSocketClient<S> {
+    /// `handshake` performs the opening handshake for `url` over an
+    /// already connected stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::HandshakeFailed`] if the server does not
+    /// upgrade the connection or [`WebSocketError::InvalidAcceptKey`] if it
+    /// answers with the wrong key.
+    pub fn handshake(stream: S, url: &url::Url, config: WebSocketConfig) -> WebSocketResult<Self> {
+        let key = generate_key();
+        let request = handshake_request(url, &config, &key)?;
+
+        let mut stream = BufReader::new(stream);
+        stream.get_mut().write_all(request.as_bytes())?;
+        stream.get_mut().flush()?;
+
+        let (status_line, response_headers) = read_response_head(&mut stream)?;
+        let session = Session::accept(&status_line, &response_headers, &key, config)?;
+        Ok(Self { stream, session })
+    }
+}
+
+/// `target` returns whether `url` is secure along with the host and port
+/// to connect to.
+pub(crate) fn target(url: &url::Url) -> WebSocketResult<(bool, String, u16)> {
+    let secure = match url.scheme() {
+        "ws" => false,
+        "wss" => true,
+        other => return Err(WebSocketError::UnsupportedScheme(other.to_string())),
+    };
+
+    let host = url.host_str().unwrap_or("localhost").to_string();
+    let port = url
+        .port_or_known_default()
+        .unwrap_or(if secure { 443 } else { 80 });
+    Ok((secure, host, port))
+}
+
+/// `connect_plain` opens the tcp connection to `host`, through the proxy
+/// picked by `config` if any.
+pub(crate) fn connect_plain(
+    url: &url::Url,
+    host: &str,
+    port: u16,
+    config: &WebSocketConfig,
+) -> WebSocketResult<std::net::TcpStream> {
+    config
+        .proxy
+        .connect(
+            url.scheme(),
+            host,
+            port,
+            config.connect_timeout,
+            &config.resolver,
+        )
+        .map_err(|err| match err {
+            ProxyError::IO(err) => WebSocketError::IO(err),
+            other => WebSocketError::Proxy(other),
+        })
+}
+
+/// `handshake_request` renders the opening handshake request for `url`
+/// offering `key`.
+pub(crate) fn handshake_request(
+    url: &url::Url,
+    config: &WebSocketConfig,
+    key: &str,
+) -> WebSocketResult<String> {
+    let mut target = url.path().to_string();
+    if let Some(query) = url.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    let mut host = url.host_str().unwrap_or("localhost").to_string();
+    if let Some(port) = url.port() {
+        host = format!("{host}:{port}");
+    }
+
+    let mut headers = config.headers.clone();
+    headers.insert(SimpleHeader::HOST, host);
+    headers.insert(SimpleHeader::UPGRADE, "websocket".into());
+    headers.insert(SimpleHeader::CONNECTION, "Upgrade".into());
+    headers.insert(SimpleHeader::SEC_WEBSOCKET_KEY, key.to_string());
+    headers.insert(SimpleHeader::SEC_WEBSOCKET_VERSION, "13".into());
+    if !config.protocols.is_empty() {
+        headers.insert(
+            SimpleHeader::SEC_WEBSOCKET_PROTOCOL,
+            config.protocols.join(", "),
+        );
+    }
+    if config.deflate {
+        headers.insert(
+            SimpleHeader::SEC_WEBSOCKET_EXTENSIONS,
+            PERMESSAGE_DEFLATE_OFFER.into(),
+        );
+    }
+
+    let request = SimpleIncomingRequest::builder()
+        .with_plain_url(target)
+        .with_method(SimpleMethod::GET)
+        .with_headers(headers)
+        .build()
+        .map_err(|err| WebSocketError::HandshakeFailed(err.to_string()))?;
+    Http11::request(request)
+        .http_render_string()
+        .map_err(|err| WebSocketError::HandshakeFailed(err.to_string()))
+}
+
+/// `accepted_deflate` reads the permessage-deflate parameters the server
+/// answered with, returning whether it keeps no context between messages,
+/// or `None` when it did not accept the extension.
+fn accepted_deflate(extensions: &str) -> WebSocketResult<Option<bool>> {
+    for extension in extensions.split(',') {
+        let mut params = extension.split(';').map(str::trim);
+        if params.next() != Some("permessage-deflate") {
+            continue;
+        }
+
+        let mut no_context_takeover = false;
+        for param in params {
+            match param.split_once('=').map_or(param, |(name, _)| name.trim()) {
+                "server_no_context_takeover" => no_context_takeover = true,
+                "client_no_context_takeover" | "server_max_window_bits" => {}
+                other => {
+                    return Err(WebSocketError::HandshakeFailed(format!(
+                        "unexpected permessage-deflate parameter {other}"
+                    )));
+                }
+            }
+        }
+        return Ok(Some(no_context_takeover));
+    }
+    Ok(None)
+}
+
+fn read_response_head<S: Read>(
+    stream: &mut BufReader<S>,
+) -> WebSocketResult<(String, SimpleHeaders)> {
+    let mut status_line = String::new();
+    let mut headers = SimpleHeaders::new();
+    let mut consumed = 0;
+
+    loop {
+        let mut line = String::new();
+        let read = stream.read_line(&mut line)?;
+        if read == 0 {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+
+        consumed += read;
+        if consumed > MAX_HANDSHAKE_SIZE {
+            return Err(WebSocketError::HandshakeFailed(
+                "response headers too large".into(),
+            ));
+        }
+
+        if !response_head_line(&line, &mut status_line, &mut headers) {
+            return Ok((status_line, headers));
+        }
+    }
+}
+
+/// `response_head_line` adds a line of the handshake response to the
+/// status line or headers, returning false on the blank line ending them.
+pub(crate) fn response_head_line(
+    line: &str,
+    status_line: &mut String,
+    headers: &mut SimpleHeaders,
+) -> bool {
+    let line = line.trim_end();
+    if line.is_empty() {
+        return false;
+    }
+    if status_line.is_empty() {
+        *status_line = line.to_string();
+    } else if let Some((key, value)) = line.split_once(':') {
+        headers.insert(
+            SimpleHeader::from(key.trim().to_string()),
+            value.trim().to_string(),
+        );
+    }
+    true
+}
+
+// -- Methods
+
+impl<S: Read + Write> WebSocketClient<S> {
+    /// `protocol` returns the sub-protocol the server selected.
+    #[must_use]
+    pub fn protocol(&self) -> Option<&str> {
+        self.session.protocol.as_deref()
+    }
+
+    /// `is_deflate_enabled` returns true if permessage-deflate was
+    /// negotiated.
+    #[must_use]
+    pub fn is_deflate_enabled(&self) -> bool {
+        self.session.inflater.is_some()
+    }
+
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.session.closed
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> &S {
+        self.stream.get_ref()
+    }
+
+    /// `send` sends a message as a single frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::ConnectionClosed`] once a close frame was
+    /// sent or received, or an IO error if writing fails.
+    pub fn send(&mut self, message: WebSocketMessage) -> WebSocketResult<()> {
+        let frame = self.session.outgoing(message)?;
+        self.write_frame(&frame)
+    }
+
+    /// `send_text` sends a text message.
+    ///
+    /// # Errors
+    ///
+    /// See [`WebSocketClient::send`].
+    pub fn send_text<T: Into<String>>(&mut self, text: T) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Text(text.into()))
+    }
+
+    /// `send_binary` sends a binary message.
+    ///
+    /// # Errors
+    ///
+    /// See [`WebSocketClient::send`].
+    pub fn send_binary<B: Into<Vec<u8>>>(&mut self, data: B) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Binary(data.into()))
+    }
+
+    /// `ping` sends a ping, the matching pong is returned by `recv`.
+    ///
+    /// # Errors
+    ///
+    /// See [`WebSocketClient::send`].
+    pub fn ping<B: Into<Vec<u8>>>(&mut self, data: B) -> WebSocketResult<()> {
+        self.send(WebSocketMessage::Ping(data.into()))
+    }
+
+    /// `recv` blocks until the next message arrives, reassembling
+    /// fragmented messages and answering pings and close frames.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::ConnectionClosed`] once the close
+    /// handshake completed, or an error if the peer breaks the protocol.
+    pub fn recv(&mut self) -> WebSocketResult<WebSocketMessage> {
+        if self.session.closed {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+
+        loop {
+            let mut replies = Vec::new();
+            let received = Frame::decode(
+                &mut self.stream,
+                self.session.config.max_message_size,
+                Role::Client,
+            )
+            .and_then(|frame| self.session.incoming(frame, &mut replies))
+            .map_err(|err| self.session.fail(err, &mut replies));
+
+            for reply in &replies {
+                match &received {
+                    Ok(_) => self.write_frame(reply)?,
+                    Err(_) => drop(self.write_frame(reply)),
+                }
+            }
+            if let Some(message) = received? {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// `close` starts the closing handshake and waits for the server to
+    /// answer it, messages received in between are discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an IO error if the close frame can not be sent.
+    pub fn close(&mut self, close: Option<CloseFrame>) -> WebSocketResult<()> {
+        if self.session.closed {
+            return Ok(());
+        }
+        if !self.session.close_sent {
+            self.send(WebSocketMessage::Close(close))?;
+        }
+
+        loop {
+            match self.recv() {
+                Ok(WebSocketMessage::Close(_)) | Err(WebSocketError::ConnectionClosed) => {
+                    self.session.closed = true;
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    self.session.closed = true;
+                    tracing::debug!("Websocket closed without close handshake: {err:?}");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn write_frame(&mut self, frame: &Frame) -> WebSocketResult<()> {
+        let writer = self.stream.get_mut();
+        writer.write_all(&frame.encode(Some(new_mask())))?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+pub(crate) fn new_mask() -> [u8; 4] {
+    let mut mask = [0; 4];
+    OsRng.fill_bytes(&mut mask);
+    mask
+}
+
+impl Session {
+    /// `accept` checks the handshake response against the request offering
+    /// `key`, returning the session it opened.
+    pub(crate) fn accept(
+        status_line: &str,
+        response_headers: &SimpleHeaders,
+        key: &str,
+        config: WebSocketConfig,
+    ) -> WebSocketResult<Self> {
+        if status_line.split_whitespace().nth(1) != Some("101") {
+            return Err(WebSocketError::HandshakeFailed(status_line.to_string()));
+        }
+
+        let header_contains = |header: SimpleHeader, expected: &str| {
+            response_headers.get(&header).is_some_and(|value| {
+                value
+                    .split(',')
+                    .any(|part| part.trim().eq_ignore_ascii_case(expected))
+            })
+        };
+        if !header_contains(SimpleHeader::UPGRADE, "websocket")
+            || !header_contains(SimpleHeader::CONNECTION, "upgrade")
+        {
+            return Err(WebSocketError::HandshakeFailed(
+                "server did not upgrade the connection".into(),
+            ));
+        }
+
+        if response_headers.get(&SimpleHeader::SEC_WEBSOCKET_ACCEPT) != Some(&accept_key(key)) {
+            return Err(WebSocketError::InvalidAcceptKey);
+        }
+
+        let deflate = match response_headers.get(&SimpleHeader::SEC_WEBSOCKET_EXTENSIONS) {
+            Some(extensions) if config.deflate => accepted_deflate(extensions)?,
+            _ => None,
+        };
+
+        Ok(Self {
+            protocol: response_headers
+                .get(&SimpleHeader::SEC_WEBSOCKET_PROTOCOL)
+                .cloned(),
+            config,
+            inflater: deflate.map(MessageInflater::new),
+            close_sent: false,
+            closed: false,
+            fragments: None,
+        })
+    }
+
+    /// `outgoing` returns the frame carrying `message`.
+    pub(crate) fn outgoing(&mut self, message: WebSocketMessage) -> WebSocketResult<Frame> {
+        if self.close_sent || self.closed {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+
+        match message {
+            WebSocketMessage::Text(text) => self.data_frame(OpCode::Text, text.into_bytes()),
+            WebSocketMessage::Binary(data) => self.data_frame(OpCode::Binary, data),
+            WebSocketMessage::Ping(data) => control_frame(OpCode::Ping, data),
+            WebSocketMessage::Pong(data) => control_frame(OpCode::Pong, data),
+            WebSocketMessage::Close(close) => {
+                self.close_sent = true;
+                let payload = close.map(|close| close.to_payload()).unwrap_or_default();
+                Ok(Frame::new(OpCode::Close, payload))
+            }
+        }
+    }
+
+    /// `incoming` handles a received frame, returning the message it
+    /// completes and adding the frames answering it to `replies`.
+    pub(crate) fn incoming(
+        &mut self,
+        frame: Frame,
+        replies: &mut Vec<Frame>,
+    ) -> WebSocketResult<Option<WebSocketMessage>> {
+        if frame.compressed
+            && (self.inflater.is_none() || !matches!(frame.opcode, OpCode::Text | OpCode::Binary))
+        {
+            return Err(WebSocketError::UnexpectedCompression);
+        }
+
+        match frame.opcode {
+            OpCode::Ping => {
+                if !self.close_sent {
+                    replies.push(Frame::new(OpCode::Pong, frame.payload.clone()));
+                }
+                return Ok(Some(WebSocketMessage::Ping(frame.payload)));
+            }
+            OpCode::Pong => return Ok(Some(WebSocketMessage::Pong(frame.payload))),
+            OpCode::Close => {
+                let close = CloseFrame::from_payload(&frame.payload)?;
+                if !self.close_sent {
+                    let code = close
+                        .as_ref()
+                        .map_or(CloseFrame::NORMAL, |close| close.code);
+                    self.close_sent = true;
+                    replies.push(Frame::new(OpCode::Close, code.to_be_bytes().to_vec()));
+                }
+                self.closed = true;
+                return Ok(Some(WebSocketMessage::Close(close)));
+            }
+            OpCode::Text | OpCode::Binary => {
+                if self.fragments.is_some() {
+                    return Err(WebSocketError::UnexpectedContinuation);
+                }
+                self.fragments = Some((frame.opcode, frame.compressed, frame.payload));
+            }
+            OpCode::Continuation => match self.fragments.as_mut() {
+                Some((_, _, payload)) => payload.extend_from_slice(&frame.payload),
+                None => return Err(WebSocketError::UnexpectedContinuation),
+            },
+        }
+
+        let size = self
+            .fragments
+            .as_ref()
+            .map_or(0, |(_, _, payload)| payload.len() as u64);
+        if size > self.config.max_message_size {
+            return Err(WebSocketError::MessageTooLarge(size));
+        }
+
+        match self.fragments.take() {
+            Some((opcode, compressed, payload)) if frame.fin => {
+                self.finish_message(opcode, compressed, payload).map(Some)
+            }
+            fragments => {
+                self.fragments = fragments;
+                Ok(None)
+            }
+        }
+    }
+
+    /// `fail` handles an error reading from the peer. End of stream
+    /// closes the session, a protocol violation is answered with the
+    /// matching close frame in `replies` before handing back the error.
+    pub(crate) fn fail(&mut self, err: WebSocketError, replies: &mut Vec<Frame>) -> WebSocketError {
+        let code = match &err {
+            WebSocketError::IO(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                self.closed = true;
+                return WebSocketError::ConnectionClosed;
+            }
+            WebSocketError::InvalidUtf8(_) => CloseFrame::INVALID_DATA,
+            WebSocketError::MessageTooLarge(_) => CloseFrame::MESSAGE_TOO_BIG,
+            _ => CloseFrame::PROTOCOL_ERROR,
+        };
+        if !self.close_sent {
+            self.close_sent = true;
+            replies.push(Frame::new(OpCode::Close, code.to_be_bytes().to_vec()));
+        }
+        self.closed = true;
+        err
+    }
+
+    fn finish_message(
+        &mut self,
+        opcode: OpCode,
+        compressed: bool,
+        payload: Vec<u8>,
+    ) -> WebSocketResult<WebSocketMessage> {
+        let payload = match self.inflater.as_mut() {
+            Some(inflater) if compressed => {
+                inflater.inflate(&payload, self.config.max_message_size)?
+            }
+            _ => payload,
+        };
+
+        if opcode == OpCode::Text {
+            return Ok(WebSocketMessage::Text(String::from_utf8(payload)?));
+        }
+        Ok(WebSocketMessage::Binary(payload))
+    }
+
+    fn data_frame(&self, opcode: OpCode, payload: Vec<u8>) -> WebSocketResult<Frame> {
+        if self.inflater.is_none() {
+            return Ok(Frame::new(opcode, payload));
+        }
+        let mut frame = Frame::new(opcode, deflate_message(&payload)?);
+        frame.compressed = true;
+        Ok(frame)
+    }
+}
+
+fn control_frame(opcode: OpCode, payload: Vec<u8>) -> WebSocketResult<Frame> {
+    if payload.len() > super::MAX_CONTROL_PAYLOAD {
+        return Err(WebSocketError::ControlFrameTooLarge(payload.len() as u64));
+    }
+    Ok(Frame::new(opcode, payload))
+}
+
+#[cfg(test)]
+mod test_websocket_client {
+    use super::*;
+    use crate::panic_if_failed;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// `serve_one` accepts a single connection, completes the handshake
+    /// and echoes data messages back until the client closes.
+    fn serve_one(listener: TcpListener, deflate: bool) -> thread::JoinHandle<Vec<Frame>> {
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("should accept");
+            let mut reader = BufReader::new(stream);
+
+            let (_, headers) = read_response_head(&mut reader).expect("should read request");
+            let key = headers
+                .get(&SimpleHeader::SEC_WEBSOCKET_KEY)
+                .expect("should send key")
+                .clone();
+
+            let extensions = if deflate {
+                format!("Sec-WebSocket-Extensions: {PERMESSAGE_DEFLATE_OFFER}\r\n")
+            } else {
+                String::new()
+            };
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n{extensions}\r\n",
+                accept_key(&key)
+            );
+            reader
+                .get_mut()
+                .write_all(response.as_bytes())
+                .expect("should respond");
+
+            reader
+                .get_mut()
+                .write_all(&Frame::new(OpCode::Ping, b"hb".to_vec()).encode(None))
+                .expect("should ping");
+
+            let mut received = Vec::new();
+            loop {
+                let frame =
+                    Frame::decode(&mut reader, 1 << 20, Role::Server).expect("should decode");
+                received.push(frame.clone());
+                match frame.opcode {
+                    OpCode::Close => {
+                        reader
+                            .get_mut()
+                            .write_all(&Frame::new(OpCode::Close, frame.payload).encode(None))
+                            .expect("should answer close");
+                        return received;
+                    }
+                    OpCode::Text | OpCode::Binary => {
+                        let mut echo = Frame::new(frame.opcode, frame.payload);
+                        echo.compressed = frame.compressed;
+                        reader
+                            .get_mut()
+                            .write_all(&echo.encode(None))
+                            .expect("should echo");
+                    }
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn client_exchanges_messages_and_closes() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3814"));
+        let server = serve_one(listener, false);
+
+        let mut client = panic_if_failed!(WebSocketClient::connect("ws://127.0.0.1:3814/chat"));
+        assert_eq!(
+            panic_if_failed!(client.recv()),
+            WebSocketMessage::Ping(b"hb".to_vec())
+        );
+
+        panic_if_failed!(client.send_text("hello"));
+        assert_eq!(
+            panic_if_failed!(client.recv()),
+            WebSocketMessage::Text("hello".into())
+        );
+
+        panic_if_failed!(client.close(Some(CloseFrame::new(CloseFrame::NORMAL, "bye"))));
+        assert!(client.is_closed());
+
+        let received = server.join().expect("server should finish");
+        let opcodes: Vec<OpCode> = received.iter().map(|frame| frame.opcode).collect();
+        assert_eq!(opcodes, vec![OpCode::Pong, OpCode::Text, OpCode::Close]);
+    }
+
+    #[test]
+    fn client_keeps_fragments_across_control_frames() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3819"));
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("should accept");
+            let mut reader = BufReader::new(stream);
+            let (_, headers) = read_response_head(&mut reader).expect("should read request");
+            let key = headers
+                .get(&SimpleHeader::SEC_WEBSOCKET_KEY)
+                .expect("should send key")
+                .clone();
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(&key)
+            );
+
+            let mut first = Frame::new(OpCode::Text, b"hello ".to_vec());
+            first.fin = false;
+            let ping = Frame::new(OpCode::Ping, b"hb".to_vec());
+            let last = Frame::new(OpCode::Continuation, b"world".to_vec());
+
+            let writer = reader.get_mut();
+            writer
+                .write_all(response.as_bytes())
+                .expect("should respond");
+            for frame in [first, ping, last] {
+                writer
+                    .write_all(&frame.encode(None))
+                    .expect("should send frame");
+            }
+            Frame::decode(&mut reader, 1 << 20, Role::Server).expect("should receive pong")
+        });
+
+        let mut client = panic_if_failed!(WebSocketClient::connect("ws://127.0.0.1:3819/"));
+        assert_eq!(
+            panic_if_failed!(client.recv()),
+            WebSocketMessage::Ping(b"hb".to_vec())
+        );
+        assert_eq!(
+            panic_if_failed!(client.recv()),
+            WebSocketMessage::Text("hello world".into())
+        );
+
+        let pong = server.join().expect("server should finish");
+        assert_eq!(pong.opcode, OpCode::Pong);
+    }
+
+    #[test]
+    fn client_negotiates_deflate() {
+        let listener = panic_if_failed!(TcpListener::bind("127.0.0.1:3815"));
+        let server = serve_one(listener, true);
+
+        let config = WebSocketConfig::default().with_deflate(true);
+        let mut client = panic_if_failed!(WebSocketClient::connect_with(
+            "ws://127.0.0.1:3815/",
+            config
+        ));
+        assert!(client.is_deflate_enabled());
+        let _ = panic_if_failed!(client.recv());
+
+        let message = "compress me ".repeat(50);
+        panic_if_failed!(client.send_text(message.clone()));
+        assert_eq!(
+            panic_if_failed!(client.recv()),
+            WebSocketMessage::Text(message)
+        );
+        panic_if_failed!(client.close(None));
+
+        let received = server.join().expect("server should finish");
+        assert!(received[1].compressed);
+    }
+}
@@ -0,0 +1,663 @@
+use std::io::{self, Read, Write};
+use std::string::FromUtf8Error;
+
+use base64::Engine;
+use derive_more::From;
+use rand::RngCore;
+
+use crate::wire::tcp::TlsError;
+
+/// `WEBSOCKET_GUID` is appended to the handshake key before hashing it
+/// into the `Sec-WebSocket-Accept` value (RFC 6455 section 1.3).
+pub const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// `MAX_CONTROL_PAYLOAD` is the largest payload a ping, pong or close
+/// frame may carry.
+pub const MAX_CONTROL_PAYLOAD: usize = 125;
+
+pub type WebSocketResult<T> = std::result::Result<T, WebSocketError>;
+
+#[derive(From, Debug)]
+pub enum WebSocketError {
+    IO(io::Error),
+    InvalidUtf8(FromUtf8Error),
+    Url(url::ParseError),
+
+    #[from(ignore)]
+    UnsupportedScheme(String),
+
+    #[from(ignore)]
+    HandshakeFailed(String),
+
+    #[from(ignore)]
+    InvalidAcceptKey,
+
+    #[from(ignore)]
+    InvalidOpCode(u8),
+
+    #[from(ignore)]
+    UnexpectedContinuation,
+
+    #[from(ignore)]
+    FragmentedControlFrame,
+
+    #[from(ignore)]
+    ControlFrameTooLarge(u64),
+
+    #[from(ignore)]
+    MessageTooLarge(u64),
+
+    #[from(ignore)]
+    UnexpectedCompression,
+
+    /// a frame arrived masked although it came from the server.
+    #[from(ignore)]
+    MaskedFrame,
+
+    /// a frame arrived unmasked although it came from a client.
+    #[from(ignore)]
+    UnmaskedFrame,
+
+    /// a frame set RSV2 or RSV3, which no negotiated extension defines.
+    #[from(ignore)]
+    ReservedBits(u8),
+
+    #[from(ignore)]
+    Tls(TlsError),
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[from(ignore)]
+    Proxy(crate::wire::tcp::ProxyError),
+
+    ConnectionClosed,
+}
+
+impl std::error::Error for WebSocketError {}
+
+impl core::fmt::Display for WebSocketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `accept_key` computes the `Sec-WebSocket-Accept` value a server must
+/// answer with for the given `Sec-WebSocket-Key`.
+#[must_use]
+pub fn accept_key(key: &str) -> String {
+    let mut hasher = sha1_smol::Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.digest().bytes())
+}
+
+/// `generate_key` returns a random `Sec-WebSocket-Key`.
+#[must_use]
+pub fn generate_key() -> String {
+    let mut nonce = [0; 16];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    base64::engine::general_purpose::STANDARD.encode(nonce)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpCode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl OpCode {
+    /// `from_u8` maps the 4 bit opcode of a frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::InvalidOpCode`] for reserved opcodes.
+    pub fn from_u8(value: u8) -> WebSocketResult<Self> {
+        match value {
+            0x0 => Ok(Self::Continuation),
+            0x1 => Ok(Self::Text),
+            0x2 => Ok(Self::Binary),
+            0x8 => Ok(Self::Close),
+            0x9 => Ok(Self::Ping),
+            0xA => Ok(Self::Pong),
+            other => Err(WebSocketError::InvalidOpCode(other)),
+        }
+    }
+
+    #[must_use]
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            Self::Continuation => 0x0,
+            Self::Text => 0x1,
+            Self::Binary => 0x2,
+            Self::Close => 0x8,
+            Self::Ping => 0x9,
+            Self::Pong => 0xA,
+        }
+    }
+
+    #[must_use]
+    pub fn is_control(&self) -> bool {
+        matches!(self, Self::Close | Self::Ping | Self::Pong)
+    }
+}
+
+/// `Role` is the side of the connection decoding frames, it decides
+/// whether they must arrive masked (RFC 6455 section 5.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// clients read server frames, which are never masked.
+    Client,
+
+    /// servers read client frames, which are always masked.
+    Server,
+}
+
+/// `FrameHead` is the first two bytes of a frame, split into its fields.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrameHead {
+    pub fin: bool,
+    pub compressed: bool,
+    pub opcode: OpCode,
+    pub masked: bool,
+
+    /// `length` is the 7 bit payload length, 126 and 127 announce an
+    /// extended length of 2 and 8 bytes.
+    pub length: u8,
+}
+
+impl FrameHead {
+    /// `parse` splits the head of a frame read by `role`, rejecting the
+    /// reserved bits and masking RFC 6455 section 5.2 forbids.
+    pub(crate) fn parse(head: [u8; 2], role: Role) -> WebSocketResult<Self> {
+        let reserved = head[0] & 0x30;
+        if reserved != 0 {
+            return Err(WebSocketError::ReservedBits(reserved >> 4));
+        }
+
+        let masked = head[1] & 0x80 != 0;
+        match (role, masked) {
+            (Role::Client, true) => return Err(WebSocketError::MaskedFrame),
+            (Role::Server, false) => return Err(WebSocketError::UnmaskedFrame),
+            _ => {}
+        }
+
+        Ok(Self {
+            fin: head[0] & 0x80 != 0,
+            compressed: head[0] & 0x40 != 0,
+            opcode: OpCode::from_u8(head[0] & 0x0F)?,
+            masked,
+            length: head[1] & 0x7F,
+        })
+    }
+
+    /// `extended_length` is how many bytes the payload length takes after
+    /// the head.
+    pub(crate) fn extended_length(self) -> usize {
+        match self.length {
+            126 => 2,
+            127 => 8,
+            _ => 0,
+        }
+    }
+
+    /// `payload_length` validates the length read from `extended`, the
+    /// bytes announced by [`FrameHead::extended_length`], against the
+    /// control frame rules and `max_payload`.
+    pub(crate) fn payload_length(
+        self,
+        extended: &[u8],
+        max_payload: u64,
+    ) -> WebSocketResult<usize> {
+        let length = match extended.len() {
+            0 => u64::from(self.length),
+            _ => extended
+                .iter()
+                .fold(0u64, |length, byte| (length << 8) | u64::from(*byte)),
+        };
+
+        if self.opcode.is_control() {
+            if !self.fin {
+                return Err(WebSocketError::FragmentedControlFrame);
+            }
+            if length > MAX_CONTROL_PAYLOAD as u64 {
+                return Err(WebSocketError::ControlFrameTooLarge(length));
+            }
+        }
+        if length > max_payload {
+            return Err(WebSocketError::MessageTooLarge(length));
+        }
+        usize::try_from(length).map_err(|_| WebSocketError::MessageTooLarge(length))
+    }
+
+    /// `into_frame` unmasks `payload` with `mask` and completes the frame.
+    pub(crate) fn into_frame(self, mut payload: Vec<u8>, mask: Option<[u8; 4]>) -> Frame {
+        if let Some(key) = mask {
+            for (index, byte) in payload.iter_mut().enumerate() {
+                *byte ^= key[index % 4];
+            }
+        }
+        Frame {
+            fin: self.fin,
+            compressed: self.compressed,
+            opcode: self.opcode,
+            payload,
+        }
+    }
+}
+
+/// `Frame` is a single websocket frame as sent over the wire, with its
+/// payload already unmasked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub fin: bool,
+
+    /// `compressed` is the RSV1 bit, set on the first frame of a message
+    /// compressed with permessage-deflate.
+    pub compressed: bool,
+    pub opcode: OpCode,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(opcode: OpCode, payload: Vec<u8>) -> Self {
+        Self {
+            fin: true,
+            compressed: false,
+            opcode,
+            payload,
+        }
+    }
+
+    /// `encode` renders the frame, masking the payload when a mask is
+    /// given as clients must do (RFC 6455 section 5.3).
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn encode(&self, mask: Option<[u8; 4]>) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(self.payload.len() + 14);
+
+        let mut first = self.opcode.as_u8();
+        if self.fin {
+            first |= 0x80;
+        }
+        if self.compressed {
+            first |= 0x40;
+        }
+        encoded.push(first);
+
+        let mask_bit = if mask.is_some() { 0x80 } else { 0x00 };
+        let length = self.payload.len();
+        if length < 126 {
+            encoded.push(mask_bit | length as u8);
+        } else if let Ok(length) = u16::try_from(length) {
+            encoded.push(mask_bit | 0x7E);
+            encoded.extend_from_slice(&length.to_be_bytes());
+        } else {
+            encoded.push(mask_bit | 0x7F);
+            encoded.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+
+        match mask {
+            Some(key) => {
+                encoded.extend_from_slice(&key);
+                encoded.extend(
+                    self.payload
+                        .iter()
+                        .enumerate()
+                        .map(|(index, byte)| byte ^ key[index % 4]),
+                );
+            }
+            None => encoded.extend_from_slice(&self.payload),
+        }
+        encoded
+    }
+
+    /// `decode` reads the next frame from `reader` on behalf of `role`,
+    /// rejecting payloads larger than `max_payload` before allocating
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WebSocketError`] if reading fails or the frame breaks
+    /// the framing rules of RFC 6455.
+    pub fn decode<R: Read>(reader: &mut R, max_payload: u64, role: Role) -> WebSocketResult<Self> {
+        let mut head = [0u8; 2];
+        reader.read_exact(&mut head)?;
+        let head = FrameHead::parse(head, role)?;
+
+        let mut extended = [0u8; 8];
+        let extended = &mut extended[..head.extended_length()];
+        reader.read_exact(extended)?;
+        let length = head.payload_length(extended, max_payload)?;
+
+        let mask = if head.masked {
+            let mut key = [0u8; 4];
+            reader.read_exact(&mut key)?;
+            Some(key)
+        } else {
+            None
+        };
+
+        let mut payload = vec![0u8; length];
+        reader.read_exact(&mut payload)?;
+        Ok(head.into_frame(payload, mask))
+    }
+}
+
+/// `CloseFrame` is the status code and reason carried by a close frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CloseFrame {
+    pub code: u16,
+    pub reason: String,
+}
+
+impl CloseFrame {
+    pub const NORMAL: u16 = 1000;
+    pub const GOING_AWAY: u16 = 1001;
+    pub const PROTOCOL_ERROR: u16 = 1002;
+    pub const INVALID_DATA: u16 = 1007;
+    pub const MESSAGE_TOO_BIG: u16 = 1009;
+
+    pub fn new<S: Into<String>>(code: u16, reason: S) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+
+    /// `from_payload` parses the payload of a close frame, an empty
+    /// payload carries no status.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::InvalidUtf8`] if the reason is not utf-8.
+    pub fn from_payload(payload: &[u8]) -> WebSocketResult<Option<Self>> {
+        if payload.len() < 2 {
+            return Ok(None);
+        }
+        let code = u16::from_be_bytes([payload[0], payload[1]]);
+        let reason = String::from_utf8(payload[2..].to_vec())?;
+        Ok(Some(Self { code, reason }))
+    }
+
+    #[must_use]
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut payload = self.code.to_be_bytes().to_vec();
+        payload.extend_from_slice(self.reason.as_bytes());
+        payload.truncate(MAX_CONTROL_PAYLOAD);
+        payload
+    }
+}
+
+/// `WebSocketMessage` is a complete message, reassembled from its
+/// frames.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close(Option<CloseFrame>),
+}
+
+// -- permessage-deflate (RFC 7692)
+
+/// `DEFLATE_TRAILER` is the empty stored block every compressed message
+/// ends with, it is stripped when sending and restored when receiving.
+pub(crate) const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xFF, 0xFF];
+
+/// `INFLATE_CHUNK` is how much room is added to the output for each step
+/// of inflating a message.
+const INFLATE_CHUNK: usize = 16 * 1024;
+
+/// `deflate_message` compresses a message payload, a fresh compressor is
+/// used per message since the client negotiates no context takeover.
+///
+/// # Errors
+///
+/// Returns [`WebSocketError::IO`] if compression fails.
+pub fn deflate_message(payload: &[u8]) -> WebSocketResult<Vec<u8>> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(payload)?;
+
+    // flushing ends the output on the empty stored block, taking it out
+    // before the encoder drops keeps the final block it would add out of
+    // the message.
+    encoder.flush()?;
+    let mut compressed = std::mem::take(encoder.get_mut());
+    if compressed.ends_with(&DEFLATE_TRAILER) {
+        compressed.truncate(compressed.len() - DEFLATE_TRAILER.len());
+    }
+    Ok(compressed)
+}
+
+/// `inflate_message` decompresses a single message payload on its own,
+/// refusing to grow it past `max_size`.
+///
+/// # Errors
+///
+/// See [`MessageInflater::inflate`].
+pub fn inflate_message(payload: &[u8], max_size: u64) -> WebSocketResult<Vec<u8>> {
+    MessageInflater::new(true).inflate(payload, max_size)
+}
+
+/// `MessageInflater` decompresses the messages of a permessage-deflate
+/// connection. Unless the server agreed to `server_no_context_takeover`
+/// its messages may refer back to earlier ones, so the sliding window is
+/// kept between them.
+pub struct MessageInflater {
+    decompress: flate2::Decompress,
+    no_context_takeover: bool,
+}
+
+impl core::fmt::Debug for MessageInflater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageInflater")
+            .field("no_context_takeover", &self.no_context_takeover)
+            .finish_non_exhaustive()
+    }
+}
+
+// -- Constructors
+
+impl MessageInflater {
+    #[must_use]
+    pub fn new(no_context_takeover: bool) -> Self {
+        Self {
+            decompress: flate2::Decompress::new(false),
+            no_context_takeover,
+        }
+    }
+}
+
+// -- Methods
+
+impl MessageInflater {
+    /// `inflate` decompresses the next message payload, refusing to grow
+    /// it past `max_size`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WebSocketError::MessageTooLarge`] if the message inflates
+    /// past `max_size` or [`WebSocketError::IO`] if the payload is not
+    /// valid deflate.
+    pub fn inflate(&mut self, payload: &[u8], max_size: u64) -> WebSocketResult<Vec<u8>> {
+        let mut compressed = Vec::with_capacity(payload.len() + DEFLATE_TRAILER.len());
+        compressed.extend_from_slice(payload);
+        compressed.extend_from_slice(&DEFLATE_TRAILER);
+
+        let result = self.inflate_all(&compressed, max_size);
+        if self.no_context_takeover || !matches!(result, Ok((_, false))) {
+            self.decompress.reset(false);
+        }
+        result.map(|(inflated, _)| inflated)
+    }
+
+    /// `inflate_all` runs `compressed` through the decompressor, returning
+    /// the output and whether the deflate stream ended with it.
+    fn inflate_all(
+        &mut self,
+        compressed: &[u8],
+        max_size: u64,
+    ) -> WebSocketResult<(Vec<u8>, bool)> {
+        let invalid =
+            |err: flate2::DecompressError| io::Error::new(io::ErrorKind::InvalidData, err);
+        let start = self.decompress.total_in();
+        let mut inflated = Vec::new();
+
+        loop {
+            let consumed = usize::try_from(self.decompress.total_in() - start)
+                .unwrap_or(usize::MAX)
+                .min(compressed.len());
+            let produced = inflated.len();
+
+            inflated.reserve(INFLATE_CHUNK);
+            let status = self
+                .decompress
+                .decompress_vec(
+                    &compressed[consumed..],
+                    &mut inflated,
+                    flate2::FlushDecompress::Sync,
+                )
+                .map_err(invalid)?;
+            if inflated.len() as u64 > max_size {
+                return Err(WebSocketError::MessageTooLarge(inflated.len() as u64));
+            }
+
+            let done = self.decompress.total_in() - start >= compressed.len() as u64;
+            match status {
+                flate2::Status::StreamEnd => return Ok((inflated, true)),
+                _ if done && inflated.len() < inflated.capacity() => {
+                    return Ok((inflated, false));
+                }
+                _ if self.decompress.total_in() - start == consumed as u64
+                    && inflated.len() == produced =>
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "deflate stream made no progress",
+                    )
+                    .into());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_websocket_frames {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_example() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_round_trip_with_and_without_mask() {
+        for size in [5, 300, 70_000] {
+            let frame = Frame::new(OpCode::Binary, vec![7u8; size]);
+
+            let masked = frame.encode(Some([1, 2, 3, 4]));
+            let decoded = Frame::decode(&mut masked.as_slice(), 1 << 20, Role::Server)
+                .expect("should decode");
+            assert_eq!(decoded, frame);
+
+            let plain = frame.encode(None);
+            let decoded =
+                Frame::decode(&mut plain.as_slice(), 1 << 20, Role::Client).expect("should decode");
+            assert_eq!(decoded, frame);
+        }
+
+        let oversized = Frame::new(OpCode::Text, vec![b'a'; 200]).encode(None);
+        assert!(matches!(
+            Frame::decode(&mut oversized.as_slice(), 100, Role::Client),
+            Err(WebSocketError::MessageTooLarge(200))
+        ));
+
+        let ping = Frame::new(OpCode::Ping, vec![0; 126]).encode(None);
+        assert!(matches!(
+            Frame::decode(&mut ping.as_slice(), 1 << 20, Role::Client),
+            Err(WebSocketError::ControlFrameTooLarge(126))
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_wrong_masking_and_reserved_bits() {
+        let frame = Frame::new(OpCode::Text, b"hello".to_vec());
+
+        let masked = frame.encode(Some([1, 2, 3, 4]));
+        assert!(matches!(
+            Frame::decode(&mut masked.as_slice(), 1 << 20, Role::Client),
+            Err(WebSocketError::MaskedFrame)
+        ));
+
+        let plain = frame.encode(None);
+        assert!(matches!(
+            Frame::decode(&mut plain.as_slice(), 1 << 20, Role::Server),
+            Err(WebSocketError::UnmaskedFrame)
+        ));
+
+        for (bit, reserved) in [(0x20, 2), (0x10, 1)] {
+            let mut encoded = frame.encode(None);
+            encoded[0] |= bit;
+            assert!(matches!(
+                Frame::decode(&mut encoded.as_slice(), 1 << 20, Role::Client),
+                Err(WebSocketError::ReservedBits(found)) if found == reserved
+            ));
+        }
+    }
+
+    #[test]
+    fn deflate_round_trips() {
+        let message = b"hello hello hello hello websocket".repeat(20);
+        let compressed = deflate_message(&message).expect("should compress");
+        assert!(compressed.len() < message.len());
+        assert_eq!(
+            inflate_message(&compressed, 1 << 20).expect("should inflate"),
+            message
+        );
+        assert!(matches!(
+            inflate_message(&compressed, 10),
+            Err(WebSocketError::MessageTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn inflater_keeps_the_window_between_messages() {
+        // a compressor keeping its context, as servers do without
+        // server_no_context_takeover
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut compress = |message: &[u8]| {
+            encoder.write_all(message).expect("should compress");
+            encoder.flush().expect("should flush");
+            let mut compressed = std::mem::take(encoder.get_mut());
+            compressed.truncate(compressed.len() - DEFLATE_TRAILER.len());
+            compressed
+        };
+
+        let message = b"the same words over and over again".repeat(4);
+        let first = compress(&message);
+        let second = compress(&message);
+        assert!(second.len() < first.len());
+
+        let mut inflater = MessageInflater::new(false);
+        for compressed in [first, second.clone()] {
+            assert_eq!(
+                inflater
+                    .inflate(&compressed, 1 << 20)
+                    .expect("should inflate"),
+                message
+            );
+        }
+        assert_ne!(inflate_message(&second, 1 << 20).ok(), Some(message));
+    }
+}
@@ -0,0 +1,14 @@
+mod frame;
+pub use frame::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod client;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use client::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod async_client;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use async_client::*;
@@ -62,6 +62,12 @@ pub fn register(command: clap::Command) -> clap::Command {
                     .action(clap::ArgAction::Set)
                     .value_parser(clap::value_parser!(String)),
             )
+            .arg(
+                clap::Arg::new("env_file")
+                    .long("env_file")
+                    .action(clap::ArgAction::Append)
+                    .value_parser(clap::value_parser!(String)),
+            )
             .arg_required_else_help(true),
     )
 }
@@ -99,6 +105,11 @@ pub async fn run(args: &clap::ArgMatches) -> std::result::Result<(), BoxedError>
         .get_one::<usize>("destination_port")
         .expect("should have destination port");
 
+    let env_files: Vec<String> = args
+        .get_many::<String>("env_file")
+        .map(|files| files.cloned().collect())
+        .unwrap_or_default();
+
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::TRACE)
         .finish();
@@ -118,6 +129,8 @@ pub async fn run(args: &clap::ArgMatches) -> std::result::Result<(), BoxedError>
         workspace_root: project_directory.clone(),
         watch_directory: project_directory.clone(),
         wait_before_reload: time::Duration::from_millis(300), // magic number that works
+        env: HashMap::new(),
+        env_files,
         target_directory: String::from(format!("{}/target", project_directory.clone())),
         run_arguments: vec!["cargo", "run", "--bin", binary_name.as_str()].to_vec_string(),
         build_arguments: vec!["cargo", "build", "--bin", binary_name.as_str()].to_vec_string(),
@@ -0,0 +1,168 @@
+use futures::future::{self, Either};
+
+pub use tokio_util::sync::CancellationToken;
+
+use crate::{async_trait, TryFrom};
+
+/// The error of a conversion which can be cancelled through a
+/// [`CancellationToken`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum CancellableError<E> {
+    /// The token was cancelled before the conversion finished.
+    Cancelled,
+
+    /// The conversion itself failed.
+    Failed(E),
+}
+
+impl<E: std::fmt::Debug> std::error::Error for CancellableError<E> {}
+
+impl<E: std::fmt::Debug> std::fmt::Display for CancellableError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// A variant of [`TryFrom`] whose conversion can be aborted through a
+/// [`CancellationToken`], e.g. to stop deserializing a request body once
+/// the client went away.
+///
+/// Implementors check the token at the points where stopping is safe,
+/// [`try_from_or_cancel`] covers plain [`TryFrom`] conversions.
+#[async_trait]
+pub trait TryFromCancellable<T>: Sized {
+    /// The type returned in the event of a conversion error.
+    type Error;
+
+    /// Performs the conversion unless `cancel` is cancelled first.
+    async fn try_from_cancellable(
+        value: T,
+        cancel: CancellationToken,
+    ) -> Result<Self, CancellableError<Self::Error>>;
+}
+
+/// Runs the [`TryFrom`] conversion of `value` into `U`, dropping it as soon
+/// as `cancel` is cancelled.
+pub async fn try_from_or_cancel<T, U>(
+    value: T,
+    cancel: &CancellationToken,
+) -> Result<U, CancellableError<U::Error>>
+where
+    U: TryFrom<T>,
+{
+    if cancel.is_cancelled() {
+        return Err(CancellableError::Cancelled);
+    }
+
+    let conversion = U::try_from(value);
+    let cancelled = std::pin::pin!(cancel.cancelled());
+    match future::select(conversion, cancelled).await {
+        Either::Left((result, _)) => result.map_err(CancellableError::Failed),
+        Either::Right(((), _)) => Err(CancellableError::Cancelled),
+    }
+}
+
+#[cfg(test)]
+mod cancellation_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use futures::channel::mpsc;
+    use futures::{executor, Stream, StreamExt};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Positive(i32);
+
+    #[async_trait]
+    impl TryFrom<i32> for Positive {
+        type Error = &'static str;
+
+        async fn try_from(value: i32) -> Result<Self, Self::Error> {
+            if value <= 0 {
+                return Err("should be positive");
+            }
+            Ok(Positive(value))
+        }
+    }
+
+    #[async_trait]
+    impl TryFrom<()> for Positive {
+        type Error = &'static str;
+
+        async fn try_from((): ()) -> Result<Self, Self::Error> {
+            future::pending().await
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Joined(String);
+
+    #[async_trait]
+    impl<S> TryFrom<S> for Joined
+    where
+        S: Stream<Item = String> + Send + Unpin + 'static,
+    {
+        type Error = &'static str;
+
+        async fn try_from(mut parts: S) -> Result<Self, Self::Error> {
+            let mut joined = String::new();
+            while let Some(part) = parts.next().await {
+                joined.push_str(&part);
+            }
+            Ok(Joined(joined))
+        }
+    }
+
+    #[tokio::test]
+    async fn cancels_in_the_middle_of_a_stream() {
+        let cancel = CancellationToken::new();
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::unbounded();
+        let parts = receiver.inspect({
+            let consumed = consumed.clone();
+            move |_: &String| {
+                consumed.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let conversion = tokio::spawn({
+            let cancel = cancel.clone();
+            async move { try_from_or_cancel::<_, Joined>(parts, &cancel).await }
+        });
+
+        sender.unbounded_send("first".into()).expect("should send");
+        while consumed.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        cancel.cancel();
+
+        assert_eq!(
+            conversion.await.expect("should finish"),
+            Err(CancellableError::Cancelled)
+        );
+        assert_eq!(consumed.load(Ordering::SeqCst), 1);
+        assert!(sender.unbounded_send("second".into()).is_err());
+    }
+
+    #[test]
+    fn converts_unless_cancelled() {
+        let cancel = CancellationToken::new();
+        assert_eq!(
+            executor::block_on(try_from_or_cancel::<_, Positive>(3, &cancel)),
+            Ok(Positive(3))
+        );
+        assert_eq!(
+            executor::block_on(try_from_or_cancel::<_, Positive>(0, &cancel)),
+            Err(CancellableError::Failed("should be positive"))
+        );
+
+        let pending = try_from_or_cancel::<_, Positive>((), &cancel);
+        cancel.cancel();
+        assert_eq!(
+            executor::block_on(pending),
+            Err(CancellableError::Cancelled)
+        );
+    }
+}
@@ -44,10 +44,18 @@
 #![forbid(unsafe_code, future_incompatible, rust_2018_idioms)]
 #![deny(missing_debug_implementations, nonstandard_style)]
 
+mod cancellation;
+mod policies;
+mod streams;
+
 pub use async_trait::async_trait;
+pub use cancellation::*;
+pub use policies::*;
+pub use streams::*;
 
 /// A shared prelude.
 pub mod prelude {
+    pub use super::TryCollect as _;
     pub use super::TryFrom as _;
     pub use super::TryInto as _;
 }
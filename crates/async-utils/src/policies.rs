@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use foundation_core::retries::{RetryError, RetryPolicy};
+
+use crate::TryFrom;
+
+/// The error of a conversion bounded by a deadline.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TimeoutError<E> {
+    /// The conversion did not finish within the giving duration.
+    TimedOut(Duration),
+
+    /// The conversion itself failed.
+    Failed(E),
+}
+
+impl<E: std::fmt::Debug> std::error::Error for TimeoutError<E> {}
+
+impl<E: std::fmt::Debug> std::fmt::Display for TimeoutError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Runs the [`TryFrom`] conversion of `value` into `U`, giving up once it
+/// takes longer than `deadline`.
+///
+/// This must run within a tokio runtime with its timer enabled.
+pub async fn try_from_with_timeout<T, U>(
+    value: T,
+    deadline: Duration,
+) -> Result<U, TimeoutError<U::Error>>
+where
+    U: TryFrom<T>,
+{
+    match tokio::time::timeout(deadline, U::try_from(value)).await {
+        Ok(result) => result.map_err(TimeoutError::Failed),
+        Err(_) => Err(TimeoutError::TimedOut(deadline)),
+    }
+}
+
+/// Runs the [`TryFrom`] conversion of `value` into `U` under `policy`,
+/// converting a fresh clone of `value` on every attempt. The policy decides
+/// which errors are retried, the waits in between and the per-attempt
+/// timeout.
+///
+/// ```ignore
+/// let policy = RetryPolicy::new(3).with_attempt_timeout(Duration::from_secs(2));
+/// let body: RemoteBody = try_from_with_retry(request, &policy).await?;
+/// ```
+pub async fn try_from_with_retry<T, U>(
+    value: T,
+    policy: &RetryPolicy<U::Error>,
+) -> Result<U, RetryError<U::Error>>
+where
+    T: Clone,
+    U: TryFrom<T>,
+{
+    policy
+        .run_async(|_attempt| U::try_from(value.clone()))
+        .await
+}
+
+#[cfg(test)]
+mod policies_tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use foundation_core::retries::SameBackoffDecider;
+
+    use super::*;
+    use crate::async_trait;
+
+    /// `Source` converts into a `Flaky` which fails until `fail_until`
+    /// attempts were made on the shared counter, sleeping `delay` on each.
+    #[derive(Clone, Debug)]
+    struct Source {
+        attempts: Arc<AtomicU32>,
+        fail_until: u32,
+        delay: Duration,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Flaky(u32);
+
+    #[async_trait]
+    impl TryFrom<Source> for Flaky {
+        type Error = &'static str;
+
+        async fn try_from(source: Source) -> Result<Self, Self::Error> {
+            tokio::time::sleep(source.delay).await;
+            let attempt = source.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt < source.fail_until {
+                return Err("connection reset");
+            }
+            Ok(Flaky(attempt))
+        }
+    }
+
+    fn source(fail_until: u32, delay: Duration) -> Source {
+        Source {
+            attempts: Arc::new(AtomicU32::new(0)),
+            fail_until,
+            delay,
+        }
+    }
+
+    #[tokio::test]
+    async fn times_out_slow_conversions() {
+        let slow = source(0, Duration::from_secs(5));
+        assert_eq!(
+            try_from_with_timeout::<_, Flaky>(slow, Duration::from_millis(10)).await,
+            Err(TimeoutError::TimedOut(Duration::from_millis(10)))
+        );
+
+        let fast = source(0, Duration::ZERO);
+        assert_eq!(
+            try_from_with_timeout::<_, Flaky>(fast, Duration::from_secs(1)).await,
+            Ok(Flaky(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_failed_conversions() {
+        let policy =
+            RetryPolicy::new(3).with_decider(SameBackoffDecider::new(Duration::from_millis(1)));
+
+        let recovering = source(3, Duration::ZERO);
+        assert_eq!(
+            try_from_with_retry::<_, Flaky>(recovering, &policy)
+                .await
+                .unwrap(),
+            Flaky(3)
+        );
+
+        let failing = source(10, Duration::ZERO);
+        let err = try_from_with_retry::<_, Flaky>(failing, &policy)
+            .await
+            .unwrap_err();
+        assert_eq!(err.attempts(), 3);
+        assert_eq!(err.into_inner(), Some("connection reset"));
+    }
+}
@@ -0,0 +1,81 @@
+use futures::Stream;
+
+use crate::async_trait;
+
+/// Builds a value from an async stream of its parts, e.g. a `MyBody` from
+/// the chunks of a request body, consuming the parts as they arrive instead
+/// of buffering them first. It is the streamed counterpart of
+/// [`TryFrom`](crate::TryFrom).
+#[async_trait]
+pub trait TryFromStream<T>: Sized {
+    /// The type returned in the event of a conversion error.
+    type Error;
+
+    /// Performs the conversion, consuming the stream.
+    async fn try_from_stream<S>(stream: S) -> Result<Self, Self::Error>
+    where
+        S: Stream<Item = T> + Send + Unpin;
+}
+
+/// Collects a stream into any type implementing [`TryFromStream`], the
+/// reciprocal of it in the way [`TryInto`](crate::TryInto) is of
+/// [`TryFrom`](crate::TryFrom).
+#[async_trait]
+pub trait TryCollect: Stream + Send + Sized {
+    /// Performs the conversion, consuming the stream.
+    async fn try_collect_into<U>(self) -> Result<U, U::Error>
+    where
+        U: TryFromStream<Self::Item>;
+}
+
+#[async_trait]
+impl<S> TryCollect for S
+where
+    S: Stream + Send + Unpin,
+{
+    async fn try_collect_into<U>(self) -> Result<U, U::Error>
+    where
+        U: TryFromStream<Self::Item>,
+    {
+        U::try_from_stream(self).await
+    }
+}
+
+#[cfg(test)]
+mod streams_tests {
+    use futures::{executor, stream, StreamExt};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Body(String);
+
+    #[async_trait]
+    impl TryFromStream<Result<&'static [u8], &'static str>> for Body {
+        type Error = &'static str;
+
+        async fn try_from_stream<S>(mut stream: S) -> Result<Self, Self::Error>
+        where
+            S: Stream<Item = Result<&'static [u8], &'static str>> + Send + Unpin,
+        {
+            let mut body = Vec::new();
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(chunk?);
+            }
+            String::from_utf8(body)
+                .map(Body)
+                .map_err(|_| "body is not utf-8")
+        }
+    }
+
+    #[test]
+    fn collects_stream_of_parts() {
+        let chunks = stream::iter([Ok(b"hello ".as_slice()), Ok(b"world".as_slice())]);
+        let body: Result<Body, _> = executor::block_on(chunks.try_collect_into());
+        assert_eq!(body, Ok(Body(String::from("hello world"))));
+
+        let failing = stream::iter([Ok(b"hello".as_slice()), Err("connection reset")]);
+        let body: Result<Body, _> = executor::block_on(failing.try_collect_into());
+        assert_eq!(body, Err("connection reset"));
+    }
+}
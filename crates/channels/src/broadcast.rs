@@ -1,10 +1,15 @@
 use crate::mspc::{self, ChannelError};
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
 use std::sync;
+use std::task::{Context, Poll};
 
 pub fn create<E: Send + 'static>(initial_subscribers_capacity: usize) -> Broadcast<E> {
     Broadcast::<E>::new(initial_subscribers_capacity)
 }
 
+type Subscribers<E> = sync::Mutex<Vec<Option<mspc::SendChannel<sync::Arc<E>>>>>;
+
 /// Broadcast is multi-produre multi-subscriber multi-cast implements
 /// that is an eager deliver-er of messages.
 ///
@@ -18,7 +23,7 @@ pub fn create<E: Send + 'static>(initial_subscribers_capacity: usize) -> Broadca
 pub struct Broadcast<E: Send + 'static> {
     message_receiver: mspc::ReceiveChannel<E>,
     message_sender: mspc::SendChannel<E>,
-    subscribers: sync::Arc<sync::Mutex<Vec<Option<mspc::SendChannel<sync::Arc<E>>>>>>,
+    subscribers: sync::Arc<Subscribers<E>>,
 }
 
 impl<E: Send + 'static> Clone for Broadcast<E> {
@@ -55,6 +60,23 @@ impl<E: Send + 'static> Broadcast<E> {
         self.deliver_pending_messages();
     }
 
+    /// `broadcast_all` broadcasts every item of `stream` as it arrives.
+    pub async fn broadcast_all<S: Stream<Item = E>>(&mut self, stream: S) {
+        let mut stream = std::pin::pin!(stream);
+        while let Some(item) = stream.next().await {
+            self.broadcast(item);
+        }
+    }
+
+    /// `subscribe_stream` subscribes as a [`BroadcastStream`], which can be
+    /// cloned into further subscribers.
+    pub fn subscribe_stream(&mut self) -> BroadcastStream<E> {
+        BroadcastStream {
+            receiver: self.subscribe().into_stream(),
+            subscribers: sync::Arc::downgrade(&self.subscribers),
+        }
+    }
+
     pub fn subscribe(&mut self) -> mspc::ReceiveChannel<sync::Arc<E>> {
         let (sender, receiver) = mspc::create::<sync::Arc<E>>();
         self.add_and_deliver_pending_messages(sender);
@@ -94,10 +116,52 @@ impl<E: Send + 'static> Broadcast<E> {
     }
 }
 
+/// `BroadcastStream` is a subscription to a [`Broadcast`] usable as a
+/// [`Stream`]. Unlike cloning a [`mspc::ReceiveChannel`], which splits the
+/// messages between the clones, each clone is its own subscriber and
+/// observes every message broadcast after it was created.
+///
+/// The stream only holds a weak handle on the subscriber list, so it ends
+/// once every [`Broadcast`] is dropped.
+pub struct BroadcastStream<E: Send + 'static> {
+    subscribers: sync::Weak<Subscribers<E>>,
+    receiver: mspc::ReceiveStream<sync::Arc<E>>,
+}
+
+impl<E: Send + 'static> Clone for BroadcastStream<E> {
+    fn clone(&self) -> Self {
+        let (sender, receiver) = mspc::create::<sync::Arc<E>>();
+
+        // with every broadcaster gone the sender is dropped here, which
+        // leaves the clone already ended just like the stream it came from.
+        if let Some(subscribers) = self.subscribers.upgrade() {
+            subscribers.lock().unwrap().push(Some(sender));
+        }
+
+        Self {
+            receiver: receiver.into_stream(),
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+// the subscriber list is only kept to subscribe clones and never polled,
+// so it does not need to stay pinned.
+impl<E: Send + 'static> Unpin for BroadcastStream<E> {}
+
+impl<E: Send + 'static> Stream for BroadcastStream<E> {
+    type Item = sync::Arc<E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().receiver.poll_next_unpin(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use crate::broadcast;
+    use futures::StreamExt;
 
     #[test]
     fn broadcast_should_cache_pending_messages_when_no_subscribers() {
@@ -187,4 +251,43 @@ mod tests {
         assert!(!subscriber2.is_empty().unwrap());
         assert!(matches!(subscriber.is_empty(), Err(_)));
     }
+
+    #[tokio::test]
+    async fn broadcast_stream_clones_each_observe_every_message() {
+        let mut broadcaster = broadcast::create::<String>(5);
+
+        let first = broadcaster.subscribe_stream();
+        let second = first.clone();
+
+        broadcaster
+            .broadcast_all(futures::stream::iter(["one", "two"].map(String::from)))
+            .await;
+
+        let first: Vec<String> = first.take(2).map(|item| (*item).clone()).collect().await;
+        let second: Vec<String> = second.take(2).map(|item| (*item).clone()).collect().await;
+        assert_eq!(first, vec!["one", "two"]);
+        assert_eq!(second, first);
+    }
+
+    #[tokio::test]
+    async fn broadcast_stream_ends_once_every_broadcaster_is_dropped() {
+        let mut broadcaster = broadcast::create::<String>(5);
+        let other = broadcaster.clone();
+
+        let first = broadcaster.subscribe_stream();
+        let second = first.clone();
+
+        broadcaster.broadcast(String::from("one"));
+        drop(broadcaster);
+        drop(other);
+
+        let third = first.clone();
+
+        let first: Vec<String> = first.map(|item| (*item).clone()).collect().await;
+        let second: Vec<String> = second.map(|item| (*item).clone()).collect().await;
+        let third: Vec<String> = third.map(|item| (*item).clone()).collect().await;
+        assert_eq!(first, vec!["one"]);
+        assert_eq!(second, vec!["one"]);
+        assert_eq!(third, Vec::<String>::new());
+    }
 }
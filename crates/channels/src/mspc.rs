@@ -1,9 +1,12 @@
 // Crate implementing the Engineering Principles of Channels
 
+use std::pin::Pin;
 use std::sync::{self, Arc};
+use std::task::{Context, Poll};
 
 use async_channel;
 use crossbeam::atomic;
+use futures::{Stream, StreamExt};
 use thiserror::Error;
 
 pub type ChannelResult<T> = anyhow::Result<T, ChannelError>;
@@ -145,6 +148,16 @@ impl<T> SendChannel<T> {
             None => Err(ChannelError::Closed),
         }
     }
+
+    /// [`SendChannel`].`async_send_all()` sends every item of `stream` into the
+    /// channel, stopping at the first failed send.
+    pub async fn async_send_all<S: Stream<Item = T>>(&mut self, stream: S) -> ChannelResult<()> {
+        let mut stream = std::pin::pin!(stream);
+        while let Some(item) = stream.next().await {
+            self.async_send(item).await?;
+        }
+        Ok(())
+    }
 }
 
 pub struct ReceiveChannel<T> {
@@ -169,6 +182,15 @@ impl<T> ReceiveChannel<T> {
         }
     }
 
+    /// `into_stream` turns the channel into a [`ReceiveStream`] for use with
+    /// the combinators of the futures crate.
+    pub fn into_stream(self) -> ReceiveStream<T> {
+        ReceiveStream {
+            read_flag: self.read_flag,
+            src: self.src.map(Box::pin),
+        }
+    }
+
     pub fn drain(&mut self) -> Drain<T> {
         Drain { receiver: self }
     }
@@ -256,6 +278,37 @@ impl<T> ReceiveChannel<T> {
     }
 }
 
+/// `ReceiveStream` is a [`ReceiveChannel`] turned into a [`Stream`] of its
+/// messages through [`ReceiveChannel::into_stream`], it ends once every
+/// sender is gone.
+pub struct ReceiveStream<T> {
+    read_flag: Arc<atomic::AtomicCell<bool>>,
+    src: Option<Pin<Box<async_channel::Receiver<T>>>>,
+}
+
+impl<T> Stream for ReceiveStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(src) = &mut this.src else {
+            return Poll::Ready(None);
+        };
+
+        match src.poll_next_unpin(cx) {
+            Poll::Ready(Some(item)) => {
+                this.read_flag.store(true);
+                Poll::Ready(Some(item))
+            }
+            Poll::Ready(None) => {
+                _ = this.src.take();
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
 pub struct Drain<'a, T> {
     receiver: &'a mut ReceiveChannel<T>,
 }
@@ -278,6 +331,7 @@ impl<'a, T> Iterator for Drain<'a, T> {
 mod tests {
 
     use crate::mspc::{create, ChannelError};
+    use futures::StreamExt;
     use std::time::Duration;
 
     #[test]
@@ -334,6 +388,20 @@ mod tests {
         assert_eq!(String::from("new text"), recv_message);
     }
 
+    #[tokio::test]
+    async fn should_be_able_to_use_channel_as_a_stream() {
+        let (mut sender, receiver) = create::<usize>();
+
+        sender
+            .async_send_all(futures::stream::iter(1..=3))
+            .await
+            .expect("should have sent stream");
+        sender.close().expect("should have closed");
+
+        let doubled: Vec<usize> = receiver.into_stream().map(|item| item * 2).collect().await;
+        assert_eq!(doubled, vec![2, 4, 6]);
+    }
+
     #[tokio::test]
     async fn should_be_able_to_send_channel_into_another_thread() {
         let (mut sender, mut receiver) = create::<String>();
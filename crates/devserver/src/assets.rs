@@ -42,13 +42,19 @@ fn sse_endpoint_reloader(
     _addr: SocketAddr,
     _request: crate::types::HyperRequest,
     running_notification: broadcast::Receiver<()>,
+    status: Option<crate::StatusBoard>,
 ) -> pin::Pin<Box<crate::types::HyperFuture>> {
     Box::pin(async move {
+        // the guard lives as long as the stream, so the client stays
+        // counted until its connection is dropped.
+        let client_guard = status.map(|board| board.reload_client());
+
         let running_stream = BroadcastStream::new(running_notification);
         Ok(Sse::new(
             // when declaring Result types for such cases, the error type must be explicit
             // else you will have type inference compiler errors
-            running_stream.map(|_| -> Result<Event, crate::types::BoxedError> {
+            running_stream.map(move |_| -> Result<Event, crate::types::BoxedError> {
+                let _ = &client_guard;
                 Ok(Event::default()
                     .data("ready")
                     .comment("indicates we should reload page")
@@ -77,6 +83,22 @@ pub fn create_sse_endpoint_handler(
     running_notification: broadcast::Sender<()>,
 ) -> sync::Arc<crate::types::HyperFunc> {
     sync::Arc::new(move |addr, request| {
-        sse_endpoint_reloader(addr, request, running_notification.subscribe())
+        sse_endpoint_reloader(addr, request, running_notification.subscribe(), None)
+    })
+}
+
+/// `create_tracked_sse_endpoint_handler` works like `create_sse_endpoint_handler`
+/// but also counts the connected live reload clients on the giving `StatusBoard`.
+pub fn create_tracked_sse_endpoint_handler(
+    running_notification: broadcast::Sender<()>,
+    status: crate::StatusBoard,
+) -> sync::Arc<crate::types::HyperFunc> {
+    sync::Arc::new(move |addr, request| {
+        sse_endpoint_reloader(
+            addr,
+            request,
+            running_notification.subscribe(),
+            Some(status.clone()),
+        )
     })
 }
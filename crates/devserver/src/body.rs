@@ -1,4 +1,5 @@
 use http_body_util::{combinators::BoxBody, BodyExt, Empty, Full};
+use std::sync;
 
 pub(crate) fn host_addr(uri: &http::Uri) -> Option<String> {
     uri.authority().and_then(|auth| Some(auth.to_string()))
@@ -15,3 +16,156 @@ pub fn full<T: Into<bytes::Bytes>>(chunk: T) -> BoxBody<bytes::Bytes, hyper::Err
         .map_err(|never| match never {})
         .boxed()
 }
+
+/// `TransformedBody` wraps a response body applying a chain of `BodyTransform`
+/// to each data frame as it streams through, the body is never fully buffered.
+pub struct TransformedBody<B> {
+    inner: B,
+    transforms: Vec<Box<dyn crate::BodyTransform>>,
+    trailers: Option<http_body::Frame<bytes::Bytes>>,
+    finished: bool,
+}
+
+// -- Constructors
+
+impl<B> TransformedBody<B> {
+    pub fn new(inner: B, transforms: Vec<Box<dyn crate::BodyTransform>>) -> Self {
+        Self {
+            inner,
+            transforms,
+            trailers: None,
+            finished: false,
+        }
+    }
+}
+
+// -- Transform chain
+
+impl<B> TransformedBody<B> {
+    fn apply(&mut self, chunk: bytes::Bytes) -> bytes::Bytes {
+        self.transforms
+            .iter_mut()
+            .fold(chunk, |data, transform| transform.transform(data))
+    }
+
+    fn flush(&mut self) -> bytes::Bytes {
+        let mut carried = bytes::Bytes::new();
+        for transform in &mut self.transforms {
+            let mut output = if carried.is_empty() {
+                Vec::new()
+            } else {
+                transform.transform(carried).to_vec()
+            };
+            output.extend_from_slice(&transform.finish());
+            carried = bytes::Bytes::from(output);
+        }
+        carried
+    }
+}
+
+impl<B> http_body::Body for TransformedBody<B>
+where
+    B: http_body::Body<Data = bytes::Bytes> + Unpin,
+{
+    type Data = bytes::Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        use std::task::Poll;
+
+        let this = self.get_mut();
+        loop {
+            if this.finished {
+                return Poll::Ready(this.trailers.take().map(Ok));
+            }
+
+            match std::pin::Pin::new(&mut this.inner).poll_frame(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => {
+                        let transformed = this.apply(data);
+                        if transformed.is_empty() {
+                            continue;
+                        }
+                        return Poll::Ready(Some(Ok(http_body::Frame::data(transformed))));
+                    }
+                    Err(trailers) => {
+                        // flush held back data before the trailers go out.
+                        this.trailers = Some(trailers);
+                        this.finished = true;
+                    }
+                },
+                Poll::Ready(None) => {
+                    this.finished = true;
+                }
+            }
+
+            let remaining = this.flush();
+            if !remaining.is_empty() {
+                return Poll::Ready(Some(Ok(http_body::Frame::data(remaining))));
+            }
+        }
+    }
+}
+
+/// `apply_transformers` runs the matching `ResponseTransformer` over a proxied
+/// response, header rewrites are applied directly while body rewrites wrap
+/// the body in a `TransformedBody`.
+///
+/// Body rewrites are skipped for encoded (e.g gzip) responses since we
+/// would be matching against compressed bytes.
+pub fn apply_transformers<B>(
+    response: hyper::Response<B>,
+    transformers: &[sync::Arc<dyn crate::ResponseTransformer>],
+) -> crate::types::HyperResponse
+where
+    B: http_body::Body<Data = bytes::Bytes> + Unpin + Send + 'static,
+    B::Error: Into<crate::types::BoxedError>,
+{
+    let (mut parts, body) = response.into_parts();
+
+    let content_type = parts
+        .headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    let is_encoded = parts
+        .headers
+        .get(http::header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| !value.eq_ignore_ascii_case("identity"));
+
+    let mut body_transforms = Vec::new();
+    for transformer in transformers {
+        if !transformer.matches(content_type.as_deref()) {
+            continue;
+        }
+
+        transformer.transform_headers(&mut parts.headers);
+
+        if is_encoded {
+            continue;
+        }
+
+        if let Some(transform) = transformer.body_transform() {
+            body_transforms.push(transform);
+        }
+    }
+
+    if body_transforms.is_empty() {
+        return hyper::Response::from_parts(parts, axum::body::Body::new(body));
+    }
+
+    // the body length is no longer known ahead of time.
+    parts.headers.remove(http::header::CONTENT_LENGTH);
+
+    hyper::Response::from_parts(
+        parts,
+        axum::body::Body::new(TransformedBody::new(body, body_transforms)),
+    )
+}
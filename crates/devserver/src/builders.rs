@@ -1,10 +1,10 @@
 use tokio::sync::broadcast;
 
 use crate::{
-    assets,
+    assets, dashboard,
     types::{JoinHandle, Result},
-    BinaryApp, CargoShellBuilder, DirectoryWatcher, Operator, ParrellelOps, ProjectDefinition,
-    StreamTCPApp,
+    BinaryApp, CargoShellBuilder, DirectoryWatcher, EnvFileWatcher, Operator, ParrellelOps,
+    ProjectDefinition, StatusBoard, StatusTracker, StreamTCPApp, UpstreamProbe,
 };
 use std::{sync, time};
 
@@ -13,6 +13,8 @@ pub struct HttpDevService {
     pub package_changes: broadcast::Sender<()>,
     pub package_built: broadcast::Sender<()>,
     pub package_started: broadcast::Sender<()>,
+    pub package_failed: broadcast::Sender<String>,
+    pub status: StatusBoard,
 }
 
 // -- Constructors
@@ -22,12 +24,15 @@ impl HttpDevService {
         let (package_changes, _) = broadcast::channel::<()>(2);
         let (package_started, _) = broadcast::channel::<()>(2);
         let (package_built, _) = broadcast::channel::<()>(2);
+        let (package_failed, _) = broadcast::channel::<String>(2);
 
         Self {
             project,
             package_built,
             package_changes,
             package_started,
+            package_failed,
+            status: StatusBoard::new(),
         }
     }
 }
@@ -39,6 +44,7 @@ impl HttpDevService {
 impl HttpDevService {
     pub async fn start(&mut self, canceller: broadcast::Receiver<()>) -> Result<JoinHandle<()>> {
         let package_started = &self.package_started;
+        let status = &self.status;
         self.project.and_proxy_routes(move |routes| {
             // add the script for sse based refresh
             routes
@@ -48,7 +54,13 @@ impl HttpDevService {
             // sse endpoint that the script must call into
             routes
                 .entry(assets::RELOADER_SSE_ENDPOINT.to_string())
-                .or_insert(assets::create_sse_endpoint_handler(package_started.clone()));
+                .or_insert(assets::create_tracked_sse_endpoint_handler(
+                    package_started.clone(),
+                    status.clone(),
+                ));
+
+            // status dashboard for the dev service
+            dashboard::register_dashboard_routes(routes, status);
         });
 
         let project_directory_watcher = DirectoryWatcher::new(
@@ -62,6 +74,7 @@ impl HttpDevService {
             self.project.clone(),
             self.package_built.clone(),
             self.package_changes.clone(),
+            self.package_failed.clone(),
         );
 
         // app_runner restarts when app_builder says its done building
@@ -74,11 +87,33 @@ impl HttpDevService {
         let service_proxy =
             StreamTCPApp::shared(time::Duration::from_secs(1), self.project.proxy.clone());
 
+        let status_tracker = StatusTracker::new(
+            self.project.crate_name.clone(),
+            self.status.clone(),
+            self.package_changes.clone(),
+            self.package_built.clone(),
+            self.package_started.clone(),
+            self.package_failed.clone(),
+        );
+
+        let upstream_probe = UpstreamProbe::new(
+            self.status.clone(),
+            self.project.proxy.destinations(&self.project.crate_name),
+        );
+
+        // env file changes only need the binary restarted, which the
+        // app_runner does whenever it sees a build notification.
+        let env_file_watcher =
+            EnvFileWatcher::new(self.project.env_files.clone(), self.package_built.clone());
+
         let command = ParrellelOps::new(vec![
             Box::new(app_builder),
             Box::new(app_runner),
             Box::new(service_proxy),
             Box::new(project_directory_watcher),
+            Box::new(status_tracker),
+            Box::new(upstream_probe),
+            Box::new(env_file_watcher),
         ]);
 
         let command_op = command.run(canceller);
@@ -2,34 +2,18 @@
 // cargo application after building said application. This lets us control rebuilding and running
 // of application in a different thread based on specifics.
 
-use crate::types::{self, BoxedError};
+use crate::types;
 use crate::{
     operators::{self, Operator},
     types::JoinHandle,
-    ProjectDefinition, SenderExt,
+    DevServerError, ProjectDefinition, SenderExt,
 };
-use derive_more::From;
 use std::{
     process::{self, Stdio},
     sync,
 };
 use tokio::sync::broadcast;
 
-#[derive(Debug, From)]
-pub enum CargoShellError {
-    CargoCheckFailed,
-    CargoBinaryRunFailed,
-    ShellError(BoxedError),
-}
-
-impl std::error::Error for CargoShellError {}
-
-impl core::fmt::Display for CargoShellError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
-    }
-}
-
 type CargoShellResult<T> = types::Result<T>;
 
 /// CargoShellApp implements a cargo project builder and compiler that
@@ -42,6 +26,8 @@ pub struct CargoShellBuilder {
     pub project: ProjectDefinition,
     pub build_notifier: broadcast::Sender<()>,
     pub file_notifications: broadcast::Sender<()>,
+    // delivers the error output of failed checks and builds.
+    pub failure_notifier: broadcast::Sender<String>,
 }
 
 // constructors
@@ -50,11 +36,13 @@ impl CargoShellBuilder {
         project: ProjectDefinition,
         build_notifier: broadcast::Sender<()>,
         file_notifications: broadcast::Sender<()>,
+        failure_notifier: broadcast::Sender<String>,
     ) -> sync::Arc<Self> {
         sync::Arc::new(Self {
             project,
             file_notifications,
             build_notifier,
+            failure_notifier,
         })
     }
 }
@@ -65,6 +53,7 @@ impl Clone for CargoShellBuilder {
             project: self.project.clone(),
             build_notifier: self.build_notifier.clone(),
             file_notifications: self.file_notifications.clone(),
+            failure_notifier: self.failure_notifier.clone(),
         }
     }
 }
@@ -111,6 +100,12 @@ impl CargoShellBuilder {
         Ok(())
     }
 
+    fn notify_failure(&self, message: String) {
+        if self.failure_notifier.send(message).is_err() {
+            ewe_trace::warn!("No one is listening for build failure messages");
+        }
+    }
+
     async fn run_build(&self) -> CargoShellResult<()> {
         ewe_trace::info!(
             "Building project binary with cargo (project={}, binary={:?})",
@@ -135,13 +130,19 @@ impl CargoShellBuilder {
                     self.project.run_arguments,
                 );
                 if !result.status.success() {
+                    let error_output = String::from_utf8_lossy(&result.stderr).to_string();
                     ewe_trace::error!(
                         "Running command `cargo build` returned error (project={}, binary={:?})\n\t{:?}",
                         self.project.crate_name,
                         self.project.run_arguments,
-                        String::from_utf8(result.stderr).expect("should correct decode error"),
+                        error_output,
                     );
-                    return Err(Box::new(CargoShellError::CargoCheckFailed));
+                    self.notify_failure(error_output.clone());
+                    return Err(Box::new(DevServerError::BuildFailed {
+                        project: self.project.crate_name.clone(),
+                        command: String::from("cargo build"),
+                        output: error_output,
+                    }));
                 }
                 Ok(())
             }
@@ -152,7 +153,11 @@ impl CargoShellBuilder {
                     self.project.run_arguments,
                     err,
                 );
-                Err(Box::new(CargoShellError::ShellError(Box::new(err))))
+                self.notify_failure(err.to_string());
+                Err(Box::new(DevServerError::CommandFailed {
+                    command: String::from("cargo build"),
+                    source: err,
+                }))
             }
         }
     }
@@ -172,13 +177,19 @@ impl CargoShellBuilder {
                     self.project.run_arguments,
                 );
                 if !result.status.success() {
+                    let error_output = String::from_utf8_lossy(&result.stderr).to_string();
                     ewe_trace::error!(
                         "Running command `cargo check` returned error (project={}, binary={:?})\n\t{:?}",
                         self.project.crate_name,
                         self.project.run_arguments,
-                        String::from_utf8(result.stderr).expect("should correct decode error"),
+                        error_output,
                     );
-                    return Err(Box::new(CargoShellError::CargoCheckFailed));
+                    self.notify_failure(error_output.clone());
+                    return Err(Box::new(DevServerError::BuildFailed {
+                        project: self.project.crate_name.clone(),
+                        command: String::from("cargo check"),
+                        output: error_output,
+                    }));
                 }
                 Ok(())
             }
@@ -189,7 +200,11 @@ impl CargoShellBuilder {
                     self.project.run_arguments,
                     err,
                 );
-                Err(Box::new(CargoShellError::ShellError(Box::new(err))))
+                self.notify_failure(err.to_string());
+                Err(Box::new(DevServerError::CommandFailed {
+                    command: String::from("cargo check"),
+                    source: err,
+                }))
             }
         }
     }
@@ -252,7 +267,13 @@ impl Operator for sync::Arc<BinaryApp> {
                         }
 
                         ewe_trace::info!("Restarting latest version of binary");
-                        binary_handle = Some(handle.run_binary().expect("re-run binary"));
+                        binary_handle = match handle.run_binary() {
+                            Ok(child) => Some(child),
+                            Err(err) => {
+                                ewe_trace::error!("Failed to restart binary: {:?}", err);
+                                None
+                            }
+                        };
 
                         ewe_trace::info!("Restart done!");
                         if let Err(_) = run_sender.send_in((), wait_before_reload.clone()).await {
@@ -286,10 +307,23 @@ impl BinaryApp {
         let mut binary_and_arguments = self.project.run_arguments.clone();
         let run_arguments = binary_and_arguments.split_off(1);
 
+        let environment = match self.project.environment() {
+            Ok(variables) => variables,
+            Err(err) => {
+                ewe_trace::error!(
+                    "Failed to load environment for binary={:?}: {:?}",
+                    self.project.crate_name,
+                    err,
+                );
+                return Err(Box::new(DevServerError::Environment(err)));
+            }
+        };
+
         let mut command = process::Command::new(binary_and_arguments.pop().unwrap());
         match command
             .current_dir(self.project.workspace_root.clone())
             .args(run_arguments)
+            .envs(environment)
             .stdin(Stdio::null())
             .stdout(Stdio::inherit())
             .stderr(Stdio::inherit())
@@ -310,7 +344,10 @@ impl BinaryApp {
                     self.project.run_arguments,
                     err,
                 );
-                Err(Box::new(CargoShellError::ShellError(Box::new(err))))
+                Err(Box::new(DevServerError::BinaryFailed {
+                    binary: self.project.crate_name.clone(),
+                    source: err,
+                }))
             }
         }
     }
@@ -0,0 +1,243 @@
+// Implements network condition simulation for the dev proxy, letting
+// frontend developers exercise loading states and retry flows against
+// their real backend by adding latency, bandwidth caps and failures.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time;
+
+use axum::body;
+use http::StatusCode;
+use serde::Deserialize;
+
+use crate::types::HyperResponseResult;
+
+/// `NetworkConditions` describes the simulated network a request goes through.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NetworkConditions {
+    /// latency added before the request is forwarded.
+    #[serde(default)]
+    pub latency_ms: u64,
+
+    /// random extra latency between zero and the giving value.
+    #[serde(default)]
+    pub jitter_ms: u64,
+
+    /// caps how fast the response body is streamed back to the client.
+    #[serde(default)]
+    pub bandwidth_bytes_per_sec: Option<u64>,
+
+    /// percentage (0 to 100) of requests that fail with a 503.
+    #[serde(default)]
+    pub failure_percent: f64,
+}
+
+// -- Constructors
+
+impl NetworkConditions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn latency(mut self, latency: time::Duration) -> Self {
+        self.latency_ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    #[must_use]
+    pub fn jitter(mut self, jitter: time::Duration) -> Self {
+        self.jitter_ms = u64::try_from(jitter.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    #[must_use]
+    pub fn bandwidth(mut self, bytes_per_sec: u64) -> Self {
+        self.bandwidth_bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+
+    #[must_use]
+    pub fn failure_percent(mut self, percent: f64) -> Self {
+        self.failure_percent = percent.clamp(0.0, 100.0);
+        self
+    }
+}
+
+// -- Simulation
+
+impl NetworkConditions {
+    fn should_fail(&self) -> bool {
+        self.failure_percent > 0.0 && fastrand::f64() * 100.0 < self.failure_percent
+    }
+
+    fn delay(&self) -> time::Duration {
+        let jitter = if self.jitter_ms > 0 {
+            fastrand::u64(0..=self.jitter_ms)
+        } else {
+            0
+        };
+        time::Duration::from_millis(self.latency_ms.saturating_add(jitter))
+    }
+
+    /// `apply` runs the giving proxy operation under the simulated conditions.
+    pub async fn apply<F>(self, operation: F) -> HyperResponseResult
+    where
+        F: Future<Output = HyperResponseResult>,
+    {
+        if self.should_fail() {
+            ewe_trace::info!("Simulating network failure for request");
+            return Ok(hyper::Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(body::Body::new(crate::full("simulated network failure")))
+                .expect("should build response"));
+        }
+
+        let delay = self.delay();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+
+        let response = operation.await?;
+        match self.bandwidth_bytes_per_sec {
+            Some(rate) if rate > 0 => {
+                Ok(response.map(|b| body::Body::new(ThrottledBody::new(b, rate))))
+            }
+            _ => Ok(response),
+        }
+    }
+}
+
+/// `RouteConditions` applies `NetworkConditions` to requests whose
+/// path starts with the giving prefix.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteConditions {
+    pub path_prefix: String,
+    #[serde(flatten)]
+    pub conditions: NetworkConditions,
+}
+
+// -- Constructors
+
+impl RouteConditions {
+    pub fn new<S: Into<String>>(path_prefix: S, conditions: NetworkConditions) -> Self {
+        Self {
+            path_prefix: path_prefix.into(),
+            conditions,
+        }
+    }
+}
+
+/// `conditions_for` returns the conditions of the first route matching the path.
+pub fn conditions_for(routes: &[RouteConditions], path: &str) -> Option<NetworkConditions> {
+    routes
+        .iter()
+        .find(|route| path.starts_with(route.path_prefix.as_str()))
+        .map(|route| route.conditions.clone())
+}
+
+/// `ThrottledBody` paces a body's data frames to stay within a
+/// bytes-per-second rate, splitting large frames into smaller slices.
+pub struct ThrottledBody<B> {
+    inner: B,
+    bytes_per_sec: u64,
+    pending: bytes::Bytes,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+// -- Constructors
+
+impl<B> ThrottledBody<B> {
+    pub fn new(inner: B, bytes_per_sec: u64) -> Self {
+        Self {
+            inner,
+            bytes_per_sec: bytes_per_sec.max(1),
+            pending: bytes::Bytes::new(),
+            delay: None,
+        }
+    }
+
+    /// slices are sized to about a tenth of a second worth of data.
+    fn slice_size(&self) -> usize {
+        usize::try_from((self.bytes_per_sec / 10).max(1)).unwrap_or(usize::MAX)
+    }
+}
+
+impl<B> http_body::Body for ThrottledBody<B>
+where
+    B: http_body::Body<Data = bytes::Bytes> + Unpin,
+{
+    type Data = bytes::Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                if delay.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.delay = None;
+            }
+
+            if !this.pending.is_empty() {
+                let size = this.pending.len().min(this.slice_size());
+                let slice = this.pending.split_to(size);
+
+                #[allow(clippy::cast_precision_loss)]
+                let wait = time::Duration::from_secs_f64(size as f64 / this.bytes_per_sec as f64);
+                this.delay = Some(Box::pin(tokio::time::sleep(wait)));
+
+                return Poll::Ready(Some(Ok(http_body::Frame::data(slice))));
+            }
+
+            match Pin::new(&mut this.inner).poll_frame(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => this.pending = data,
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                },
+                Poll::Ready(other) => return Poll::Ready(other),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_matching_route_wins() {
+        let routes = vec![
+            RouteConditions::new("/api/slow", NetworkConditions::new().bandwidth(1024)),
+            RouteConditions::new("/api", NetworkConditions::new().failure_percent(100.0)),
+        ];
+
+        let slow = conditions_for(&routes, "/api/slow/items").expect("should match");
+        assert_eq!(slow.bandwidth_bytes_per_sec, Some(1024));
+
+        let api = conditions_for(&routes, "/api/users").expect("should match");
+        assert!(api.should_fail());
+
+        assert!(conditions_for(&routes, "/index.html").is_none());
+    }
+
+    #[test]
+    fn delay_stays_within_jitter() {
+        let conditions = NetworkConditions::new()
+            .latency(time::Duration::from_millis(100))
+            .jitter(time::Duration::from_millis(50));
+
+        for _ in 0..20 {
+            let delay = conditions.delay();
+            assert!(delay >= time::Duration::from_millis(100));
+            assert!(delay <= time::Duration::from_millis(150));
+        }
+    }
+}
@@ -1,10 +1,14 @@
 // Types for the packages
 
+use std::collections::HashMap;
 use std::time;
 
 use derive_more::{Debug, From};
 
-use crate::{types::HyperFuncMap, ProxyType};
+use crate::{
+    types::{HyperFuncMap, ResponseTransformerList},
+    ProxyType,
+};
 
 /// ProjectDefinition defines the underlying project location, directory path and target crate
 /// we want executed in our behalf for the dev service.
@@ -18,6 +22,11 @@ pub struct ProjectDefinition {
     pub build_arguments: Vec<String>,
     pub run_arguments: Vec<String>,
     pub wait_before_reload: time::Duration,
+    /// env are variables set on the app process, they take precedence over `env_files`.
+    pub env: HashMap<String, String>,
+    /// `env_files` are .env files loaded in order into the app process's environment,
+    /// changes to them restart the app.
+    pub env_files: Vec<String>,
 }
 
 impl core::fmt::Display for ProjectDefinition {
@@ -32,4 +41,8 @@ impl ProjectDefinition {
     pub fn and_proxy_routes(&mut self, mutator: impl Fn(&mut HyperFuncMap)) {
         self.proxy.and_routes(mutator);
     }
+
+    pub fn and_proxy_transformers(&mut self, mutator: impl Fn(&mut ResponseTransformerList)) {
+        self.proxy.and_transformers(mutator);
+    }
 }
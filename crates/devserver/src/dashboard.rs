@@ -0,0 +1,182 @@
+// Implements the devserver's status dashboard, served as an html page
+// rendered from the embedded template and as json for tooling.
+
+use axum::body;
+use ewe_templates::minijinja;
+use http::StatusCode;
+use serde::Serialize;
+use std::{sync, time};
+
+use crate::types::{HyperFunc, HyperFuncMap};
+use crate::{AppStatus, StatusBoard, UpstreamHealth};
+
+/// The embedded minijinja template for the status dashboard page.
+pub static DASHBOARD_TEMPLATE: &str = include_str!("./dashboard.html");
+
+/// `DASHBOARD_ENDPOINT` serves the html status dashboard.
+pub static DASHBOARD_ENDPOINT: &str = "/__dev/";
+
+/// `DASHBOARD_JSON_ENDPOINT` serves the json equivalent of the dashboard.
+pub static DASHBOARD_JSON_ENDPOINT: &str = "/__dev/status.json";
+
+fn secs_ago(at: time::SystemTime) -> u64 {
+    at.elapsed().unwrap_or_default().as_secs()
+}
+
+fn as_millis(duration: time::Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppReport {
+    pub name: String,
+    pub state: String,
+    pub rebuilds: usize,
+    pub updated_secs_ago: u64,
+    pub last_build_duration_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub watcher_events: usize,
+    pub last_watcher_event_secs_ago: Option<u64>,
+}
+
+impl From<AppStatus> for AppReport {
+    fn from(status: AppStatus) -> Self {
+        Self {
+            name: status.name,
+            state: status.state.to_string(),
+            rebuilds: status.rebuilds,
+            updated_secs_ago: secs_ago(status.updated_at),
+            last_build_duration_ms: status.last_build_duration.map(as_millis),
+            last_error: status.last_error,
+            watcher_events: status.watcher_events,
+            last_watcher_event_secs_ago: status.last_watcher_event.map(secs_ago),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamReport {
+    pub name: String,
+    pub addr: String,
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    pub checked_secs_ago: u64,
+}
+
+impl From<UpstreamHealth> for UpstreamReport {
+    fn from(health: UpstreamHealth) -> Self {
+        Self {
+            name: health.name,
+            addr: health.addr,
+            healthy: health.healthy,
+            latency_ms: health.latency.map(as_millis),
+            checked_secs_ago: secs_ago(health.checked_at),
+        }
+    }
+}
+
+/// `DashboardReport` is the serializable view of a `StatusBoard` used
+/// for both the html and json dashboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardReport {
+    pub apps: Vec<AppReport>,
+    pub upstreams: Vec<UpstreamReport>,
+    pub reload_clients: usize,
+}
+
+impl From<&StatusBoard> for DashboardReport {
+    fn from(board: &StatusBoard) -> Self {
+        Self {
+            apps: board.snapshot().into_iter().map(AppReport::from).collect(),
+            upstreams: board
+                .upstreams()
+                .into_iter()
+                .map(UpstreamReport::from)
+                .collect(),
+            reload_clients: board.reload_clients(),
+        }
+    }
+}
+
+pub fn render_dashboard(report: &DashboardReport) -> Result<String, minijinja::Error> {
+    let mut env = minijinja::Environment::new();
+    env.add_template("dashboard.html", DASHBOARD_TEMPLATE)?;
+    env.get_template("dashboard.html")?.render(report)
+}
+
+fn respond(status: StatusCode, content_type: &str, content: String) -> crate::types::HyperResponse {
+    hyper::Response::builder()
+        .header("Content-Type", content_type)
+        .status(status)
+        .body(body::Body::new(crate::full(content)))
+        .expect("should build response")
+}
+
+pub fn create_dashboard_handler(board: StatusBoard) -> sync::Arc<HyperFunc> {
+    sync::Arc::new(move |_addr, _request| {
+        let report = DashboardReport::from(&board);
+        Box::pin(async move {
+            Ok(match render_dashboard(&report) {
+                Ok(page) => respond(StatusCode::OK, "text/html; charset=utf-8", page),
+                Err(err) => {
+                    ewe_trace::error!("Failed to render status dashboard: {:?}", err);
+                    respond(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "text/plain",
+                        format!("failed to render dashboard: {err}"),
+                    )
+                }
+            })
+        })
+    })
+}
+
+pub fn create_dashboard_json_handler(board: StatusBoard) -> sync::Arc<HyperFunc> {
+    sync::Arc::new(move |_addr, _request| {
+        let report = DashboardReport::from(&board);
+        Box::pin(async move {
+            Ok(match serde_json::to_string(&report) {
+                Ok(content) => respond(StatusCode::OK, "application/json", content),
+                Err(err) => respond(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "text/plain",
+                    format!("failed to encode status: {err}"),
+                ),
+            })
+        })
+    })
+}
+
+/// `register_dashboard_routes` adds the dashboard endpoints to the proxy routes.
+pub fn register_dashboard_routes(routes: &mut HyperFuncMap, board: &StatusBoard) {
+    let page = create_dashboard_handler(board.clone());
+    routes
+        .entry(DASHBOARD_ENDPOINT.trim_end_matches('/').to_string())
+        .or_insert(page.clone());
+    routes.entry(DASHBOARD_ENDPOINT.to_string()).or_insert(page);
+    routes
+        .entry(DASHBOARD_JSON_ENDPOINT.to_string())
+        .or_insert(create_dashboard_json_handler(board.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppState;
+
+    #[test]
+    fn can_render_dashboard_page() {
+        let board = StatusBoard::new();
+        board.update("admin", AppState::Building);
+        board.fail("admin", "error[E0308]: mismatched types");
+        let _client = board.reload_client();
+
+        let report = DashboardReport::from(&board);
+        assert_eq!(report.reload_clients, 1);
+        assert_eq!(report.apps[0].state, "Failed");
+
+        let page = render_dashboard(&report).expect("should render");
+        assert!(page.contains("admin"));
+        assert!(page.contains("mismatched types"));
+    }
+}
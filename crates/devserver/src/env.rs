@@ -0,0 +1,219 @@
+// Implements environment management for the app under development, loading
+// variables from .env files and explicit definitions and restarting the app
+// when any of the .env files change.
+
+use std::collections::HashMap;
+use std::path;
+
+use derive_more::From;
+use ewe_watch_utils::watch_path;
+use tokio::sync::broadcast;
+
+use crate::types::JoinHandle;
+use crate::{Operator, ProjectDefinition};
+
+// -- Errors
+
+#[derive(Debug, From)]
+pub enum EnvError {
+    #[from(ignore)]
+    InvalidLine {
+        file: String,
+        line: usize,
+    },
+
+    FailedReading(std::io::Error),
+}
+
+impl std::error::Error for EnvError {}
+
+impl core::fmt::Display for EnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+type EnvResult<T> = std::result::Result<T, EnvError>;
+
+fn unquote(value: &str) -> String {
+    let trimmed = value.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+        return trimmed[1..trimmed.len() - 1]
+            .replace("\\n", "\n")
+            .replace("\\t", "\t")
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+    }
+    if trimmed.len() >= 2 && trimmed.starts_with('\'') && trimmed.ends_with('\'') {
+        return trimmed[1..trimmed.len() - 1].to_string();
+    }
+
+    // unquoted values may carry trailing comments.
+    match trimmed.find(" #") {
+        Some(index) => trimmed[..index].trim_end().to_string(),
+        None => trimmed.to_string(),
+    }
+}
+
+/// `parse_env` parses the content of a .env file into its key-value pairs.
+///
+/// Supports blank lines, `#` comments, an optional `export` prefix and
+/// single or double quoted values.
+pub fn parse_env(file: &str, content: &str) -> EnvResult<Vec<(String, String)>> {
+    let mut variables = Vec::new();
+    for (index, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(EnvError::InvalidLine {
+                file: file.to_string(),
+                line: index + 1,
+            });
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(EnvError::InvalidLine {
+                file: file.to_string(),
+                line: index + 1,
+            });
+        }
+
+        variables.push((key.to_string(), unquote(value)));
+    }
+    Ok(variables)
+}
+
+/// `load_env_files` loads the giving .env files in order, later files
+/// override earlier ones and missing files are skipped.
+pub fn load_env_files(files: &[String]) -> EnvResult<HashMap<String, String>> {
+    let mut variables = HashMap::new();
+    for file in files {
+        if !path::Path::new(file).exists() {
+            ewe_trace::warn!("Skipping missing env file: {}", file);
+            continue;
+        }
+
+        let content = std::fs::read_to_string(file)?;
+        variables.extend(parse_env(file, &content)?);
+    }
+    Ok(variables)
+}
+
+impl ProjectDefinition {
+    /// `environment` returns the variables the app should be started with,
+    /// explicit `env` definitions take precedence over .env file values.
+    pub fn environment(&self) -> EnvResult<HashMap<String, String>> {
+        let mut variables = load_env_files(&self.env_files)?;
+        variables.extend(self.env.clone());
+        Ok(variables)
+    }
+}
+
+/// `EnvFileWatcher` watches the project's .env files and notifies the
+/// restart channel whenever one of them changes, so the app picks up
+/// the new environment without a rebuild.
+pub struct EnvFileWatcher {
+    pub env_files: Vec<String>,
+    pub restart_sender: broadcast::Sender<()>,
+}
+
+// -- Constructors
+
+impl EnvFileWatcher {
+    pub fn new(env_files: Vec<String>, restart_sender: broadcast::Sender<()>) -> Self {
+        Self {
+            env_files,
+            restart_sender,
+        }
+    }
+}
+
+// -- Operator implementation
+
+impl Operator for EnvFileWatcher {
+    fn run(&self, mut cancel_signal: broadcast::Receiver<()>) -> JoinHandle<()> {
+        let mut handles = Vec::new();
+
+        for env_file in &self.env_files {
+            let file_path = match std::env::current_dir() {
+                Ok(current_dir) => current_dir.join(env_file),
+                Err(_) => path::PathBuf::from(env_file),
+            };
+
+            // editors tend to replace files on save, so we watch the parent
+            // directory rather than the file itself.
+            let parent = file_path
+                .parent()
+                .map_or_else(|| String::from("."), |p| p.to_string_lossy().to_string());
+
+            let sender = self.restart_sender.clone();
+            let target = file_path.clone();
+            let watch_callback = move |_, _, _, paths: Vec<path::PathBuf>| {
+                if paths.iter().any(|changed| changed == &target) {
+                    ewe_trace::info!("Env file changed, restarting app: {:?}", target);
+                    if sender.send(()).is_err() {
+                        ewe_trace::warn!("No one is listening for env file changes");
+                    }
+                }
+                Ok(())
+            };
+
+            match watch_path(300, parent.clone(), false, watch_callback) {
+                Ok(handle) => handles.push(handle),
+                Err(err) => {
+                    let err = crate::DevServerError::watcher_failed(parent, err);
+                    ewe_trace::error!("Failed to watch env file {}: {}", env_file, err);
+                }
+            }
+        }
+
+        tokio::spawn(async move {
+            let _ = cancel_signal.recv().await;
+            for handle in handles {
+                handle.1.stop();
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_env_content() {
+        let content = r#"
+            # database settings
+            DATABASE_URL=postgres://localhost/app
+            export API_KEY="secret \"value\""
+            GREETING='hello # world'
+            PORT=3000 # dev port
+            EMPTY=
+        "#;
+
+        let variables: HashMap<String, String> = parse_env(".env", content)
+            .expect("should parse")
+            .into_iter()
+            .collect();
+
+        assert_eq!(variables["DATABASE_URL"], "postgres://localhost/app");
+        assert_eq!(variables["API_KEY"], "secret \"value\"");
+        assert_eq!(variables["GREETING"], "hello # world");
+        assert_eq!(variables["PORT"], "3000");
+        assert_eq!(variables["EMPTY"], "");
+    }
+
+    #[test]
+    fn rejects_lines_without_assignment() {
+        assert!(matches!(
+            parse_env(".env", "VALID=1\nINVALID"),
+            Err(EnvError::InvalidLine { line: 2, .. })
+        ));
+    }
+}
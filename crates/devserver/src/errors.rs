@@ -1,19 +1,248 @@
+use std::io;
+
 use derive_more::From;
 
 use crate::types::BoxedError;
+use crate::{EnvError, ProxyType, WorkspaceError};
 
 // -- Errors
 
+/// `DevServerError` is the error returned by the devserver's operators,
+/// each variant carries the context and underlying error needed to act on
+/// it and its `Display` output includes a suggested fix where we have one.
 #[derive(Debug, From)]
-pub enum StreamError {
-    Failed,
-    FailedStreaming(BoxedError),
+pub enum DevServerError {
+    /// cargo ran but rejected the project, `output` is cargo's stderr.
+    #[from(ignore)]
+    BuildFailed {
+        project: String,
+        command: String,
+        output: String,
+    },
+
+    /// a command (e.g cargo) could not be executed at all.
+    #[from(ignore)]
+    CommandFailed {
+        command: String,
+        source: io::Error,
+    },
+
+    /// the app's binary could not be started.
+    #[from(ignore)]
+    BinaryFailed {
+        binary: String,
+        source: io::Error,
+    },
+
+    /// the app could not be reached by the proxy.
+    #[from(ignore)]
+    UpstreamUnreachable {
+        addr: String,
+        source: io::Error,
+    },
+
+    /// the proxy could not listen on its address as it is already taken.
+    #[from(ignore)]
+    PortInUse {
+        addr: String,
+        source: io::Error,
+    },
+
+    /// the proxy could not listen on its address for other reasons.
+    #[from(ignore)]
+    BindFailed {
+        addr: String,
+        source: io::Error,
+    },
+
+    /// the OS limit of watched files was reached.
+    #[from(ignore)]
+    WatcherExhausted {
+        directory: String,
+    },
+
+    #[from(ignore)]
+    WatcherFailed {
+        directory: String,
+        source: BoxedError,
+    },
+
+    #[from(ignore)]
+    WatcherStopped {
+        directory: String,
+    },
+
+    #[from(ignore)]
+    TlsSetup(BoxedError),
+
+    #[from(ignore)]
+    UnsupportedProxy(Box<ProxyType>),
+
+    #[from(ignore)]
+    ProxyFailed(BoxedError),
+
+    StreamingFailed(hyper::Error),
+    Environment(EnvError),
+    Workspace(WorkspaceError),
 }
 
-impl std::error::Error for StreamError {}
+// -- Constructors
 
-impl core::fmt::Display for StreamError {
+impl DevServerError {
+    /// `bind_failed` classifies a listener bind failure, separating an
+    /// address already in use from other failures.
+    pub fn bind_failed<S: Into<String>>(addr: S, source: io::Error) -> Self {
+        let addr = addr.into();
+        if source.kind() == io::ErrorKind::AddrInUse {
+            return Self::PortInUse { addr, source };
+        }
+        Self::BindFailed { addr, source }
+    }
+
+    /// `watcher_failed` classifies a failure to create a file watcher,
+    /// separating an exhausted OS watch limit from other failures.
+    pub fn watcher_failed<S: Into<String>>(directory: S, source: anyhow::Error) -> Self {
+        let directory = directory.into();
+        let exhausted = source
+            .downcast_ref::<notify::Error>()
+            .is_some_and(|err| matches!(err.kind, notify::ErrorKind::MaxFilesWatch));
+        if exhausted {
+            return Self::WatcherExhausted { directory };
+        }
+        Self::WatcherFailed {
+            directory,
+            source: source.into(),
+        }
+    }
+}
+
+// -- Methods
+
+impl DevServerError {
+    /// `suggestion` returns a hint on how to resolve the error, if any.
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Self::BuildFailed { .. } => {
+                Some("fix the compiler errors above, the app rebuilds on save")
+            }
+            Self::CommandFailed { .. } => {
+                Some("ensure cargo is installed and available in your PATH")
+            }
+            Self::BinaryFailed { .. } => {
+                Some("check the app's run_arguments point to a runnable binary")
+            }
+            Self::UpstreamUnreachable { .. } => {
+                Some("ensure the app is running and listening on its destination address")
+            }
+            Self::PortInUse { .. } => {
+                Some("stop the process using the port or configure another proxy port")
+            }
+            Self::WatcherExhausted { .. } => {
+                Some("raise the limit with `sysctl fs.inotify.max_user_watches=524288`")
+            }
+            Self::TlsSetup(_) => {
+                Some("check the certificate and key files exist and are valid PEM")
+            }
+            Self::Environment(EnvError::InvalidLine { .. }) => {
+                Some("lines in .env files must have the form KEY=value")
+            }
+            Self::Workspace(WorkspaceError::AmbiguousDefaultApp(_)) => {
+                Some("give all but one app a mount path or host")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl std::error::Error for DevServerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CommandFailed { source, .. }
+            | Self::BinaryFailed { source, .. }
+            | Self::UpstreamUnreachable { source, .. }
+            | Self::PortInUse { source, .. }
+            | Self::BindFailed { source, .. } => Some(source),
+            Self::WatcherFailed { source, .. }
+            | Self::TlsSetup(source)
+            | Self::ProxyFailed(source) => Some(source.as_ref()),
+            Self::StreamingFailed(err) => Some(err),
+            Self::Environment(err) => Some(err),
+            Self::Workspace(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for DevServerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Self::BuildFailed {
+                project,
+                command,
+                output,
+            } => write!(f, "`{command}` failed for {project}:\n{output}")?,
+            Self::CommandFailed { command, source } => {
+                write!(f, "failed to execute `{command}`: {source}")?;
+            }
+            Self::BinaryFailed { binary, source } => {
+                write!(f, "failed to start binary {binary}: {source}")?;
+            }
+            Self::UpstreamUnreachable { addr, source } => {
+                write!(f, "upstream {addr} is unreachable: {source}")?;
+            }
+            Self::PortInUse { addr, .. } => write!(f, "address {addr} is already in use")?,
+            Self::BindFailed { addr, source } => write!(f, "failed to listen on {addr}: {source}")?,
+            Self::WatcherExhausted { directory } => {
+                write!(f, "file watch limit reached while watching {directory}")?;
+            }
+            Self::WatcherFailed { directory, source } => {
+                write!(f, "failed to watch {directory}: {source}")?;
+            }
+            Self::WatcherStopped { directory } => {
+                write!(f, "watcher for {directory} did not stop correctly")?;
+            }
+            Self::TlsSetup(err) => write!(f, "failed to setup tls: {err}")?,
+            Self::UnsupportedProxy(proxy) => write!(f, "operation not supported by proxy {proxy}")?,
+            Self::ProxyFailed(err) => write!(f, "proxy failed: {err}")?,
+            Self::StreamingFailed(err) => write!(f, "failed to stream connection: {err}")?,
+            Self::Environment(err) => write!(f, "invalid environment: {err}")?,
+            Self::Workspace(err) => write!(f, "invalid workspace: {err}")?,
+        }
+
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, "\n  hint: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_failures_are_classified() {
+        let in_use =
+            DevServerError::bind_failed("0.0.0.0:3000", io::Error::from(io::ErrorKind::AddrInUse));
+        assert!(matches!(in_use, DevServerError::PortInUse { .. }));
+        assert!(in_use.to_string().contains("hint:"));
+
+        let denied = DevServerError::bind_failed(
+            "0.0.0.0:80",
+            io::Error::from(io::ErrorKind::PermissionDenied),
+        );
+        assert!(matches!(denied, DevServerError::BindFailed { .. }));
+        assert!(std::error::Error::source(&denied).is_some());
+    }
+
+    #[test]
+    fn watcher_limit_is_classified() {
+        let err = DevServerError::watcher_failed(
+            "./src",
+            anyhow::Error::new(notify::Error::new(notify::ErrorKind::MaxFilesWatch)),
+        );
+        assert!(matches!(err, DevServerError::WatcherExhausted { .. }));
+        assert!(err.to_string().contains("max_user_watches"));
     }
 }
@@ -4,27 +4,38 @@
 mod body;
 mod builders;
 mod cargo;
+mod conditions;
 mod core;
+mod env;
 mod errors;
 mod operators;
 mod proxy;
 mod sender_ext;
+mod spa;
+mod status;
 mod streams;
 mod vec_ext;
 mod watchers;
+mod workspace;
 
 pub mod assets;
+pub mod dashboard;
 pub mod types;
 
 pub use body::*;
 pub use builders::*;
 pub use cargo::*;
+pub use conditions::*;
 pub use core::*;
+pub use env::*;
 pub use errors::*;
 pub use operators::*;
 pub use proxy::*;
 pub use sender_ext::*;
+pub use spa::*;
+pub use status::*;
 pub use vec_ext::*;
 pub use watchers::*;
+pub use workspace::*;
 
 // re-export core type without types module
@@ -85,3 +85,390 @@ impl Operator for ParrellelOps {
         })
     }
 }
+
+// -- Response transformers
+
+/// `BodyTransform` rewrites a response body one chunk at a time as it streams
+/// through the proxy, a new instance is created for every response so
+/// implementations can freely keep state between chunks.
+pub trait BodyTransform: Send {
+    /// transform receives the next chunk of the body and returns what should
+    /// be sent to the client, returning an empty `Bytes` holds back the chunk.
+    fn transform(&mut self, chunk: bytes::Bytes) -> bytes::Bytes;
+
+    /// finish is called once the upstream body is exhausted allowing any
+    /// held back data to be flushed out.
+    fn finish(&mut self) -> bytes::Bytes {
+        bytes::Bytes::new()
+    }
+}
+
+/// `ResponseTransformer` is a middleware applied to every proxied response
+/// whose content-type it matches, letting it rewrite headers and optionally
+/// provide a `BodyTransform` for the body.
+pub trait ResponseTransformer: Send + Sync {
+    /// matches returns true if the transformer should be applied to a response
+    /// with the giving content-type.
+    fn matches(&self, content_type: Option<&str>) -> bool;
+
+    /// `transform_headers` lets the transformer add, remove or modify response headers.
+    fn transform_headers(&self, _headers: &mut http::HeaderMap) {}
+
+    /// `body_transform` returns a new `BodyTransform` for a matching response
+    /// if the transformer wishes to rewrite the body.
+    fn body_transform(&self) -> Option<Box<dyn BodyTransform>> {
+        None
+    }
+}
+
+fn content_type_matches(content_types: &[String], content_type: Option<&str>) -> bool {
+    if content_types.is_empty() {
+        return true;
+    }
+    match content_type {
+        Some(value) => {
+            let mime = value.split(';').next().unwrap_or("").trim();
+            content_types
+                .iter()
+                .any(|target| mime.eq_ignore_ascii_case(target))
+        }
+        None => false,
+    }
+}
+
+/// `StreamReplace` is a `BodyTransform` that replaces occurrences of a needle
+/// with a replacement, it holds back just enough bytes between chunks to
+/// catch matches that span chunk boundaries without buffering the whole body.
+pub struct StreamReplace {
+    needle: Vec<u8>,
+    replacement: bytes::Bytes,
+    ignore_case: bool,
+    limit: Option<usize>,
+    fallback: Option<bytes::Bytes>,
+    replaced: usize,
+    pending: Vec<u8>,
+}
+
+// -- Constructors
+
+impl StreamReplace {
+    pub fn new<N, R>(needle: N, replacement: R) -> Self
+    where
+        N: Into<Vec<u8>>,
+        R: Into<bytes::Bytes>,
+    {
+        Self {
+            needle: needle.into(),
+            replacement: replacement.into(),
+            ignore_case: false,
+            limit: None,
+            fallback: None,
+            replaced: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// `ignore_case` makes needle matching ascii case insensitive.
+    #[must_use]
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// limit caps how many replacements are performed.
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// fallback sets content to append at the end of the body if
+    /// the needle was never found.
+    #[must_use]
+    pub fn fallback<R: Into<bytes::Bytes>>(mut self, content: R) -> Self {
+        self.fallback = Some(content.into());
+        self
+    }
+}
+
+// -- Matching details
+
+impl StreamReplace {
+    fn exhausted(&self) -> bool {
+        match self.limit {
+            Some(limit) => self.replaced >= limit,
+            None => false,
+        }
+    }
+
+    fn find(&self, haystack: &[u8]) -> Option<usize> {
+        if self.needle.is_empty() || haystack.len() < self.needle.len() {
+            return None;
+        }
+        haystack.windows(self.needle.len()).position(|window| {
+            if self.ignore_case {
+                window.eq_ignore_ascii_case(&self.needle)
+            } else {
+                window == self.needle.as_slice()
+            }
+        })
+    }
+}
+
+impl BodyTransform for StreamReplace {
+    fn transform(&mut self, chunk: bytes::Bytes) -> bytes::Bytes {
+        if self.exhausted() && self.pending.is_empty() {
+            return chunk;
+        }
+
+        self.pending.extend_from_slice(&chunk);
+
+        let mut output = Vec::with_capacity(self.pending.len());
+        let mut position = 0;
+        while !self.exhausted() {
+            match self.find(&self.pending[position..]) {
+                Some(index) => {
+                    output.extend_from_slice(&self.pending[position..position + index]);
+                    output.extend_from_slice(&self.replacement);
+                    position += index + self.needle.len();
+                    self.replaced += 1;
+                }
+                None => break,
+            }
+        }
+
+        let remaining = self.pending.len() - position;
+        let keep = if self.exhausted() {
+            0
+        } else {
+            remaining.min(self.needle.len().saturating_sub(1))
+        };
+
+        let flush_until = self.pending.len() - keep;
+        output.extend_from_slice(&self.pending[position..flush_until]);
+        self.pending.drain(..flush_until);
+
+        bytes::Bytes::from(output)
+    }
+
+    fn finish(&mut self) -> bytes::Bytes {
+        let mut output = std::mem::take(&mut self.pending);
+        if self.replaced == 0 {
+            if let Some(fallback) = &self.fallback {
+                output.extend_from_slice(fallback);
+            }
+        }
+        bytes::Bytes::from(output)
+    }
+}
+
+/// `ScriptInjector` injects a script tag right before the closing body tag
+/// of html responses, appending it to the end if no body tag was found.
+pub struct ScriptInjector {
+    script_tag: String,
+}
+
+// -- Constructors
+
+impl ScriptInjector {
+    pub fn new<S: Into<String>>(script_src: S) -> Self {
+        Self {
+            script_tag: format!("<script src=\"{}\"></script>", script_src.into()),
+        }
+    }
+
+    /// reloader returns a `ScriptInjector` for the devserver's live reload script.
+    #[must_use]
+    pub fn reloader() -> Self {
+        Self::new(crate::assets::RELOADER_SCRIPT_ENDPOINT)
+    }
+}
+
+impl ResponseTransformer for ScriptInjector {
+    fn matches(&self, content_type: Option<&str>) -> bool {
+        content_type_matches(&[String::from("text/html")], content_type)
+    }
+
+    fn body_transform(&self) -> Option<Box<dyn BodyTransform>> {
+        Some(Box::new(
+            StreamReplace::new("</body>", format!("{}</body>", self.script_tag))
+                .ignore_case()
+                .limit(1)
+                .fallback(self.script_tag.clone()),
+        ))
+    }
+}
+
+/// `UrlRewriter` rewrites absolute urls (e.g `http://0.0.0.0:3200`) pointing to the
+/// proxied destination into the giving replacement within matching content-types.
+pub struct UrlRewriter {
+    from: String,
+    to: String,
+    content_types: Vec<String>,
+}
+
+// -- Constructors
+
+impl UrlRewriter {
+    pub fn new<F, T>(from: F, to: T) -> Self
+    where
+        F: Into<String>,
+        T: Into<String>,
+    {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            content_types: vec![
+                String::from("text/html"),
+                String::from("text/css"),
+                String::from("text/javascript"),
+                String::from("application/javascript"),
+                String::from("application/json"),
+            ],
+        }
+    }
+
+    /// `with_content_types` replaces the list of content-types the rewriter applies to.
+    #[must_use]
+    pub fn with_content_types(mut self, content_types: Vec<String>) -> Self {
+        self.content_types = content_types;
+        self
+    }
+}
+
+impl ResponseTransformer for UrlRewriter {
+    fn matches(&self, content_type: Option<&str>) -> bool {
+        content_type_matches(&self.content_types, content_type)
+    }
+
+    fn body_transform(&self) -> Option<Box<dyn BodyTransform>> {
+        Some(Box::new(StreamReplace::new(
+            self.from.as_bytes(),
+            self.to.clone(),
+        )))
+    }
+}
+
+/// `CorsHeaders` appends the relevant CORS headers to every proxied response.
+pub struct CorsHeaders {
+    origin: String,
+    methods: String,
+    headers: String,
+}
+
+// -- Constructors
+
+impl CorsHeaders {
+    pub fn new<S: Into<String>>(allow_origin: S) -> Self {
+        Self {
+            origin: allow_origin.into(),
+            methods: String::from("GET, POST, PUT, PATCH, DELETE, OPTIONS"),
+            headers: String::from("*"),
+        }
+    }
+
+    /// permissive allows every origin, method and header.
+    #[must_use]
+    pub fn permissive() -> Self {
+        Self::new("*")
+    }
+
+    #[must_use]
+    pub fn allow_methods<S: Into<String>>(mut self, methods: S) -> Self {
+        self.methods = methods.into();
+        self
+    }
+
+    #[must_use]
+    pub fn allow_headers<S: Into<String>>(mut self, headers: S) -> Self {
+        self.headers = headers.into();
+        self
+    }
+}
+
+impl ResponseTransformer for CorsHeaders {
+    fn matches(&self, _content_type: Option<&str>) -> bool {
+        true
+    }
+
+    fn transform_headers(&self, headers: &mut http::HeaderMap) {
+        let values = [
+            (http::header::ACCESS_CONTROL_ALLOW_ORIGIN, &self.origin),
+            (http::header::ACCESS_CONTROL_ALLOW_METHODS, &self.methods),
+            (http::header::ACCESS_CONTROL_ALLOW_HEADERS, &self.headers),
+        ];
+
+        for (name, value) in values {
+            match http::HeaderValue::from_str(value) {
+                Ok(header_value) => {
+                    headers.insert(name, header_value);
+                }
+                Err(err) => {
+                    ewe_trace::error!("Invalid CORS header value {:?}: {:?}", value, err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_transformers {
+    use super::*;
+
+    fn run_chunks(transform: &mut dyn BodyTransform, chunks: &[&str]) -> String {
+        let mut output = Vec::new();
+        for chunk in chunks {
+            output
+                .extend_from_slice(&transform.transform(bytes::Bytes::from((*chunk).to_string())));
+        }
+        output.extend_from_slice(&transform.finish());
+        String::from_utf8(output).expect("should be utf8")
+    }
+
+    #[test]
+    fn stream_replace_matches_across_chunk_boundaries() {
+        let mut replacer = StreamReplace::new("http://0.0.0.0:3200", "http://localhost:3000");
+        let content = run_chunks(
+            &mut replacer,
+            &[
+                "<a href=\"http://0.0.",
+                "0.0:3200/home\">",
+                "http://0.0.0.0:3200",
+            ],
+        );
+        assert_eq!(
+            content,
+            "<a href=\"http://localhost:3000/home\">http://localhost:3000"
+        );
+    }
+
+    #[test]
+    fn script_injector_places_script_before_body_close() {
+        let injector = ScriptInjector::new("/reload.js");
+        let mut transform = injector.body_transform().expect("should have transform");
+        let content = run_chunks(
+            transform.as_mut(),
+            &["<html><body><p>hello</p></BO", "DY></html>"],
+        );
+        assert_eq!(
+            content,
+            "<html><body><p>hello</p><script src=\"/reload.js\"></script></body></html>"
+        );
+    }
+
+    #[test]
+    fn script_injector_appends_when_body_tag_missing() {
+        let injector = ScriptInjector::new("/reload.js");
+        let mut transform = injector.body_transform().expect("should have transform");
+        let content = run_chunks(transform.as_mut(), &["<p>hello</p>"]);
+        assert_eq!(content, "<p>hello</p><script src=\"/reload.js\"></script>");
+    }
+
+    #[test]
+    fn content_types_match_ignoring_parameters() {
+        let injector = ScriptInjector::reloader();
+        assert!(injector.matches(Some("text/html; charset=utf-8")));
+        assert!(!injector.matches(Some("application/json")));
+        assert!(!injector.matches(None));
+    }
+}
@@ -1,4 +1,3 @@
-use derive_more::From;
 use hyper_util::rt;
 use std::net::SocketAddr;
 use std::{sync, time};
@@ -8,26 +7,11 @@ use tokio::sync::broadcast;
 use tokio::net;
 
 use crate::streams;
-use crate::types::{Http1, Http2, Http3, HyperFuncMap, JoinHandle, Result, Tunnel};
-use crate::Operator;
-
-// -- Errors
-
-#[derive(Debug, From)]
-pub enum ProxyError {
-    FailedProxyConnection,
-    ConnectionDrop,
-    StreamingFailed,
-    TunnelNotSupported(ProxyType),
-}
-
-impl std::error::Error for ProxyError {}
-
-impl core::fmt::Display for ProxyError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
-    }
-}
+use crate::types::{
+    Http1, Http2, Http3, HyperFuncMap, JoinHandle, ProxyRemoteConfig, ResponseTransformerList,
+    Result, Tunnel,
+};
+use crate::{DevServerError, Operator};
 
 // -- Proxy Types
 
@@ -56,6 +40,42 @@ impl ProxyType {
             Self::Http3(http3) => http3.and_routes(mutator),
         }
     }
+
+    pub fn and_transformers(&mut self, mutator: impl Fn(&mut ResponseTransformerList)) {
+        match self {
+            Self::Tunnel(_) => panic!("Tunnel() do not have response transformers"),
+            Self::Http1(http1) => http1.and_transformers(mutator),
+            Self::Http2(http2) => http2.and_transformers(mutator),
+            Self::Http3(http3) => http3.and_transformers(mutator),
+        }
+    }
+}
+
+// -- Getters
+
+impl ProxyType {
+    /// `destinations` returns the named upstream destinations of the proxy,
+    /// the default destination is named after the giving name while mounts
+    /// are named by their host and path prefix.
+    pub fn destinations(&self, name: &str) -> Vec<(String, ProxyRemoteConfig)> {
+        match self {
+            Self::Tunnel(t) => vec![(name.to_string(), t.destination.clone())],
+            Self::Http1(t) => {
+                let mut destinations = vec![(name.to_string(), t.destination.clone())];
+                for mount in &t.mounts {
+                    let mount_name = format!(
+                        "{}{}",
+                        mount.host.clone().unwrap_or_default(),
+                        mount.path_prefix.clone().unwrap_or_default()
+                    );
+                    destinations.push((mount_name, mount.destination.clone()));
+                }
+                destinations
+            }
+            Self::Http2(t) => vec![(name.to_string(), t.destination.clone())],
+            Self::Http3(t) => vec![(name.to_string(), t.destination.clone())],
+        }
+    }
 }
 
 // -- Streaming implementations
@@ -74,7 +94,7 @@ impl ProxyType {
                 );
                 Ok(())
             }
-            _ => Err(Box::new(ProxyError::TunnelNotSupported(self)).into()),
+            _ => Err(Box::new(DevServerError::UnsupportedProxy(Box::new(self))).into()),
         }
     }
 
@@ -92,7 +112,7 @@ impl ProxyType {
                 );
                 Ok(())
             }
-            _ => Err(Box::new(ProxyError::TunnelNotSupported(self)).into()),
+            _ => Err(Box::new(DevServerError::UnsupportedProxy(Box::new(self))).into()),
         }
     }
 }
@@ -136,7 +156,9 @@ impl ProxyRemote {
                 match &self.0 {
                     ProxyType::Http1(t) => {
                         ewe_trace::info!("Creating TCPListener for {} (addr_str: {}, protocol: Http1) to {}", t.source, t.source.to_string(), t.destination);
-                        let source_listener = net::TcpListener::bind(t.source.to_string()).await?;
+                        let source_listener = net::TcpListener::bind(t.source.to_string())
+                            .await
+                            .map_err(|err| DevServerError::bind_failed(t.source.to_string(), err))?;
 
                         loop {
                             let proxy_elem = self.0.clone();
@@ -167,7 +189,9 @@ impl ProxyRemote {
                     },
                     ProxyType::Tunnel(t) => {
                         ewe_trace::info!("Creating TCPListener for {} (addr_str: {}, protocol: tunnel) to {}", t.source, t.source.to_string(), t.destination);
-                        let source_listener = net::TcpListener::bind(t.source.to_string()).await?;
+                        let source_listener = net::TcpListener::bind(t.source.to_string())
+                            .await
+                            .map_err(|err| DevServerError::bind_failed(t.source.to_string(), err))?;
 
                         loop {
                             let proxy_elem = self.0.clone();
@@ -196,7 +220,7 @@ impl ProxyRemote {
                         }
                         Ok(())
                     },
-                    _ => Err(Box::new(ProxyError::TunnelNotSupported(self.0.clone())).into())
+                    _ => Err(Box::new(DevServerError::UnsupportedProxy(Box::new(self.0.clone()))).into())
                 }
 
             } => {
@@ -252,8 +276,8 @@ impl Operator for sync::Arc<StreamTCPApp> {
             match proxy_handler.await? {
                 Ok(_) => Ok(()),
                 Err(err) => {
-                    ewe_trace::error!("Failed to properly end tcp proxy: {:?}", err);
-                    Err(Box::new(ProxyError::FailedProxyConnection).into())
+                    ewe_trace::error!("Failed to properly end tcp proxy: {}", err);
+                    Err(Box::new(DevServerError::ProxyFailed(err)).into())
                 }
             }
         })
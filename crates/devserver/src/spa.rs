@@ -0,0 +1,129 @@
+// Implements the history API fallback for single page apps, where a
+// browser navigating to a client side route should get the app's index
+// page instead of a 404 from the upstream server.
+
+use http::header;
+use http_body_util::combinators::BoxBody;
+
+/// `SpaFallback` describes which page is served when an upstream responds
+/// with a 404 for a page navigation.
+///
+/// Only `GET` and `HEAD` requests that accept `text/html` and whose last
+/// path segment has no file extension are considered page navigations,
+/// missing assets (e.g `/app.js`) and API calls still get their 404.
+#[derive(Debug, Clone)]
+pub struct SpaFallback {
+    pub index_path: String,
+}
+
+// -- Constructors
+
+impl Default for SpaFallback {
+    fn default() -> Self {
+        Self::new("/index.html")
+    }
+}
+
+impl SpaFallback {
+    pub fn new<S: Into<String>>(index_path: S) -> Self {
+        let index_path = index_path.into();
+        let index_path = if index_path.starts_with('/') {
+            index_path
+        } else {
+            format!("/{index_path}")
+        };
+        Self { index_path }
+    }
+}
+
+// -- Methods
+
+impl SpaFallback {
+    /// `is_navigation` returns true if the request looks like a browser
+    /// navigating to a page of the app.
+    pub fn is_navigation<B>(&self, req: &http::Request<B>) -> bool {
+        if req.method() != http::Method::GET && req.method() != http::Method::HEAD {
+            return false;
+        }
+
+        let accepts_html = req
+            .headers()
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.contains("text/html"));
+        if !accepts_html {
+            return false;
+        }
+
+        let path = req.uri().path();
+        if path == self.index_path {
+            return false;
+        }
+
+        let last_segment = path.rsplit('/').next().unwrap_or_default();
+        !last_segment.contains('.')
+    }
+
+    /// `fallback_request` builds the request for the index page, carrying
+    /// over the method and headers of the original request.
+    pub fn fallback_request(
+        &self,
+        method: &http::Method,
+        headers: &http::HeaderMap,
+    ) -> Option<http::Request<BoxBody<bytes::Bytes, hyper::Error>>> {
+        let mut request = http::Request::builder()
+            .method(method.clone())
+            .uri(self.index_path.as_str())
+            .body(crate::empty())
+            .ok()?;
+
+        *request.headers_mut() = headers.clone();
+        request.headers_mut().remove(header::CONTENT_LENGTH);
+        Some(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: http::Method, path: &str, accept: &str) -> http::Request<()> {
+        http::Request::builder()
+            .method(method)
+            .uri(path)
+            .header(header::ACCEPT, accept)
+            .body(())
+            .expect("should build request")
+    }
+
+    #[test]
+    fn only_page_navigations_fall_back() {
+        let fallback = SpaFallback::default();
+        let html = "text/html,application/xhtml+xml,*/*;q=0.8";
+
+        assert!(fallback.is_navigation(&request(http::Method::GET, "/users/1", html)));
+        assert!(fallback.is_navigation(&request(http::Method::HEAD, "/", html)));
+
+        assert!(!fallback.is_navigation(&request(http::Method::GET, "/app.js", html)));
+        assert!(!fallback.is_navigation(&request(http::Method::GET, "/index.html", html)));
+        assert!(!fallback.is_navigation(&request(
+            http::Method::GET,
+            "/api/users",
+            "application/json"
+        )));
+        assert!(!fallback.is_navigation(&request(http::Method::POST, "/users", html)));
+    }
+
+    #[test]
+    fn fallback_request_targets_index_page() {
+        let fallback = SpaFallback::new("app.html");
+        let original = request(http::Method::GET, "/users/1?tab=2", "text/html");
+
+        let req = fallback
+            .fallback_request(original.method(), original.headers())
+            .expect("should build fallback request");
+        assert_eq!(req.uri().path(), "/app.html");
+        assert_eq!(req.headers().get(header::ACCEPT).unwrap(), "text/html");
+    }
+}
@@ -0,0 +1,385 @@
+// Implements the status tracking of the apps managed by the devserver,
+// letting us report on what each app is doing at any point in time.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{sync, time};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::types::{JoinHandle, ProxyRemoteConfig};
+use crate::Operator;
+
+/// `AppState` describes what stage of the development cycle an app is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AppState {
+    Idle,
+    Building,
+    Built,
+    Running,
+    Failed,
+}
+
+impl core::fmt::Display for AppState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// `AppStatus` is a point-in-time snapshot of an app's state.
+#[derive(Debug, Clone)]
+pub struct AppStatus {
+    pub name: String,
+    pub state: AppState,
+    pub rebuilds: usize,
+    pub updated_at: time::SystemTime,
+    pub build_started_at: Option<time::Instant>,
+    pub last_build_duration: Option<time::Duration>,
+    pub last_error: Option<String>,
+    pub watcher_events: usize,
+    pub last_watcher_event: Option<time::SystemTime>,
+}
+
+// -- Constructors
+
+impl AppStatus {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            state: AppState::Idle,
+            rebuilds: 0,
+            updated_at: time::SystemTime::now(),
+            build_started_at: None,
+            last_build_duration: None,
+            last_error: None,
+            watcher_events: 0,
+            last_watcher_event: None,
+        }
+    }
+}
+
+impl core::fmt::Display for AppStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let elapsed = self.updated_at.elapsed().unwrap_or_default().as_secs();
+        write!(
+            f,
+            "{}: {} (rebuilds: {}, updated {}s ago)",
+            self.name, self.state, self.rebuilds, elapsed
+        )
+    }
+}
+
+/// `UpstreamHealth` is the result of the last reachability probe
+/// of a proxy destination.
+#[derive(Debug, Clone)]
+pub struct UpstreamHealth {
+    pub name: String,
+    pub addr: String,
+    pub healthy: bool,
+    pub latency: Option<time::Duration>,
+    pub checked_at: time::SystemTime,
+}
+
+#[derive(Default)]
+struct BoardState {
+    apps: BTreeMap<String, AppStatus>,
+    upstreams: BTreeMap<String, UpstreamHealth>,
+}
+
+/// `StatusBoard` holds the combined status of all apps served by a devserver
+/// process, it is cheap to clone and all clones share the same board.
+#[derive(Clone, Default)]
+pub struct StatusBoard {
+    state: sync::Arc<sync::Mutex<BoardState>>,
+    reload_clients: sync::Arc<AtomicUsize>,
+}
+
+// -- Constructors
+
+impl StatusBoard {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// -- Methods
+
+impl StatusBoard {
+    pub fn register<S: Into<String>>(&self, name: S) {
+        let name = name.into();
+        let mut board = self.state.lock().expect("should acquire status lock");
+        board
+            .apps
+            .entry(name.clone())
+            .or_insert_with(|| AppStatus::new(name));
+    }
+
+    pub fn update(&self, name: &str, state: AppState) {
+        let mut board = self.state.lock().expect("should acquire status lock");
+        let status = board
+            .apps
+            .entry(name.to_string())
+            .or_insert_with(|| AppStatus::new(name));
+
+        match state {
+            AppState::Building => {
+                status.rebuilds += 1;
+                status.watcher_events += 1;
+                status.last_watcher_event = Some(time::SystemTime::now());
+                status.build_started_at = Some(time::Instant::now());
+            }
+            AppState::Built | AppState::Failed => {
+                if state == AppState::Built {
+                    status.last_error = None;
+                }
+                // restarts without a build (e.g env changes) keep the last duration.
+                if let Some(started) = status.build_started_at.take() {
+                    status.last_build_duration = Some(started.elapsed());
+                }
+            }
+            AppState::Idle | AppState::Running => {}
+        }
+
+        status.state = state;
+        status.updated_at = time::SystemTime::now();
+
+        ewe_trace::info!("Status changed: {}", status);
+    }
+
+    pub fn fail<E: Into<String>>(&self, name: &str, error: E) {
+        self.update(name, AppState::Failed);
+
+        let mut board = self.state.lock().expect("should acquire status lock");
+        if let Some(status) = board.apps.get_mut(name) {
+            status.last_error = Some(error.into());
+        }
+    }
+
+    pub fn set_upstream(&self, health: UpstreamHealth) {
+        let mut board = self.state.lock().expect("should acquire status lock");
+        board.upstreams.insert(health.name.clone(), health);
+    }
+
+    pub fn get(&self, name: &str) -> Option<AppStatus> {
+        let board = self.state.lock().expect("should acquire status lock");
+        board.apps.get(name).cloned()
+    }
+
+    pub fn snapshot(&self) -> Vec<AppStatus> {
+        let board = self.state.lock().expect("should acquire status lock");
+        board.apps.values().cloned().collect()
+    }
+
+    pub fn upstreams(&self) -> Vec<UpstreamHealth> {
+        let board = self.state.lock().expect("should acquire status lock");
+        board.upstreams.values().cloned().collect()
+    }
+
+    pub fn reload_clients(&self) -> usize {
+        self.reload_clients.load(Ordering::SeqCst)
+    }
+
+    /// `reload_client` registers a connected live reload client, the
+    /// client is counted for as long as the returned guard is alive.
+    pub fn reload_client(&self) -> ReloadClientGuard {
+        self.reload_clients.fetch_add(1, Ordering::SeqCst);
+        ReloadClientGuard(self.reload_clients.clone())
+    }
+}
+
+impl core::fmt::Display for StatusBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for status in self.snapshot() {
+            writeln!(f, "{status}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `ReloadClientGuard` decrements the live reload client count when dropped.
+pub struct ReloadClientGuard(sync::Arc<AtomicUsize>);
+
+impl Drop for ReloadClientGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// `StatusTracker` listens on an app's notification channels and
+/// reflects them as state changes on a `StatusBoard`.
+pub struct StatusTracker {
+    pub name: String,
+    pub board: StatusBoard,
+    pub package_changes: broadcast::Sender<()>,
+    pub package_built: broadcast::Sender<()>,
+    pub package_started: broadcast::Sender<()>,
+    pub package_failed: broadcast::Sender<String>,
+}
+
+// -- Constructors
+
+impl StatusTracker {
+    pub fn new<S: Into<String>>(
+        name: S,
+        board: StatusBoard,
+        package_changes: broadcast::Sender<()>,
+        package_built: broadcast::Sender<()>,
+        package_started: broadcast::Sender<()>,
+        package_failed: broadcast::Sender<String>,
+    ) -> Self {
+        let name = name.into();
+        board.register(name.clone());
+        Self {
+            name,
+            board,
+            package_changes,
+            package_built,
+            package_started,
+            package_failed,
+        }
+    }
+}
+
+// -- Operator implementation
+
+impl Operator for StatusTracker {
+    fn run(&self, mut signal: broadcast::Receiver<()>) -> JoinHandle<()> {
+        let name = self.name.clone();
+        let board = self.board.clone();
+        let mut changes = self.package_changes.subscribe();
+        let mut built = self.package_built.subscribe();
+        let mut started = self.package_started.subscribe();
+        let mut failed = self.package_failed.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok(()) = changes.recv() => board.update(&name, AppState::Building),
+                    Ok(()) = built.recv() => board.update(&name, AppState::Built),
+                    Ok(()) = started.recv() => board.update(&name, AppState::Running),
+                    Ok(error) = failed.recv() => board.fail(&name, error),
+                    _ = signal.recv() => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// `UpstreamProbe` periodically checks that the proxy destinations
+/// accept connections, recording the results on a `StatusBoard`.
+pub struct UpstreamProbe {
+    pub board: StatusBoard,
+    pub upstreams: Vec<(String, ProxyRemoteConfig)>,
+    pub interval: time::Duration,
+}
+
+// -- Constructors
+
+impl UpstreamProbe {
+    pub fn new(board: StatusBoard, upstreams: Vec<(String, ProxyRemoteConfig)>) -> Self {
+        Self {
+            board,
+            upstreams,
+            interval: time::Duration::from_secs(2),
+        }
+    }
+}
+
+// -- Probing
+
+impl UpstreamProbe {
+    async fn probe(name: String, remote: ProxyRemoteConfig) -> UpstreamHealth {
+        let addr = remote.to_string();
+        let started = time::Instant::now();
+        let connection = tokio::time::timeout(
+            time::Duration::from_secs(1),
+            tokio::net::TcpStream::connect(addr.clone()),
+        )
+        .await;
+
+        let healthy = matches!(connection, Ok(Ok(_)));
+        UpstreamHealth {
+            name,
+            addr,
+            healthy,
+            latency: healthy.then(|| started.elapsed()),
+            checked_at: time::SystemTime::now(),
+        }
+    }
+}
+
+// -- Operator implementation
+
+impl Operator for UpstreamProbe {
+    fn run(&self, mut signal: broadcast::Receiver<()>) -> JoinHandle<()> {
+        let board = self.board.clone();
+        let upstreams = self.upstreams.clone();
+        let interval = self.interval;
+
+        tokio::spawn(async move {
+            loop {
+                for (name, remote) in &upstreams {
+                    board.set_upstream(Self::probe(name.clone(), remote.clone()).await);
+                }
+
+                tokio::select! {
+                    () = tokio::time::sleep(interval) => {}
+                    _ = signal.recv() => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_board_counts_rebuilds() {
+        let board = StatusBoard::new();
+        board.register("admin");
+        board.update("admin", AppState::Building);
+        board.update("admin", AppState::Running);
+        board.update("admin", AppState::Building);
+
+        let status = board.get("admin").expect("should have admin status");
+        assert_eq!(status.state, AppState::Building);
+        assert_eq!(status.rebuilds, 2);
+        assert_eq!(board.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn status_board_records_failures_and_durations() {
+        let board = StatusBoard::new();
+        board.update("admin", AppState::Building);
+        board.fail("admin", "cargo check failed");
+
+        let status = board.get("admin").expect("should have admin status");
+        assert_eq!(status.state, AppState::Failed);
+        assert_eq!(status.last_error.as_deref(), Some("cargo check failed"));
+        assert!(status.last_build_duration.is_some());
+
+        board.update("admin", AppState::Building);
+        board.update("admin", AppState::Built);
+        let status = board.get("admin").expect("should have admin status");
+        assert!(status.last_error.is_none());
+    }
+
+    #[test]
+    fn reload_clients_are_counted_while_connected() {
+        let board = StatusBoard::new();
+        let first = board.reload_client();
+        let second = board.reload_client();
+        assert_eq!(board.reload_clients(), 2);
+
+        drop(first);
+        assert_eq!(board.reload_clients(), 1);
+        drop(second);
+        assert_eq!(board.reload_clients(), 0);
+    }
+}
@@ -1,6 +1,6 @@
 use axum::body;
 use http::StatusCode;
-use http_body_util::BodyExt;
+use http_body_util::{combinators::BoxBody, BodyExt};
 use hyper::client;
 use hyper::server;
 use hyper::service;
@@ -14,13 +14,16 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use tokio::{net, sync::broadcast};
 
+use crate::apply_transformers;
+use crate::conditions_for;
 use crate::empty;
 use crate::full;
 use crate::host_addr;
 use crate::types::Http1;
 use crate::types::Result;
 use crate::types::Tunnel;
-use crate::StreamError;
+use crate::SpaFallback;
+use crate::DevServerError;
 
 const DEFAULT_BUF_SIZE: usize = 1024;
 
@@ -148,8 +151,9 @@ pub async fn stream_http1(
             Ok(())
         }
         Err(err) => {
-            ewe_trace::error!("Failed to stream http1 connection correctly");
-            Err(Box::new(StreamError::FailedStreaming(Box::new(err))).into())
+            let err = DevServerError::StreamingFailed(err);
+            ewe_trace::error!("Failed to stream http1 connection correctly: {}", err);
+            Err(Box::new(err).into())
         }
     }
 }
@@ -165,7 +169,7 @@ impl service::Service<crate::types::HyperRequest> for Http1Service {
     type Response = crate::types::HyperResponse;
     type Future = Pin<Box<HttpFuture<Self::Response, Self::Error>>>;
 
-    fn call(&self, req: crate::types::HyperRequest) -> Self::Future {
+    fn call(&self, mut req: crate::types::HyperRequest) -> Self::Future {
         let req_path = req.uri().path();
         if let Some(static_routes) = &self.1.routes {
             if let Some(handler) = static_routes.get(req_path) {
@@ -173,7 +177,15 @@ impl service::Service<crate::types::HyperRequest> for Http1Service {
             }
         }
 
-        let destination_addr = self.1.destination.to_string();
+        let conditions = conditions_for(&self.1.conditions, req.uri().path());
+        let destination_addr = self.1.resolve_destination(&mut req).to_string();
+        let transformers = self.1.transformers.clone();
+        let spa_fallback = self
+            .1
+            .spa_fallback
+            .clone()
+            .filter(|fallback| fallback.is_navigation(&req))
+            .map(|fallback| (fallback, req.method().clone(), req.headers().clone()));
         let stream_operation = async move {
             if req.method() != hyper::Method::CONNECT {
                 return match net::TcpStream::connect(destination_addr.clone()).await {
@@ -197,11 +209,29 @@ impl service::Service<crate::types::HyperRequest> for Http1Service {
                                     }
                                 });
 
-                                match request_sender.send_request(req).await {
+                                match request_sender.send_request(req.map(BodyExt::boxed)).await {
                                     Ok(destination_response) => {
-                                        let resp = destination_response
-                                            .map(|b| body::Body::new(b.boxed()));
-                                        Ok(resp)
+                                        let destination_response = match spa_fallback {
+                                            Some((fallback, method, headers))
+                                                if destination_response.status()
+                                                    == StatusCode::NOT_FOUND =>
+                                            {
+                                                serve_spa_fallback(
+                                                    &mut request_sender,
+                                                    destination_response,
+                                                    &fallback,
+                                                    &method,
+                                                    &headers,
+                                                )
+                                                .await
+                                            }
+                                            _ => destination_response.map(BodyExt::boxed),
+                                        };
+
+                                        if transformers.is_empty() {
+                                            return Ok(destination_response.map(body::Body::new));
+                                        }
+                                        Ok(apply_transformers(destination_response, &transformers))
                                     }
                                     Err(err) => {
                                         ewe_trace::error!(
@@ -225,14 +255,14 @@ impl service::Service<crate::types::HyperRequest> for Http1Service {
                         }
                     }
                     Err(err) => {
-                        ewe_trace::error!(
-                            "Failed to connect to proxy destination {} due to: {:?}",
-                            destination_addr,
-                            err
-                        );
+                        let err = DevServerError::UpstreamUnreachable {
+                            addr: destination_addr,
+                            source: err,
+                        };
+                        ewe_trace::error!("Failed to connect to proxy destination: {}", err);
                         Ok(hyper::Response::builder()
-                            .status(StatusCode::INTERNAL_SERVER_ERROR)
-                            .body(body::Body::new(empty()))
+                            .status(StatusCode::BAD_GATEWAY)
+                            .body(body::Body::new(full(err.to_string())))
                             .unwrap())
                     }
                 };
@@ -290,7 +320,64 @@ impl service::Service<crate::types::HyperRequest> for Http1Service {
             }
         };
 
-        Box::pin(stream_operation)
+        match conditions {
+            Some(conditions) => Box::pin(conditions.apply(stream_operation)),
+            None => Box::pin(stream_operation),
+        }
+    }
+}
+
+type ProxyBody = BoxBody<bytes::Bytes, hyper::Error>;
+
+/// `serve_spa_fallback` re-requests the app's index page over the same
+/// destination connection after a 404 for a page navigation, returning
+/// the original 404 if the index page could not be fetched.
+async fn serve_spa_fallback(
+    request_sender: &mut client::conn::http1::SendRequest<ProxyBody>,
+    not_found: hyper::Response<hyper::body::Incoming>,
+    fallback: &SpaFallback,
+    method: &hyper::Method,
+    headers: &http::HeaderMap,
+) -> hyper::Response<ProxyBody> {
+    // the 404 body must be fully read before the connection can be reused.
+    let (parts, not_found_body) = not_found.into_parts();
+    let not_found_body = match not_found_body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(err) => {
+            ewe_trace::warn!("Failed to read 404 response for spa fallback: {:?}", err);
+            return hyper::Response::from_parts(parts, empty());
+        }
+    };
+    let not_found = || hyper::Response::from_parts(parts.clone(), full(not_found_body.clone()));
+
+    if let Err(err) = request_sender.ready().await {
+        ewe_trace::warn!(
+            "Destination connection closed before spa fallback: {:?}",
+            err
+        );
+        return not_found();
+    }
+
+    let Some(fallback_request) = fallback.fallback_request(method, headers) else {
+        return not_found();
+    };
+
+    match request_sender.send_request(fallback_request).await {
+        Ok(index_response) if index_response.status().is_success() => {
+            index_response.map(BodyExt::boxed)
+        }
+        Ok(index_response) => {
+            ewe_trace::warn!(
+                "Spa fallback {} responded with {}",
+                fallback.index_path,
+                index_response.status()
+            );
+            not_found()
+        }
+        Err(err) => {
+            ewe_trace::warn!("Failed to request spa fallback: {:?}", err);
+            not_found()
+        }
     }
 }
 
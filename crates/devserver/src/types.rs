@@ -16,7 +16,7 @@ pub type Result<T> = std::result::Result<T, BoxedError>;
 
 pub type JoinHandle<T> = tokio::task::JoinHandle<Result<T>>;
 
-#[derive(Debug, Default, Clone, From)]
+#[derive(Debug, Default, Clone, From, serde::Deserialize)]
 pub struct ProxyRemoteConfig {
     pub addr: String,
     pub port: usize,
@@ -72,12 +72,74 @@ pub type HyperFunc =
 
 pub type HyperFuncMap = HashMap<String, std::sync::Arc<HyperFunc>>;
 
+pub type ResponseTransformerList = Vec<std::sync::Arc<dyn crate::ResponseTransformer>>;
+
+/// `ProxyMount` routes requests matching a virtual host and/or path prefix
+/// to a different destination than the proxy's default one.
+#[derive(Debug, Clone)]
+pub struct ProxyMount {
+    pub host: Option<String>,
+    pub path_prefix: Option<String>,
+    pub destination: ProxyRemoteConfig,
+}
+
+// -- Constructors
+
+impl ProxyMount {
+    #[must_use]
+    pub fn new(
+        host: Option<String>,
+        path_prefix: Option<String>,
+        destination: ProxyRemoteConfig,
+    ) -> Self {
+        Self {
+            host,
+            path_prefix: path_prefix.map(|prefix| prefix.trim_end_matches('/').to_string()),
+            destination,
+        }
+    }
+}
+
+// -- Matching
+
+impl ProxyMount {
+    fn matches_host(&self, request_host: Option<&str>) -> bool {
+        match (&self.host, request_host) {
+            (None, _) => true,
+            (Some(host), Some(request_host)) => {
+                let hostname = request_host.split(':').next().unwrap_or(request_host);
+                host.eq_ignore_ascii_case(hostname)
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// `strip_path` returns the request path without the mount's prefix
+    /// if the path falls under the mount.
+    fn strip_path<'a>(&self, path: &'a str) -> Option<&'a str> {
+        match &self.path_prefix {
+            None => Some(path),
+            Some(prefix) if prefix.is_empty() => Some(path),
+            Some(prefix) => match path.strip_prefix(prefix.as_str()) {
+                Some("") => Some("/"),
+                Some(rest) if rest.starts_with('/') => Some(rest),
+                _ => None,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, From)]
 pub struct Http1 {
     pub source: ProxyRemoteConfig,
     pub destination: ProxyRemoteConfig,
     #[debug(skip)]
     pub routes: Option<HyperFuncMap>,
+    #[debug(skip)]
+    pub transformers: ResponseTransformerList,
+    pub mounts: Vec<ProxyMount>,
+    pub conditions: Vec<crate::RouteConditions>,
+    pub spa_fallback: Option<crate::SpaFallback>,
 }
 
 impl Http1 {
@@ -90,6 +152,10 @@ impl Http1 {
             source,
             destination,
             routes,
+            transformers: Vec::new(),
+            mounts: Vec::new(),
+            conditions: Vec::new(),
+            spa_fallback: None,
         }
     }
 
@@ -106,6 +172,69 @@ impl Http1 {
             }
         };
     }
+
+    pub fn and_transformers(&mut self, mutator: impl Fn(&mut ResponseTransformerList)) {
+        mutator(&mut self.transformers);
+    }
+
+    pub fn and_mounts(&mut self, mutator: impl Fn(&mut Vec<ProxyMount>)) {
+        mutator(&mut self.mounts);
+    }
+
+    pub fn and_conditions(&mut self, mutator: impl Fn(&mut Vec<crate::RouteConditions>)) {
+        mutator(&mut self.conditions);
+    }
+
+    /// `with_spa_fallback` serves the giving fallback page for page
+    /// navigations the destination responds to with a 404.
+    pub fn with_spa_fallback(&mut self, fallback: crate::SpaFallback) {
+        self.spa_fallback = Some(fallback);
+    }
+
+    /// `resolve_destination` returns the destination for the giving request
+    /// based on the registered mounts, rewriting the request path when a
+    /// path prefix mount was matched, falling back to the default destination.
+    pub fn resolve_destination(&self, req: &mut HyperRequest) -> ProxyRemoteConfig {
+        let request_host = req
+            .headers()
+            .get(http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        for mount in &self.mounts {
+            if !mount.matches_host(request_host.as_deref()) {
+                continue;
+            }
+
+            let Some(stripped_path) = mount.strip_path(req.uri().path()) else {
+                continue;
+            };
+
+            if stripped_path != req.uri().path() {
+                let path_and_query = match req.uri().query() {
+                    Some(query) => format!("{stripped_path}?{query}"),
+                    None => stripped_path.to_string(),
+                };
+
+                let mut parts = req.uri().clone().into_parts();
+                match http::uri::PathAndQuery::try_from(path_and_query) {
+                    Ok(value) => {
+                        parts.path_and_query = Some(value);
+                        if let Ok(uri) = http::Uri::from_parts(parts) {
+                            *req.uri_mut() = uri;
+                        }
+                    }
+                    Err(err) => {
+                        ewe_trace::error!("Failed to rewrite mounted request path: {:?}", err);
+                    }
+                }
+            }
+
+            return mount.destination.clone();
+        }
+
+        self.destination.clone()
+    }
 }
 
 impl core::fmt::Display for Http1 {
@@ -124,6 +253,8 @@ pub struct Http2 {
     pub destination: ProxyRemoteConfig,
     #[debug(skip)]
     pub routes: Option<HyperFuncMap>,
+    #[debug(skip)]
+    pub transformers: ResponseTransformerList,
 }
 
 impl Http2 {
@@ -136,6 +267,7 @@ impl Http2 {
             source,
             destination,
             routes,
+            transformers: Vec::new(),
         }
     }
 
@@ -152,6 +284,10 @@ impl Http2 {
             }
         };
     }
+
+    pub fn and_transformers(&mut self, mutator: impl Fn(&mut ResponseTransformerList)) {
+        mutator(&mut self.transformers);
+    }
 }
 
 impl core::fmt::Display for Http2 {
@@ -171,6 +307,8 @@ pub struct Http3 {
 
     #[debug(skip)]
     pub routes: Option<HyperFuncMap>,
+    #[debug(skip)]
+    pub transformers: ResponseTransformerList,
 }
 
 impl Http3 {
@@ -183,6 +321,7 @@ impl Http3 {
             source,
             destination,
             routes,
+            transformers: Vec::new(),
         }
     }
 
@@ -199,6 +338,10 @@ impl Http3 {
             }
         };
     }
+
+    pub fn and_transformers(&mut self, mutator: impl Fn(&mut ResponseTransformerList)) {
+        mutator(&mut self.transformers);
+    }
 }
 
 impl core::fmt::Display for Http3 {
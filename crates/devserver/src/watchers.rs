@@ -1,22 +1,9 @@
-use derive_more::derive::From;
 use tokio::sync::broadcast;
 
 use crate::operators::Operator;
+use crate::DevServerError;
 use ewe_watch_utils::watch_path;
 
-#[derive(Debug, From)]
-pub enum DirectoryWatcherError {
-    FailedToFinishedCorrectly,
-}
-
-impl std::error::Error for DirectoryWatcherError {}
-
-impl core::fmt::Display for DirectoryWatcherError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
-    }
-}
-
 pub struct DirectoryWatcher {
     pub directory: String,
     pub file_change_sender: broadcast::Sender<()>,
@@ -32,8 +19,15 @@ impl Operator for DirectoryWatcher {
             Ok(())
         };
 
-        let watcher_handler = watch_path(300, self.directory.clone(), true, watch_callback)
-            .expect("should create watcher");
+        let directory = self.directory.clone();
+        let watcher_handler = match watch_path(300, directory.clone(), true, watch_callback) {
+            Ok(handler) => handler,
+            Err(err) => {
+                let err = DevServerError::watcher_failed(directory, err);
+                ewe_trace::error!("Failed to create directory watcher: {}", err);
+                return tokio::spawn(async move { Err(Box::new(err).into()) });
+            }
+        };
 
         let _ = tokio::spawn(async move {
             let _ = cancel_signal.recv().await;
@@ -44,7 +38,7 @@ impl Operator for DirectoryWatcher {
             Ok(_) => Ok(()),
             Err(err) => {
                 ewe_trace::error!("Failed to correct destroy directory watcher: {:?}", err);
-                Err(Box::new(DirectoryWatcherError::FailedToFinishedCorrectly).into())
+                Err(Box::new(DevServerError::WatcherStopped { directory }).into())
             }
         })
     }
@@ -0,0 +1,484 @@
+// Implements serving multiple apps from a single devserver process where each
+// app gets its own watcher, build pipeline and mount path or virtual host
+// behind one shared proxy, all defined from a single TOML config.
+
+use std::collections::{HashMap, HashSet};
+use std::{path, sync, time};
+
+use derive_more::From;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::types::{Http1, JoinHandle, ProxyMount, ProxyRemoteConfig, Result};
+use crate::{
+    assets, dashboard, BinaryApp, CargoShellBuilder, DirectoryWatcher, EnvFileWatcher, Operator,
+    ParrellelOps, ProjectDefinition, ProxyType, RouteConditions, SpaFallback, StatusBoard,
+    StatusTracker, StreamTCPApp, UpstreamProbe,
+};
+
+// -- Errors
+
+#[derive(Debug, From)]
+pub enum WorkspaceError {
+    #[from(ignore)]
+    NoApps,
+
+    #[from(ignore)]
+    DuplicateApp(String),
+
+    #[from(ignore)]
+    AmbiguousDefaultApp(Vec<String>),
+
+    FailedReading(std::io::Error),
+    InvalidConfig(toml::de::Error),
+}
+
+impl std::error::Error for WorkspaceError {}
+
+impl core::fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+// -- Configuration
+
+fn default_wait_before_reload_ms() -> u64 {
+    300
+}
+
+/// `WorkspaceProxyConfig` defines the address the shared proxy listens on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceProxyConfig {
+    pub addr: String,
+    pub port: usize,
+
+    /// index page served for page navigations that 404, for single page apps.
+    #[serde(default)]
+    pub spa_fallback: Option<String>,
+}
+
+/// `AppConfig` defines a single app within the workspace.
+///
+/// Each app must either have a `mount` path, a virtual `host` or both,
+/// at most one app may have neither which then serves as the default
+/// destination for all unmatched requests.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AppConfig {
+    pub name: String,
+    pub workspace_root: String,
+    pub destination: ProxyRemoteConfig,
+    pub crate_name: Option<String>,
+    pub binary_name: Option<String>,
+    pub watch_directory: Option<String>,
+    pub mount: Option<String>,
+    pub host: Option<String>,
+    pub build_arguments: Option<Vec<String>>,
+    pub run_arguments: Option<Vec<String>>,
+    #[serde(default = "default_wait_before_reload_ms")]
+    pub wait_before_reload_ms: u64,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub env_files: Vec<String>,
+}
+
+/// `WorkspaceConfig` is the single TOML definition of all apps the
+/// devserver should serve, e.g:
+///
+/// ```toml
+/// [proxy]
+/// addr = "0.0.0.0"
+/// port = 3000
+/// spa_fallback = "/index.html"
+///
+/// [[app]]
+/// name = "admin"
+/// workspace_root = "./apps/admin"
+/// mount = "/admin"
+/// destination = { addr = "0.0.0.0", port = 3201 }
+/// env_files = ["./apps/admin/.env"]
+/// env = { RUST_LOG = "debug" }
+///
+/// [[app]]
+/// name = "store"
+/// workspace_root = "./apps/store"
+/// host = "store.localhost"
+/// destination = { addr = "0.0.0.0", port = 3202 }
+///
+/// [[condition]]
+/// path_prefix = "/admin/api"
+/// latency_ms = 800
+/// failure_percent = 10.0
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceConfig {
+    pub proxy: WorkspaceProxyConfig,
+    #[serde(rename = "app", default)]
+    pub apps: Vec<AppConfig>,
+    #[serde(rename = "condition", default)]
+    pub conditions: Vec<RouteConditions>,
+}
+
+// -- Constructors
+
+impl WorkspaceConfig {
+    pub fn from_toml(content: &str) -> std::result::Result<Self, WorkspaceError> {
+        let config: Self = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load<P: AsRef<path::Path>>(config_path: P) -> std::result::Result<Self, WorkspaceError> {
+        let content = std::fs::read_to_string(config_path)?;
+        Self::from_toml(&content)
+    }
+}
+
+// -- Validation
+
+impl WorkspaceConfig {
+    fn validate(&self) -> std::result::Result<(), WorkspaceError> {
+        if self.apps.is_empty() {
+            return Err(WorkspaceError::NoApps);
+        }
+
+        let mut names = HashSet::new();
+        for app in &self.apps {
+            if !names.insert(app.name.clone()) {
+                return Err(WorkspaceError::DuplicateApp(app.name.clone()));
+            }
+        }
+
+        let unmounted: Vec<String> = self
+            .apps
+            .iter()
+            .filter(|app| app.mount.is_none() && app.host.is_none())
+            .map(|app| app.name.clone())
+            .collect();
+
+        if unmounted.len() > 1 {
+            return Err(WorkspaceError::AmbiguousDefaultApp(unmounted));
+        }
+
+        Ok(())
+    }
+
+    /// `default_app` returns the app serving unmatched requests, this is the
+    /// app without a mount or host if any, else the first app.
+    fn default_app(&self) -> &AppConfig {
+        self.apps
+            .iter()
+            .find(|app| app.mount.is_none() && app.host.is_none())
+            .unwrap_or(&self.apps[0])
+    }
+
+    pub fn proxy_source(&self) -> ProxyRemoteConfig {
+        ProxyRemoteConfig::new(self.proxy.addr.clone(), self.proxy.port)
+    }
+}
+
+impl AppConfig {
+    pub fn project_definition(&self, proxy: ProxyType) -> ProjectDefinition {
+        let crate_name = self.crate_name.clone().unwrap_or_else(|| self.name.clone());
+        let binary_name = self
+            .binary_name
+            .clone()
+            .unwrap_or_else(|| crate_name.clone());
+
+        ProjectDefinition {
+            proxy,
+            crate_name,
+            workspace_root: self.workspace_root.clone(),
+            watch_directory: self
+                .watch_directory
+                .clone()
+                .unwrap_or_else(|| self.workspace_root.clone()),
+            target_directory: format!("{}/target", self.workspace_root),
+            wait_before_reload: time::Duration::from_millis(self.wait_before_reload_ms),
+            env: self.env.clone(),
+            env_files: self.env_files.clone(),
+            build_arguments: self.build_arguments.clone().unwrap_or_else(|| {
+                vec![
+                    String::from("cargo"),
+                    String::from("build"),
+                    String::from("--bin"),
+                    binary_name.clone(),
+                ]
+            }),
+            run_arguments: self.run_arguments.clone().unwrap_or_else(|| {
+                vec![
+                    String::from("cargo"),
+                    String::from("run"),
+                    String::from("--bin"),
+                    binary_name.clone(),
+                ]
+            }),
+        }
+    }
+
+    pub fn proxy_mount(&self) -> Option<ProxyMount> {
+        if self.mount.is_none() && self.host.is_none() {
+            return None;
+        }
+        Some(ProxyMount::new(
+            self.host.clone(),
+            self.mount.clone(),
+            self.destination.clone(),
+        ))
+    }
+}
+
+/// `ReloadRelay` forwards an app's started notifications into the
+/// workspace wide reload channel used by the live reload endpoint.
+struct ReloadRelay {
+    app_started: broadcast::Sender<()>,
+    reload: broadcast::Sender<()>,
+}
+
+impl Operator for ReloadRelay {
+    fn run(&self, mut signal: broadcast::Receiver<()>) -> JoinHandle<()> {
+        let mut app_started = self.app_started.subscribe();
+        let reload = self.reload.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Ok(()) = app_started.recv() => {
+                        if reload.send(()).is_err() {
+                            ewe_trace::warn!("No one is listening for reload messages");
+                        }
+                    },
+                    _ = signal.recv() => break,
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// `WorkspaceDevService` serves all apps of a `WorkspaceConfig` behind a
+/// single proxy, reporting their combined status on a shared `StatusBoard`.
+pub struct WorkspaceDevService {
+    pub config: WorkspaceConfig,
+    pub status: StatusBoard,
+    pub package_started: broadcast::Sender<()>,
+}
+
+// -- Constructors
+
+impl WorkspaceDevService {
+    pub fn new(config: WorkspaceConfig) -> Self {
+        let (package_started, _) = broadcast::channel::<()>(2);
+        Self {
+            config,
+            package_started,
+            status: StatusBoard::new(),
+        }
+    }
+}
+
+// -- Core Starter
+
+impl WorkspaceDevService {
+    pub fn proxy(&self) -> ProxyType {
+        let default_app = self.config.default_app();
+        let mut http1 = Http1::new(
+            self.config.proxy_source(),
+            default_app.destination.clone(),
+            None,
+        );
+
+        let package_started = &self.package_started;
+        let status = &self.status;
+        http1.and_routes(move |routes| {
+            routes
+                .entry(assets::RELOADER_SCRIPT_ENDPOINT.to_string())
+                .or_insert(sync::Arc::new(assets::sse_endpoint_script));
+
+            routes
+                .entry(assets::RELOADER_SSE_ENDPOINT.to_string())
+                .or_insert(assets::create_tracked_sse_endpoint_handler(
+                    package_started.clone(),
+                    status.clone(),
+                ));
+
+            dashboard::register_dashboard_routes(routes, status);
+        });
+
+        let mounts: Vec<ProxyMount> = self
+            .config
+            .apps
+            .iter()
+            .filter_map(AppConfig::proxy_mount)
+            .collect();
+        http1.and_mounts(move |registered| registered.extend(mounts.iter().cloned()));
+
+        let conditions = self.config.conditions.clone();
+        http1.and_conditions(move |registered| registered.extend(conditions.iter().cloned()));
+
+        if let Some(index_path) = &self.config.proxy.spa_fallback {
+            http1.with_spa_fallback(SpaFallback::new(index_path.clone()));
+        }
+
+        ProxyType::Http1(http1)
+    }
+
+    pub async fn start(&mut self, canceller: broadcast::Receiver<()>) -> Result<JoinHandle<()>> {
+        let proxy = self.proxy();
+
+        let mut operators: Vec<Box<dyn Operator + Send + Sync>> = Vec::new();
+        let mut change_triggers = Vec::new();
+
+        for app in &self.config.apps {
+            let (package_changes, _) = broadcast::channel::<()>(2);
+            let (package_built, _) = broadcast::channel::<()>(2);
+            let (package_started, _) = broadcast::channel::<()>(2);
+            let (package_failed, _) = broadcast::channel::<String>(2);
+
+            let project = app.project_definition(proxy.clone());
+
+            ewe_trace::info!(
+                "Registering workspace app: {} (mount={:?}, host={:?}, destination={})",
+                app.name,
+                app.mount,
+                app.host,
+                app.destination,
+            );
+
+            operators.push(Box::new(DirectoryWatcher::new(
+                project.watch_directory.clone(),
+                package_changes.clone(),
+            )));
+            operators.push(Box::new(CargoShellBuilder::shared(
+                project.clone(),
+                package_built.clone(),
+                package_changes.clone(),
+                package_failed.clone(),
+            )));
+            operators.push(Box::new(EnvFileWatcher::new(
+                project.env_files.clone(),
+                package_built.clone(),
+            )));
+            operators.push(Box::new(BinaryApp::shared(
+                project,
+                package_built.clone(),
+                package_started.clone(),
+            )));
+            operators.push(Box::new(StatusTracker::new(
+                app.name.clone(),
+                self.status.clone(),
+                package_changes.clone(),
+                package_built,
+                package_started.clone(),
+                package_failed,
+            )));
+            operators.push(Box::new(ReloadRelay {
+                app_started: package_started,
+                reload: self.package_started.clone(),
+            }));
+
+            change_triggers.push(package_changes);
+        }
+
+        operators.push(Box::new(UpstreamProbe::new(
+            self.status.clone(),
+            self.config
+                .apps
+                .iter()
+                .map(|app| (app.name.clone(), app.destination.clone()))
+                .collect(),
+        )));
+
+        operators.push(Box::new(StreamTCPApp::shared(
+            time::Duration::from_secs(1),
+            proxy,
+        )));
+
+        let command_op = ParrellelOps::new(operators).run(canceller);
+
+        for trigger in change_triggers {
+            trigger
+                .send(())
+                .expect("should have delivered trigger message");
+        }
+
+        Ok(command_op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [proxy]
+        addr = "0.0.0.0"
+        port = 3000
+        spa_fallback = "/index.html"
+
+        [[app]]
+        name = "store"
+        workspace_root = "./apps/store"
+        destination = { addr = "0.0.0.0", port = 3201 }
+
+        [[app]]
+        name = "admin"
+        workspace_root = "./apps/admin"
+        mount = "/admin"
+        destination = { addr = "0.0.0.0", port = 3202 }
+
+        [[condition]]
+        path_prefix = "/admin/api"
+        latency_ms = 500
+        bandwidth_bytes_per_sec = 2048
+    "#;
+
+    #[test]
+    fn can_load_workspace_config() {
+        let config = WorkspaceConfig::from_toml(CONFIG).expect("should parse config");
+        assert_eq!(config.apps.len(), 2);
+        assert_eq!(config.default_app().name, "store");
+
+        let admin = &config.apps[1];
+        let project = admin.project_definition(WorkspaceDevService::new(config.clone()).proxy());
+        assert_eq!(project.crate_name, "admin");
+        assert_eq!(project.watch_directory, "./apps/admin");
+        assert_eq!(
+            project.run_arguments,
+            vec!["cargo", "run", "--bin", "admin"]
+        );
+        assert!(admin.proxy_mount().is_some());
+
+        assert_eq!(config.proxy.spa_fallback.as_deref(), Some("/index.html"));
+        assert_eq!(config.conditions.len(), 1);
+        assert_eq!(config.conditions[0].conditions.latency_ms, 500);
+        assert_eq!(
+            config.conditions[0].conditions.bandwidth_bytes_per_sec,
+            Some(2048)
+        );
+    }
+
+    #[test]
+    fn rejects_ambiguous_default_apps() {
+        let config = r#"
+            [proxy]
+            addr = "0.0.0.0"
+            port = 3000
+
+            [[app]]
+            name = "store"
+            workspace_root = "./apps/store"
+            destination = { addr = "0.0.0.0", port = 3201 }
+
+            [[app]]
+            name = "admin"
+            workspace_root = "./apps/admin"
+            destination = { addr = "0.0.0.0", port = 3202 }
+        "#;
+
+        assert!(matches!(
+            WorkspaceConfig::from_toml(config),
+            Err(WorkspaceError::AmbiguousDefaultApp(_))
+        ));
+    }
+}
@@ -1,5 +1,19 @@
-/// Crate to abstract out tracing so it never shows up in release builds using macros
-/// See similar: https://doc.rust-lang.org/src/std/macros.rs.html#138-145.
+//! Crate to abstract out tracing so it never shows up in release builds using macros
+//! See similar: <https://doc.rust-lang.org/src/std/macros.rs.html#138-145>.
+
+// Re-exported so the macros work, structured fields included, in crates
+// that do not depend on tracing themselves.
+#[doc(hidden)]
+pub use tracing;
+
+#[doc(hidden)]
+pub mod throttle;
+
+#[cfg(feature = "init")]
+mod subscriber;
+
+#[cfg(feature = "init")]
+pub use subscriber::*;
 
 #[cfg(not(feature = "log_info"))]
 #[macro_export]
@@ -29,7 +43,7 @@ macro_rules! debug {
 #[macro_export]
 macro_rules! info {
     ($($t:tt)*) => {
-        tracing::info!($($t)*);
+        $crate::tracing::info!($($t)*);
     };
 }
 
@@ -37,7 +51,7 @@ macro_rules! info {
 #[macro_export]
 macro_rules! warn {
     ($($t:tt)*) => {
-        tracing::warn!($($t)*);
+        $crate::tracing::warn!($($t)*);
     };
 }
 
@@ -45,7 +59,7 @@ macro_rules! warn {
 #[macro_export]
 macro_rules! debug {
     ($($t:tt)*) => {
-        tracing::debug!($($t)*);
+        $crate::tracing::debug!($($t)*);
     };
 }
 
@@ -53,10 +67,119 @@ macro_rules! debug {
 #[macro_export]
 macro_rules! error {
     ($($t:tt)*) => {
-        tracing::error!($($t)*);
+        $crate::tracing::error!($($t)*);
     };
 }
 
+/// Enters an info level span until the end of the current scope,
+/// `enter_span!("request", id = 1)`.
+#[cfg(not(any(feature = "log_info", feature = "log_debug")))]
+#[macro_export]
+macro_rules! enter_span {
+    ($($t:tt)*) => {};
+}
+
+/// Wraps a future in an info level span,
+/// `instrument!(handle(request), "request", id = 1).await`.
+#[cfg(not(any(feature = "log_info", feature = "log_debug")))]
+#[macro_export]
+macro_rules! instrument {
+    ($future:expr, $($t:tt)*) => {
+        $future
+    };
+}
+
+#[cfg(any(feature = "log_info", feature = "log_debug"))]
+#[macro_export]
+macro_rules! enter_span {
+    ($($t:tt)*) => {
+        let _span_guard = $crate::tracing::info_span!($($t)*).entered();
+    };
+}
+
+#[cfg(any(feature = "log_info", feature = "log_debug"))]
+#[macro_export]
+macro_rules! instrument {
+    ($future:expr, $($t:tt)*) => {
+        $crate::tracing::Instrument::instrument($future, $crate::tracing::info_span!($($t)*))
+    };
+}
+
+/// Logs a warning at most once every `interval_ms` per callsite, counting
+/// the suppressed ones in a `suppressed` field,
+/// `warn_throttled!(1_000, path = %path, "watcher event dropped")`.
+#[cfg(not(any(feature = "log_warnings", feature = "log_debug")))]
+#[macro_export]
+macro_rules! warn_throttled {
+    ($($t:tt)*) => {};
+}
+
+/// Logs an error at most once every `interval_ms` per callsite, see
+/// [`warn_throttled`].
+#[cfg(not(any(feature = "log_errors", feature = "log_debug")))]
+#[macro_export]
+macro_rules! error_throttled {
+    ($($t:tt)*) => {};
+}
+
+/// Logs a warning only the first time its callsite is reached.
+#[cfg(not(any(feature = "log_warnings", feature = "log_debug")))]
+#[macro_export]
+macro_rules! warn_once {
+    ($($t:tt)*) => {};
+}
+
+/// Logs an error only the first time its callsite is reached.
+#[cfg(not(any(feature = "log_errors", feature = "log_debug")))]
+#[macro_export]
+macro_rules! error_once {
+    ($($t:tt)*) => {};
+}
+
+#[cfg(any(feature = "log_warnings", feature = "log_debug"))]
+#[macro_export]
+macro_rules! warn_throttled {
+    ($interval_ms:expr, $($t:tt)*) => {{
+        static THROTTLE: $crate::throttle::Throttle = $crate::throttle::Throttle::new();
+        if let Some(suppressed) = THROTTLE.allow($interval_ms) {
+            $crate::tracing::warn!(suppressed, $($t)*);
+        }
+    }};
+}
+
+#[cfg(any(feature = "log_errors", feature = "log_debug"))]
+#[macro_export]
+macro_rules! error_throttled {
+    ($interval_ms:expr, $($t:tt)*) => {{
+        static THROTTLE: $crate::throttle::Throttle = $crate::throttle::Throttle::new();
+        if let Some(suppressed) = THROTTLE.allow($interval_ms) {
+            $crate::tracing::error!(suppressed, $($t)*);
+        }
+    }};
+}
+
+#[cfg(any(feature = "log_warnings", feature = "log_debug"))]
+#[macro_export]
+macro_rules! warn_once {
+    ($($t:tt)*) => {{
+        static ONCE: $crate::throttle::Once = $crate::throttle::Once::new();
+        if ONCE.first() {
+            $crate::tracing::warn!($($t)*);
+        }
+    }};
+}
+
+#[cfg(any(feature = "log_errors", feature = "log_debug"))]
+#[macro_export]
+macro_rules! error_once {
+    ($($t:tt)*) => {{
+        static ONCE: $crate::throttle::Once = $crate::throttle::Once::new();
+        if ONCE.first() {
+            $crate::tracing::error!($($t)*);
+        }
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +202,40 @@ mod tests {
         warn!("Help me out: {}", 1);
         error!("Help me out: {}", 1);
     }
+
+    #[test]
+    #[traced_test]
+    #[cfg(all(feature = "log_info", feature = "log_warnings"))]
+    fn test_logs_with_fields_and_spans() {
+        let user_id = 42;
+        {
+            enter_span!("session", user_id);
+            info!(user_id, kind = "password", "logged in");
+        }
+        warn!(attempts = 3, "slow login");
+
+        assert!(logs_contain("session{user_id=42}"));
+        assert!(logs_contain("user_id=42 kind=\"password\""));
+        assert!(logs_contain("attempts=3"));
+    }
+
+    #[test]
+    #[traced_test]
+    #[cfg(all(feature = "log_warnings", feature = "log_errors"))]
+    fn test_throttled_and_once_logs() {
+        for attempt in 0..5 {
+            warn_throttled!(60_000, attempt, "proxy unreachable");
+            error_once!(attempt, "watcher crashed");
+        }
+
+        logs_assert(|lines: &[&str]| {
+            let throttled = lines.iter().filter(|line| line.contains("proxy unreachable"));
+            let once = lines.iter().filter(|line| line.contains("watcher crashed"));
+            match (throttled.count(), once.count()) {
+                (1, 1) => Ok(()),
+                counts => Err(format!("unexpected log counts {counts:?}")),
+            }
+        });
+        assert!(logs_contain("suppressed=0 attempt=0"));
+    }
 }
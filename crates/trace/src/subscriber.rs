@@ -0,0 +1,211 @@
+//! Installs a global subscriber fitting the target, see [`init`].
+
+use std::sync::OnceLock;
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::filter::{EnvFilter, ParseError};
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::reload;
+use tracing_subscriber::util::{SubscriberInitExt, TryInitError};
+use tracing_subscriber::Registry;
+
+/// Returns the most verbose level enabled through the `log_*` features,
+/// events above it never reach the subscriber anyway.
+#[must_use]
+pub fn max_level() -> LevelFilter {
+    if cfg!(feature = "log_debug") {
+        LevelFilter::DEBUG
+    } else if cfg!(feature = "log_info") {
+        LevelFilter::INFO
+    } else if cfg!(feature = "log_warnings") {
+        LevelFilter::WARN
+    } else if cfg!(feature = "log_errors") {
+        LevelFilter::ERROR
+    } else {
+        LevelFilter::OFF
+    }
+}
+
+/// `LOG_ENV` holds `RUST_LOG` style directives read by [`init`], e.g
+/// `EWE_LOG=info,ewe_devserver=debug`, `RUST_LOG` is used when it is unset.
+pub const LOG_ENV: &str = "EWE_LOG";
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum LogInitError {
+    InvalidDirectives(ParseError),
+    AlreadyInitialized(TryInitError),
+    NotInitialized,
+    Reload(reload::Error),
+}
+
+impl std::error::Error for LogInitError {}
+
+impl core::fmt::Display for LogInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Installs the global subscriber, writing to stderr on native targets and
+/// to `console.log`/`console.error` on wasm32.
+///
+/// Directives come from [`LOG_ENV`] or `RUST_LOG`, defaulting to
+/// [`max_level`]. Fails when a global subscriber was already installed.
+pub fn init() -> Result<(), LogInitError> {
+    let directives = std::env::var(LOG_ENV)
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .unwrap_or_default();
+    init_with_filter(&directives)
+}
+
+/// Installs the global subscriber like [`init`] with the given `RUST_LOG`
+/// style directives, e.g `warn,ewe_watchers=debug`.
+pub fn init_with_filter(directives: &str) -> Result<(), LogInitError> {
+    let (subscriber, handle) = build(parse_filter(directives)?);
+    subscriber
+        .try_init()
+        .map_err(LogInitError::AlreadyInitialized)?;
+    let _ = FILTER_HANDLE.set(handle);
+    Ok(())
+}
+
+/// Replaces the directives of the subscriber installed by [`init`] at
+/// runtime, so verbosity can be toggled without a rebuild. Levels above
+/// the ones compiled in through the `log_*` features still log nothing.
+pub fn set_filter(directives: &str) -> Result<(), LogInitError> {
+    let handle = FILTER_HANDLE.get().ok_or(LogInitError::NotInitialized)?;
+    handle
+        .reload(parse_filter(directives)?)
+        .map_err(LogInitError::Reload)
+}
+
+/// `parse_filter` parses `directives`, falling back to [`max_level`] when
+/// they are empty.
+fn parse_filter(directives: &str) -> Result<EnvFilter, LogInitError> {
+    EnvFilter::builder()
+        .with_default_directive(max_level().into())
+        .parse(directives)
+        .map_err(LogInitError::InvalidDirectives)
+}
+
+fn build(
+    filter: EnvFilter,
+) -> (
+    impl tracing::Subscriber + Send + Sync,
+    reload::Handle<EnvFilter, Registry>,
+) {
+    let (filter, handle) = reload::Layer::new(filter);
+    let subscriber = tracing_subscriber::registry().with(filter).with(output());
+    (subscriber, handle)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn output<S>() -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer().with_writer(std::io::stderr)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn output<S>() -> impl Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    tracing_subscriber::fmt::layer()
+        .without_time()
+        .with_writer(console::MakeConsoleWriter)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod console {
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// `MakeConsoleWriter` hands out writers sending each event to the
+    /// browser console, errors and warnings go to `console.error`.
+    pub struct MakeConsoleWriter;
+
+    pub struct ConsoleWriter {
+        buffer: Vec<u8>,
+        is_error: bool,
+    }
+
+    impl std::io::Write for ConsoleWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            if self.buffer.is_empty() {
+                return Ok(());
+            }
+
+            let line = String::from_utf8_lossy(&self.buffer);
+            let line = line.trim_end();
+            if self.is_error {
+                web_sys::console::error_1(&line.into());
+            } else {
+                web_sys::console::log_1(&line.into());
+            }
+            self.buffer.clear();
+            Ok(())
+        }
+    }
+
+    impl Drop for ConsoleWriter {
+        fn drop(&mut self) {
+            let _ = std::io::Write::flush(self);
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for MakeConsoleWriter {
+        type Writer = ConsoleWriter;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            ConsoleWriter {
+                buffer: Vec::new(),
+                is_error: false,
+            }
+        }
+
+        fn make_writer_for(&'a self, meta: &tracing::Metadata<'_>) -> Self::Writer {
+            ConsoleWriter {
+                buffer: Vec::new(),
+                is_error: *meta.level() <= tracing::Level::WARN,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod subscriber_tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_target_and_reloads_at_runtime() {
+        if cfg!(feature = "log_debug") {
+            assert_eq!(max_level(), LevelFilter::DEBUG);
+        }
+
+        let filter = parse_filter("warn,noisy=error").expect("should parse");
+        let (subscriber, handle) = build(filter);
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!tracing::enabled!(tracing::Level::INFO));
+            assert!(tracing::enabled!(target: "quiet", tracing::Level::WARN));
+            assert!(!tracing::enabled!(target: "noisy", tracing::Level::WARN));
+
+            handle
+                .reload(parse_filter("info").expect("should parse"))
+                .expect("should reload");
+            assert!(tracing::enabled!(target: "noisy", tracing::Level::INFO));
+        });
+
+        assert!(matches!(
+            parse_filter("info,=nope"),
+            Err(LogInitError::InvalidDirectives(_))
+        ));
+    }
+}
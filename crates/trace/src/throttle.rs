@@ -0,0 +1,134 @@
+//! Per-callsite state behind the `*_throttled!` and `*_once!` macros.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// `Throttle` lets one event through per interval, counting the ones it
+/// held back in between. The macros keep one in a static per callsite.
+pub struct Throttle {
+    last_emit_ms: AtomicU64,
+    suppressed: AtomicU64,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Throttle {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            last_emit_ms: AtomicU64::new(u64::MAX),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns how many events were suppressed since the last one when
+    /// this one may be logged, `None` when it falls within `interval_ms`.
+    pub fn allow(&self, interval_ms: u64) -> Option<u64> {
+        self.allow_at(now_ms(), interval_ms)
+    }
+
+    fn allow_at(&self, now: u64, interval_ms: u64) -> Option<u64> {
+        let last = self.last_emit_ms.load(Ordering::Relaxed);
+        if last != u64::MAX && now.saturating_sub(last) < interval_ms {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        // only one of the racing threads gets to log, the others count as
+        // suppressed.
+        if self
+            .last_emit_ms
+            .compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(self.suppressed.swap(0, Ordering::Relaxed))
+    }
+}
+
+/// `Once` lets a single event through, for the `*_once!` macros.
+pub struct Once(AtomicBool);
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Once {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    pub fn first(&self) -> bool {
+        !self.0.swap(true, Ordering::Relaxed)
+    }
+}
+
+/// Milliseconds since the first throttled event of the process.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now_ms() -> u64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    let elapsed = START.get_or_init(std::time::Instant::now).elapsed();
+    u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX - 1)
+}
+
+/// Milliseconds since the first throttled event of the process, read from
+/// the JavaScript clock as `std::time::Instant` panics in the browser.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_ms() -> u64 {
+    static START: std::sync::OnceLock<f64> = std::sync::OnceLock::new();
+    let start = *START.get_or_init(js_sys::Date::now);
+    // the float to int cast saturates, a clock moved backwards reads as 0.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let elapsed = (js_sys::Date::now() - start) as u64;
+    elapsed.min(u64::MAX - 1)
+}
+
+#[cfg(test)]
+mod throttle_tests {
+    use super::*;
+
+    #[test]
+    fn throttle_counts_suppressed_events() {
+        let throttle = Throttle::new();
+        assert_eq!(throttle.allow_at(0, 100), Some(0));
+        assert_eq!(throttle.allow_at(10, 100), None);
+        assert_eq!(throttle.allow_at(99, 100), None);
+        assert_eq!(throttle.allow_at(100, 100), Some(2));
+        assert_eq!(throttle.allow_at(150, 100), None);
+
+        let once = Once::new();
+        assert!(once.first());
+        assert!(!once.first());
+    }
+
+    #[test]
+    fn events_losing_the_race_to_log_are_counted() {
+        let throttle = std::sync::Arc::new(Throttle::new());
+        let barrier = std::sync::Arc::new(std::sync::Barrier::new(8));
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let (throttle, barrier) = (throttle.clone(), barrier.clone());
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    throttle.allow_at(0, 100).is_some()
+                })
+            })
+            .collect();
+
+        let logged = threads
+            .into_iter()
+            .map(|thread| thread.join().expect("should finish"))
+            .filter(|logged| *logged)
+            .count();
+        assert_eq!(logged, 1);
+        assert_eq!(throttle.allow_at(100, 100), Some(7));
+    }
+}
@@ -52,6 +52,8 @@ async fn main() {
         workspace_root: project_directory.clone(),
         watch_directory: project_directory.clone(),
         wait_before_reload: time::Duration::from_millis(300), // magic number that works
+        env: HashMap::new(),
+        env_files: vec![format!("{}/.env", project_directory)],
         run_arguments: vec!["cargo", "run", "--bin", &binary_name].to_vec_string(),
         build_arguments: vec!["cargo", "build", "--bin", &binary_name].to_vec_string(),
         target_directory: String::from(format!("{}/target", project_directory.clone())),